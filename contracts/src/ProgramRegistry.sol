@@ -8,6 +8,8 @@ import "@openzeppelin/contracts/utils/Strings.sol";
  * @title ProgramRegistry
  * @notice Stores metadata for verifiable computation programs (e.g., Pico zkVM ELF binaries).
  * @dev Stores program hash (SHA256) and a location hint (URL, IPFS CID) for off-chain fetching.
+ * A program hashed with another algorithm annotates its location with it, e.g.
+ * "https://example.com/p.elf#hash=blake3" or "data:application/octet-stream;hash=blake3;base64,...".
  * Uses Ownable for contract administration and allows program owners to update their entries.
  */
 contract ProgramRegistry is Ownable {
@@ -17,10 +19,18 @@ contract ProgramRegistry is Ownable {
         string location; // URL, IPFS CID (e.g., "ipfs://Qm...", "https://...")
         address owner;   // Address that registered/owns the program entry
         bool exists;     // Flag to check existence, prevents updating non-existent entries implicitly
+        bytes32 programHash; // Hash the entry was registered under, returned so callers can confirm the lookup
     }
 
     mapping(bytes32 => ProgramInfo) public programs;
 
+    struct ProgramSchemaRef {
+        string uri;         // Where the program's JSON interface schema is published (URL, data: URI)
+        bytes32 schemaHash; // SHA256 of the schema document, checked by fetchers
+    }
+
+    mapping(bytes32 => ProgramSchemaRef) public schemas;
+
     // --- Events ---
 
     /**
@@ -46,6 +56,14 @@ contract ProgramRegistry is Ownable {
      */
     event ProgramEntryTransferred(bytes32 indexed programHash, address indexed newOwner);
 
+    /**
+     * @notice Emitted when a program's interface schema is set or replaced.
+     * @param programHash The SHA256 hash of the program binary.
+     * @param uri Where the schema document is published.
+     * @param schemaHash The SHA256 hash of the schema document.
+     */
+    event ProgramSchemaSet(bytes32 indexed programHash, string uri, bytes32 schemaHash);
+
     // --- Errors --- // Consider using Custom Errors for gas savings
     error ProgramRegistry__LocationCannotBeEmpty();
     error ProgramRegistry__ProgramNotFound();
@@ -79,7 +97,8 @@ contract ProgramRegistry is Ownable {
         programs[_programHash] = ProgramInfo({
             location: _location,
             owner: msg.sender,
-            exists: true
+            exists: true,
+            programHash: _programHash
         });
 
         emit ProgramRegistered(_programHash, _location, msg.sender);
@@ -127,6 +146,29 @@ contract ProgramRegistry is Ownable {
         emit ProgramEntryTransferred(_programHash, _newProgramOwner);
     }
 
+    /**
+     * @notice Publishes (or replaces) the interface schema of a registered program.
+     * @dev Only the current owner of the program entry can call this.
+     * @param _programHash SHA256 hash of the program binary.
+     * @param _uri Where to fetch the schema document. Must not be empty.
+     * @param _schemaHash SHA256 hash of the schema document.
+     */
+    function setProgramSchema(bytes32 _programHash, string calldata _uri, bytes32 _schemaHash) external {
+        if (bytes(_uri).length == 0) {
+            revert ProgramRegistry__LocationCannotBeEmpty();
+        }
+        ProgramInfo storage info = programs[_programHash];
+        if (!info.exists) {
+            revert ProgramRegistry__ProgramNotFound();
+        }
+        if (msg.sender != info.owner) {
+            revert ProgramRegistry__NotProgramOwner();
+        }
+
+        schemas[_programHash] = ProgramSchemaRef({uri: _uri, schemaHash: _schemaHash});
+        emit ProgramSchemaSet(_programHash, _uri, _schemaHash);
+    }
+
     // --- Views ---
 
     /**
@@ -154,6 +196,34 @@ contract ProgramRegistry is Ownable {
         return info.location;
     }
 
+    /**
+     * @notice Get the stored hash and location for a registered program.
+     * @param _programHash SHA256 hash of the program binary.
+     * @return programHash The hash stored with the entry, for callers to check against the requested one.
+     * @return location String indicating where to download the program. Reverts if not found.
+     */
+    function getProgram(bytes32 _programHash) external view returns (bytes32 programHash, string memory location) {
+        ProgramInfo storage info = programs[_programHash];
+        if (!info.exists) {
+            revert ProgramRegistry__ProgramNotFound();
+        }
+        return (info.programHash, info.location);
+    }
+
+    /**
+     * @notice Get where a registered program's interface schema is published.
+     * @param _programHash SHA256 hash of the program binary.
+     * @return uri Where to fetch the schema document; empty if the owner never set one.
+     * @return schemaHash SHA256 hash of the schema document. Reverts if the program is not found.
+     */
+    function getProgramSchema(bytes32 _programHash) external view returns (string memory uri, bytes32 schemaHash) {
+        if (!programs[_programHash].exists) {
+            revert ProgramRegistry__ProgramNotFound();
+        }
+        ProgramSchemaRef storage schema = schemas[_programHash];
+        return (schema.uri, schema.schemaHash);
+    }
+
      /**
      * @notice Check if a program hash is registered.
      * @param _programHash SHA256 hash of the program binary.
@@ -1,6 +1,10 @@
 use blueprint_sdk::build;
 use blueprint_sdk::tangle::blueprint;
-use pico_coprocessor_service_blueprint_lib::{generate_coprocessor_proof, generate_proof};
+use pico_coprocessor_service_blueprint_lib::{
+    check_program_matches_registry, describe_program, generate_coprocessor_proof, generate_proof,
+    generate_proof_multi, generate_proof_or_default, list_programs, prove, resume_proof,
+    verify_proof, warm_program,
+};
 use std::path::Path;
 use std::process;
 
@@ -22,7 +26,19 @@ fn main() {
         name: "experiment",
         master_manager_revision: "Latest",
         manager: { Evm = "HelloBlueprint" },
-        jobs: [generate_proof, generate_coprocessor_proof]
+        jobs: [
+            generate_proof,
+            generate_coprocessor_proof,
+            verify_proof,
+            list_programs,
+            resume_proof,
+            prove,
+            generate_proof_or_default,
+            generate_proof_multi,
+            describe_program,
+            warm_program,
+            check_program_matches_registry
+        ]
     };
 
     match blueprint {
@@ -1,35 +1,29 @@
 // pico-coprocessor-service-bin/src/main.rs
 use blueprint_sdk::{
-    Job,
-    Router,                     // Ensure Job and Router are imported
-    alloy::primitives::Address, // Import Address
+    Router,
     contexts::tangle::TangleClientContext,
     crypto::{sp_core::SpSr25519, tangle_pair_signer::TanglePairSigner},
     keystore::backends::Backend,
     runner::{BlueprintRunner, config::BlueprintEnvironment, tangle::config::TangleConfig},
-    tangle::{
-        consumer::TangleConsumer, filters::MatchesServiceId, layers::TangleLayer,
-        producer::TangleProducer,
-    },
+    tangle::{consumer::TangleConsumer, filters::MatchesServiceId, producer::TangleProducer},
 };
-// Import new types and jobs from lib
 use pico_coprocessor_service_blueprint_lib::{
-    GENERATE_COPROCESSOR_PROOF_JOB_ID,
-    GENERATE_PROOF_JOB_ID,
-    ServiceContext,
-    generate_coprocessor_proof,
-    generate_proof,
-    say_hello, // Jobs
+    ProofServiceBuilder, ServiceConfig, graceful_shutdown, is_prover_worker, run_prover_worker,
+    serve_metrics,
 };
-use std::{path::PathBuf, str::FromStr}; // For PathBuf and FromStr
 use tower::filter::FilterLayer;
 use tracing::error;
 use tracing::level_filters::LevelFilter;
-use url::Url; // For parsing RPC URL
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Use Box<dyn Error> for broader error handling
+    // A SubprocessProver re-runs this binary to prove one request; stdout carries its result
+    if is_prover_worker() {
+        setup_worker_log();
+        run_prover_worker().await?;
+        return Ok(());
+    }
     setup_log();
     tracing::info!("Starting Pico Coprocessor Service Blueprint Runner...");
 
@@ -57,50 +51,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!(%service_id, "Using Tangle Service ID");
 
     // --- Service Specific Configuration ---
-    // Get these from environment variables or a config file via BlueprintEnvironment extensions
-    // Example using environment variables (add error handling)
-    let eth_rpc_env =
-        std::env::var("ETH_RPC_URL").map_err(|_| "ETH_RPC_URL environment variable not set")?;
-    let eth_rpc_url =
-        Url::parse(&eth_rpc_env).map_err(|e| format!("Invalid ETH_RPC_URL: {}", e))?;
-
-    let registry_addr_env = std::env::var("REGISTRY_CONTRACT_ADDRESS")
-        .map_err(|_| "REGISTRY_CONTRACT_ADDRESS environment variable not set")?;
-    let registry_contract_address = Address::from_str(&registry_addr_env)
-        .map_err(|e| format!("Invalid REGISTRY_CONTRACT_ADDRESS: {}", e))?;
-
-    let temp_dir_base_env =
-        std::env::var("TEMP_DIR_BASE").unwrap_or_else(|_| "/tmp/pico-service".to_string());
-    let temp_dir_base = PathBuf::from(temp_dir_base_env);
-
-    tracing::info!(rpc_url = %eth_rpc_url, registry = %registry_contract_address, temp_dir = ?temp_dir_base, "Service configuration loaded");
+    // Optional config file (SERVICE_CONFIG, TOML or JSON); environment variables take precedence
+    let config =
+        ServiceConfig::load().map_err(|e| format!("Failed to load configuration: {}", e))?;
+    let service_mode = config
+        .mode()
+        .map_err(|e| format!("Invalid SERVICE_MODE: {}", e))?;
+    let shutdown_grace = config.shutdown_grace();
 
     // --- Create Service Context ---
-    let service_context =
-        ServiceContext::new(eth_rpc_url, registry_contract_address, temp_dir_base)
-            .map_err(|e| format!("Failed to create service context: {:?}", e))?;
+    let service_context = config
+        .build_context()
+        .map_err(|e| format!("Failed to create service context: {}", e))?;
+    tracing::info!(rpc_url = %service_context.eth_rpc_url, registry = %service_context.registry_contract_address, temp_dir = ?service_context.temp_dir_base, mode = ?service_mode, "Service configuration loaded");
+    // Prometheus scrape target for the proof counters, when configured
+    if let Some(addr) = config.metrics_addr {
+        let metrics = service_context.metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve_metrics(addr, metrics).await {
+                error!("Metrics server failed: {}", e);
+            }
+        });
+    }
+    // Shares the in-flight tracker with the router's copy
+    let shutdown_context = service_context.clone();
     tracing::info!("Service context created.");
 
     // --- Build Router ---
-    let router = Router::new()
-        // Add routes for each job ID
-        .route(GENERATE_PROOF_JOB_ID, generate_proof.layer(TangleLayer))
-        .route(
-            GENERATE_COPROCESSOR_PROOF_JOB_ID,
-            generate_coprocessor_proof.layer(TangleLayer),
-        ) // Add new route
+    // Verification and registry queries run in every mode; proving jobs only on proving nodes
+    let mut jobs = ProofServiceBuilder::new(service_mode);
+    let router = jobs
+        .register_jobs(Router::new())
+        .map_err(|e| format!("Failed to register jobs: {}", e))?
         // Global filter layer
         .layer(FilterLayer::new(MatchesServiceId(service_id)))
         // Add the shared context
         .with_context(service_context);
-    tracing::info!("Router configured with {} jobs.", 3); // Update count
+    tracing::info!(job_ids = ?jobs.job_ids(), "Router configured with {} jobs.", jobs.job_ids().len());
 
     // --- Build and Run Runner ---
     let runner_result = BlueprintRunner::builder(tangle_config, env)
         .router(router)
         .producer(tangle_producer)
         .consumer(tangle_consumer)
-        .with_shutdown_handler(async { println!("Shutting down Pico Coprocessor Service!") })
+        .with_shutdown_handler(async move {
+            println!("Shutting down Pico Coprocessor Service!");
+            graceful_shutdown(&shutdown_context, shutdown_grace).await;
+        })
         .run()
         .await;
 
@@ -128,3 +125,17 @@ pub fn setup_log() {
         .try_init(); // Use try_init to avoid panic if already initialized
     tracing::info!("Logging initialized.");
 }
+
+// A prover worker's stdout is reserved for its response, so it logs to stderr only, which it
+// shares with the service
+pub fn setup_worker_log() {
+    use tracing_subscriber::util::SubscriberInitExt;
+    let filter = tracing_subscriber::EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+
+    let _ = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(filter)
+        .try_init();
+}
@@ -0,0 +1,126 @@
+// Fast-mode proving throughput of the trading-volume example at varying receipt counts.
+// Run with `cargo bench -p pico-coprocessor-service-blueprint-lib --features test-fixtures`;
+// skipped when the example ELF is missing.
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use pico_coprocessor_service_blueprint_lib::{
+    LocalProver, ProofServiceError, ProveOptions, ProverBackend, ProvingType, decode_hex,
+    fixtures::CoprocessorFixtureBuilder,
+};
+use pico_sdk::client::DefaultProverClient;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Trading-volume example program, relative to the lib crate root
+const TRADING_VOLUME_ELF: &str = "tests/fixtures/trading_volume.elf";
+
+const RECEIPT_COUNTS: [usize; 3] = [1, 4, 16];
+
+// Timing of a single proving run
+struct BenchReport {
+    cycles: u64,
+    elapsed: Duration,
+}
+
+impl BenchReport {
+    fn cycles_per_sec(&self) -> f64 {
+        self.cycles as f64 / self.elapsed.as_secs_f64()
+    }
+
+    fn proofs_per_sec(&self) -> f64 {
+        1.0 / self.elapsed.as_secs_f64()
+    }
+}
+
+// Proves `inputs_hex` with `elf` and reports cycles and wall-clock time. Cycles come from a
+// separate emulation pass, which is not included in `elapsed`.
+async fn bench_prove(
+    elf: &[u8],
+    inputs_hex: &str,
+    proving_type: &ProvingType,
+) -> Result<BenchReport, ProofServiceError> {
+    let cycles = count_cycles(elf, inputs_hex)?;
+    let output_dir = tempfile::tempdir()?;
+
+    let start = Instant::now();
+    LocalProver
+        .prove(
+            elf,
+            inputs_hex,
+            proving_type,
+            output_dir.path(),
+            &ProveOptions::default(),
+        )
+        .await?;
+
+    Ok(BenchReport {
+        cycles,
+        elapsed: start.elapsed(),
+    })
+}
+
+// Number of RISC-V cycles the program takes on `inputs_hex`
+fn count_cycles(elf: &[u8], inputs_hex: &str) -> Result<u64, ProofServiceError> {
+    let client = DefaultProverClient::new(elf);
+    let stdin_builder = client.get_stdin_builder();
+    stdin_builder.borrow_mut().write(&decode_hex(inputs_hex)?);
+    let (cycles, _public_values) = client.emulate(stdin_builder);
+    Ok(cycles)
+}
+
+fn fast_proving(c: &mut Criterion) {
+    let elf = match std::fs::read(Path::new(TRADING_VOLUME_ELF)) {
+        Ok(elf) => elf,
+        Err(e) => {
+            eprintln!(
+                "Skipping proving benches, {} unreadable: {}",
+                TRADING_VOLUME_ELF, e
+            );
+            return;
+        }
+    };
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("execute_pico_prove/fast");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(1)); // proofs/sec
+    for receipts in RECEIPT_COUNTS {
+        let inputs = CoprocessorFixtureBuilder::new()
+            .num_receipts(receipts)
+            .build()
+            .inputs_hex();
+
+        let report = runtime
+            .block_on(bench_prove(&elf, &inputs, &ProvingType::Fast))
+            .expect("warm-up proof failed");
+        println!(
+            "{} receipts: {} cycles, {:.0} cycles/sec, {:.3} proofs/sec",
+            receipts,
+            report.cycles,
+            report.cycles_per_sec(),
+            report.proofs_per_sec()
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(receipts),
+            &inputs,
+            |b, inputs| {
+                b.iter_custom(|iters| {
+                    (0..iters)
+                        .map(|_| {
+                            runtime
+                                .block_on(bench_prove(&elf, inputs, &ProvingType::Fast))
+                                .expect("proof failed")
+                                .elapsed
+                        })
+                        .sum::<Duration>()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, fast_proving);
+criterion_main!(benches);
@@ -0,0 +1,180 @@
+// pico-coprocessor-service-lib/src/archive.rs
+use crate::context::ServiceContext;
+use crate::errors::ProofServiceError;
+use crate::types::{BlockchainData, BlockchainDataSource, ProofResult};
+use blueprint_sdk::alloy::primitives::B256;
+use blueprint_sdk::{debug, error, info};
+use flate2::read::GzDecoder;
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// Archive entry holding the result itself (proof, public values, program hash, inputs, proving
+/// type, prover version and the rest of `ProofResult`) as JSON.
+pub const ARCHIVE_MANIFEST: &str = "manifest.json";
+/// Archive directory the EVM artifacts of a `FullWithEvm` result are stored under.
+pub const ARCHIVE_EVM_DIR: &str = "evm";
+/// Entry of a [`BlockchainDataSource::Archive`] holding the `BlockchainData` JSON.
+pub const BLOCKCHAIN_DATA_ENTRY: &str = "blockchain_data.json";
+
+// Leading bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Files under a result's output_dir that make up its EVM proof, as read by `read_evm_artifacts`
+const EVM_ARTIFACTS: [&str; 3] = ["proof.data", "pv_file", "inputs.json"];
+
+impl ProofResult {
+    /// Writes the result to `path` as a self-contained tar archive for sharing or later verification.
+    ///
+    /// The archive holds [`ARCHIVE_MANIFEST`] plus, when `output_dir` still holds them, the EVM
+    /// artifacts under [`ARCHIVE_EVM_DIR`]. Reload it with [`ProofResult::from_archive`].
+    pub fn to_archive(&self, path: &Path) -> Result<(), ProofServiceError> {
+        let mut builder = tar::Builder::new(std::fs::File::create(path)?);
+        append_file(
+            &mut builder,
+            ARCHIVE_MANIFEST,
+            &serde_json::to_vec_pretty(self)?,
+        )?;
+
+        if let Some(output_dir) = &self.output_dir {
+            for artifact in EVM_ARTIFACTS {
+                let artifact_path = Path::new(output_dir).join(artifact);
+                if artifact_path.is_file() {
+                    let contents = std::fs::read(&artifact_path)?;
+                    append_file(
+                        &mut builder,
+                        &format!("{}/{}", ARCHIVE_EVM_DIR, artifact),
+                        &contents,
+                    )?;
+                }
+            }
+        }
+
+        builder.into_inner()?.sync_all()?;
+        debug!(path = ?path, "Wrote proof archive");
+        Ok(())
+    }
+
+    /// Reads a result written by [`ProofResult::to_archive`], refusing a manifest larger than
+    /// `max_manifest_bytes`.
+    ///
+    /// The EVM artifacts stay in the archive; any tar tool extracts them.
+    pub fn from_archive(path: &Path, max_manifest_bytes: usize) -> Result<Self, ProofServiceError> {
+        let mut archive = tar::Archive::new(std::fs::File::open(path)?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.path()?.as_ref() == Path::new(ARCHIVE_MANIFEST) {
+                let manifest = read_entry(&mut entry, max_manifest_bytes)?;
+                return Ok(serde_json::from_slice(&manifest)?);
+            }
+        }
+        Err(ProofServiceError::InvalidInput(format!(
+            "Proof archive {:?} has no {}",
+            path, ARCHIVE_MANIFEST
+        )))
+    }
+}
+
+fn append_file(
+    builder: &mut tar::Builder<std::fs::File>,
+    name: &str,
+    contents: &[u8],
+) -> Result<(), ProofServiceError> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, name, contents)?;
+    Ok(())
+}
+
+// Reads an archive entry of at most `max_bytes`. The declared size is checked before anything is
+// decompressed, and the read is bounded too, so a compressed archive can't expand past the limit.
+fn read_entry<R: Read>(
+    entry: &mut tar::Entry<'_, R>,
+    max_bytes: usize,
+) -> Result<Vec<u8>, ProofServiceError> {
+    let too_large = |size: u64| ProofServiceError::InputsTooLarge {
+        size: usize::try_from(size).unwrap_or(usize::MAX),
+        limit: max_bytes,
+    };
+    if entry.size() > max_bytes as u64 {
+        return Err(too_large(entry.size()));
+    }
+    let mut data = Vec::new();
+    entry
+        .by_ref()
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut data)?;
+    if data.len() > max_bytes {
+        return Err(too_large(data.len() as u64));
+    }
+    Ok(data)
+}
+
+/// Downloads the archive at `source`, checks its SHA256 against `expected_hash` and unpacks the
+/// `BlockchainData` it holds. Archives, and the data they unpack to, larger than the context's
+/// `max_input_bytes` are refused.
+pub async fn fetch_blockchain_data(
+    ctx: &ServiceContext,
+    source: &BlockchainDataSource,
+    expected_hash: &B256,
+) -> Result<BlockchainData, ProofServiceError> {
+    let BlockchainDataSource::Archive(url) = source;
+    info!(%url, "Downloading blockchain data archive");
+    let response = ctx.http_client.get(url.clone()).send().await?;
+    if !response.status().is_success() {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Failed to download blockchain data from {}: Status {}",
+            url,
+            response.status()
+        )));
+    }
+    let mut bytes = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        bytes.extend_from_slice(&chunk?);
+        if bytes.len() > ctx.max_input_bytes {
+            return Err(ProofServiceError::InputsTooLarge {
+                size: bytes.len(),
+                limit: ctx.max_input_bytes,
+            });
+        }
+    }
+
+    let actual_hash = B256::from_slice(&Sha256::digest(&bytes));
+    if actual_hash != *expected_hash {
+        error!(%url, expected = %expected_hash, got = %actual_hash, "Blockchain data archive hash mismatch!");
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Blockchain data archive at {} has hash {}, expected {}",
+            url, actual_hash, expected_hash
+        )));
+    }
+    unpack_blockchain_data(&bytes, ctx.max_input_bytes)
+}
+
+/// Reads the [`BLOCKCHAIN_DATA_ENTRY`] of a tar archive, gzip-compressed or not, refusing an entry
+/// larger than `max_bytes` once decompressed.
+pub fn unpack_blockchain_data(
+    archive_bytes: &[u8],
+    max_bytes: usize,
+) -> Result<BlockchainData, ProofServiceError> {
+    let reader: Box<dyn Read + '_> = if archive_bytes.starts_with(&GZIP_MAGIC) {
+        Box::new(GzDecoder::new(archive_bytes))
+    } else {
+        Box::new(archive_bytes)
+    };
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_ref() == Path::new(BLOCKCHAIN_DATA_ENTRY) {
+            let data = read_entry(&mut entry, max_bytes)?;
+            return Ok(serde_json::from_slice(&data)?);
+        }
+    }
+    Err(ProofServiceError::InvalidInput(format!(
+        "Blockchain data archive has no {}",
+        BLOCKCHAIN_DATA_ENTRY
+    )))
+}
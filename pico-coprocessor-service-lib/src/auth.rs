@@ -0,0 +1,128 @@
+// pico-coprocessor-service-lib/src/auth.rs
+use crate::canonical::to_canonical_json;
+use crate::context::ServiceContext;
+use crate::encoding::{decode_hex, strip_hex_prefix};
+use crate::errors::ProofServiceError;
+use crate::types::{
+    CoprocessorProofRequest, MultiProofRequest, ProofRequest, RequestSignature, ResumeProofRequest,
+    WarmProgramRequest,
+};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::Serialize;
+
+/// A job request that can carry a caller signature.
+pub trait SignedRequest: Serialize + Clone {
+    fn signature(&self) -> Option<&RequestSignature>;
+    fn set_signature(&mut self, signature: Option<RequestSignature>);
+
+    /// Bytes covered by the signature: the request with `signature` unset, encoded with
+    /// [`to_canonical_json`] so the bytes don't depend on the struct's field order.
+    fn signing_payload(&self) -> Result<Vec<u8>, ProofServiceError> {
+        let mut unsigned = self.clone();
+        unsigned.set_signature(None);
+        to_canonical_json(&unsigned)
+    }
+}
+
+impl SignedRequest for ProofRequest {
+    fn signature(&self) -> Option<&RequestSignature> {
+        self.signature.as_ref()
+    }
+
+    fn set_signature(&mut self, signature: Option<RequestSignature>) {
+        self.signature = signature;
+    }
+}
+
+impl SignedRequest for CoprocessorProofRequest {
+    fn signature(&self) -> Option<&RequestSignature> {
+        self.signature.as_ref()
+    }
+
+    fn set_signature(&mut self, signature: Option<RequestSignature>) {
+        self.signature = signature;
+    }
+}
+
+impl SignedRequest for MultiProofRequest {
+    fn signature(&self) -> Option<&RequestSignature> {
+        self.signature.as_ref()
+    }
+
+    fn set_signature(&mut self, signature: Option<RequestSignature>) {
+        self.signature = signature;
+    }
+}
+
+impl SignedRequest for ResumeProofRequest {
+    fn signature(&self) -> Option<&RequestSignature> {
+        self.signature.as_ref()
+    }
+
+    fn set_signature(&mut self, signature: Option<RequestSignature>) {
+        self.signature = signature;
+    }
+}
+
+impl SignedRequest for WarmProgramRequest {
+    fn signature(&self) -> Option<&RequestSignature> {
+        self.signature.as_ref()
+    }
+
+    fn set_signature(&mut self, signature: Option<RequestSignature>) {
+        self.signature = signature;
+    }
+}
+
+/// Signs `request` with `signing_key`, replacing any existing signature.
+pub fn sign_request<R: SignedRequest>(
+    mut request: R,
+    signing_key: &SigningKey,
+) -> Result<R, ProofServiceError> {
+    let payload = request.signing_payload()?;
+    let signature = signing_key.sign(&payload);
+    request.set_signature(Some(RequestSignature {
+        public_key: hex::encode(signing_key.verifying_key().as_bytes()),
+        signature: hex::encode(signature.to_bytes()),
+    }));
+    Ok(request)
+}
+
+/// Verifies the request signature against `ctx.authorized_keys`.
+///
+/// Returns the caller's hex public key once verified, or `None` when authentication is disabled
+/// (no authorized keys configured).
+pub fn authenticate<R: SignedRequest>(
+    ctx: &ServiceContext,
+    request: &R,
+) -> Result<Option<String>, ProofServiceError> {
+    let Some(authorized_keys) = &ctx.authorized_keys else {
+        return Ok(None);
+    };
+    let signed = request
+        .signature()
+        .ok_or_else(|| ProofServiceError::Unauthorized("request is not signed".to_string()))?;
+
+    let key_bytes: [u8; 32] = decode_hex(&signed.public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ProofServiceError::Unauthorized("malformed public key".to_string()))?;
+    if !authorized_keys.contains(&key_bytes) {
+        return Err(ProofServiceError::Unauthorized(
+            "signer is not authorized".to_string(),
+        ));
+    }
+    let verifying_key = VerifyingKey::from_bytes(&key_bytes)
+        .map_err(|_| ProofServiceError::Unauthorized("malformed public key".to_string()))?;
+    let signature = decode_hex(&signed.signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| ProofServiceError::Unauthorized("malformed signature".to_string()))?;
+
+    verifying_key
+        .verify_strict(&request.signing_payload()?, &signature)
+        .map_err(|_| ProofServiceError::Unauthorized("invalid signature".to_string()))?;
+    Ok(Some(
+        strip_hex_prefix(&signed.public_key).to_ascii_lowercase(),
+    ))
+}
@@ -0,0 +1,456 @@
+// pico-coprocessor-service-lib/src/backend.rs
+use crate::errors::ProofServiceError;
+use crate::memory;
+use crate::pico::{self, ProveOptions, ProverCache};
+use crate::types::{ProofResult, ProvingType};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::{debug, info};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use url::Url;
+
+/// Timeout of each request to a remote prover when `remote_prover_timeout_secs` isn't configured,
+/// so a remote prover that never answers fails the proof instead of holding it forever.
+pub const DEFAULT_REMOTE_PROVER_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Environment variable a [`SubprocessProver`] sets on the process it starts, telling the service
+/// binary to run as a prover worker (see [`run_prover_worker`]) instead of starting the service.
+pub const PROVER_WORKER_ENV: &str = "PICO_PROVER_WORKER";
+
+/// Where proofs are computed once a job has fetched and verified the program.
+///
+/// The backend is chosen on `ServiceContext::prover_backend`; everything else (auth, validation,
+/// program fetching, caching) still runs on the node that received the job.
+pub trait ProverBackend: Send + Sync {
+    fn prove<'a>(
+        &'a self,
+        elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        output_base_dir: &'a Path,
+        options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>>;
+
+    /// Prepares to prove `elf` as `proving_type` without proving anything, e.g. by setting up
+    /// its prover in `cache` ahead of the first proof. Returns whether the prover is ready in
+    /// `cache`; backends proving elsewhere have nothing to prepare.
+    fn warm<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        _proving_type: &'a ProvingType,
+        _cache: &'a ProverCache,
+    ) -> BoxFuture<'a, Result<bool, ProofServiceError>> {
+        Box::pin(async { Ok(false) })
+    }
+
+    /// Whether the inputs stay in this process while proving. Backends handing them to another
+    /// process or host don't serve `private_inputs` requests.
+    fn keeps_inputs_in_process(&self) -> bool {
+        true
+    }
+
+    /// Whether proofs apply the options Pico only reads from the environment of the process it
+    /// runs in: a `shard_size` override, and the `memory_limit_bytes` of a `FullWithEvm` proof's
+    /// gnark container. The service never changes its own environment, so requests relying on
+    /// them are refused up front by backends that don't.
+    fn applies_process_env(&self) -> bool {
+        false
+    }
+}
+
+/// Proves in this process with the Pico SDK.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalProver;
+
+impl ProverBackend for LocalProver {
+    fn prove<'a>(
+        &'a self,
+        elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        output_base_dir: &'a Path,
+        options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(pico::execute_pico_prove_elf(
+            elf,
+            inputs_hex,
+            proving_type,
+            output_base_dir,
+            options,
+        ))
+    }
+
+    fn warm<'a>(
+        &'a self,
+        elf: &'a [u8],
+        proving_type: &'a ProvingType,
+        cache: &'a ProverCache,
+    ) -> BoxFuture<'a, Result<bool, ProofServiceError>> {
+        let (elf, proving_type, cache) = (elf.to_vec(), proving_type.clone(), cache.clone());
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || cache.warm(&elf, &proving_type))
+                .await
+                .map_err(|e| {
+                    ProofServiceError::InternalError(format!("Prover client setup failed: {}", e))
+                })
+        })
+    }
+}
+
+/// Body a [`RemoteProver`] POSTs to its endpoint. The remote service answers with the
+/// JSON [`ProofResult`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteProveRequest {
+    /// Base64 encoded ELF, already verified against the requested program hash.
+    pub elf: String,
+    /// Hex encoded program inputs.
+    pub inputs: String,
+    pub proving_type: ProvingType,
+    pub options: ProveOptions,
+}
+
+/// Delegates proving to a remote proving service over HTTP.
+///
+/// Fast proofs it returns are verified against the ELF before they are used; proofs of the other
+/// proving types can't be checked in-process and are returned as the remote prover sent them.
+#[derive(Debug, Clone)]
+pub struct RemoteProver {
+    pub endpoint: Url,
+    client: reqwest::Client,
+}
+
+impl RemoteProver {
+    /// Remote prover whose requests time out after [`DEFAULT_REMOTE_PROVER_TIMEOUT`].
+    pub fn new(endpoint: Url) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(DEFAULT_REMOTE_PROVER_TIMEOUT)
+            .build()
+            .expect("Failed to build the remote prover HTTP client");
+        Self::with_client(endpoint, client)
+    }
+
+    pub fn with_client(endpoint: Url, client: reqwest::Client) -> Self {
+        Self { endpoint, client }
+    }
+
+    async fn prove_remote(
+        &self,
+        request: &RemoteProveRequest,
+    ) -> Result<ProofResult, ProofServiceError> {
+        info!(endpoint = %self.endpoint, proving_type = ?request.proving_type, "Delegating proof to remote prover");
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .json(request)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProofServiceError::ProvingError(format!(
+                "Remote prover {} returned status {}: {}",
+                self.endpoint, status, body
+            )));
+        }
+        let result = response.json::<ProofResult>().await.map_err(|e| {
+            ProofServiceError::ProvingError(format!(
+                "Invalid proof result from remote prover {}: {}",
+                self.endpoint, e
+            ))
+        })?;
+        debug!(endpoint = %self.endpoint, "Remote prover returned a proof");
+        Ok(result)
+    }
+
+    // The remote prover is trusted with the work, not the outcome: Fast proofs (including a
+    // Full proof's Fast fallback) are verified against the ELF before they are returned
+    async fn verify_remote_result(
+        &self,
+        elf: &[u8],
+        proving_type: &ProvingType,
+        result: ProofResult,
+        cache: ProverCache,
+    ) -> Result<ProofResult, ProofServiceError> {
+        if proving_type.is_fast() && !result.proving_type.is_fast() {
+            return Err(ProofServiceError::ProvingError(format!(
+                "Remote prover {} answered a {:?} request with a {:?} proof",
+                self.endpoint, proving_type, result.proving_type
+            )));
+        }
+        if !result.proving_type.is_fast() {
+            return Ok(result);
+        }
+        let elf = elf.to_vec();
+        let (valid, result) = tokio::task::spawn_blocking(move || {
+            let valid = pico::verify_pico_proof_elf(&elf, &result, &cache);
+            (valid, result)
+        })
+        .await
+        .map_err(|e| {
+            ProofServiceError::InternalError(format!("Remote proof verification failed: {}", e))
+        })?;
+        if !valid? {
+            return Err(ProofServiceError::ProvingError(format!(
+                "Remote prover {} returned a proof that does not verify",
+                self.endpoint
+            )));
+        }
+        debug!(endpoint = %self.endpoint, "Remote proof verified");
+        Ok(result)
+    }
+}
+
+impl ProverBackend for RemoteProver {
+    fn prove<'a>(
+        &'a self,
+        elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            let request = RemoteProveRequest {
+                elf: STANDARD.encode(elf),
+                inputs: inputs_hex.to_string(),
+                proving_type: proving_type.clone(),
+                options: options.clone(),
+            };
+            let result = self.prove_remote(&request).await?;
+            let cache = options
+                .prover_cache
+                .clone()
+                .unwrap_or_else(|| ProverCache::new(0));
+            self.verify_remote_result(elf, proving_type, result, cache)
+                .await
+        })
+    }
+
+    fn keeps_inputs_in_process(&self) -> bool {
+        false
+    }
+
+    // The options travel with the request; applying them is up to the remote prover
+    fn applies_process_env(&self) -> bool {
+        true
+    }
+}
+
+/// What a [`SubprocessProver`] writes to its worker's stdin.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProverWorkerRequest {
+    #[serde(flatten)]
+    pub prove: RemoteProveRequest,
+    /// Directory the worker writes proof artifacts to, as for an in-process proof.
+    pub output_base_dir: PathBuf,
+    // The node-local options `RemoteProveRequest` never sends: the worker runs on this node, so
+    // it writes and runs exactly where an in-process proof would
+    #[serde(default)]
+    pub evm_output_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub evm_output_retention: Option<Duration>,
+    #[serde(default)]
+    pub evm_docker_image: Option<String>,
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+}
+
+impl ProverWorkerRequest {
+    /// The options the worker proves with, node-local ones included.
+    pub fn options(&self) -> ProveOptions {
+        ProveOptions {
+            evm_output_dir: self.evm_output_dir.clone(),
+            evm_output_retention: self.evm_output_retention,
+            evm_docker_image: self.evm_docker_image.clone(),
+            output_dir: self.output_dir.clone(),
+            ..self.prove.options.clone()
+        }
+    }
+}
+
+/// What a prover worker prints as the last line of its stdout.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverWorkerResponse {
+    Proof(ProofResult),
+    /// The proof failed in the worker; the message of its error.
+    Error(String),
+}
+
+/// Proves in a child process with the Pico SDK, so a crash in the prover's native code fails the
+/// proof instead of taking the whole service down.
+///
+/// The child is `program` run with [`PROVER_WORKER_ENV`] set: by default the service binary
+/// itself, which hands over to [`run_prover_worker`]. It reads a [`ProverWorkerRequest`] from
+/// stdin and prints a [`ProverWorkerResponse`]; a child that exits without one has crashed.
+#[derive(Debug, Clone)]
+pub struct SubprocessProver {
+    /// Worker executable, or its name to look up on `PATH`.
+    pub program: PathBuf,
+}
+
+impl SubprocessProver {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+
+    /// Runs workers from the executable of this process.
+    pub fn current_exe() -> Result<Self, ProofServiceError> {
+        let program = std::env::current_exe().map_err(|e| {
+            ProofServiceError::ConfigError(format!(
+                "Failed to locate the service binary for subprocess proving: {}",
+                e
+            ))
+        })?;
+        Ok(Self::new(program))
+    }
+
+    async fn prove_in_subprocess(
+        &self,
+        request: &ProverWorkerRequest,
+    ) -> Result<ProofResult, ProofServiceError> {
+        info!(program = ?self.program, proving_type = ?request.prove.proving_type, "Proving in a prover process");
+        let body = serde_json::to_vec(request)?;
+        let mut command = tokio::process::Command::new(&self.program);
+        command.env(PROVER_WORKER_ENV, "1");
+        // Pico reads the shard size from the environment, so the worker starts with the override set
+        if let Some(shard_size) = request.prove.options.shard_size {
+            pico::validate_shard_size(shard_size)?;
+            command.env(pico::SHARD_SIZE_ENV, shard_size.to_string());
+        }
+        // Likewise the gnark container's overrides reach Pico through the worker's PATH; the shim
+        // is kept until the worker exits
+        let overrides = pico::docker_overrides(&request.options());
+        let _docker_shim =
+            if request.prove.proving_type == ProvingType::FullWithEvm && !overrides.is_empty() {
+                let shim = memory::DockerShim::create(&request.output_base_dir, &overrides)?;
+                command.envs(shim.child_env()?);
+                Some(shim)
+            } else {
+                None
+            };
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                ProofServiceError::ProvingError(format!(
+                    "Failed to start prover process {:?}: {}",
+                    self.program, e
+                ))
+            })?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        // A worker that dies before reading its request closes the pipe; its exit says why
+        let write = async move {
+            let _ = stdin.write_all(&body).await;
+        };
+        let (_, output) = tokio::join!(write, child.wait_with_output());
+        let output = output.map_err(|e| {
+            ProofServiceError::ProvingError(format!("Failed to wait for prover process: {}", e))
+        })?;
+
+        // The response is the last line, whatever else the prover printed before it
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let response = stdout
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|line| serde_json::from_str::<ProverWorkerResponse>(line).ok());
+        match response {
+            Some(ProverWorkerResponse::Proof(result)) if output.status.success() => {
+                debug!(program = ?self.program, "Prover process returned a proof");
+                Ok(result)
+            }
+            Some(ProverWorkerResponse::Error(message)) => Err(ProofServiceError::ProvingError(
+                format!("Prover process failed: {}", message),
+            )),
+            _ => Err(ProofServiceError::ProvingError(format!(
+                "prover process crashed ({})",
+                output.status
+            ))),
+        }
+    }
+}
+
+impl ProverBackend for SubprocessProver {
+    fn prove<'a>(
+        &'a self,
+        elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        output_base_dir: &'a Path,
+        options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            let request = ProverWorkerRequest {
+                prove: RemoteProveRequest {
+                    elf: STANDARD.encode(elf),
+                    inputs: inputs_hex.to_string(),
+                    proving_type: proving_type.clone(),
+                    options: options.clone(),
+                },
+                output_base_dir: output_base_dir.to_path_buf(),
+                evm_output_dir: options.evm_output_dir.clone(),
+                evm_output_retention: options.evm_output_retention,
+                evm_docker_image: options.evm_docker_image.clone(),
+                output_dir: options.output_dir.clone(),
+            };
+            self.prove_in_subprocess(&request).await
+        })
+    }
+
+    // The worker gets the inputs on stdin and logs to the service's stderr
+    fn keeps_inputs_in_process(&self) -> bool {
+        false
+    }
+
+    // Each worker is started with the request's shard size and Docker shim in its environment
+    fn applies_process_env(&self) -> bool {
+        true
+    }
+}
+
+/// Whether this process was started by a [`SubprocessProver`] to prove a request.
+pub fn is_prover_worker() -> bool {
+    std::env::var_os(PROVER_WORKER_ENV).is_some()
+}
+
+/// Proves the [`ProverWorkerRequest`] on stdin in this process and prints the
+/// [`ProverWorkerResponse`]. Service binaries call it at the top of `main` when
+/// [`is_prover_worker`], and exit once it returns.
+pub async fn run_prover_worker() -> Result<(), ProofServiceError> {
+    let mut body = Vec::new();
+    tokio::io::stdin().read_to_end(&mut body).await?;
+    let request: ProverWorkerRequest = serde_json::from_slice(&body)?;
+    let elf = STANDARD.decode(&request.prove.elf).map_err(|e| {
+        ProofServiceError::InvalidInput(format!("Invalid ELF in prover worker request: {}", e))
+    })?;
+
+    let options = request.options();
+    let response = match pico::execute_pico_prove_elf(
+        &elf,
+        &request.prove.inputs,
+        &request.prove.proving_type,
+        &request.output_base_dir,
+        &options,
+    )
+    .await
+    {
+        Ok(result) => ProverWorkerResponse::Proof(result),
+        Err(e) => ProverWorkerResponse::Error(e.to_string()),
+    };
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,108 @@
+// pico-coprocessor-service-lib/src/brevis.rs
+use crate::errors::ProofServiceError;
+use crate::jobs::coprocessor::SdkSavedInputs;
+use blueprint_sdk::{debug, info};
+use std::path::{Path, PathBuf};
+
+/// File name the coprocessor SDK's `save_inputs` writes the request to.
+pub const BREVIS_REQUEST_FILE: &str = "request_prove_inputs.json";
+/// Environment variable `brevis-request` reads the request file path from.
+pub const BREVIS_REQUEST_DATA_ENV: &str = "REQUEST_DATA_FILE";
+
+/// Submits coprocessor requests through the `brevis-request` binary.
+///
+/// The binary's request format is the JSON the coprocessor SDK's `save_inputs` writes (see
+/// [`CoprocessorProofRequest::sdk_inputs`](crate::CoprocessorProofRequest::sdk_inputs)); the client
+/// writes that file and runs the binary on it. The binary's protocol isn't published, so it is the
+/// only way requests are submitted; [`write_request`](Self::write_request) alone prepares the file
+/// for callers that submit it some other way.
+#[derive(Debug, Clone)]
+pub struct BrevisRequestClient {
+    /// Path to the `brevis-request` binary, or its name to look up on `PATH`.
+    pub binary: PathBuf,
+}
+
+/// A successful `brevis-request` run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrevisRequestOutcome {
+    /// Standard output, parsed when the binary printed JSON.
+    pub response: Option<serde_json::Value>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Default for BrevisRequestClient {
+    fn default() -> Self {
+        Self::new("brevis-request")
+    }
+}
+
+impl BrevisRequestClient {
+    pub fn new(binary: impl Into<PathBuf>) -> Self {
+        Self {
+            binary: binary.into(),
+        }
+    }
+
+    /// Writes `sdk_inputs` to `dir` as the request file the binary consumes, byte for byte as
+    /// `save_inputs` produced it. Rejects JSON that isn't in the SDK's format.
+    pub fn write_request(
+        &self,
+        sdk_inputs: &str,
+        dir: &Path,
+    ) -> Result<PathBuf, ProofServiceError> {
+        SdkSavedInputs::parse(sdk_inputs)?;
+        let path = dir.join(BREVIS_REQUEST_FILE);
+        std::fs::write(&path, sdk_inputs)?;
+        debug!(path = ?path, "Wrote brevis-request request file");
+        Ok(path)
+    }
+
+    /// Writes the request file under `work_dir` and runs the binary on it.
+    ///
+    /// A non-zero exit is a [`ProofServiceError::ProvingError`] carrying the binary's stderr.
+    pub async fn submit(
+        &self,
+        sdk_inputs: &str,
+        work_dir: &Path,
+    ) -> Result<BrevisRequestOutcome, ProofServiceError> {
+        let request_dir = tempfile::Builder::new()
+            .prefix("brevis_request_")
+            .tempdir_in(work_dir)
+            .map_err(|e| {
+                ProofServiceError::TempDirError(format!(
+                    "Failed to create brevis-request dir: {}",
+                    e
+                ))
+            })?;
+        let request_file = self.write_request(sdk_inputs, request_dir.path())?;
+
+        info!(binary = ?self.binary, request = ?request_file, "Running brevis-request");
+        let output = tokio::process::Command::new(&self.binary)
+            .env(BREVIS_REQUEST_DATA_ENV, &request_file)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| {
+                ProofServiceError::ConfigError(format!(
+                    "Failed to run brevis-request {:?}: {}",
+                    self.binary, e
+                ))
+            })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        if !output.status.success() {
+            return Err(ProofServiceError::ProvingError(format!(
+                "brevis-request exited with {}: {}",
+                output.status,
+                stderr.trim()
+            )));
+        }
+        Ok(BrevisRequestOutcome {
+            response: serde_json::from_str(stdout.trim()).ok(),
+            stdout,
+            stderr,
+        })
+    }
+}
@@ -0,0 +1,132 @@
+// pico-coprocessor-service-lib/src/cache.rs
+use crate::types::{ProofResult, ProvingType};
+use blueprint_sdk::alloy::primitives::B256;
+use blueprint_sdk::warn;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Identifies a proof by everything that determines it.
+///
+/// For chain-state proofs the anchor block is part of the key, so identical data read at another
+/// block is proven again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProofCacheKey {
+    pub program_hash: B256,
+    pub proving_type: ProvingType,
+    pub anchor_block_hash: Option<B256>,
+    pub inputs_digest: [u8; 32], // sha256 of the exact prover inputs
+}
+
+impl ProofCacheKey {
+    pub fn new(
+        program_hash: B256,
+        proving_type: ProvingType,
+        anchor_block_hash: Option<B256>,
+        inputs: &[u8],
+    ) -> Self {
+        Self {
+            program_hash,
+            proving_type,
+            anchor_block_hash,
+            inputs_digest: Sha256::digest(inputs).into(),
+        }
+    }
+}
+
+/// Proofs kept by [`ProofCache::default`].
+pub const DEFAULT_PROOF_CACHE_ENTRIES: usize = 256;
+
+/// In-memory store of finished proofs, shared by every clone of the context.
+///
+/// It holds at most [`max_entries`](Self::max_entries) proofs: inserting past that evicts the
+/// least recently used one.
+#[derive(Debug, Clone)]
+pub struct ProofCache {
+    inner: Arc<Mutex<CacheEntries>>,
+    max_entries: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    // Each proof with the tick it was last inserted or served at
+    entries: HashMap<ProofCacheKey, (ProofResult, u64)>,
+    tick: u64,
+}
+
+impl Default for ProofCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROOF_CACHE_ENTRIES)
+    }
+}
+
+impl ProofCache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(CacheEntries::default())),
+            max_entries,
+        }
+    }
+
+    /// Bound on the number of stored proofs.
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// The proof stored under `key`. A stored result of another proving type than the key's is
+    /// treated as a miss, so e.g. a `Full` request is never answered with a `Fast` proof.
+    pub fn get(&self, key: &ProofCacheKey) -> Option<ProofResult> {
+        let result = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.tick += 1;
+            let tick = inner.tick;
+            let (result, last_used) = inner.entries.get_mut(key)?;
+            *last_used = tick;
+            result.clone()
+        };
+        if result.proving_type != key.proving_type {
+            warn!(
+                requested = ?key.proving_type,
+                cached = ?result.proving_type,
+                "Cached proof has another proving type; proving again"
+            );
+            return None;
+        }
+        Some(result)
+    }
+
+    /// Stores `result` under `key`, without the fields that describe the run that produced it
+    /// rather than the proof (its id, logs, timings, RISCV proof and artifact dir), evicting the
+    /// least recently used proof when the cache is full.
+    pub fn insert(&self, key: ProofCacheKey, mut result: ProofResult) {
+        result.proof_id = None;
+        result.debug_log = None;
+        result.phase_timings = None;
+        result.riscv_proof = None;
+        result.output_dir = None;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.entries.insert(key, (result, tick));
+        while inner.entries.len() > self.max_entries {
+            let Some(oldest) = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
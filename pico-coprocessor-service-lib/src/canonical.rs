@@ -0,0 +1,47 @@
+// pico-coprocessor-service-lib/src/canonical.rs
+use crate::errors::ProofServiceError;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Serializes `value` as compact JSON with every object's keys sorted, whatever order the struct
+/// fields or map entries come in, so equal values always produce the same bytes.
+///
+/// Used for the coprocessor input bundle, whose hex is the proof's `inputs` and part of the cache
+/// key. Programs deserializing the bundle with serde are unaffected by the key order.
+pub fn to_canonical_json<T: Serialize>(value: &T) -> Result<Vec<u8>, ProofServiceError> {
+    let value = serde_json::to_value(value)?;
+    let mut out = Vec::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out)
+}
+
+fn write_canonical(value: &Value, out: &mut Vec<u8>) -> Result<(), serde_json::Error> {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push(b'{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, key)?;
+                out.push(b':');
+                write_canonical(item, out)?;
+            }
+            out.push(b'}');
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(b']');
+        }
+        scalar => serde_json::to_writer(&mut *out, scalar)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// pico-coprocessor-service-lib/src/checkpoint.rs
+use crate::errors::ProofServiceError;
+use crate::types::{ProofRequest, ProofResult};
+use blueprint_sdk::debug;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Progress of a proof persisted so it can be resumed after a restart.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofCheckpoint {
+    pub proof_id: String,
+    pub request: ProofRequest,
+    /// Authenticated caller that requested the proof, the only one allowed to resume it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller: Option<String>,
+    /// Output of the RISCV phase, once it has completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub riscv_proof: Option<ProofResult>,
+}
+
+/// Durable directory of `{proof_id}.json` checkpoints. Unlike `temp_dir_base`, it must survive restarts.
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, ProofServiceError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            ProofServiceError::ConfigError(format!(
+                "Failed to create checkpoint dir {:?}: {}",
+                dir, e
+            ))
+        })?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `checkpoint`, replacing any previous one for the same proof.
+    pub fn save(&self, checkpoint: &ProofCheckpoint) -> Result<(), ProofServiceError> {
+        let path = self.path_for(&checkpoint.proof_id)?;
+        // Write then rename so a crash never leaves a truncated checkpoint behind
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(checkpoint)?)?;
+        std::fs::rename(&tmp, &path)?;
+        debug!(proof_id = %checkpoint.proof_id, "Saved proof checkpoint");
+        Ok(())
+    }
+
+    pub fn load(&self, proof_id: &str) -> Result<Option<ProofCheckpoint>, ProofServiceError> {
+        match std::fs::read(self.path_for(proof_id)?) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn remove(&self, proof_id: &str) -> Result<(), ProofServiceError> {
+        match std::fs::remove_file(self.path_for(proof_id)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Proof ids with a pending checkpoint.
+    pub fn pending(&self) -> Result<Vec<String>, ProofServiceError> {
+        let mut ids: Vec<String> = std::fs::read_dir(&self.dir)?
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.strip_suffix(".json").map(str::to_string)
+            })
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn path_for(&self, proof_id: &str) -> Result<PathBuf, ProofServiceError> {
+        // Proof ids are `{millis}_{alnum}`; anything else could escape the directory
+        if proof_id.is_empty()
+            || !proof_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Invalid proof_id: {}",
+                proof_id
+            )));
+        }
+        Ok(self.dir.join(format!("{}.json", proof_id)))
+    }
+}
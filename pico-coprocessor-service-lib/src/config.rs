@@ -0,0 +1,527 @@
+// pico-coprocessor-service-lib/src/config.rs
+use crate::backend::{DEFAULT_REMOTE_PROVER_TIMEOUT, RemoteProver, SubprocessProver};
+use crate::cache::{DEFAULT_PROOF_CACHE_ENTRIES, ProofCache};
+use crate::checkpoint::CheckpointStore;
+use crate::context::{HardLimits, HttpProxyConfig, ServiceContext, ServiceMode};
+use crate::elf_cache::ElfCache;
+use crate::encoding::decode_hex;
+use crate::errors::ProofServiceError;
+use crate::metrics::ProofMetrics;
+use crate::pico::ProverCache;
+use crate::queue::ProvingQueue;
+use crate::rate_limit::RateLimiter;
+use crate::s3::{DEFAULT_S3_REGION, S3Config, S3Store};
+use crate::shutdown::DEFAULT_SHUTDOWN_GRACE;
+use crate::types::{ProofRequest, RegistryRef};
+use blueprint_sdk::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/// Environment variable naming the config file `main` loads, if any.
+pub const SERVICE_CONFIG_ENV: &str = "SERVICE_CONFIG";
+/// `temp_dir_base` used when neither the config file nor `TEMP_DIR_BASE` sets one.
+pub const DEFAULT_TEMP_DIR_BASE: &str = "/tmp/pico-service";
+
+/// Per-caller token bucket (see [`RateLimiter`]); unauthenticated callers share one bucket.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    pub capacity: u32,
+    pub refill_per_sec: f64,
+}
+
+/// Service configuration, read from a TOML or JSON file and overridden by environment variables.
+///
+/// Every field is optional in the file. The environment variables the service has always read
+/// (`ETH_RPC_URL`, `REGISTRY_CONTRACT_ADDRESS`, `TEMP_DIR_BASE`, ...) take precedence over the
+/// file, see [`ServiceConfig::apply_env_overrides`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServiceConfig {
+    /// Ethereum RPC endpoint (`ETH_RPC_URL`). Required.
+    pub eth_rpc_url: Option<Url>,
+    /// Registry contract, as an address or ENS name (`REGISTRY_CONTRACT_ADDRESS`). Required.
+    pub registry_contract_address: Option<String>,
+    /// Registries searched in order when the primary lacks a program (`FALLBACK_REGISTRY_ADDRESSES`,
+    /// comma separated).
+    pub fallback_registries: Vec<String>,
+    /// Base path for temporary files (`TEMP_DIR_BASE`); [`DEFAULT_TEMP_DIR_BASE`] if unset.
+    pub temp_dir_base: Option<PathBuf>,
+    /// `prove-and-verify` or `verify-only` (`SERVICE_MODE`).
+    pub service_mode: Option<String>,
+    /// Time in-flight jobs get to finish on shutdown (`SHUTDOWN_GRACE_SECS`).
+    pub shutdown_grace_secs: Option<u64>,
+    /// Durable directory for proof checkpoints (`CHECKPOINT_DIR`); unset disables them.
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Durable directory for verified program ELFs (`ELF_CACHE_DIR`); unset fetches every time.
+    pub elf_cache_dir: Option<PathBuf>,
+    /// Bound on the ELF cache's total size (`ELF_CACHE_MAX_BYTES`), beyond which the least
+    /// recently used ELFs are evicted; unset never evicts.
+    pub elf_cache_max_bytes: Option<u64>,
+    /// ELFs whose prover client, and separately whose `Full` prover chain, are kept for later
+    /// proofs (`PROVER_CACHE_CAPACITY`), evicting the least recently used; unset keeps
+    /// [`PROVER_CLIENT_CACHE_CAPACITY`](crate::pico::PROVER_CLIENT_CACHE_CAPACITY), 0 keeps none.
+    pub prover_cache_capacity: Option<usize>,
+    /// Maximum concurrent proofs (`PROVING_CONCURRENCY`); unset runs every proof at once.
+    pub proving_concurrency: Option<usize>,
+    /// Remote prover to delegate proving to (`REMOTE_PROVER_URL`).
+    pub remote_prover_url: Option<Url>,
+    /// Timeout of each request to the remote prover; [`DEFAULT_REMOTE_PROVER_TIMEOUT`] if unset.
+    pub remote_prover_timeout_secs: Option<u64>,
+    /// Prove in a child process of the service binary (`PROVER_SUBPROCESS`), so a prover crash
+    /// fails the proof instead of the service; exclusive with `remote_prover_url`.
+    pub prover_subprocess: bool,
+    /// Persistent home for `FullWithEvm` artifacts (`EVM_OUTPUT_DIR`).
+    pub evm_output_dir: Option<PathBuf>,
+    /// Age after which artifact dirs under `evm_output_dir` are removed
+    /// (`EVM_OUTPUT_RETENTION_SECS`); unset keeps them until removed by hand.
+    pub evm_output_retention_secs: Option<u64>,
+    /// gnark image for `FullWithEvm` proving (`EVM_DOCKER_IMAGE`); applied by prover subprocesses,
+    /// so it needs `prover_subprocess`.
+    pub evm_docker_image: Option<String>,
+    /// Directory coprocessor requests' `dump_inputs_to` files are written to (`DUMP_INPUTS_DIR`);
+    /// unset rejects dumps.
+    pub dump_inputs_dir: Option<PathBuf>,
+    /// Directories a request's `temp_dir_override` may be or lie beneath (`ALLOWED_TEMP_DIRS`,
+    /// comma separated); unset rejects overrides.
+    pub allowed_temp_dirs: Vec<PathBuf>,
+    /// Honor requests' `insecure_skip_hash_verify` (`ALLOW_INSECURE`); development only.
+    pub allow_insecure: bool,
+    /// Upper bound on decoded `inputs` bytes accepted by `generate_proof`.
+    pub max_input_bytes: Option<usize>,
+    /// Upper bound on the serialized coprocessor inputs written to the prover's stdin.
+    pub max_prover_input_bytes: Option<usize>,
+    /// Upper bound on a downloaded or data URI program ELF.
+    pub max_elf_bytes: Option<u64>,
+    /// Caps on coprocessor request contents.
+    pub hard_limits: Option<HardLimits>,
+    /// Hosts a request's `eth_rpc_url_override` may point at (`ALLOWED_RPC_HOSTS`, comma
+    /// separated); unset allows any.
+    pub allowed_rpc_hosts: Option<Vec<String>>,
+    /// Proxy for program and data downloads (`OUTBOUND_PROXY_URL`, with `OUTBOUND_PROXY_USERNAME`
+    /// and `OUTBOUND_PROXY_PASSWORD`); unset connects directly.
+    pub http_proxy: Option<HttpProxyConfig>,
+    /// Content types a program download must declare.
+    pub allowed_elf_content_types: Option<Vec<String>>,
+    /// Oldest prover version whose proofs `verify_proof` accepts.
+    pub min_prover_version: Option<String>,
+    /// Object storage for requests with an S3 `output_sink` (`S3_ENDPOINT`, `S3_REGION`, with
+    /// credentials from `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`), confined to the bucket
+    /// `S3_BUCKET` and the key prefix `S3_KEY_PREFIX`; unset rejects them.
+    pub s3: Option<S3Config>,
+    /// Serve finished proofs again for identical requests.
+    pub proof_cache: bool,
+    /// Bound on the proofs `proof_cache` keeps, beyond which the least recently used are
+    /// evicted; unset keeps up to [`DEFAULT_PROOF_CACHE_ENTRIES`].
+    pub proof_cache_max_entries: Option<usize>,
+    /// Hex ed25519 public keys allowed to submit requests (`AUTHORIZED_KEYS`, comma separated);
+    /// unset accepts unsigned requests.
+    pub authorized_keys: Option<Vec<String>>,
+    /// Per-caller limit on proving jobs, keyed by `authorized_keys` signer.
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Address to serve Prometheus metrics on (`METRICS_ADDR`); unset serves none.
+    pub metrics_addr: Option<SocketAddr>,
+    /// Cap on distinct tagged metric series, see [`ProofMetrics::new`].
+    pub max_tagged_series: Option<usize>,
+    /// Request proven by `generate_proof_or_default` when called without one, e.g. to pick the
+    /// default proving type.
+    pub default_proof_request: Option<ProofRequest>,
+}
+
+impl ServiceConfig {
+    /// Reads a config file: TOML if its extension is `.toml`, JSON otherwise.
+    pub fn from_file(path: &Path) -> Result<Self, ProofServiceError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ProofServiceError::ConfigError(format!("Failed to read config file {:?}: {}", path, e))
+        })?;
+        let invalid = |e: String| {
+            ProofServiceError::ConfigError(format!("Invalid config file {:?}: {}", path, e))
+        };
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            toml::from_str(&contents).map_err(|e| invalid(e.to_string()))
+        } else {
+            serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))
+        }
+    }
+
+    /// Loads the file named by [`SERVICE_CONFIG_ENV`] (defaults when unset), then applies the
+    /// process environment on top.
+    pub fn load() -> Result<Self, ProofServiceError> {
+        let mut config = match std::env::var(SERVICE_CONFIG_ENV) {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) => Self::default(),
+        };
+        config.apply_env_overrides(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    /// Overrides fields with the environment variables `var` returns, which take precedence over
+    /// the file.
+    pub fn apply_env_overrides(
+        &mut self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ProofServiceError> {
+        fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, ProofServiceError>
+        where
+            T::Err: std::fmt::Display,
+        {
+            value
+                .trim()
+                .parse()
+                .map_err(|e| ProofServiceError::ConfigError(format!("Invalid {}: {}", name, e)))
+        }
+
+        if let Some(value) = var("ETH_RPC_URL") {
+            self.eth_rpc_url = Some(parse("ETH_RPC_URL", &value)?);
+        }
+        if let Some(value) = var("REGISTRY_CONTRACT_ADDRESS") {
+            self.registry_contract_address = Some(value);
+        }
+        if let Some(value) = var("FALLBACK_REGISTRY_ADDRESSES") {
+            self.fallback_registries = value
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(value) = var("TEMP_DIR_BASE") {
+            self.temp_dir_base = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("SERVICE_MODE") {
+            self.service_mode = Some(value);
+        }
+        if let Some(value) = var("SHUTDOWN_GRACE_SECS") {
+            self.shutdown_grace_secs = Some(parse("SHUTDOWN_GRACE_SECS", &value)?);
+        }
+        if let Some(value) = var("CHECKPOINT_DIR") {
+            self.checkpoint_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("ELF_CACHE_DIR") {
+            self.elf_cache_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("ELF_CACHE_MAX_BYTES") {
+            self.elf_cache_max_bytes = Some(parse("ELF_CACHE_MAX_BYTES", &value)?);
+        }
+        if let Some(value) = var("PROVER_CACHE_CAPACITY") {
+            self.prover_cache_capacity = Some(parse("PROVER_CACHE_CAPACITY", &value)?);
+        }
+        if let Some(value) = var("PROVING_CONCURRENCY") {
+            self.proving_concurrency = Some(parse("PROVING_CONCURRENCY", &value)?);
+        }
+        if let Some(value) = var("REMOTE_PROVER_URL") {
+            self.remote_prover_url = Some(parse("REMOTE_PROVER_URL", &value)?);
+        }
+        if let Some(value) = var("PROVER_SUBPROCESS") {
+            self.prover_subprocess = value == "1" || value.eq_ignore_ascii_case("true");
+        }
+        if let Some(value) = var("EVM_OUTPUT_DIR") {
+            self.evm_output_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("EVM_OUTPUT_RETENTION_SECS") {
+            self.evm_output_retention_secs = Some(parse("EVM_OUTPUT_RETENTION_SECS", &value)?);
+        }
+        if let Some(value) = var("EVM_DOCKER_IMAGE") {
+            self.evm_docker_image = Some(value);
+        }
+        if let Some(value) = var("DUMP_INPUTS_DIR") {
+            self.dump_inputs_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("ALLOWED_TEMP_DIRS") {
+            self.allowed_temp_dirs = value
+                .split(',')
+                .map(str::trim)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .collect();
+        }
+        if let Some(value) = var("ALLOW_INSECURE") {
+            self.allow_insecure = value == "1" || value.eq_ignore_ascii_case("true");
+        }
+        if let Some(value) = var("ALLOWED_RPC_HOSTS") {
+            self.allowed_rpc_hosts = Some(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
+        if let Some(value) = var("OUTBOUND_PROXY_URL") {
+            self.http_proxy = Some(HttpProxyConfig {
+                url: parse("OUTBOUND_PROXY_URL", &value)?,
+                username: None,
+                password: None,
+            });
+        }
+        let username = var("OUTBOUND_PROXY_USERNAME");
+        let password = var("OUTBOUND_PROXY_PASSWORD");
+        if username.is_some() || password.is_some() {
+            let Some(proxy) = &mut self.http_proxy else {
+                return Err(ProofServiceError::ConfigError(
+                    "OUTBOUND_PROXY_USERNAME/PASSWORD set without a proxy URL".to_string(),
+                ));
+            };
+            if username.is_some() {
+                proxy.username = username;
+            }
+            if password.is_some() {
+                proxy.password = password;
+            }
+        }
+        if let Some(value) = var("S3_ENDPOINT") {
+            let endpoint = parse("S3_ENDPOINT", &value)?;
+            match &mut self.s3 {
+                Some(s3) => s3.endpoint = endpoint,
+                None => {
+                    self.s3 = Some(S3Config {
+                        endpoint,
+                        region: DEFAULT_S3_REGION.to_string(),
+                        access_key_id: String::new(),
+                        secret_access_key: String::new(),
+                        bucket: String::new(),
+                        key_prefix: String::new(),
+                    })
+                }
+            }
+        }
+        // The AWS variables are commonly set for other tools, so they only apply to a configured store
+        if let Some(s3) = &mut self.s3 {
+            if let Some(value) = var("S3_REGION") {
+                s3.region = value;
+            }
+            if let Some(value) = var("AWS_ACCESS_KEY_ID") {
+                s3.access_key_id = value;
+            }
+            if let Some(value) = var("AWS_SECRET_ACCESS_KEY") {
+                s3.secret_access_key = value;
+            }
+            if let Some(value) = var("S3_BUCKET") {
+                s3.bucket = value;
+            }
+            if let Some(value) = var("S3_KEY_PREFIX") {
+                s3.key_prefix = value;
+            }
+        }
+        if let Some(value) = var("AUTHORIZED_KEYS") {
+            self.authorized_keys = Some(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
+        if let Some(value) = var("METRICS_ADDR") {
+            self.metrics_addr = Some(parse("METRICS_ADDR", &value)?);
+        }
+        Ok(())
+    }
+
+    /// Time in-flight jobs get to finish on shutdown.
+    pub fn shutdown_grace(&self) -> Duration {
+        self.shutdown_grace_secs
+            .map_or(DEFAULT_SHUTDOWN_GRACE, Duration::from_secs)
+    }
+
+    /// Service mode, `ProveAndVerify` unless configured.
+    pub fn mode(&self) -> Result<ServiceMode, ProofServiceError> {
+        match &self.service_mode {
+            Some(mode) => ServiceMode::from_str(mode),
+            None => Ok(ServiceMode::default()),
+        }
+    }
+
+    /// Builds the service context the configuration describes.
+    pub fn build_context(&self) -> Result<ServiceContext, ProofServiceError> {
+        let missing = |field: &str, env: &str| {
+            ProofServiceError::ConfigError(format!(
+                "{} not configured (set it in the config file or {})",
+                field, env
+            ))
+        };
+        let parse_registry = |registry: &str| {
+            RegistryRef::from_str(registry).map_err(|e| {
+                ProofServiceError::ConfigError(format!("Invalid registry {:?}: {}", registry, e))
+            })
+        };
+
+        let eth_rpc_url = self
+            .eth_rpc_url
+            .clone()
+            .ok_or_else(|| missing("eth_rpc_url", "ETH_RPC_URL"))?;
+        let registry = self
+            .registry_contract_address
+            .as_deref()
+            .ok_or_else(|| missing("registry_contract_address", "REGISTRY_CONTRACT_ADDRESS"))?;
+        let temp_dir_base = self
+            .temp_dir_base
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_TEMP_DIR_BASE));
+
+        let mut ctx = ServiceContext::new(eth_rpc_url, parse_registry(registry)?, temp_dir_base)?;
+        ctx.mode = self.mode()?;
+        ctx.fallback_registries = self
+            .fallback_registries
+            .iter()
+            .map(|registry| parse_registry(registry))
+            .collect::<Result<_, _>>()?;
+        if let Some(dir) = &self.checkpoint_dir {
+            ctx.checkpoints = Some(CheckpointStore::new(dir)?);
+        }
+        if let Some(dir) = &self.elf_cache_dir {
+            let mut cache = ElfCache::new(dir)?;
+            if let Some(max_bytes) = self.elf_cache_max_bytes {
+                cache = cache.with_max_bytes(max_bytes);
+            }
+            ctx.elf_cache = Some(cache);
+        }
+        if let Some(capacity) = self.prover_cache_capacity {
+            ctx.prover_cache = ProverCache::new(capacity);
+        }
+        if let Some(permits) = self.proving_concurrency {
+            if permits == 0 {
+                return Err(ProofServiceError::ConfigError(
+                    "proving_concurrency must be at least 1".to_string(),
+                ));
+            }
+            ctx.proving_queue = Some(ProvingQueue::new(permits));
+        }
+        if let Some(endpoint) = &self.remote_prover_url {
+            let timeout = self
+                .remote_prover_timeout_secs
+                .map_or(DEFAULT_REMOTE_PROVER_TIMEOUT, Duration::from_secs);
+            let client = reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .map_err(|e| {
+                    ProofServiceError::ConfigError(format!(
+                        "Failed to build remote prover client: {}",
+                        e
+                    ))
+                })?;
+            info!(%endpoint, "Delegating proofs to remote prover");
+            ctx.prover_backend = Arc::new(RemoteProver::with_client(endpoint.clone(), client));
+        }
+        if self.prover_subprocess {
+            if self.remote_prover_url.is_some() {
+                return Err(ProofServiceError::ConfigError(
+                    "prover_subprocess can't be combined with remote_prover_url".to_string(),
+                ));
+            }
+            let prover = SubprocessProver::current_exe()?;
+            info!(program = ?prover.program, "Proving in prover subprocesses");
+            ctx.prover_backend = Arc::new(prover);
+        }
+        // Only prover subprocesses run the gnark container through the Docker shim applying it
+        if self.evm_docker_image.is_some() && !self.prover_subprocess {
+            return Err(ProofServiceError::ConfigError(
+                "evm_docker_image needs prover_subprocess; without it every FullWithEvm proof \
+                 would fail"
+                    .to_string(),
+            ));
+        }
+        ctx.evm_output_dir = self.evm_output_dir.clone();
+        ctx.evm_output_retention = self.evm_output_retention_secs.map(Duration::from_secs);
+        ctx.evm_docker_image = self.evm_docker_image.clone();
+        ctx.dump_inputs_dir = self.dump_inputs_dir.clone();
+        ctx.allowed_temp_dirs = self.allowed_temp_dirs.clone();
+        if self.allow_insecure {
+            warn!("allow_insecure is set: requests may skip program hash verification");
+            ctx.allow_insecure = true;
+        }
+        if let Some(max_input_bytes) = self.max_input_bytes {
+            ctx.max_input_bytes = max_input_bytes;
+        }
+        if let Some(max_prover_input_bytes) = self.max_prover_input_bytes {
+            ctx.max_prover_input_bytes = max_prover_input_bytes;
+        }
+        if let Some(max_elf_bytes) = self.max_elf_bytes {
+            ctx.max_elf_bytes = max_elf_bytes;
+        }
+        if let Some(hard_limits) = &self.hard_limits {
+            ctx.hard_limits = hard_limits.clone();
+        }
+        if let Some(proxy) = &self.http_proxy {
+            info!(proxy = %proxy.url, "Downloading through HTTP proxy");
+            ctx.use_http_proxy(proxy)?;
+        }
+        ctx.allowed_elf_content_types = self.allowed_elf_content_types.clone();
+        ctx.allowed_rpc_hosts = self.allowed_rpc_hosts.as_ref().map(|hosts| {
+            hosts
+                .iter()
+                .map(|host| host.trim().to_ascii_lowercase())
+                .collect()
+        });
+        ctx.min_prover_version = self.min_prover_version.clone();
+        if let Some(s3) = &self.s3 {
+            if s3.access_key_id.is_empty() || s3.secret_access_key.is_empty() {
+                return Err(ProofServiceError::ConfigError(
+                    "s3 requires credentials (AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY)"
+                        .to_string(),
+                ));
+            }
+            if s3.bucket.is_empty() {
+                return Err(ProofServiceError::ConfigError(
+                    "s3 requires the bucket proofs are uploaded to (S3_BUCKET)".to_string(),
+                ));
+            }
+            info!(endpoint = %s3.endpoint, bucket = %s3.bucket, key_prefix = %s3.key_prefix, "Uploading S3 output_sink proofs");
+            ctx.s3_store = Some(S3Store::new(s3.clone()));
+        }
+        if self.proof_cache {
+            let max_entries = self
+                .proof_cache_max_entries
+                .unwrap_or(DEFAULT_PROOF_CACHE_ENTRIES);
+            if max_entries == 0 {
+                return Err(ProofServiceError::ConfigError(
+                    "proof_cache_max_entries must be at least 1".to_string(),
+                ));
+            }
+            ctx.proof_cache = Some(ProofCache::new(max_entries));
+        }
+        if let Some(keys) = &self.authorized_keys {
+            if keys.is_empty() {
+                return Err(ProofServiceError::ConfigError(
+                    "authorized_keys is empty, so no request would be accepted".to_string(),
+                ));
+            }
+            let keys = keys
+                .iter()
+                .map(|key| {
+                    decode_hex(key)
+                        .ok()
+                        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                        .filter(|bytes| ed25519_dalek::VerifyingKey::from_bytes(bytes).is_ok())
+                        .ok_or_else(|| {
+                            ProofServiceError::ConfigError(format!(
+                                "Invalid authorized key {:?}: expected a hex ed25519 public key",
+                                key
+                            ))
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+            ctx.authorized_keys = Some(keys);
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            if ctx.authorized_keys.is_none() {
+                warn!("rate_limit without authorized_keys: all callers share one bucket");
+            }
+            ctx.rate_limiter = Some(RateLimiter::new(
+                rate_limit.capacity,
+                rate_limit.refill_per_sec,
+            )?);
+        }
+        ctx.default_proof_request = self.default_proof_request.clone();
+        if let Some(max_tagged_series) = self.max_tagged_series {
+            ctx.metrics = ProofMetrics::new(max_tagged_series);
+        }
+        Ok(ctx)
+    }
+}
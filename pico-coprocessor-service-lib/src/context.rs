@@ -1,27 +1,197 @@
 // pico-coprocessor-service-lib/src/context.rs
+use crate::backend::{LocalProver, ProverBackend};
+use crate::cache::ProofCache;
+use crate::checkpoint::CheckpointStore;
+use crate::elf_cache::ElfCache;
+use crate::ens::{self, ENS_REGISTRY_ADDRESS, EnsCache};
 use crate::errors::ProofServiceError;
+use crate::evm::ChainIdCache;
+use crate::metrics::ProofMetrics;
+use crate::pico::ProverCache;
+use crate::preprocess::InputPreprocessor;
+use crate::program::{DownloadDedup, HashProgressCallback};
+use crate::queue::ProvingQueue;
+use crate::rate_limit::{RateLimiter, UNAUTHENTICATED_CALLER};
+use crate::s3::S3Store;
+use crate::shutdown::InFlightTracker;
+use crate::types::{ProofRequest, RegistryRef};
 use blueprint_sdk::alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use url::Url;
 
+/// Default cap on decoded proof inputs (64 MiB).
+pub const DEFAULT_MAX_INPUT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default cap on the serialized inputs handed to the prover's stdin (64 MiB).
+pub const DEFAULT_MAX_PROVER_INPUT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default cap on downloaded and inline program ELFs (256 MiB).
+pub const DEFAULT_MAX_ELF_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Content types accepted for downloaded ELFs when `allowed_elf_content_types` is enabled.
+pub const DEFAULT_ELF_CONTENT_TYPES: &[&str] = &[
+    "application/octet-stream",
+    "application/x-elf",
+    "application/x-executable",
+];
+
+/// Caps on coprocessor request contents, enforced regardless of the request's declared `max_sizes`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HardLimits {
+    pub max_receipts_hard: usize,
+    pub max_logs_per_receipt_hard: usize,
+    pub max_storage_slots_hard: usize,
+    pub max_transactions_hard: usize,
+}
+
+impl Default for HardLimits {
+    fn default() -> Self {
+        Self {
+            max_receipts_hard: 4096,
+            max_logs_per_receipt_hard: 256,
+            max_storage_slots_hard: 4096,
+            max_transactions_hard: 4096,
+        }
+    }
+}
+
+/// Proxy the service's HTTP client connects through for program, schema and blockchain data
+/// downloads, with optional basic-auth credentials. The password is left out of `Debug`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HttpProxyConfig {
+    pub url: Url,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl std::fmt::Debug for HttpProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpProxyConfig")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Which jobs a deployment of the service runs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ServiceMode {
+    /// Generate and verify proofs.
+    #[default]
+    ProveAndVerify,
+    /// Only verify proofs; proving jobs are rejected (no prover resources needed).
+    VerifyOnly,
+}
+
+impl FromStr for ServiceMode {
+    type Err = ProofServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "prove-and-verify" | "prove_and_verify" | "proveandverify" => Ok(Self::ProveAndVerify),
+            "verify-only" | "verify_only" | "verifyonly" => Ok(Self::VerifyOnly),
+            other => Err(ProofServiceError::ConfigError(format!(
+                "Unknown service mode: {}",
+                other
+            ))),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ServiceContext {
     // Client for downloading ELF files
     pub http_client: reqwest::Client,
     // Default configuration for interacting with Ethereum node and registry contract
     pub eth_rpc_url: Url,
-    pub registry_contract_address: Address,
+    pub registry_contract_address: RegistryRef,
+    // Registries queried in order when a program isn't in the primary (or overridden) one
+    pub fallback_registries: Vec<RegistryRef>,
+    // ENS registry used to resolve registry names, and names resolved so far
+    pub ens_registry_address: Address,
+    pub ens_cache: EnsCache,
+    // Chain IDs of the RPC endpoints results were recorded against
+    pub chain_ids: ChainIdCache,
     // Base path for storing temporary files (downloaded ELFs, proof outputs)
     pub temp_dir_base: PathBuf,
+    // Persistent home for FullWithEvm artifacts (ProofResult::output_dir); None uses the job's temp dir
+    pub evm_output_dir: Option<PathBuf>,
+    // Age after which artifact dirs under evm_output_dir are pruned; None keeps them
+    pub evm_output_retention: Option<Duration>,
+    // gnark image for FullWithEvm proving (e.g. pinned by digest); None uses Pico's default
+    pub evm_docker_image: Option<String>,
+    // Directory requests' dump_inputs_to files are written to; None rejects dumps
+    pub dump_inputs_dir: Option<PathBuf>,
+    // Directories a request's temp_dir_override may be or lie beneath; empty rejects overrides
+    pub allowed_temp_dirs: Vec<PathBuf>,
+    // Honor requests' insecure_skip_hash_verify for local programs; development only
+    pub allow_insecure: bool,
+    // Whether this node proves or only verifies
+    pub mode: ServiceMode,
+    // Hooks applied to coprocessor BlockchainData before it is sent to the zkVM, in order
+    pub input_preprocessors: Vec<Arc<dyn InputPreprocessor>>,
+    // Request proven by generate_proof_or_default when called without one; None makes it an error
+    pub default_proof_request: Option<ProofRequest>,
+    // Upper bound on decoded `inputs` bytes accepted by generate_proof
+    pub max_input_bytes: usize,
+    // Upper bound on the serialized coprocessor inputs (bundle or SDK inputs) written to the prover's stdin
+    pub max_prover_input_bytes: usize,
+    // Upper bound on a downloaded or data URI program ELF
+    pub max_elf_bytes: u64,
+    // Prove fetched programs from a memory mapping of the job's copy instead of reading them
+    pub mmap_programs: bool,
+    // Concurrent fetches of the same program share one download; None downloads once per request
+    pub download_dedup: Option<DownloadDedup>,
+    // Verified ELFs kept by program hash across requests (and warm_program); None fetches every time
+    pub elf_cache: Option<ElfCache>,
+    // Prover clients and Full prover chains kept for later proofs of the same ELF (and warm_program)
+    pub prover_cache: ProverCache,
+    // Notified as program ELFs are hashed (downloads and local copies); None reports nothing
+    pub hash_progress: Option<HashProgressCallback>,
+    // Content types a program download must declare; None accepts any response
+    pub allowed_elf_content_types: Option<Vec<String>>,
+    // Bounds on coprocessor BlockchainData independent of the requested max_sizes
+    pub hard_limits: HardLimits,
+    // Oldest prover version whose proofs verify_proof accepts; None accepts any
+    pub min_prover_version: Option<String>,
+    // Hosts a request's eth_rpc_url_override may point at (lowercase); None allows any
+    pub allowed_rpc_hosts: Option<HashSet<String>>,
+    // ed25519 public keys allowed to submit requests; None disables authentication
+    pub authorized_keys: Option<HashSet<[u8; 32]>>,
+    // Per-caller limit on proving jobs, unauthenticated callers sharing one bucket; None disables it
+    pub rate_limiter: Option<RateLimiter>,
+    // Proving jobs in progress, drained on shutdown
+    pub in_flight: InFlightTracker,
+    // Computes proofs: in process by default, in a child process (SubprocessProver) to contain
+    // prover crashes, or delegated to a remote prover
+    pub prover_backend: Arc<dyn ProverBackend>,
+    // Limits concurrent proofs, granting slots by request priority; None runs every proof at once
+    pub proving_queue: Option<ProvingQueue>,
+    // Finished proofs served again for identical requests; None disables caching
+    pub proof_cache: Option<ProofCache>,
+    // Durable checkpoints for resuming interrupted Full proofs; None disables them
+    pub checkpoints: Option<CheckpointStore>,
+    // Object storage for requests with an S3 output_sink; None rejects such requests
+    pub s3_store: Option<S3Store>,
+    // Counters of returned proofs, labelled with the requests' tags
+    pub metrics: ProofMetrics,
 }
 
 impl ServiceContext {
     pub fn new(
         default_eth_rpc_url: Url,
-        default_registry_contract_address: Address,
+        default_registry_contract_address: impl Into<RegistryRef>,
         temp_dir_base: PathBuf,
     ) -> Result<Self, ProofServiceError> {
-        // Validate temp dir exists and is writable? Or create if not exists?
+        // Create the temp dir if missing, then make sure proofs will be able to write to it
         if !temp_dir_base.exists() {
             std::fs::create_dir_all(&temp_dir_base).map_err(|e| {
                 ProofServiceError::ConfigError(format!(
@@ -35,6 +205,13 @@ impl ServiceContext {
                 temp_dir_base
             )));
         }
+        // Probe with a file that is removed on drop, so a read-only mount fails here, not mid-proof
+        tempfile::NamedTempFile::new_in(&temp_dir_base).map_err(|e| {
+            ProofServiceError::ConfigError(format!(
+                "temp dir not writable: {:?}: {}",
+                temp_dir_base, e
+            ))
+        })?;
 
         let http_c = reqwest::Client::builder().build().map_err(|e| {
             ProofServiceError::ConfigError(format!("Failed to build HTTP client: {}", e))
@@ -43,13 +220,148 @@ impl ServiceContext {
         Ok(Self {
             http_client: http_c,
             eth_rpc_url: default_eth_rpc_url,
-            registry_contract_address: default_registry_contract_address,
+            registry_contract_address: default_registry_contract_address.into(),
+            fallback_registries: Vec::new(),
+            ens_registry_address: ENS_REGISTRY_ADDRESS,
+            ens_cache: EnsCache::default(),
+            chain_ids: ChainIdCache::default(),
             temp_dir_base,
+            evm_output_dir: None,
+            evm_output_retention: None,
+            evm_docker_image: None,
+            dump_inputs_dir: None,
+            allowed_temp_dirs: Vec::new(),
+            allow_insecure: false,
+            mode: ServiceMode::default(),
+            input_preprocessors: Vec::new(),
+            default_proof_request: None,
+            max_input_bytes: DEFAULT_MAX_INPUT_BYTES,
+            max_prover_input_bytes: DEFAULT_MAX_PROVER_INPUT_BYTES,
+            max_elf_bytes: DEFAULT_MAX_ELF_BYTES,
+            mmap_programs: false,
+            download_dedup: Some(DownloadDedup::default()),
+            elf_cache: None,
+            prover_cache: ProverCache::default(),
+            hash_progress: None,
+            allowed_elf_content_types: None,
+            hard_limits: HardLimits::default(),
+            min_prover_version: None,
+            allowed_rpc_hosts: None,
+            authorized_keys: None,
+            rate_limiter: None,
+            in_flight: InFlightTracker::default(),
+            prover_backend: Arc::new(LocalProver),
+            proving_queue: None,
+            proof_cache: None,
+            checkpoints: None,
+            s3_store: None,
+            metrics: ProofMetrics::default(),
         })
     }
 
-    // Helper to get registry address, considering override
-    pub fn get_registry_address(&self) -> Address {
-        self.registry_contract_address
+    // Registry address for a request, considering override; ENS names resolve via eth_rpc_url and are cached
+    pub async fn get_registry_address(
+        &self,
+        registry_override: Option<&RegistryRef>,
+    ) -> Result<Address, ProofServiceError> {
+        self.resolve_registry(registry_override.unwrap_or(&self.registry_contract_address))
+            .await
+    }
+
+    // Registries to search for a program, in priority order: the override (or primary), then the fallbacks
+    pub fn registry_search_order<'a>(
+        &'a self,
+        registry_override: Option<&'a RegistryRef>,
+    ) -> impl Iterator<Item = &'a RegistryRef> {
+        std::iter::once(registry_override.unwrap_or(&self.registry_contract_address))
+            .chain(&self.fallback_registries)
+    }
+
+    // Address of a registry, resolving ENS names via eth_rpc_url with caching
+    pub async fn resolve_registry(
+        &self,
+        registry: &RegistryRef,
+    ) -> Result<Address, ProofServiceError> {
+        match registry {
+            RegistryRef::Address(address) => Ok(*address),
+            RegistryRef::Ens(name) => {
+                ens::resolve_ens_name(
+                    &self.eth_rpc_url,
+                    self.ens_registry_address,
+                    name,
+                    &self.ens_cache,
+                )
+                .await
+            }
+        }
+    }
+
+    // Takes a token from the caller's rate limit bucket; unauthenticated (None) callers share one
+    pub fn check_rate_limit(&self, caller: Option<&str>) -> Result<(), ProofServiceError> {
+        match &self.rate_limiter {
+            Some(limiter) => limiter.check(caller.unwrap_or(UNAUTHENTICATED_CALLER)),
+            None => Ok(()),
+        }
+    }
+
+    // Takes `n` tokens from the caller's rate limit bucket, all of them or none
+    pub fn check_rate_limit_n(
+        &self,
+        caller: Option<&str>,
+        n: u32,
+    ) -> Result<(), ProofServiceError> {
+        match &self.rate_limiter {
+            Some(limiter) => limiter.check_n(caller.unwrap_or(UNAUTHENTICATED_CALLER), n),
+            None => Ok(()),
+        }
+    }
+
+    // RPC endpoint a request's chain data and registry lookups go to: its override, once checked to be
+    // a URL on an allow-listed host, or eth_rpc_url
+    pub fn rpc_url_for(&self, rpc_override: Option<&str>) -> Result<Url, ProofServiceError> {
+        let Some(rpc_override) = rpc_override else {
+            return Ok(self.eth_rpc_url.clone());
+        };
+        let url = Url::parse(rpc_override).map_err(|e| {
+            ProofServiceError::InvalidInput(format!("Invalid eth_rpc_url_override: {}", e))
+        })?;
+        if let Some(allowed) = &self.allowed_rpc_hosts {
+            let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+            if !allowed.contains(&host) {
+                return Err(ProofServiceError::InvalidInput(format!(
+                    "rpc host not allowed: {}",
+                    host
+                )));
+            }
+        }
+        Ok(url)
+    }
+
+    // Rebuilds http_client to connect through the proxy; without a call downloads connect directly
+    pub fn use_http_proxy(&mut self, proxy: &HttpProxyConfig) -> Result<(), ProofServiceError> {
+        let mut reqwest_proxy = reqwest::Proxy::all(proxy.url.as_str()).map_err(|e| {
+            ProofServiceError::ConfigError(format!("Invalid HTTP proxy {}: {}", proxy.url, e))
+        })?;
+        if let Some(username) = &proxy.username {
+            reqwest_proxy =
+                reqwest_proxy.basic_auth(username, proxy.password.as_deref().unwrap_or_default());
+        }
+        self.http_client = reqwest::Client::builder()
+            .proxy(reqwest_proxy)
+            .build()
+            .map_err(|e| {
+                ProofServiceError::ConfigError(format!("Failed to build HTTP client: {}", e))
+            })?;
+        Ok(())
+    }
+
+    // Rejects proving jobs on verify-only nodes
+    pub fn ensure_can_prove(&self) -> Result<(), ProofServiceError> {
+        match self.mode {
+            ServiceMode::ProveAndVerify => Ok(()),
+            ServiceMode::VerifyOnly => Err(ProofServiceError::UnsupportedProvingType(
+                "service is verify-only".to_string(),
+            )),
+        }
     }
 }
@@ -0,0 +1,236 @@
+// pico-coprocessor-service-lib/src/elf_cache.rs
+use crate::errors::ProofServiceError;
+use blueprint_sdk::debug;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Subdirectory holding each distinct ELF once, as `{sha256 of the ELF}.elf`
+const CONTENT_DIR: &str = "content";
+
+/// Directory of verified program ELFs stored as `{program_hash}.elf`, so a program is fetched
+/// once and later requests for the same hash skip the registry lookup and the download.
+///
+/// Entries are only added after their hash has been verified, and `warm_program` fills it ahead
+/// of the first request. Like the checkpoint directory it should outlive restarts.
+///
+/// The bytes live in a content-addressed store under the directory, and each program hash entry
+/// is a hard link into it, so registry entries for byte-identical ELFs share one stored copy.
+///
+/// With [`with_max_bytes`](Self::with_max_bytes) the store is kept under a size bound: inserting
+/// past it evicts the least recently used ELFs, together with every entry for them.
+#[derive(Debug, Clone)]
+pub struct ElfCache {
+    dir: PathBuf,
+    max_bytes: Option<u64>,
+}
+
+impl ElfCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, ProofServiceError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(dir.join(CONTENT_DIR)).map_err(|e| {
+            ProofServiceError::ConfigError(format!(
+                "Failed to create ELF cache dir {:?}: {}",
+                dir, e
+            ))
+        })?;
+        Ok(Self {
+            dir,
+            max_bytes: None,
+        })
+    }
+
+    /// Bounds the stored ELFs to `max_bytes` in total.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Bound on the stored ELFs' total size; `None` never evicts.
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    /// Directory of the content-addressed store, with one `{sha256}.elf` per distinct ELF.
+    pub fn content_dir(&self) -> PathBuf {
+        self.dir.join(CONTENT_DIR)
+    }
+
+    /// Path of the cached ELF for `program_hash`, if it has been cached.
+    pub fn get(&self, program_hash: &str) -> Option<PathBuf> {
+        let path = self.path_for(program_hash).ok()?;
+        if !path.is_file() {
+            return None;
+        }
+        // Eviction goes by modification time; entries share it with their stored copy
+        if self.max_bytes.is_some() {
+            let _ = std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+        }
+        Some(path)
+    }
+
+    /// Stores the verified ELF at `elf_path` under `program_hash`, returning its path.
+    ///
+    /// An ELF already stored for another program hash isn't copied again: the new entry links to
+    /// the stored copy, or gets a copy of its own where hard links aren't supported. An ELF larger
+    /// than [`max_bytes`](Self::max_bytes) is refused.
+    pub fn insert(
+        &self,
+        program_hash: &str,
+        elf_path: &Path,
+    ) -> Result<PathBuf, ProofServiceError> {
+        self.insert_with_digest(program_hash, elf_path, None)
+    }
+
+    /// [`insert`](Self::insert) for an ELF whose SHA-256 hex digest is already known, e.g. from
+    /// verifying it, so it isn't read and hashed again to find its stored copy.
+    pub fn insert_with_digest(
+        &self,
+        program_hash: &str,
+        elf_path: &Path,
+        sha256_hex: Option<&str>,
+    ) -> Result<PathBuf, ProofServiceError> {
+        let path = self.path_for(program_hash)?;
+        if let Some(max_bytes) = self.max_bytes {
+            let len = std::fs::metadata(elf_path)?.len();
+            if len > max_bytes {
+                return Err(ProofServiceError::InvalidInput(format!(
+                    "ELF of {} bytes exceeds the ELF cache's {} bytes",
+                    len, max_bytes
+                )));
+            }
+        }
+        let content = self.store_content(elf_path, sha256_hex)?;
+        // Link then rename so a concurrent reader never sees a partial entry
+        let staging = tempfile::tempdir_in(&self.dir)?;
+        let staged = staging.path().join("program.elf");
+        if std::fs::hard_link(&content, &staged).is_err() {
+            std::fs::copy(&content, &staged)?;
+        }
+        std::fs::rename(&staged, &path)?;
+        debug!(program_hash, path = ?path, content = ?content, "Cached program ELF");
+        self.evict(&content)?;
+        Ok(path)
+    }
+
+    /// The entry for `program_hash`, inserting the ELF at `elf_path` on a miss, with the file
+    /// system work done on a blocking thread so async callers don't stall the runtime.
+    pub async fn get_or_insert(
+        &self,
+        program_hash: &str,
+        elf_path: &Path,
+        sha256_hex: Option<&str>,
+    ) -> Result<PathBuf, ProofServiceError> {
+        let cache = self.clone();
+        let program_hash = program_hash.to_string();
+        let elf_path = elf_path.to_path_buf();
+        let sha256_hex = sha256_hex.map(str::to_string);
+        tokio::task::spawn_blocking(move || match cache.get(&program_hash) {
+            Some(path) => Ok(path),
+            None => cache.insert_with_digest(&program_hash, &elf_path, sha256_hex.as_deref()),
+        })
+        .await
+        .map_err(|e| ProofServiceError::InternalError(format!("ELF cache insert failed: {}", e)))?
+    }
+
+    // Removes the least recently used ELFs until the store fits max_bytes, sparing `keep`
+    fn evict(&self, keep: &Path) -> Result<(), ProofServiceError> {
+        let Some(max_bytes) = self.max_bytes else {
+            return Ok(());
+        };
+        let mut stored = Vec::new();
+        for entry in std::fs::read_dir(self.content_dir())? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                stored.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+        let mut total: u64 = stored.iter().map(|(_, len, _)| len).sum();
+        stored.sort();
+        for (_, len, content) in stored {
+            if total <= max_bytes {
+                break;
+            }
+            if content != keep {
+                self.remove_content(&content, len)?;
+                total -= len;
+            }
+        }
+        Ok(())
+    }
+
+    // Removes a stored ELF of `len` bytes along with the program hash entries holding its bytes
+    fn remove_content(&self, content: &Path, len: u64) -> Result<(), ProofServiceError> {
+        let digest = content
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() || metadata.len() != len {
+                continue;
+            }
+            let mut hasher = Sha256::new();
+            std::io::copy(&mut std::fs::File::open(&path)?, &mut hasher)?;
+            if hex::encode(hasher.finalize()) == digest {
+                std::fs::remove_file(&path)?;
+            }
+        }
+        std::fs::remove_file(content)?;
+        debug!(content = ?content, "Evicted program ELF from the cache");
+        Ok(())
+    }
+
+    // Copies the ELF into the content store unless an identical one is there, returning its path
+    fn store_content(
+        &self,
+        elf_path: &Path,
+        sha256_hex: Option<&str>,
+    ) -> Result<PathBuf, ProofServiceError> {
+        let digest = match sha256_hex {
+            Some(hex) => hex.trim_start_matches("0x").to_ascii_lowercase(),
+            None => {
+                let mut hasher = Sha256::new();
+                std::io::copy(&mut std::fs::File::open(elf_path)?, &mut hasher)?;
+                hex::encode(hasher.finalize())
+            }
+        };
+        if digest.is_empty() || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Invalid SHA-256 digest for the ELF cache: {}",
+                digest
+            )));
+        }
+        let content_dir = self.content_dir();
+        let path = content_dir.join(format!("{}.elf", digest));
+        if !path.is_file() {
+            // Copy then rename so a concurrent reader never sees a partial ELF
+            let tmp = tempfile::NamedTempFile::new_in(&content_dir)?;
+            std::fs::copy(elf_path, tmp.path())?;
+            tmp.persist(&path).map_err(|e| e.error)?;
+        }
+        Ok(path)
+    }
+
+    // Hashes come from requests, so only hex names are accepted for the file
+    fn path_for(&self, program_hash: &str) -> Result<PathBuf, ProofServiceError> {
+        let hash = program_hash.trim_start_matches("0x").to_ascii_lowercase();
+        if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Invalid program hash for the ELF cache: {}",
+                program_hash
+            )));
+        }
+        Ok(self.dir.join(format!("{}.elf", hash)))
+    }
+}
@@ -0,0 +1,175 @@
+// pico-coprocessor-service-lib/src/encoding.rs
+use crate::errors::ProofServiceError;
+use crate::types::{InputsEncoding, OutputEncoding, ProofResult};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::alloy::primitives::{B256, keccak256};
+use parity_scale_codec::{Decode, Encode};
+use std::io::Read;
+use std::str::FromStr;
+
+/// Largest size compressed public values may expand to in [`ProofResult::public_values_bytes`]
+/// (64 MiB), so a small result can't decompress into an allocation the process can't hold.
+pub const MAX_PUBLIC_VALUES_BYTES: usize = 64 * 1024 * 1024;
+
+/// `value` without a leading `0x` (or `0X`), which clients routinely put on hex strings.
+pub fn strip_hex_prefix(value: &str) -> &str {
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+}
+
+/// Decodes hex given with or without a `0x` prefix, for every hex field the service accepts.
+pub fn decode_hex(value: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    hex::decode(strip_hex_prefix(value))
+}
+
+impl OutputEncoding {
+    pub fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            OutputEncoding::Hex => hex::encode(bytes),
+            OutputEncoding::Base64 => STANDARD.encode(bytes),
+        }
+    }
+
+    pub fn decode(self, encoded: &str) -> Result<Vec<u8>, ProofServiceError> {
+        match self {
+            OutputEncoding::Hex => Ok(decode_hex(encoded)?),
+            OutputEncoding::Base64 => STANDARD.decode(encoded).map_err(|e| {
+                ProofServiceError::InvalidInput(format!("Invalid base64 field: {}", e))
+            }),
+        }
+    }
+}
+
+/// Re-encodes the byte fields of `result` (`proof`, `public_values`, `inputs`, `public_values_decoded`,
+/// `riscv_proof`)
+/// from its current encoding to `encoding`, recording the new encoding on the result.
+/// `inputs` holding JSON text ([`InputsEncoding::JsonUtf8`]) are left unchanged.
+pub fn reencode_result(
+    mut result: ProofResult,
+    encoding: OutputEncoding,
+) -> Result<ProofResult, ProofServiceError> {
+    let current = result.output_encoding;
+    if current == encoding {
+        return Ok(result);
+    }
+    let convert = |field: &str| -> Result<String, ProofServiceError> {
+        Ok(encoding.encode(&current.decode(field)?))
+    };
+    result.proof = convert(&result.proof)?;
+    result.public_values = convert(&result.public_values)?;
+    // JSON text inputs aren't byte encoded, so they stay as they are
+    if result.inputs_encoding != InputsEncoding::JsonUtf8 {
+        result.inputs = convert(&result.inputs)?;
+    }
+    if let Some(decoded) = &result.public_values_decoded {
+        result.public_values_decoded = Some(convert(decoded)?);
+    }
+    if let Some(riscv_proof) = &result.riscv_proof {
+        result.riscv_proof = Some(convert(riscv_proof)?);
+    }
+    result.output_encoding = encoding;
+    Ok(result)
+}
+
+/// Replaces `public_values` with its zstd compression (encoded per `output_encoding`, like the raw
+/// bytes) and sets `public_values_compressed`. Read the bytes back with
+/// [`ProofResult::public_values_bytes`].
+pub fn compress_public_values(mut result: ProofResult) -> Result<ProofResult, ProofServiceError> {
+    if result.public_values_compressed {
+        return Ok(result);
+    }
+    let raw = result.output_encoding.decode(&result.public_values)?;
+    let compressed = zstd::encode_all(raw.as_slice(), 0)?;
+    result.public_values = result.output_encoding.encode(&compressed);
+    result.public_values_compressed = true;
+    Ok(result)
+}
+
+/// The fields of a proof an on-chain verifier needs, SCALE encoded by [`ProofResult::to_scale`].
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct ProofMetadata {
+    pub program_hash: [u8; 32],
+    pub public_values: Vec<u8>,
+    /// keccak256 of the raw proof bytes.
+    pub proof_commitment: [u8; 32],
+}
+
+impl ProofMetadata {
+    pub fn from_scale(mut bytes: &[u8]) -> Result<Self, ProofServiceError> {
+        Self::decode(&mut bytes).map_err(|e| {
+            ProofServiceError::InvalidInput(format!("Invalid SCALE proof metadata: {}", e))
+        })
+    }
+}
+
+impl ProofResult {
+    /// SCALE encoding of the result's [`ProofMetadata`], for on-chain verifiers; the JSON
+    /// serialization of the full result remains the format for off-chain tooling.
+    pub fn to_scale(&self) -> Result<Vec<u8>, ProofServiceError> {
+        let program_hash = B256::from_str(&self.program_hash).map_err(|_| {
+            ProofServiceError::InvalidInput(format!(
+                "Invalid program_hash format (expected 32-byte hex): {}",
+                self.program_hash
+            ))
+        })?;
+        let metadata = ProofMetadata {
+            program_hash: program_hash.0,
+            public_values: self.public_values_bytes()?,
+            proof_commitment: self.compute_proof_commitment()?.0,
+        };
+        Ok(metadata.encode())
+    }
+
+    /// `keccak256` of the raw proof bytes, whatever `output_encoding` they are held in.
+    pub fn compute_proof_commitment(&self) -> Result<B256, ProofServiceError> {
+        Ok(keccak256(self.output_encoding.decode(&self.proof)?))
+    }
+
+    /// The public values the program committed, decoded according to `output_encoding` and
+    /// decompressed when `public_values_compressed` is set, up to [`MAX_PUBLIC_VALUES_BYTES`].
+    pub fn public_values_bytes(&self) -> Result<Vec<u8>, ProofServiceError> {
+        self.public_values_bytes_within(MAX_PUBLIC_VALUES_BYTES)
+    }
+
+    /// [`public_values_bytes`](Self::public_values_bytes), failing when compressed public values
+    /// expand past `max_bytes`.
+    pub fn public_values_bytes_within(
+        &self,
+        max_bytes: usize,
+    ) -> Result<Vec<u8>, ProofServiceError> {
+        let bytes = self.output_encoding.decode(&self.public_values)?;
+        if !self.public_values_compressed {
+            return Ok(bytes);
+        }
+        let invalid = |e: std::io::Error| {
+            ProofServiceError::InvalidInput(format!("Invalid compressed public values: {}", e))
+        };
+        let mut decompressed = Vec::new();
+        zstd::Decoder::new(bytes.as_slice())
+            .map_err(invalid)?
+            .take(max_bytes as u64 + 1)
+            .read_to_end(&mut decompressed)
+            .map_err(invalid)?;
+        if decompressed.len() > max_bytes {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Compressed public values expand past {} bytes",
+                max_bytes
+            )));
+        }
+        Ok(decompressed)
+    }
+
+    /// The bytes the program read as input, decoded according to `inputs_encoding` and
+    /// `output_encoding`.
+    pub fn decode_inputs(&self) -> Result<Vec<u8>, ProofServiceError> {
+        match self.inputs_encoding {
+            InputsEncoding::JsonUtf8 => Ok(self.inputs.as_bytes().to_vec()),
+            InputsEncoding::Hex | InputsEncoding::HexOfJson => {
+                self.output_encoding.decode(&self.inputs)
+            }
+        }
+    }
+}
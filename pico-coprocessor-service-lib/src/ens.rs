@@ -0,0 +1,102 @@
+// pico-coprocessor-service-lib/src/ens.rs
+use crate::errors::ProofServiceError;
+use blueprint_sdk::{
+    alloy::{
+        primitives::{Address, B256, address, keccak256},
+        sol,
+    },
+    evm::util::get_provider_http,
+};
+use blueprint_sdk::{debug, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// ENS registry deployment shared by mainnet and the public testnets.
+pub const ENS_REGISTRY_ADDRESS: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+sol! {
+    #[sol(rpc)]
+    interface EnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+
+    #[sol(rpc)]
+    interface EnsAddrResolver {
+        function addr(bytes32 node) external view returns (address);
+    }
+}
+
+/// Resolved ENS names, shared by every clone of the context.
+#[derive(Debug, Clone, Default)]
+pub struct EnsCache {
+    entries: Arc<Mutex<HashMap<String, Address>>>,
+}
+
+impl EnsCache {
+    pub fn get(&self, name: &str) -> Option<Address> {
+        self.entries.lock().unwrap().get(name).copied()
+    }
+
+    pub fn insert(&self, name: &str, address: Address) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), address);
+    }
+}
+
+/// EIP-137 namehash of `name`.
+pub fn namehash(name: &str) -> B256 {
+    let mut node = B256::ZERO;
+    if name.is_empty() {
+        return node;
+    }
+    for label in name.rsplit('.') {
+        node = keccak256([node.as_slice(), keccak256(label.as_bytes()).as_slice()].concat());
+    }
+    node
+}
+
+/// Resolves `name` to an address through the ENS registry at `ens_registry`, consulting `cache` first.
+pub async fn resolve_ens_name(
+    rpc_url: &Url,
+    ens_registry: Address,
+    name: &str,
+    cache: &EnsCache,
+) -> Result<Address, ProofServiceError> {
+    let name = name.to_ascii_lowercase();
+    if let Some(address) = cache.get(&name) {
+        return Ok(address);
+    }
+    debug!(%name, %ens_registry, "Resolving ENS name");
+
+    let node = namehash(&name);
+    let provider = get_provider_http(rpc_url.as_str());
+    let resolver = EnsRegistry::new(ens_registry, provider.clone())
+        .resolver(node)
+        .call()
+        .await?
+        ._0;
+    if resolver == Address::ZERO {
+        return Err(ProofServiceError::ConfigError(format!(
+            "ENS name {} has no resolver",
+            name
+        )));
+    }
+    let address = EnsAddrResolver::new(resolver, provider)
+        .addr(node)
+        .call()
+        .await?
+        ._0;
+    if address == Address::ZERO {
+        return Err(ProofServiceError::ConfigError(format!(
+            "ENS name {} does not resolve to an address",
+            name
+        )));
+    }
+
+    info!(%name, %address, "Resolved ENS name");
+    cache.insert(&name, address);
+    Ok(address)
+}
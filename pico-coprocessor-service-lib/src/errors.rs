@@ -20,12 +20,18 @@ pub enum ProofServiceError {
     ProgramHashMismatch { expected: String, got: String },
     #[error("Invalid Input Data: {0}")]
     InvalidInput(String),
+    #[error("Inputs Too Large: {size} bytes exceeds the limit of {limit} bytes")]
+    InputsTooLarge { size: usize, limit: usize },
     #[error("Proving Error: {0}")]
     ProvingError(String),
+    #[error("Proof Artifact Missing: {artifact} not found in {dir}")]
+    ArtifactMissing { artifact: String, dir: String },
     #[error("Serialization/Deserialization Error: {0}")]
     SerdeError(#[from] serde_json::Error),
     #[error("Blockchain Interaction Error: {0}")]
     BlockchainError(String),
+    #[error("Chain Data Mismatch: {0}")]
+    ChainDataMismatch(String),
     #[error("Invalid Program Location URL: {0}")]
     InvalidUrl(#[from] url::ParseError),
     #[error("Unsupported Proving Type: {0}")]
@@ -34,12 +40,107 @@ pub enum ProofServiceError {
     TempDirError(String),
     #[error("Hex Decoding Error: {0}")]
     HexError(#[from] hex::FromHexError),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Rate Limited: retry after {retry_after:?}")]
+    RateLimited { retry_after: std::time::Duration },
+    #[error("Service Shutting Down")]
+    ShuttingDown,
     #[error("Internal Error: {0}")]
     InternalError(String),
     #[error("Blueprint SDK Error: {0}")]
     BlueprintSdkError(#[from] BlueprintSdkError),
 }
 
+impl ProofServiceError {
+    /// Stable numeric code reported to the chain alongside the message.
+    ///
+    /// Codes are grouped by cause (1xxx request, 2xxx program, 3xxx proving, 4xxx chain,
+    /// 5xxx service) and must never be renumbered once released.
+    pub fn code(&self) -> u16 {
+        match self {
+            ProofServiceError::InvalidInput(_) => 1000,
+            ProofServiceError::InputsTooLarge { .. } => 1001,
+            ProofServiceError::UnsupportedProvingType(_) => 1002,
+            ProofServiceError::SerdeError(_) => 1003,
+            ProofServiceError::HexError(_) => 1004,
+            ProofServiceError::InvalidUrl(_) => 1005,
+            ProofServiceError::Unauthorized(_) => 1006,
+            ProofServiceError::RateLimited { .. } => 1007,
+            ProofServiceError::ProgramNotFoundInRegistry(_) => 2000,
+            ProofServiceError::ProgramDownloadFailed(_) => 2001,
+            ProofServiceError::ProgramHashMismatch { .. } => 2002,
+            ProofServiceError::NetworkError(_) => 2003,
+            ProofServiceError::ProvingError(_) => 3000,
+            ProofServiceError::ArtifactMissing { .. } => 3001,
+            ProofServiceError::ContractCallError(_) => 4000,
+            ProofServiceError::BlockchainError(_) => 4001,
+            ProofServiceError::ChainDataMismatch(_) => 4002,
+            ProofServiceError::ConfigError(_) => 5000,
+            ProofServiceError::IoError(_) => 5001,
+            ProofServiceError::TempDirError(_) => 5002,
+            ProofServiceError::ShuttingDown => 5003,
+            ProofServiceError::InternalError(_) => 5004,
+            ProofServiceError::BlueprintSdkError(_) => 5005,
+        }
+    }
+
+    /// Whether the same request may succeed if retried later, for callers running their own retry
+    /// loops: network and RPC failures, rate limiting and a node shutting down.
+    ///
+    /// Errors caused by the request or program (bad inputs, hash mismatches, failed proofs) are
+    /// not retryable, and neither are variants that mix transient and permanent causes.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProofServiceError::NetworkError(_)
+            | ProofServiceError::RateLimited { .. }
+            | ProofServiceError::ShuttingDown
+            // Failed or missing block fetches are RPC trouble; a chain that disagrees with the
+            // request is ChainDataMismatch instead
+            | ProofServiceError::BlockchainError(_) => true,
+            // A revert answers the same way every time; a transport failure may not
+            ProofServiceError::ContractCallError(e) => {
+                matches!(e, blueprint_sdk::alloy::contract::Error::TransportError(_))
+            }
+            ProofServiceError::ConfigError(_)
+            | ProofServiceError::ChainDataMismatch(_)
+            | ProofServiceError::IoError(_)
+            | ProofServiceError::ProgramNotFoundInRegistry(_)
+            | ProofServiceError::ProgramDownloadFailed(_)
+            | ProofServiceError::ProgramHashMismatch { .. }
+            | ProofServiceError::InvalidInput(_)
+            | ProofServiceError::InputsTooLarge { .. }
+            | ProofServiceError::ProvingError(_)
+            | ProofServiceError::ArtifactMissing { .. }
+            | ProofServiceError::SerdeError(_)
+            | ProofServiceError::InvalidUrl(_)
+            | ProofServiceError::UnsupportedProvingType(_)
+            | ProofServiceError::TempDirError(_)
+            | ProofServiceError::HexError(_)
+            | ProofServiceError::Unauthorized(_)
+            | ProofServiceError::InternalError(_)
+            | ProofServiceError::BlueprintSdkError(_) => false,
+        }
+    }
+}
+
+/// Error reported to Tangle for a failed job: the stable code plus the formatted message.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("[E{code}] {message}")]
+pub struct JobError {
+    pub code: u16,
+    pub message: String,
+}
+
+impl From<ProofServiceError> for JobError {
+    fn from(e: ProofServiceError) -> Self {
+        JobError {
+            code: e.code(),
+            message: e.to_string(),
+        }
+    }
+}
+
 impl From<std::io::Error> for ProofServiceError {
     fn from(e: std::io::Error) -> Self {
         ProofServiceError::IoError(e.to_string())
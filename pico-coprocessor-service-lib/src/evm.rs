@@ -1,9 +1,22 @@
-use crate::{ServiceContext, errors::ProofServiceError, types::ProgramLocation};
+use crate::{
+    ServiceContext,
+    errors::ProofServiceError,
+    program::DATA_URI_SCHEME,
+    types::{ProgramLocation, RegistryRef},
+};
 use blueprint_sdk::{
-    alloy::{primitives::B256, sol},
+    alloy::{
+        eips::BlockNumberOrTag,
+        primitives::{Address, B256},
+        providers::Provider,
+        sol,
+        sol_types::SolError,
+    },
     evm::util::get_provider_http,
 };
-use blueprint_sdk::{debug, info};
+use blueprint_sdk::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use url::Url;
 
 sol!(
@@ -13,29 +26,299 @@ sol!(
     "../contracts/out/ProgramRegistry.sol/ProgramRegistry.json"
 );
 
-/// Fetches the program location from the EVM registry contract.
+/// Fetches the program location from the EVM registry contract (the context's, unless overridden)
+/// on the chain behind `rpc_url`, falling back to `ServiceContext::fallback_registries` in order
+/// while the program isn't found. ENS registry names still resolve via `ServiceContext::eth_rpc_url`.
+///
+/// Returns the location with the address of the registry that had it. Fails with
+/// `ProgramNotFoundInRegistry` if no registry has it, and with `ProgramHashMismatch` if the hash
+/// stored with the entry found isn't `program_hash`.
 pub async fn get_program_location_from_registry(
     context: &ServiceContext,
+    rpc_url: &Url,
+    registry_override: Option<&RegistryRef>,
+    program_hash: &B256,
+) -> Result<(ProgramLocation, Address), ProofServiceError> {
+    for registry in context.registry_search_order(registry_override) {
+        let registry_address = context.resolve_registry(registry).await?;
+        let (stored_hash, location) = match get_program_entry_from_registry(
+            context,
+            rpc_url,
+            &RegistryRef::Address(registry_address),
+            program_hash,
+        )
+        .await
+        {
+            Ok(entry) => entry,
+            Err(ProofServiceError::ProgramNotFoundInRegistry(_)) => {
+                debug!(%registry, %program_hash, "Program not in registry, trying the next one");
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if stored_hash != *program_hash {
+            error!(requested = %program_hash, stored = %stored_hash, "Registry entry hash mismatch!");
+            return Err(ProofServiceError::ProgramHashMismatch {
+                expected: program_hash.to_string(),
+                got: stored_hash.to_string(),
+            });
+        }
+        return Ok((location, registry_address));
+    }
+    Err(ProofServiceError::ProgramNotFoundInRegistry(
+        program_hash.to_string(),
+    ))
+}
+
+/// Fetches the hash stored with an entry of `registry` on the chain behind `rpc_url` alongside the
+/// entry's program location.
+///
+/// Registries deployed before `getProgram` was added don't store the hash; for those the location
+/// comes from `getProgramLocation` and the requested hash, which keys the entry, is returned.
+pub async fn get_program_entry_from_registry(
+    context: &ServiceContext,
+    rpc_url: &Url,
+    registry: &RegistryRef,
     program_hash: &B256,
-) -> Result<ProgramLocation, ProofServiceError> {
-    let registry_address = context.get_registry_address();
+) -> Result<(B256, ProgramLocation), ProofServiceError> {
+    let registry_address = context.resolve_registry(registry).await?;
     debug!(%registry_address, %program_hash, "Querying ProgramRegistry contract for location");
 
     // Create a contract instance
-    let provider = get_provider_http(context.eth_rpc_url.as_str());
+    let provider = get_provider_http(rpc_url.as_str());
     let contract = ProgramRegistry::new(registry_address, provider);
 
-    // Prepare the call object for getProgramLocation
-    let call = contract.getProgramLocation(*program_hash);
+    // Execute the call; the registry reverts with ProgramRegistry__ProgramNotFound for unknown hashes,
+    // and without data if it predates getProgram
+    let (stored_hash, location_string) = match contract.getProgram(*program_hash).call().await {
+        // Success: result is ProgramRegistry::getProgramReturn { programHash: B256, location: String }
+        Ok(result) => (result.programHash, result.location),
+        Err(e) if is_missing_function(&e) => {
+            warn!(%registry_address, "Registry has no getProgram, falling back to getProgramLocation");
+            match contract.getProgramLocation(*program_hash).call().await {
+                Ok(result) => (*program_hash, result.location),
+                Err(e) => return Err(registry_call_error(e, program_hash)),
+            }
+        }
+        Err(e) => return Err(registry_call_error(e, program_hash)),
+    };
+    // An entry whose location was never set is as good as missing
+    if location_string.trim().is_empty() {
+        return Err(ProofServiceError::ProgramNotFoundInRegistry(
+            program_hash.to_string(),
+        ));
+    }
+    info!(%program_hash, %stored_hash, %location_string, "Found program location in registry");
 
-    // Execute the call
-    let result = call.call().await?;
-    // Success: result is ProgramRegistry::getProgramLocationReturn { location: String }
-    let location_string = result.location; // Access the named field
-    info!(%program_hash, %location_string, "Found program location in registry");
+    // Inline programs are kept as-is and decoded when fetched
+    if location_string.starts_with(DATA_URI_SCHEME) {
+        return Ok((stored_hash, ProgramLocation::DataUri(location_string)));
+    }
 
     // Attempt to parse as URL. Need robust handling for other schemes (ipfs://)
     // This basic parsing assumes http/https.
     let url = Url::parse(&location_string).map_err(|e| ProofServiceError::InvalidUrl(e))?;
-    Ok(ProgramLocation::RemoteUrl(url))
+    Ok((stored_hash, ProgramLocation::RemoteUrl(url)))
+}
+
+/// Fetches where a program's interface schema is published, as `(uri, schema_hash)`, from the
+/// context's registry unless overridden, on the chain behind `rpc_url`.
+///
+/// Fails with `ProgramNotFoundInRegistry` if the program isn't registered there; the URI is empty
+/// when its owner never published a schema.
+pub async fn get_program_schema_from_registry(
+    context: &ServiceContext,
+    rpc_url: &Url,
+    registry_override: Option<&RegistryRef>,
+    program_hash: &B256,
+) -> Result<(String, B256), ProofServiceError> {
+    let registry_address = context.get_registry_address(registry_override).await?;
+    debug!(%registry_address, %program_hash, "Querying ProgramRegistry contract for schema");
+
+    let provider = get_provider_http(rpc_url.as_str());
+    let contract = ProgramRegistry::new(registry_address, provider);
+    let result = match contract.getProgramSchema(*program_hash).call().await {
+        Ok(result) => result,
+        Err(e) if is_program_not_found(&e) => {
+            return Err(ProofServiceError::ProgramNotFoundInRegistry(
+                program_hash.to_string(),
+            ));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    info!(%program_hash, uri = %result.uri, schema_hash = %result.schemaHash, "Found program schema in registry");
+    Ok((result.uri, result.schemaHash))
+}
+
+fn is_program_not_found(err: &blueprint_sdk::alloy::contract::Error) -> bool {
+    err.as_revert_data().is_some_and(|data| {
+        data.starts_with(&ProgramRegistry::ProgramRegistry__ProgramNotFound::SELECTOR)
+    })
+}
+
+// A contract without the called function (and no fallback) reverts with empty data
+fn is_missing_function(err: &blueprint_sdk::alloy::contract::Error) -> bool {
+    err.as_revert_data().is_some_and(|data| data.is_empty())
+}
+
+fn registry_call_error(
+    err: blueprint_sdk::alloy::contract::Error,
+    program_hash: &B256,
+) -> ProofServiceError {
+    if is_program_not_found(&err) {
+        ProofServiceError::ProgramNotFoundInRegistry(program_hash.to_string())
+    } else {
+        err.into()
+    }
+}
+
+/// Lists every program in the registry with its current location.
+///
+/// The registry has no enumeration method, so this replays `ProgramRegistered` and `ProgramUpdated`
+/// events from genesis. Programs are returned in registration order.
+pub async fn list_programs(ctx: &ServiceContext) -> Result<Vec<(B256, String)>, ProofServiceError> {
+    let registry_address = ctx.get_registry_address(None).await?;
+    debug!(%registry_address, "Listing programs from ProgramRegistry events");
+
+    let provider = get_provider_http(ctx.eth_rpc_url.as_str());
+    let contract = ProgramRegistry::new(registry_address, provider);
+
+    let mut registered = contract
+        .ProgramRegistered_filter()
+        .from_block(BlockNumberOrTag::Earliest)
+        .query()
+        .await?;
+    let mut updated = contract
+        .ProgramUpdated_filter()
+        .from_block(BlockNumberOrTag::Earliest)
+        .query()
+        .await?;
+    registered.sort_by_key(|(_, log)| (log.block_number, log.log_index));
+    updated.sort_by_key(|(_, log)| (log.block_number, log.log_index));
+
+    let mut programs: Vec<(B256, String)> = Vec::with_capacity(registered.len());
+    let mut positions: HashMap<B256, usize> = HashMap::new();
+    for (event, _) in registered {
+        positions.insert(event.programHash, programs.len());
+        programs.push((event.programHash, event.location));
+    }
+    for (event, _) in updated {
+        if let Some(&pos) = positions.get(&event.programHash) {
+            programs[pos].1 = event.newLocation;
+        }
+    }
+
+    info!(count = programs.len(), "Listed programs from registry");
+    Ok(programs)
+}
+
+/// Chain IDs of the RPC endpoints queried so far, shared by every clone of the context. An
+/// endpoint's chain doesn't change, so each is asked once.
+#[derive(Debug, Clone, Default)]
+pub struct ChainIdCache {
+    entries: Arc<Mutex<HashMap<Url, u64>>>,
+}
+
+impl ChainIdCache {
+    pub fn get(&self, rpc_url: &Url) -> Option<u64> {
+        self.entries.lock().unwrap().get(rpc_url).copied()
+    }
+
+    pub fn insert(&self, rpc_url: &Url, chain_id: u64) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(rpc_url.clone(), chain_id);
+    }
+}
+
+/// [`get_chain_id`], consulting `cache` first.
+pub async fn get_cached_chain_id(
+    rpc_url: &Url,
+    cache: &ChainIdCache,
+) -> Result<u64, ProofServiceError> {
+    if let Some(chain_id) = cache.get(rpc_url) {
+        return Ok(chain_id);
+    }
+    let chain_id = get_chain_id(rpc_url).await?;
+    cache.insert(rpc_url, chain_id);
+    Ok(chain_id)
+}
+
+/// Chain ID (`eth_chainId`) of the chain behind `rpc_url`.
+pub async fn get_chain_id(rpc_url: &Url) -> Result<u64, ProofServiceError> {
+    let provider = get_provider_http(rpc_url.as_str());
+    provider
+        .get_chain_id()
+        .await
+        .map_err(|e| ProofServiceError::BlockchainError(format!("Failed to fetch chain id: {}", e)))
+}
+
+/// Checks that `block_hash` is the canonical hash of block `block_number` on the chain behind `rpc_url`.
+pub async fn verify_block_anchor(
+    rpc_url: &Url,
+    block_number: u64,
+    block_hash: B256,
+) -> Result<(), ProofServiceError> {
+    debug!(block_number, %block_hash, "Verifying block anchor");
+    fetch_anchored_block(rpc_url, block_number, block_hash).await?;
+    info!(block_number, %block_hash, "Block anchor verified");
+    Ok(())
+}
+
+/// Receipts root of block `block_number`, after checking `block_hash` is its canonical hash as
+/// [`verify_block_anchor`] does.
+pub async fn get_anchored_receipts_root(
+    rpc_url: &Url,
+    block_number: u64,
+    block_hash: B256,
+) -> Result<B256, ProofServiceError> {
+    let block = fetch_anchored_block(rpc_url, block_number, block_hash).await?;
+    block["receiptsRoot"]
+        .as_str()
+        .and_then(|root| root.parse::<B256>().ok())
+        .ok_or_else(|| {
+            ProofServiceError::BlockchainError(format!(
+                "Block {} has no receiptsRoot",
+                block_number
+            ))
+        })
+}
+
+// Header of block `block_number`, failing with ChainDataMismatch unless its hash is `block_hash`
+async fn fetch_anchored_block(
+    rpc_url: &Url,
+    block_number: u64,
+    block_hash: B256,
+) -> Result<serde_json::Value, ProofServiceError> {
+    let provider = get_provider_http(rpc_url.as_str());
+    let block: Option<serde_json::Value> = provider
+        .raw_request(
+            "eth_getBlockByNumber".into(),
+            (format!("0x{:x}", block_number), false),
+        )
+        .await
+        .map_err(|e| {
+            ProofServiceError::BlockchainError(format!(
+                "Failed to fetch block {}: {}",
+                block_number, e
+            ))
+        })?;
+
+    let block = block.ok_or_else(|| {
+        ProofServiceError::BlockchainError(format!("Block {} not found", block_number))
+    })?;
+    let chain_hash = block["hash"]
+        .as_str()
+        .and_then(|h| h.parse::<B256>().ok())
+        .ok_or_else(|| {
+            ProofServiceError::BlockchainError(format!("Block {} not found", block_number))
+        })?;
+    if chain_hash != block_hash {
+        return Err(ProofServiceError::ChainDataMismatch(format!(
+            "Anchor mismatch: block {} has hash {}, request claims {}",
+            block_number, chain_hash, block_hash
+        )));
+    }
+    Ok(block)
 }
@@ -0,0 +1,76 @@
+// pico-coprocessor-service-lib/src/field_mapping.rs
+use crate::encoding::decode_hex;
+use crate::errors::ProofServiceError;
+use crate::types::{BlockchainData, FieldMapping, FieldSource, MappedField};
+use blueprint_sdk::alloy::primitives::B256;
+
+impl FieldMapping {
+    /// Extracts every mapped field from the receipts' logs, in receipt, log and spec order.
+    ///
+    /// Fails with `InvalidInput` when a log of a mapped event lacks a declared topic or data word.
+    pub fn extract(&self, data: &BlockchainData) -> Result<Vec<MappedField>, ProofServiceError> {
+        let mut fields = Vec::new();
+        for (receipt_index, receipt) in data.receipts.iter().flatten().enumerate() {
+            for (log_index, log) in receipt.logs.iter().enumerate() {
+                let Some(event) = log.topics.first() else {
+                    continue;
+                };
+                let Some(specs) = self.events.get(event) else {
+                    continue;
+                };
+                let log_data = decode_hex(&log.data_hex).map_err(|e| {
+                    ProofServiceError::InvalidInput(format!(
+                        "Receipt {} log {}: invalid data_hex: {}",
+                        receipt_index, log_index, e
+                    ))
+                })?;
+                for spec in specs {
+                    let (is_topic, field_index, value) = match spec.source {
+                        FieldSource::Topic(index) => {
+                            let topic = log.topics.get(index).ok_or_else(|| {
+                                ProofServiceError::InvalidInput(format!(
+                                    "Receipt {} log {}: field {} expects topic {}, log has {} topics",
+                                    receipt_index,
+                                    log_index,
+                                    spec.name,
+                                    index,
+                                    log.topics.len()
+                                ))
+                            })?;
+                            (true, index, *topic)
+                        }
+                        FieldSource::Data(offset) => {
+                            // An offset within 32 bytes of usize::MAX has no word after it either
+                            let word = offset
+                                .checked_add(32)
+                                .and_then(|end| log_data.get(offset..end))
+                                .ok_or_else(|| {
+                                    ProofServiceError::InvalidInput(format!(
+                                        "Receipt {} log {}: field {} expects a word at data offset {}, data is {} bytes",
+                                        receipt_index,
+                                        log_index,
+                                        spec.name,
+                                        offset,
+                                        log_data.len()
+                                    ))
+                                })?;
+                            (false, offset / 32, B256::from_slice(word))
+                        }
+                    };
+                    fields.push(MappedField {
+                        name: spec.name.clone(),
+                        receipt_index,
+                        log_index,
+                        contract: log.address,
+                        event: *event,
+                        log_pos: log.log_pos,
+                        is_topic,
+                        field_index,
+                        value,
+                    });
+                }
+            }
+        }
+        Ok(fields)
+    }
+}
@@ -0,0 +1,153 @@
+// pico-coprocessor-service-lib/src/fixtures.rs
+// Trading-volume coprocessor fixture shared by the tests, benches and tooling, so the receipt
+// layout and expected result are defined once. Built for tests and with the `test-fixtures` feature.
+use crate::canonical::to_canonical_json;
+use crate::jobs::coprocessor::CoprocessorInputBundle;
+use crate::types::{
+    BlockchainData, CoprocessorProofRequest, MaxSizes, ProgramLocation, ProvingType,
+    SerializableLog, SerializableReceipt,
+};
+use blueprint_sdk::alloy::primitives::{Address, B256, U256, address, b256};
+
+/// Uniswap V3 USDC/ETH pool the fixture's swaps come from.
+pub const USDC_POOL: Address = address!("88e6a0c2ddd26feeb64f039a2c41296fcb3f5640");
+/// `Swap` event signature (topic0).
+pub const SWAP_EVENT: B256 =
+    b256!("c42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67");
+/// Trader address, left-padded to a topic.
+pub const USER: B256 = b256!("0000000000000000000000006a000f20005980200259b80c5102003040001068");
+/// Transaction every fixture receipt belongs to.
+pub const TRANSACTION_HASH: B256 =
+    b256!("d97c7863076f6b8a2430f3cc363220a1d67ee990d2673c927c93822fa541d39c");
+/// Position of the swap log within its block.
+pub const SWAP_LOG_POS: u64 = 17;
+
+/// Builds trading-volume `BlockchainData` with the matching `MaxSizes` and expected volume.
+#[derive(Debug, Clone)]
+pub struct CoprocessorFixtureBuilder {
+    num_receipts: usize,
+    swap_amount: U256,
+    user: B256,
+    max_sizes: Option<MaxSizes>,
+}
+
+/// Output of [`CoprocessorFixtureBuilder::build`].
+#[derive(Debug, Clone)]
+pub struct CoprocessorFixture {
+    pub blockchain_data: BlockchainData,
+    pub max_sizes: MaxSizes,
+    /// Volume the trading-volume program commits for this data (32-byte big-endian).
+    pub expected_volume: U256,
+}
+
+impl Default for CoprocessorFixtureBuilder {
+    fn default() -> Self {
+        Self {
+            num_receipts: 1,
+            swap_amount: U256::from(0x10d12bdb167e201e0u128),
+            user: USER,
+            max_sizes: None,
+        }
+    }
+}
+
+impl CoprocessorFixtureBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn num_receipts(mut self, num_receipts: usize) -> Self {
+        self.num_receipts = num_receipts;
+        self
+    }
+
+    pub fn swap_amount(mut self, swap_amount: U256) -> Self {
+        self.swap_amount = swap_amount;
+        self
+    }
+
+    pub fn user(mut self, user: B256) -> Self {
+        self.user = user;
+        self
+    }
+
+    /// Overrides the smallest valid sizes derived from the data.
+    pub fn max_sizes(mut self, max_sizes: MaxSizes) -> Self {
+        self.max_sizes = Some(max_sizes);
+        self
+    }
+
+    pub fn build(self) -> CoprocessorFixture {
+        // One receipt carries the swap amount (data) and the trader (topic 1) as two logs
+        // at the same position, the layout the trading-volume program reads.
+        let receipt = SerializableReceipt {
+            transaction_hash: TRANSACTION_HASH,
+            status: Some(U256::from(1)),
+            logs: vec![
+                SerializableLog {
+                    address: USDC_POOL,
+                    topics: vec![SWAP_EVENT],
+                    data_hex: hex::encode(self.swap_amount.to_be_bytes::<32>()),
+                    log_pos: Some(SWAP_LOG_POS),
+                },
+                SerializableLog {
+                    address: USDC_POOL,
+                    topics: vec![SWAP_EVENT, self.user],
+                    data_hex: String::new(),
+                    log_pos: Some(SWAP_LOG_POS),
+                },
+            ],
+            raw_data_hex: String::new(),
+            inclusion_proof: None,
+            block_number: None,
+        };
+
+        let max_sizes = self.max_sizes.unwrap_or(MaxSizes {
+            max_receipt_size: self.num_receipts.div_ceil(32).max(1) * 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        });
+
+        CoprocessorFixture {
+            blockchain_data: BlockchainData {
+                receipts: Some(vec![receipt; self.num_receipts]),
+                storage_slots: None,
+                transactions: None,
+            },
+            max_sizes,
+            expected_volume: self.swap_amount * U256::from(self.num_receipts),
+        }
+    }
+}
+
+impl CoprocessorFixture {
+    /// A Fast proof request for the fixture data, loading the program from `elf_path`.
+    pub fn request(
+        &self,
+        program_hash: String,
+        elf_path: std::path::PathBuf,
+    ) -> CoprocessorProofRequest {
+        CoprocessorProofRequest {
+            program_hash,
+            blockchain_data: self.blockchain_data.clone(),
+            max_sizes: self.max_sizes.clone(),
+            proving_type: ProvingType::Fast,
+            program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+            ..Default::default()
+        }
+    }
+
+    /// The bundle the program reads from stdin, as built by the coprocessor job.
+    pub fn input_bundle(&self) -> CoprocessorInputBundle {
+        CoprocessorInputBundle {
+            data: self.blockchain_data.clone(),
+            sizes: self.max_sizes.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Hex encoded `input_bundle`, the `inputs` the prover receives.
+    pub fn inputs_hex(&self) -> String {
+        hex::encode(to_canonical_json(&self.input_bundle()).expect("bundle serializes"))
+    }
+}
@@ -0,0 +1,135 @@
+// pico-coprocessor-service-lib/src/jobs/check_program.rs
+use crate::{
+    context::ServiceContext,
+    errors::ProofServiceError,
+    evm, program,
+    sandbox::RequestSandbox,
+    types::{CheckProgramRequest, CheckProgramResult},
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::{
+    alloy::primitives::B256,
+    error,
+    extract::Context,
+    info,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use std::str::FromStr;
+
+/// Compares a locally built ELF with the program registered under `program_hash`, so a developer
+/// can confirm a build matches what's on-chain without proving from a location override. The ELF
+/// comes in the request and is bounded by `max_elf_bytes`.
+///
+/// The registered ELF is taken from the ELF cache, or downloaded and verified when it isn't
+/// cached; both files are hashed with the algorithm the registered location is annotated with.
+pub async fn check_program_matches_registry(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(request): TangleArg<CheckProgramRequest>,
+) -> Result<TangleResult<CheckProgramResult>, ProofServiceError> {
+    info!(
+        program_hash = %request.program_hash,
+        elf_len = request.elf.len(),
+        "Received check_program_matches_registry job request"
+    );
+
+    let program_hash_bytes = match B256::from_str(&request.program_hash) {
+        Ok(hash) => hash,
+        Err(_) => {
+            let err = ProofServiceError::InvalidInput(format!(
+                "Invalid program_hash format (expected 32-byte hex): {}",
+                request.program_hash
+            ));
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // The ELF is decoded before anything is looked up, so a malformed request costs nothing
+    let local_elf = match decode_elf(&ctx, &request.elf) {
+        Ok(elf) => elf,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    match check(&ctx, &request, &local_elf, &program_hash_bytes).await {
+        Ok(result) => {
+            info!(result = ?result, "Program checked against the registry");
+            Ok(TangleResult(result))
+        }
+        Err(err) => {
+            error!("Failed to check program against the registry: {:?}", err);
+            Err(err)
+        }
+    }
+}
+
+fn decode_elf(ctx: &ServiceContext, elf_base64: &str) -> Result<Vec<u8>, ProofServiceError> {
+    // Base64 grows the ELF by a third, so oversized requests are refused before decoding
+    if elf_base64.len() as u64 / 4 * 3 > ctx.max_elf_bytes + 2 {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "ELF exceeds max_elf_bytes ({})",
+            ctx.max_elf_bytes
+        )));
+    }
+    let elf = STANDARD
+        .decode(elf_base64.trim())
+        .map_err(|e| ProofServiceError::InvalidInput(format!("Invalid base64 ELF: {}", e)))?;
+    if elf.is_empty() {
+        return Err(ProofServiceError::InvalidInput("ELF is empty".to_string()));
+    }
+    if elf.len() as u64 > ctx.max_elf_bytes {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "ELF of {} bytes exceeds max_elf_bytes ({})",
+            elf.len(),
+            ctx.max_elf_bytes
+        )));
+    }
+    Ok(elf)
+}
+
+async fn check(
+    ctx: &ServiceContext,
+    request: &CheckProgramRequest,
+    local_elf: &[u8],
+    program_hash_bytes: &B256,
+) -> Result<CheckProgramResult, ProofServiceError> {
+    let (location, _registry) = evm::get_program_location_from_registry(
+        ctx,
+        &ctx.eth_rpc_url,
+        request.registry_address_override.as_ref(),
+        program_hash_bytes,
+    )
+    .await?;
+    let algorithm = program::location_hash_algorithm(&location)?;
+
+    let local_hash = program::hash_hex(algorithm, local_elf);
+
+    let expected_hash_hex = hex::encode(program_hash_bytes);
+    let sandbox = RequestSandbox::create(&ctx.temp_dir_base)?;
+    let (_elf_temp_dir, registered_elf) = match program::cached_program(
+        ctx,
+        &expected_hash_hex,
+        None,
+        false,
+        sandbox.path(),
+    )
+    .await?
+    {
+        Some(cached) => cached,
+        None => {
+            program::fetch_and_verify_program(ctx, &location, &expected_hash_hex, sandbox.path())
+                .await?
+        }
+    };
+    let registry_hash =
+        program::calculate_file_hash_with_algorithm(&registered_elf, algorithm, None).await?;
+
+    Ok(CheckProgramResult {
+        matches: local_hash == registry_hash,
+        local_hash,
+        registry_hash,
+    })
+}
@@ -1,9 +1,25 @@
 // pico-coprocessor-service-lib/src/jobs/generate_coprocessor_proof.rs
 use crate::{
-    context::ServiceContext,
+    archive, auth,
+    cache::ProofCacheKey,
+    canonical,
+    context::{HardLimits, ServiceContext},
+    encoding,
     errors::ProofServiceError,
-    evm, pico, program,
-    types::{BlockchainData, CoprocessorProofRequest, MaxSizes, ProofResult},
+    evm,
+    jobs::{
+        REDACTED, attach_scale_metadata, check_output_sink, compress_if_requested, deliver_proof,
+        ensure_private_inputs_allowed, ensure_prove_options_applicable, record_chain_context,
+        set_proof_commitment, withhold_private_inputs,
+    },
+    mpt, pico, program, public_values,
+    sandbox::{self, RequestSandbox},
+    types::{
+        BlockchainData, BlockchainDataSource, CommitMode, CoprocessorProofRequest, InputsEncoding,
+        MappedField, MaxSizes, ProofResult, ProvingType, ReceiptBlock, ReceiptInclusionProof,
+        SerializableReceipt,
+    },
+    validation,
 };
 use blueprint_sdk::{
     alloy::primitives::{Address, B256},
@@ -13,8 +29,13 @@ use blueprint_sdk::{
     tangle::extract::{TangleArg, TangleResult},
 };
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 use tempfile::TempDir; // For serializing inputs
+use url::Url;
 
 // Helper struct for managing temporary resources
 pub struct CoprocessorProofResources {
@@ -22,20 +43,76 @@ pub struct CoprocessorProofResources {
     elf_path: PathBuf,
     _output_temp_dir: TempDir,
     output_path: PathBuf,
+    _sandbox: RequestSandbox, // Declared last so it is removed after the dirs inside it
 }
 
 // Define a structure to bundle inputs for SCALE encoding
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
 pub struct CoprocessorInputBundle {
     pub data: BlockchainData,
     pub sizes: MaxSizes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_block_hash: Option<B256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_block_number: Option<u64>,
+    // Values extracted by the request's field_mapping, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<MappedField>>,
+    // The request's replay protection, for the program to commit alongside its result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<B256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    // Where each block's receipts are in data.receipts, when they carry block numbers (see
+    // ReceiptBlock for the grouping contract)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt_blocks: Option<Vec<ReceiptBlock>>,
 }
 
+/// Proves the request's blockchain data with the user's coprocessor program.
+///
+/// Receipts reach the program in the order they were submitted (after the context's input
+/// preprocessors), so order-sensitive aggregations such as first/last see them as the caller sent
+/// them. Set `sort_by` to have them put in a canonical order instead.
 pub async fn generate_coprocessor_proof(
     Context(ctx): Context<ServiceContext>,
-    TangleArg(request): TangleArg<CoprocessorProofRequest>,
+    TangleArg(mut request): TangleArg<CoprocessorProofRequest>,
 ) -> Result<TangleResult<ProofResult>, ProofServiceError> {
-    info!(request = ?request, "Received generate_coprocessor_proof job request");
+    if request.private_inputs {
+        info!(program_hash = %request.program_hash, blockchain_data = REDACTED, "Received generate_coprocessor_proof job request");
+    } else {
+        info!(request = ?request, "Received generate_coprocessor_proof job request");
+    }
+
+    // Verify-only nodes never prove
+    if let Err(err) = ctx.ensure_can_prove() {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Track the job so shutdown waits for it; refused once shutdown has begun
+    let _in_flight = match ctx.in_flight.begin() {
+        Ok(guard) => guard,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Reject unsigned or unauthorized callers when authentication is enabled
+    let caller = match auth::authenticate(&ctx, &request) {
+        Ok(caller) => caller,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Keep one caller from monopolizing proving
+    if let Err(err) = ctx.check_rate_limit(caller.as_deref()) {
+        error!("{}", err);
+        return Err(err);
+    }
 
     // --- 1. Preparation ---
     // Validate program hash format
@@ -51,26 +128,293 @@ pub async fn generate_coprocessor_proof(
         }
     };
 
-    // Validate max sizes (must be > 0 and multiple of 32 according to docs)
-    if request.max_sizes.max_receipt_size == 0
-        || request.max_sizes.max_receipt_size % 32 != 0
-        || request.max_sizes.max_storage_size == 0
-        || request.max_sizes.max_storage_size % 32 != 0
-        || request.max_sizes.max_tx_size == 0
-        || request.max_sizes.max_tx_size % 32 != 0
+    // Stale requests are refused before any work is done
+    if let Err(err) = validation::validate_expiry(request.expires_at, validation::unix_now()) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Tags end up as metric labels, so their number and size are bounded
+    if let Err(err) = validation::validate_tags(&request.tags) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Caller-chosen RPC endpoints are limited to the operator's allow-list; whichever endpoint
+    // applies serves every registry lookup and chain query of the job
+    let rpc_url = match ctx.rpc_url_for(request.eth_rpc_url_override.as_deref()) {
+        Ok(url) => url,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Fail before proving when the proof couldn't be delivered
+    if let Err(err) = check_output_sink(&ctx, request.output_sink.as_ref()) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // A program location this build can't fetch fails the job now, not partway through the fetch
+    if let Err(err) =
+        program::check_location_override(&ctx, request.program_location_override.as_ref())
     {
-        let err = ProofServiceError::InvalidInput(format!(
-            "Invalid max_sizes: must be > 0 and multiple of 32. Got {:?}",
-            request.max_sizes
-        ));
         error!("{}", err);
         return Err(err);
     }
 
+    // SDK-format inputs replace the request's blockchain data entirely
+    if request.sdk_inputs.is_some()
+        && (request.blockchain_data != BlockchainData::default()
+            || request.field_mapping.is_some()
+            || request.sort_by.is_some())
+    {
+        let err = ProofServiceError::InvalidInput(
+            "sdk_inputs replaces blockchain_data, field_mapping and sort_by; leave them unset"
+                .to_string(),
+        );
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // SDK-format inputs are bounded and checked like blockchain data before anything is fetched
+    if let Some(sdk_inputs) = &request.sdk_inputs {
+        if sdk_inputs.len() > ctx.max_input_bytes {
+            let err = ProofServiceError::InputsTooLarge {
+                size: sdk_inputs.len(),
+                limit: ctx.max_input_bytes,
+            };
+            error!("{}", err);
+            return Err(err);
+        }
+        let validated = SdkSavedInputs::parse(sdk_inputs)
+            .and_then(|saved| saved.validate(&ctx.hard_limits, request.anchor_block_number));
+        if let Err(err) = validated {
+            error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    // Archived data is fetched and checked against its hash, then treated as if sent inline
+    if let Some(source) = &request.blockchain_data_source {
+        let data = match resolve_blockchain_data(&ctx, &request, source).await {
+            Ok(data) => data,
+            Err(err) => {
+                error!("{}", err);
+                return Err(err);
+            }
+        };
+        request.blockchain_data = data;
+    }
+
+    // Enforce the service's hard caps before looking at the declared sizes
+    if let Err(err) = validation::validate_hard_limits(&ctx.hard_limits, &request.blockchain_data) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Validate max sizes (must be > 0 and multiple of 32 according to docs, and fit the data);
+    // SDK-format inputs declare their own
+    if request.sdk_inputs.is_none() {
+        if let Err(err) =
+            validation::validate_max_sizes(&request.max_sizes, &request.blockchain_data)
+        {
+            error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    // Receipts spanning several blocks must come in block order, up to the anchor block
+    if let Err(err) =
+        validation::validate_receipt_blocks(&request.blockchain_data, request.anchor_block_number)
+    {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Opt-in check that the anchor hash belongs to the anchor block
+    if request.verify_anchor {
+        if let Err(err) = verify_anchor(&rpc_url, &request).await {
+            error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    // Receipts carrying an inclusion proof must be in the anchor block
+    if let Err(err) = verify_receipt_inclusion(&rpc_url, &request).await {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Opt-in check of topic counts per event signature
+    if let Some(expected) = &request.expected_topic_counts {
+        if let Err(err) = validation::validate_topic_counts(&request.blockchain_data, expected) {
+            error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    // Opt-in structural check of receipt logs
+    if request.validate_log_structure {
+        if let Err(err) = validation::validate_receipt_log_structure(&request.blockchain_data) {
+            error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    // Fail fast on proving types this build doesn't support
+    if let Err(err) = pico::ensure_proving_type_supported(&request.proving_type) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Validate the shard size and memory limit overrides, if any, against what the backend applies
+    if let Err(err) = ensure_prove_options_applicable(
+        &ctx,
+        &request.proving_type,
+        request.shard_size,
+        request.memory_limit_bytes,
+    ) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Private inputs must not leave this process, through a dump or the prover backend
+    if let Err(err) = ensure_private_inputs_allowed(
+        &ctx,
+        request.private_inputs,
+        request.dump_inputs_to.is_some(),
+    ) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // A dump the service doesn't allow fails the request before the inputs are built
+    let dump_path = match &request.dump_inputs_to {
+        Some(file_name) => match sandbox::dump_path(ctx.dump_inputs_dir.as_deref(), file_name) {
+            Ok(path) => Some(path),
+            Err(err) => {
+                error!("{}", err);
+                return Err(err);
+            }
+        },
+        None => None,
+    };
+
+    // Serialize Inputs for zkVM
+    // The user's ELF program needs to deserialize this structure from stdin.
+    let prepared = match &request.sdk_inputs {
+        Some(sdk_inputs) => prepare_sdk_inputs(sdk_inputs),
+        None => prepare_input_bundle(&ctx, &request),
+    };
+    let PreparedInputs {
+        serialized_inputs,
+        sdk_init_params,
+        used_sizes,
+        receipt_count,
+    } = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            error!("Failed to build coprocessor input bundle: {:?}", e);
+            return Err(e);
+        }
+    };
+
+    // Valid max_sizes can still make a bundle larger than the prover accepts on stdin
+    let prover_input_size = serialized_inputs.len() / 2;
+    if prover_input_size > ctx.max_prover_input_bytes {
+        let err = ProofServiceError::InputsTooLarge {
+            size: prover_input_size,
+            limit: ctx.max_prover_input_bytes,
+        };
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Exactly what the program will read from stdin, for debugging it
+    if let Some(path) = &dump_path {
+        if let Err(err) = dump_inputs(path, &serialized_inputs) {
+            error!("Failed to dump coprocessor inputs: {}", err);
+            return Err(err);
+        }
+    }
+
+    // Identical data at the same anchor block yields the same proof
+    let cache_key = ProofCacheKey::new(
+        program_hash_bytes,
+        request.proving_type.clone(),
+        request.anchor_block_hash,
+        serialized_inputs.as_bytes(),
+    );
+    // A Full proof cached in another serialization is proved again rather than converted. Logs
+    // and the RISCV proof belong to a single run, so requests for them are always proven
+    let cached = ctx
+        .proof_cache
+        .as_ref()
+        .filter(|_| !request.capture_logs && !request.include_riscv_proof)
+        .and_then(|c| c.get(&cache_key))
+        .filter(|cached| {
+            cached.proving_type != ProvingType::Full
+                || cached.recursion_proof_format == request.recursion_proof_format
+        });
+    if let Some(mut cached) = cached {
+        info!(program_hash = %request.program_hash, "Serving coprocessor proof from cache");
+        // Nonce and expiry are part of the bundle, and so of the key; the tags aren't
+        cached.tags = request.tags.clone();
+        // The cached proof may come from a request that accepted empty public values
+        if let Err(err) = encoding::decode_hex(&cached.public_values)
+            .map_err(ProofServiceError::from)
+            .and_then(|pv| {
+                pico::committed_public_values(Some(pv), "Cached", request.require_public_values)
+            })
+        {
+            error!("{}", err);
+            return Err(err);
+        }
+        // The cached proof may have been requested with another commit layout
+        if let Err(err) = decode_commitment(&request, receipt_count, &mut cached) {
+            error!("Failed to decode public values: {}", err);
+            return Err(err);
+        }
+        // Served without resolving the program, so no registry was consulted
+        let cached = record_chain_context(&ctx, cached, &rpc_url, None).await;
+        let cached = encoding::reencode_result(cached, request.output_encoding)?;
+        let cached = withhold_private_inputs(cached, request.private_inputs);
+        let cached = attach_scale_metadata(cached, request.include_scale_metadata);
+        let cached = compress_if_requested(cached, request.compress_public_values)?;
+        let cached = match deliver_proof(&ctx, cached, request.output_sink.as_ref()).await {
+            Ok(result) => result,
+            Err(err) => {
+                error!("Failed to deliver proof: {}", err);
+                return Err(err);
+            }
+        };
+        ctx.metrics
+            .record_proof("generate_coprocessor_proof", &cached, true);
+        return Ok(TangleResult(cached));
+    }
+
+    // Isolate this job's files in its own sandbox under the temp base (or the caller's override)
+    let sandbox = match sandbox::sandbox_base(
+        &ctx.temp_dir_base,
+        &ctx.allowed_temp_dirs,
+        request.temp_dir_override.as_deref(),
+    )
+    .and_then(|base| RequestSandbox::create(&base))
+    {
+        Ok(sandbox) => sandbox,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+    info!(proof_id = %sandbox.proof_id(), "Assigned request sandbox");
+
     // Create a temporary directory for proof outputs
     let output_temp_dir = match tempfile::Builder::new()
         .prefix("pico_coproc_out_")
-        .tempdir_in(&ctx.temp_dir_base)
+        .tempdir_in(sandbox.path())
     {
         Ok(dir) => dir,
         Err(e) => {
@@ -86,9 +430,16 @@ pub async fn generate_coprocessor_proof(
 
     // --- 2. Get Program ELF ---
     // Fetch the user's zkVM program (which should use coprocessor-sdk)
-    let fetch_result = get_program_elf_for_coprocessor(&ctx, &request, &program_hash_bytes).await;
-    let (elf_temp_dir, elf_path) = match fetch_result {
-        Ok((dir, path)) => (dir, path),
+    let fetch_result = get_program_elf_for_coprocessor(
+        &ctx,
+        &rpc_url,
+        &request,
+        &program_hash_bytes,
+        sandbox.path(),
+    )
+    .await;
+    let (elf_temp_dir, elf_path, registry_address) = match fetch_result {
+        Ok(fetched) => fetched,
         Err(e) => {
             error!("Failed to get coprocessor program ELF: {:?}", e);
             let _ = tokio::fs::remove_dir_all(output_path).await; // Cleanup output dir
@@ -103,34 +454,95 @@ pub async fn generate_coprocessor_proof(
         elf_path: elf_path.clone(),
         _output_temp_dir: output_temp_dir,
         output_path: output_path.clone(),
+        _sandbox: sandbox,
     };
 
-    // --- 3. Serialize Inputs for zkVM ---
-    // The user's ELF program needs to deserialize this structure from stdin.
-    let input_bundle = CoprocessorInputBundle {
-        data: request.blockchain_data.clone(),
-        sizes: request.max_sizes.clone(),
+    // Wait for a proving slot when concurrency is limited; higher priorities are served first
+    let _permit = match &ctx.proving_queue {
+        Some(queue) => Some(queue.acquire(request.priority).await),
+        None => None,
     };
-    let serialized_inputs = serde_json::to_string(&input_bundle).unwrap();
 
-    // --- 4. Execute Proving ---
-    // Call the same underlying pico executor, but pass the serialized bundle as input.
-    let proof_exec_result = pico::execute_pico_prove(
-        &elf_path,
-        &serialized_inputs, // Pass the encoded bundle
-        &request.proving_type,
-        &output_path,
-    )
-    .await;
+    // --- 3. Execute Proving ---
+    // Call the configured prover backend, but pass the serialized bundle as input.
+    let options = pico::ProveOptions {
+        shard_size: request.shard_size,
+        on_full_failure: request.on_full_failure,
+        capture_logs: request.capture_logs,
+        include_riscv_proof: request.include_riscv_proof,
+        require_public_values: request.require_public_values,
+        recursion_proof_format: request.recursion_proof_format,
+        memory_limit_bytes: request.memory_limit_bytes,
+        evm_output_dir: ctx.evm_output_dir.clone(),
+        evm_output_retention: ctx.evm_output_retention,
+        evm_docker_image: ctx.evm_docker_image.clone(),
+        output_dir: None,
+        on_riscv_proof: None,
+        prover_cache: Some(ctx.prover_cache.clone()),
+    };
+    let proof_exec_result = match pico::read_elf_file(&elf_path) {
+        Ok(elf) => {
+            ctx.prover_backend
+                .prove(
+                    &elf,
+                    &serialized_inputs, // Pass the encoded bundle
+                    &request.proving_type,
+                    &output_path,
+                    &options,
+                )
+                .await
+        }
+        Err(e) => Err(e),
+    };
 
-    // --- 5. Handle Result ---
+    // --- 4. Handle Result ---
     match proof_exec_result {
         Ok(mut proof_result) => {
             // Populate remaining fields
-            proof_result.program_hash = request.program_hash;
-            // Store the hex of the SCALE encoded bundle as the "inputs" field
+            proof_result.program_hash = request.program_hash.clone();
+            proof_result.nonce = request.nonce;
+            proof_result.expires_at = request.expires_at;
+            proof_result.tags = request.tags.clone();
+            // Store the hex of the JSON encoded bundle as the "inputs" field
             proof_result.inputs = serialized_inputs;
+            proof_result.inputs_encoding = InputsEncoding::HexOfJson;
+            // Record the SDK init counts so the run can be reproduced
+            proof_result.sdk_init_params = Some(sdk_init_params);
+            if let Err(err) = set_proof_commitment(&mut proof_result) {
+                error!("{}", err);
+                return Err(err);
+            }
+            proof_result.used_sizes = Some(used_sizes);
+            // Extract the committed payload according to the declared layout and mode
+            decode_commitment(&request, receipt_count, &mut proof_result)
+                .inspect_err(|e| error!("Failed to decode public values: {}", e))?;
+            // Downgraded proofs don't satisfy the requested proving type, and proofs of an
+            // unverified program may not be of the hashed one, so neither is reused
+            let reusable = !proof_result.downgraded
+                && !(request.insecure_skip_hash_verify && ctx.allow_insecure);
+            if let (Some(cache), true) = (&ctx.proof_cache, reusable) {
+                cache.insert(cache_key, proof_result.clone());
+            }
 
+            // The cache keeps hex, the inputs and the inline proof; the caller's encoding, privacy and
+            // output sink apply on the way out, as does the chain context of this request
+            let proof_result =
+                record_chain_context(&ctx, proof_result, &rpc_url, registry_address).await;
+            let proof_result = encoding::reencode_result(proof_result, request.output_encoding)?;
+            let proof_result = withhold_private_inputs(proof_result, request.private_inputs);
+            let proof_result = attach_scale_metadata(proof_result, request.include_scale_metadata);
+            let proof_result = compress_if_requested(proof_result, request.compress_public_values)?;
+            let proof_result =
+                match deliver_proof(&ctx, proof_result, request.output_sink.as_ref()).await {
+                    Ok(result) => result,
+                    Err(err) => {
+                        error!("Failed to deliver proof: {}", err);
+                        return Err(err);
+                    }
+                };
+
+            ctx.metrics
+                .record_proof("generate_coprocessor_proof", &proof_result, false);
             info!(result = ?proof_result, "Coprocessor proof generation successful");
             Ok(TangleResult(proof_result))
         }
@@ -142,21 +554,373 @@ pub async fn generate_coprocessor_proof(
     }
 }
 
-// Helper function (similar to the one in generate_proof job)
+// What the prover is given for a request, and what the result reports about it
+struct PreparedInputs {
+    serialized_inputs: String,
+    sdk_init_params: (u32, u32, u32),
+    used_sizes: MaxSizes,
+    receipt_count: usize,
+}
+
+// The prover takes hex input; the bundle travels as hex of its canonical JSON encoding,
+// so the same bundle always yields the same inputs (and cache key).
+fn prepare_input_bundle(
+    ctx: &ServiceContext,
+    request: &CoprocessorProofRequest,
+) -> Result<PreparedInputs, ProofServiceError> {
+    let input_bundle = build_input_bundle(ctx, request)?;
+    Ok(PreparedInputs {
+        serialized_inputs: hex::encode(canonical::to_canonical_json(&input_bundle)?),
+        sdk_init_params: input_bundle.data.sdk_init_params(),
+        used_sizes: input_bundle.data.used_sizes(),
+        receipt_count: input_bundle.data.receipts.as_ref().map_or(0, Vec::len),
+    })
+}
+
+// Writes the decoded prover inputs (JSON either way) to a new file at a request's dump path
+fn dump_inputs(path: &Path, serialized_inputs: &str) -> Result<(), ProofServiceError> {
+    let inputs = hex::decode(serialized_inputs)?;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&inputs))
+        .map_err(|e| {
+            ProofServiceError::IoError(format!("Failed to write inputs to {:?}: {}", path, e))
+        })?;
+    info!(path = ?path, "Dumped coprocessor inputs");
+    Ok(())
+}
+
+// Fields of the SDK's save_inputs JSON read by the service; the file itself passes through untouched
+#[derive(Deserialize)]
+pub(crate) struct SdkSavedInputs {
+    max_receipt_size: u32,
+    max_storage_size: u32,
+    max_tx_size: u32,
+    #[serde(default)]
+    receipts: Option<Vec<SdkSavedReceipt>>,
+    #[serde(default)]
+    storage_slots: Option<Vec<serde::de::IgnoredAny>>,
+    #[serde(default)]
+    transactions: Option<Vec<serde::de::IgnoredAny>>,
+}
+
+// Fields of a receipt in the SDK's save_inputs JSON read by the service
+#[derive(Deserialize)]
+struct SdkSavedReceipt {
+    #[serde(default)]
+    block_num: Option<u64>,
+    #[serde(default)]
+    fields: Vec<serde::de::IgnoredAny>,
+}
+
+impl SdkSavedInputs {
+    pub(crate) fn parse(sdk_inputs: &str) -> Result<Self, ProofServiceError> {
+        serde_json::from_str(sdk_inputs).map_err(|e| {
+            ProofServiceError::InvalidInput(format!(
+                "Inputs are not coprocessor SDK save_inputs JSON: {}",
+                e
+            ))
+        })
+    }
+
+    // Holds SDK-format inputs to the checks blockchain data gets: the hard caps (log fields stand
+    // in for logs), sizes that are multiples of 32 holding the data (0 for a category left empty,
+    // as the SDK writes it) and receipts in block order up to the anchor. SDK receipts carry no
+    // inclusion proofs, so there is nothing to check against the anchor's receipts root.
+    fn validate(
+        &self,
+        limits: &HardLimits,
+        anchor_block_number: Option<u64>,
+    ) -> Result<(), ProofServiceError> {
+        let receipts = self.receipts.as_deref().unwrap_or_default();
+        let storage_slots = self.storage_slots.as_ref().map_or(0, Vec::len);
+        let transactions = self.transactions.as_ref().map_or(0, Vec::len);
+        validation::check_hard_limit("receipts", receipts.len(), limits.max_receipts_hard)?;
+        for (index, receipt) in receipts.iter().enumerate() {
+            validation::check_hard_limit(
+                &format!("log fields in receipt {}", index),
+                receipt.fields.len(),
+                limits.max_logs_per_receipt_hard,
+            )?;
+        }
+        validation::check_hard_limit(
+            "storage slots",
+            storage_slots,
+            limits.max_storage_slots_hard,
+        )?;
+        validation::check_hard_limit("transactions", transactions, limits.max_transactions_hard)?;
+
+        validation::check_max_size(
+            "max_receipt_size",
+            self.max_receipt_size as usize,
+            "receipts",
+            receipts.len(),
+        )?;
+        validation::check_max_size(
+            "max_storage_size",
+            self.max_storage_size as usize,
+            "storage slots",
+            storage_slots,
+        )?;
+        validation::check_max_size(
+            "max_tx_size",
+            self.max_tx_size as usize,
+            "transactions",
+            transactions,
+        )?;
+
+        let block_numbers: Vec<Option<u64>> =
+            receipts.iter().map(|receipt| receipt.block_num).collect();
+        validation::check_receipt_blocks(&block_numbers, anchor_block_number)
+    }
+}
+
+// SDK-format inputs go to the prover byte for byte; their sizes are the SDK's `Builder::init` arguments
+fn prepare_sdk_inputs(sdk_inputs: &str) -> Result<PreparedInputs, ProofServiceError> {
+    let saved = SdkSavedInputs::parse(sdk_inputs)?;
+    Ok(PreparedInputs {
+        serialized_inputs: hex::encode(sdk_inputs),
+        sdk_init_params: (
+            saved.max_receipt_size,
+            saved.max_storage_size,
+            saved.max_tx_size,
+        ),
+        used_sizes: MaxSizes {
+            max_receipt_size: saved.receipts.as_ref().map_or(0, Vec::len),
+            max_storage_size: saved.storage_slots.map_or(0, |s| s.len()),
+            max_tx_size: saved.transactions.map_or(0, |t| t.len()),
+        },
+        receipt_count: saved.receipts.map_or(0, |r| r.len()),
+    })
+}
+
+// Fills public_values_decoded (and merkle_commitment under CommitMode::MerkleRoot) from the
+// hex public values, per the request's commit format, mode and sizing policy
+fn decode_commitment(
+    request: &CoprocessorProofRequest,
+    receipt_count: usize,
+    proof_result: &mut ProofResult,
+) -> Result<(), ProofServiceError> {
+    let public_values_bytes = hex::decode(&proof_result.public_values)?;
+    let payload = public_values::decode_public_values(request.commit_format, &public_values_bytes)?;
+    let (committed, merkle_commitment) = match request.commit_mode {
+        CommitMode::Aggregate => (payload, None),
+        CommitMode::MerkleRoot => {
+            let commitment = public_values::parse_merkle_commitment(&payload, receipt_count)?;
+            (payload[..32].to_vec(), Some(commitment))
+        }
+    };
+    let decoded =
+        public_values::apply_public_values_policy(request.public_values_policy, committed)?;
+    proof_result.public_values_decoded = Some(hex::encode(decoded));
+    proof_result.merkle_commitment = merkle_commitment;
+    Ok(())
+}
+
+/// Builds the bundle the user's program reads from stdin, after stripping `0x` prefixes from the
+/// request's hex fields, running the context's input preprocessors over its blockchain data,
+/// applying its `sort_by` and extracting any mapped fields. Receipts otherwise keep their
+/// submission order. Receipts with block numbers are grouped by block in `receipt_blocks`.
+pub fn build_input_bundle(
+    ctx: &ServiceContext,
+    request: &CoprocessorProofRequest,
+) -> Result<CoprocessorInputBundle, ProofServiceError> {
+    let mut data = request.blockchain_data.clone();
+    data.strip_hex_prefixes();
+    // Inclusion is checked by the service before proving; the program never reads the proofs
+    for receipt in data.receipts.iter_mut().flatten() {
+        receipt.inclusion_proof = None;
+    }
+    for preprocessor in &ctx.input_preprocessors {
+        data = preprocessor.process(data)?;
+    }
+    if let (Some(order), Some(receipts)) = (request.sort_by, data.receipts.as_mut()) {
+        order.sort(receipts);
+    }
+    // Preprocessors may have reordered the receipts since they were validated
+    validation::validate_receipt_blocks(&data, request.anchor_block_number)?;
+    let receipt_blocks = group_receipts_by_block(data.receipts.as_deref().unwrap_or_default());
+    let fields = match &request.field_mapping {
+        Some(mapping) => Some(mapping.extract(&data)?),
+        None => None,
+    };
+    Ok(CoprocessorInputBundle {
+        data,
+        sizes: request.max_sizes.clone(),
+        anchor_block_hash: request.anchor_block_hash,
+        anchor_block_number: request.anchor_block_number,
+        fields,
+        nonce: request.nonce,
+        expires_at: request.expires_at,
+        receipt_blocks,
+    })
+}
+
+// Consecutive runs of receipts from the same block; None when receipts carry no block numbers
+fn group_receipts_by_block(receipts: &[SerializableReceipt]) -> Option<Vec<ReceiptBlock>> {
+    let mut blocks: Vec<ReceiptBlock> = Vec::new();
+    for (index, receipt) in receipts.iter().enumerate() {
+        let block_number = receipt.block_number?;
+        match blocks.last_mut() {
+            Some(block) if block.block_number == block_number => block.receipt_count += 1,
+            _ => blocks.push(ReceiptBlock {
+                block_number,
+                first_receipt: index,
+                receipt_count: 1,
+            }),
+        }
+    }
+    (!blocks.is_empty()).then_some(blocks)
+}
+
+async fn resolve_blockchain_data(
+    ctx: &ServiceContext,
+    request: &CoprocessorProofRequest,
+    source: &BlockchainDataSource,
+) -> Result<BlockchainData, ProofServiceError> {
+    if request.blockchain_data != BlockchainData::default() || request.sdk_inputs.is_some() {
+        return Err(ProofServiceError::InvalidInput(
+            "blockchain_data_source replaces blockchain_data and sdk_inputs; leave them unset"
+                .to_string(),
+        ));
+    }
+    let expected_hash = request.blockchain_data_hash.ok_or_else(|| {
+        ProofServiceError::InvalidInput(
+            "blockchain_data_source requires blockchain_data_hash".to_string(),
+        )
+    })?;
+    archive::fetch_blockchain_data(ctx, source, &expected_hash).await
+}
+
+async fn verify_anchor(
+    rpc_url: &Url,
+    request: &CoprocessorProofRequest,
+) -> Result<(), ProofServiceError> {
+    match (request.anchor_block_number, request.anchor_block_hash) {
+        (Some(number), Some(hash)) => evm::verify_block_anchor(rpc_url, number, hash).await,
+        _ => Err(ProofServiceError::InvalidInput(
+            "verify_anchor requires both anchor_block_hash and anchor_block_number".to_string(),
+        )),
+    }
+}
+
+// Checks every receipt with an `inclusion_proof` against the receipts root of the anchor block,
+// itself checked against the anchor hash; nothing is fetched when no receipt carries a proof
+async fn verify_receipt_inclusion(
+    rpc_url: &Url,
+    request: &CoprocessorProofRequest,
+) -> Result<(), ProofServiceError> {
+    let proven: Vec<(usize, &SerializableReceipt, &ReceiptInclusionProof)> = request
+        .blockchain_data
+        .receipts
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(i, receipt)| Some((i, receipt, receipt.inclusion_proof.as_ref()?)))
+        .collect();
+    if proven.is_empty() {
+        return Ok(());
+    }
+    let (Some(number), Some(hash)) = (request.anchor_block_number, request.anchor_block_hash)
+    else {
+        return Err(ProofServiceError::InvalidInput(
+            "Receipt inclusion proofs require both anchor_block_hash and anchor_block_number"
+                .to_string(),
+        ));
+    };
+    let receipts_root = evm::get_anchored_receipts_root(rpc_url, number, hash).await?;
+
+    for (i, receipt, inclusion) in proven {
+        if let Some(block_number) = receipt.block_number.filter(|&block| block != number) {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Receipt {} is from block {}, but inclusion proofs are checked against the \
+                 anchor block {}",
+                i, block_number, number
+            )));
+        }
+        let encoded = encoding::decode_hex(&receipt.raw_data_hex).map_err(|e| {
+            ProofServiceError::InvalidInput(format!("Receipt {} raw_data_hex: {}", i, e))
+        })?;
+        if encoded.is_empty() {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Receipt {} has an inclusion proof but no raw_data_hex to prove",
+                i
+            )));
+        }
+        let nodes = inclusion
+            .proof
+            .iter()
+            .map(|node| encoding::decode_hex(node))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                ProofServiceError::InvalidInput(format!("Receipt {} inclusion proof: {}", i, e))
+            })?;
+        let key = mpt::trie_index_key(inclusion.transaction_index);
+        let included = mpt::verify_proof(receipts_root, &key, &nodes).map_err(|reason| {
+            ProofServiceError::ChainDataMismatch(format!(
+                "Receipt {} is not included in block {} at index {}: {}",
+                i, number, inclusion.transaction_index, reason
+            ))
+        })?;
+        if included != encoded {
+            return Err(ProofServiceError::ChainDataMismatch(format!(
+                "Receipt {} does not match the receipt at index {} of block {}",
+                i, inclusion.transaction_index, number
+            )));
+        }
+    }
+    info!(block_number = number, %receipts_root, "Receipt inclusion verified");
+    Ok(())
+}
+
+// Helper function (similar to the one in generate_proof job), also returning the registry that
+// resolved the program
 async fn get_program_elf_for_coprocessor(
     ctx: &ServiceContext,
+    rpc_url: &Url,
     request: &CoprocessorProofRequest,
     program_hash_bytes: &B256,
-) -> Result<(TempDir, PathBuf), ProofServiceError> {
-    let location = match &request.program_location_override {
+    work_dir: &Path,
+) -> Result<(TempDir, PathBuf, Option<Address>), ProofServiceError> {
+    // A program fetched before (or warmed) needs no registry lookup or download
+    if let Some(cached) = program::cached_program(
+        ctx,
+        &request.program_hash,
+        request.program_location_override.as_ref(),
+        request.insecure_skip_hash_verify,
+        work_dir,
+    )
+    .await?
+    {
+        return Ok((cached.0, cached.1, None));
+    }
+
+    let (location, registry) = match &request.program_location_override {
         Some(loc) => {
             info!("Using coprocessor program location override: {:?}", loc);
-            loc.clone()
+            (loc.clone(), None)
         }
         None => {
             info!("Fetching coprocessor program location from registry...");
-            evm::get_program_location_from_registry(ctx, program_hash_bytes).await?
+            let (location, registry) = evm::get_program_location_from_registry(
+                ctx,
+                rpc_url,
+                request.registry_address_override.as_ref(),
+                program_hash_bytes,
+            )
+            .await?;
+            (location, Some(registry))
         }
     };
-    program::fetch_and_verify_program(ctx, &location, &request.program_hash).await
+    let (dir, path) = program::fetch_program(
+        ctx,
+        &location,
+        &request.program_hash,
+        work_dir,
+        request.insecure_skip_hash_verify,
+    )
+    .await?;
+    Ok((dir, path, registry))
 }
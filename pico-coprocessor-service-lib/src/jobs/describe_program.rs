@@ -0,0 +1,70 @@
+// pico-coprocessor-service-lib/src/jobs/describe_program.rs
+use crate::{
+    context::ServiceContext,
+    errors::ProofServiceError,
+    evm, program,
+    types::{DescribeProgramRequest, ProgramSchema},
+};
+use blueprint_sdk::{
+    alloy::primitives::B256,
+    error,
+    extract::Context,
+    info,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use std::str::FromStr;
+
+/// Returns the interface schema a program's owner published in the `ProgramRegistry`: the data
+/// it reads, the sizes it expects and the layout of its public values.
+///
+/// The schema document is fetched from the URI stored in the registry and must match the hash
+/// stored with it.
+pub async fn describe_program(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(request): TangleArg<DescribeProgramRequest>,
+) -> Result<TangleResult<ProgramSchema>, ProofServiceError> {
+    info!(program_hash = %request.program_hash, "Received describe_program job request");
+
+    let program_hash = match B256::from_str(&request.program_hash) {
+        Ok(hash) => hash,
+        Err(_) => {
+            let err = ProofServiceError::InvalidInput(format!(
+                "Invalid program_hash format (expected 32-byte hex): {}",
+                request.program_hash
+            ));
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    let (uri, schema_hash) = match evm::get_program_schema_from_registry(
+        &ctx,
+        &ctx.eth_rpc_url,
+        request.registry_address_override.as_ref(),
+        &program_hash,
+    )
+    .await
+    {
+        Ok(schema_ref) => schema_ref,
+        Err(err) => {
+            error!("Failed to look up program schema: {:?}", err);
+            return Err(err);
+        }
+    };
+    if uri.trim().is_empty() {
+        let err = ProofServiceError::InvalidInput(format!(
+            "Program {} has no published schema",
+            program_hash
+        ));
+        error!("{}", err);
+        return Err(err);
+    }
+
+    match program::fetch_program_schema(&ctx, &uri, &schema_hash).await {
+        Ok(schema) => Ok(TangleResult(schema)),
+        Err(err) => {
+            error!("Failed to fetch program schema: {:?}", err);
+            Err(err)
+        }
+    }
+}
@@ -1,34 +1,111 @@
 // pico-coprocessor-service-lib/src/jobs/generate_proof.rs
 use crate::{
+    auth,
+    checkpoint::ProofCheckpoint,
     context::ServiceContext,
+    encoding,
     errors::ProofServiceError,
-    evm, pico, program,
+    evm,
+    jobs::{
+        REDACTED, check_output_sink, ensure_private_inputs_allowed,
+        ensure_prove_options_applicable, finish_proof,
+    },
+    pico, program,
+    sandbox::{self, RequestSandbox},
     types::{ProofRequest, ProofResult},
+    validation,
 };
 use blueprint_sdk::{
-    alloy::primitives::B256,
+    alloy::primitives::{Address, B256},
     error,
     extract::Context,
     info,
     tangle::extract::{TangleArg, TangleResult},
+    warn,
+};
+use memmap2::Mmap;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
 };
-use std::{path::PathBuf, str::FromStr};
 use tempfile::TempDir; // To manage temporary directories
+use url::Url;
 
 // Wrapper struct to hold temporary resources and ensure cleanup
 struct ProofResources {
-    elf_temp_dir: TempDir, // Holds the temp dir containing the ELF, cleans up on drop
-    elf_path: PathBuf,
+    elf: LoadedElf,
     output_temp_dir: TempDir, // Holds the temp dir for proof outputs, cleans up on drop
     output_path: PathBuf,
+    _sandbox: RequestSandbox, // Declared last so it is removed after the dirs inside it
+}
+
+// Where the prover reads the program from
+enum LoadedElf {
+    // Fetched into the sandbox; the temp dir containing the ELF cleans up on drop
+    File {
+        _elf_temp_dir: TempDir,
+        elf_path: PathBuf,
+    },
+    // The fetched ELF proven from a memory mapping (ServiceContext::mmap_programs); declared
+    // before the temp dir so it is unmapped before the file is removed
+    Mapped {
+        mmap: Mmap,
+        _elf_temp_dir: TempDir,
+    },
 }
 
 pub async fn generate_proof(
     Context(ctx): Context<ServiceContext>,
     TangleArg(request): TangleArg<ProofRequest>,
 ) -> Result<TangleResult<ProofResult>, ProofServiceError> {
-    info!(request = ?request, "Received generate_proof job request");
+    if request.private_inputs {
+        info!(program_hash = %request.program_hash, inputs = REDACTED, "Received generate_proof job request");
+    } else {
+        info!(request = ?request, "Received generate_proof job request");
+    }
+
+    // Verify-only nodes never prove
+    if let Err(err) = ctx.ensure_can_prove() {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Track the job so shutdown waits for it; refused once shutdown has begun
+    let _in_flight = match ctx.in_flight.begin() {
+        Ok(guard) => guard,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Reject unsigned or unauthorized callers when authentication is enabled
+    let caller = match auth::authenticate(&ctx, &request) {
+        Ok(caller) => caller,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Keep one caller from monopolizing proving
+    if let Err(err) = ctx.check_rate_limit(caller.as_deref()) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    prove_request(ctx, request, caller).await.map(TangleResult)
+}
 
+/// Proves `request` on behalf of its authenticated `caller`, from validation through delivery of
+/// the result. `resume_proof` re-runs checkpointed requests through here, the job having been
+/// admitted (authenticated and rate-limited) already.
+pub(crate) async fn prove_request(
+    ctx: ServiceContext,
+    request: ProofRequest,
+    caller: Option<String>,
+) -> Result<ProofResult, ProofServiceError> {
     // --- 1. Preparation ---
     // Validate program hash format
     let program_hash_bytes = match B256::from_str(&request.program_hash) {
@@ -43,20 +120,111 @@ pub async fn generate_proof(
         }
     };
 
+    // Stale requests are refused before any work is done
+    if let Err(err) = validation::validate_expiry(request.expires_at, validation::unix_now()) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Tags end up as metric labels, so their number and size are bounded
+    if let Err(err) = validation::validate_tags(&request.tags) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Caller-chosen RPC endpoints are limited to the operator's allow-list; whichever endpoint
+    // applies serves every registry lookup and chain query of the job
+    let rpc_url = match ctx.rpc_url_for(request.eth_rpc_url_override.as_deref()) {
+        Ok(url) => url,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Fail before proving when the proof couldn't be delivered
+    if let Err(err) = check_output_sink(&ctx, request.output_sink.as_ref()) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // A program location this build can't fetch fails the job now, not partway through the fetch
+    if let Err(err) =
+        program::check_location_override(&ctx, request.program_location_override.as_ref())
+    {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Bound the input size before decoding anything (two hex chars per byte)
+    let input_size = encoding::strip_hex_prefix(&request.inputs).len() / 2;
+    if input_size > ctx.max_input_bytes {
+        let err = ProofServiceError::InputsTooLarge {
+            size: input_size,
+            limit: ctx.max_input_bytes,
+        };
+        error!("{}", err);
+        return Err(err);
+    }
+
     // Validate input hex format
-    if hex::decode(&request.inputs).is_err() {
+    if encoding::decode_hex(&request.inputs).is_err() {
         let err = ProofServiceError::InvalidInput(format!(
             "Invalid inputs format (expected hex): {}",
-            request.inputs
+            if request.private_inputs {
+                REDACTED
+            } else {
+                request.inputs.as_str()
+            }
         ));
         error!("{}", err);
         return Err(err);
     }
 
+    // Fail fast on proving types this build doesn't support
+    if let Err(err) = pico::ensure_proving_type_supported(&request.proving_type) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Validate the shard size and memory limit overrides, if any, against what the backend applies
+    if let Err(err) = ensure_prove_options_applicable(
+        &ctx,
+        &request.proving_type,
+        request.shard_size,
+        request.memory_limit_bytes,
+    ) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Private inputs must not leave this process
+    if let Err(err) = ensure_private_inputs_allowed(&ctx, request.private_inputs, false) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Isolate this job's files in its own sandbox under the temp base (or the caller's override)
+    let sandbox = match sandbox::sandbox_base(
+        &ctx.temp_dir_base,
+        &ctx.allowed_temp_dirs,
+        request.temp_dir_override.as_deref(),
+    )
+    .and_then(|base| RequestSandbox::create(&base))
+    {
+        Ok(sandbox) => sandbox,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+    info!(proof_id = %sandbox.proof_id(), "Assigned request sandbox");
+    let sandbox_proof_id = sandbox.proof_id().to_string();
+
     // Create a temporary directory for proof outputs for this specific job
     let output_temp_dir = match tempfile::Builder::new()
         .prefix("pico_output_")
-        .tempdir_in(&ctx.temp_dir_base)
+        .tempdir_in(sandbox.path())
     {
         Ok(dir) => dir,
         Err(e) => {
@@ -71,9 +239,36 @@ pub async fn generate_proof(
     let output_path = output_temp_dir.path().to_path_buf();
 
     // --- 2. Get Program ---
-    let fetch_result = get_program_elf(&ctx, &request, &program_hash_bytes).await;
-    let (elf_temp_dir, elf_path) = match fetch_result {
-        Ok((dir, path)) => (dir, path),
+    // Request paths are copied into the sandbox like any other program; only that copy is mapped
+    let fetch_result = match get_program_elf(
+        &ctx,
+        &rpc_url,
+        &request,
+        &program_hash_bytes,
+        sandbox.path(),
+    )
+    .await
+    {
+        Ok((dir, path, registry)) if ctx.mmap_programs => {
+            program::map_fetched_program(&path).await.map(|mmap| {
+                let elf = LoadedElf::Mapped {
+                    mmap,
+                    _elf_temp_dir: dir,
+                };
+                (elf, registry)
+            })
+        }
+        Ok((dir, path, registry)) => {
+            let elf = LoadedElf::File {
+                _elf_temp_dir: dir,
+                elf_path: path,
+            };
+            Ok((elf, registry))
+        }
+        Err(e) => Err(e),
+    };
+    let (elf, registry_address) = match fetch_result {
+        Ok(elf) => elf,
         Err(e) => {
             error!("Failed to get program ELF: {:?}", e);
             // Cleanup output dir if program fetch failed
@@ -83,60 +278,186 @@ pub async fn generate_proof(
     };
 
     // Wrap resources for automatic cleanup
-    let _resources = ProofResources {
+    let resources = ProofResources {
         // Variable binding ensures it lives long enough
-        elf_temp_dir,                     // Transfer ownership
-        elf_path: elf_path.clone(),       // Clone path for use
+        elf,                              // Transfer ownership
         output_temp_dir,                  // Transfer ownership
         output_path: output_path.clone(), // Clone path for use
+        _sandbox: sandbox,                // Removed last
+    };
+
+    // Persist long-running proofs so they can be resumed (resume_proof) if the service restarts,
+    // saving the RISCV phase again once it completes. Private inputs are never written to disk,
+    // so those proofs can't be resumed
+    let checkpoint_id = sandbox_proof_id.clone();
+    let checkpoints = ctx
+        .checkpoints
+        .clone()
+        .filter(|_| !request.proving_type.is_fast() && !request.private_inputs);
+    let on_riscv_proof = checkpoints.clone().map(|store| {
+        let checkpoint = ProofCheckpoint {
+            proof_id: checkpoint_id.clone(),
+            request: request.clone(),
+            caller: caller.clone(),
+            riscv_proof: None,
+        };
+        if let Err(e) = store.save(&checkpoint) {
+            warn!(proof_id = %checkpoint_id, "Failed to save proof checkpoint: {}", e);
+        }
+        pico::RiscvProofCallback(Arc::new(move |riscv: &ProofResult| {
+            let checkpoint = ProofCheckpoint {
+                riscv_proof: Some(riscv.clone()),
+                ..checkpoint.clone()
+            };
+            match store.save(&checkpoint) {
+                Ok(()) => info!(proof_id = %checkpoint.proof_id, "Checkpointed RISCV phase"),
+                Err(e) => {
+                    warn!(proof_id = %checkpoint.proof_id, "Failed to save RISCV checkpoint: {}", e)
+                }
+            }
+        }))
+    });
+
+    // Wait for a proving slot when concurrency is limited; higher priorities are served first
+    let _permit = match &ctx.proving_queue {
+        Some(queue) => Some(queue.acquire(request.priority).await),
+        None => None,
     };
 
     // --- 3. Execute Proving ---
-    let proof_exec_result = pico::execute_pico_prove(
-        &elf_path, // Path from fetch_result
-        &request.inputs,
-        &request.proving_type,
-        &output_path, // Use the dedicated output dir for this job
-    )
-    .await;
+    let options = pico::ProveOptions {
+        shard_size: request.shard_size,
+        on_full_failure: request.on_full_failure,
+        capture_logs: request.capture_logs,
+        include_riscv_proof: request.include_riscv_proof,
+        require_public_values: request.require_public_values,
+        recursion_proof_format: request.recursion_proof_format,
+        memory_limit_bytes: request.memory_limit_bytes,
+        evm_output_dir: ctx.evm_output_dir.clone(),
+        evm_output_retention: ctx.evm_output_retention,
+        evm_docker_image: ctx.evm_docker_image.clone(),
+        output_dir: None,
+        on_riscv_proof,
+        prover_cache: Some(ctx.prover_cache.clone()),
+    };
+    // The configured backend proves locally or delegates to a remote prover
+    let proof_exec_result = match &resources.elf {
+        LoadedElf::File { elf_path, .. } => match pico::read_elf_file(elf_path) {
+            Ok(elf) => {
+                ctx.prover_backend
+                    .prove(
+                        &elf,
+                        &request.inputs,
+                        &request.proving_type,
+                        &resources.output_path, // Use the dedicated output dir for this job
+                        &options,
+                    )
+                    .await
+            }
+            Err(e) => Err(e),
+        },
+        LoadedElf::Mapped { mmap, .. } => {
+            ctx.prover_backend
+                .prove(
+                    mmap,
+                    &request.inputs,
+                    &request.proving_type,
+                    &resources.output_path,
+                    &options,
+                )
+                .await
+        }
+    };
+
+    // Proving is over whether it succeeded or not; only an interrupted proof is resumed
+    if let Some(store) = &checkpoints {
+        if let Err(e) = store.remove(&checkpoint_id) {
+            warn!(proof_id = %checkpoint_id, "Failed to remove proof checkpoint: {}", e);
+        }
+    }
 
     // --- 4. Handle Result ---
     match proof_exec_result {
-        Ok(mut proof_result) => {
-            // Populate remaining fields
-            proof_result.program_hash = request.program_hash;
-            // Input is already hex, stored in pico::execute_pico_prove
-            // proof_result.inputs = request.inputs; // Already set inside execute_pico_prove
+        Ok(proof_result) => {
+            let proof_result = match finish_proof(
+                &ctx,
+                proof_result,
+                request,
+                sandbox_proof_id,
+                &rpc_url,
+                registry_address,
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(err) => {
+                    error!("Failed to finish proof: {}", err);
+                    return Err(err);
+                }
+            };
 
+            ctx.metrics
+                .record_proof("generate_proof", &proof_result, false);
             info!(result = ?proof_result, "Proof generation successful");
-            Ok(TangleResult(proof_result))
+            Ok(proof_result)
         }
         Err(e) => {
             error!("Proof generation failed: {:?}", e);
-            // Temp dirs (_elf_temp_dir, output_temp_dir) are cleaned up automatically when _resources goes out of scope
+            // Temp dirs (_elf_temp_dir, output_temp_dir) are cleaned up automatically when resources goes out of scope
             Err(e)
         }
     }
 }
 
-// Helper function to manage program fetching logic
-async fn get_program_elf(
+// Helper function to manage program fetching logic; also returns the registry that resolved the
+// program, None when none was consulted (a location override or a cached ELF)
+pub(crate) async fn get_program_elf(
     ctx: &ServiceContext,
+    rpc_url: &Url,
     request: &ProofRequest,
     program_hash_bytes: &B256,
-) -> Result<(TempDir, PathBuf), ProofServiceError> {
+    work_dir: &Path,
+) -> Result<(TempDir, PathBuf, Option<Address>), ProofServiceError> {
+    // A program fetched before (or warmed) needs no registry lookup or download
+    if let Some(cached) = program::cached_program(
+        ctx,
+        &request.program_hash,
+        request.program_location_override.as_ref(),
+        request.insecure_skip_hash_verify,
+        work_dir,
+    )
+    .await?
+    {
+        return Ok((cached.0, cached.1, None));
+    }
+
     // Determine location: Override > Registry
-    let location = match &request.program_location_override {
+    let (location, registry) = match &request.program_location_override {
         Some(loc) => {
             info!("Using program location override: {:?}", loc);
-            loc.clone()
+            (loc.clone(), None)
         }
         None => {
             info!("Fetching program location from registry...");
-            evm::get_program_location_from_registry(ctx, program_hash_bytes).await?
+            let (location, registry) = evm::get_program_location_from_registry(
+                ctx,
+                rpc_url,
+                request.registry_address_override.as_ref(),
+                program_hash_bytes,
+            )
+            .await?;
+            (location, Some(registry))
         }
     };
 
     // Fetch and verify
-    program::fetch_and_verify_program(ctx, &location, &request.program_hash).await
+    let (dir, path) = program::fetch_program(
+        ctx,
+        &location,
+        &request.program_hash,
+        work_dir,
+        request.insecure_skip_hash_verify,
+    )
+    .await?;
+    Ok((dir, path, registry))
 }
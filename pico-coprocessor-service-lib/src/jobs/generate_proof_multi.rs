@@ -0,0 +1,212 @@
+// pico-coprocessor-service-lib/src/jobs/generate_proof_multi.rs
+use crate::{
+    auth,
+    context::ServiceContext,
+    encoding,
+    errors::ProofServiceError,
+    jobs::{generate_proof::get_program_elf, set_proof_commitment},
+    pico, program,
+    sandbox::RequestSandbox,
+    types::{InputsEncoding, MultiProofRequest, ProofPriority, ProofRequest, ProofResult},
+};
+use blueprint_sdk::{
+    alloy::primitives::B256,
+    error,
+    extract::Context,
+    info,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use std::str::FromStr;
+
+/// Proves one program against each of `request.inputs`, returning the results in the same order.
+///
+/// The program is fetched and verified once for all of them, and proofs run in this process share
+/// the prover client cached for the ELF, so the machine is set up once too. Each input set is a
+/// separate proof: it counts against the caller's rate limit and waits for its own proving slot.
+pub async fn generate_proof_multi(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(request): TangleArg<MultiProofRequest>,
+) -> Result<TangleResult<Vec<ProofResult>>, ProofServiceError> {
+    info!(program_hash = %request.program_hash, input_sets = request.inputs.len(), "Received generate_proof_multi job request");
+
+    // Verify-only nodes never prove
+    if let Err(err) = ctx.ensure_can_prove() {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Track the job so shutdown waits for it; refused once shutdown has begun
+    let _in_flight = match ctx.in_flight.begin() {
+        Ok(guard) => guard,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Reject unsigned or unauthorized callers when authentication is enabled
+    let caller = match auth::authenticate(&ctx, &request) {
+        Ok(caller) => caller,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // --- 1. Preparation ---
+    let program_hash_bytes = match B256::from_str(&request.program_hash) {
+        Ok(hash) => hash,
+        Err(_) => {
+            let err = ProofServiceError::InvalidInput(format!(
+                "Invalid program_hash format (expected 32-byte hex): {}",
+                request.program_hash
+            ));
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // A program location this build can't fetch fails the job now, not partway through the fetch
+    if let Err(err) =
+        program::check_location_override(&ctx, request.program_location_override.as_ref())
+    {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    if request.inputs.is_empty() {
+        let err = ProofServiceError::InvalidInput("No input sets to prove".to_string());
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Check every input set before fetching anything
+    for (index, inputs) in request.inputs.iter().enumerate() {
+        let input_size = encoding::strip_hex_prefix(inputs).len() / 2;
+        if input_size > ctx.max_input_bytes {
+            let err = ProofServiceError::InputsTooLarge {
+                size: input_size,
+                limit: ctx.max_input_bytes,
+            };
+            error!(index, "{}", err);
+            return Err(err);
+        }
+        if encoding::decode_hex(inputs).is_err() {
+            let err = ProofServiceError::InvalidInput(format!(
+                "Invalid inputs format (expected hex) in input set {}: {}",
+                index, inputs
+            ));
+            error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    // Fail fast on proving types this build doesn't support
+    if let Err(err) = pico::ensure_proving_type_supported(&request.proving_type) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Every input set is a proof as far as the caller's limit is concerned, and the request takes
+    // a token for each or none; a request rejected above costs the caller nothing
+    let proofs = u32::try_from(request.inputs.len()).unwrap_or(u32::MAX);
+    if let Err(err) = ctx.check_rate_limit_n(caller.as_deref(), proofs) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Isolate this job's files in its own sandbox under the temp base
+    let sandbox = match RequestSandbox::create(&ctx.temp_dir_base) {
+        Ok(sandbox) => sandbox,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+    info!(proof_id = %sandbox.proof_id(), "Assigned request sandbox");
+
+    // --- 2. Get Program (once) ---
+    let fetch_request = ProofRequest {
+        program_hash: request.program_hash.clone(),
+        proving_type: request.proving_type.clone(),
+        program_location_override: request.program_location_override.clone(),
+        registry_address_override: request.registry_address_override.clone(),
+        ..Default::default()
+    };
+    let elf = match get_program_elf(
+        &ctx,
+        &ctx.eth_rpc_url,
+        &fetch_request,
+        &program_hash_bytes,
+        sandbox.path(),
+    )
+    .await
+    .and_then(|(_elf_temp_dir, elf_path, _registry)| pico::read_elf_file(&elf_path))
+    {
+        Ok(elf) => elf,
+        Err(e) => {
+            error!("Failed to get program ELF: {:?}", e);
+            return Err(e);
+        }
+    };
+
+    // --- 3. Prove each input set ---
+    let options = pico::ProveOptions {
+        evm_output_dir: ctx.evm_output_dir.clone(),
+        evm_output_retention: ctx.evm_output_retention,
+        evm_docker_image: ctx.evm_docker_image.clone(),
+        prover_cache: Some(ctx.prover_cache.clone()),
+        ..Default::default()
+    };
+    let mut results = Vec::with_capacity(request.inputs.len());
+    for (index, inputs) in request.inputs.iter().enumerate() {
+        let output_temp_dir = match tempfile::Builder::new()
+            .prefix("pico_output_")
+            .tempdir_in(sandbox.path())
+        {
+            Ok(dir) => dir,
+            Err(e) => {
+                let err = ProofServiceError::TempDirError(format!(
+                    "Failed to create proof output temp dir: {}",
+                    e
+                ));
+                error!("{}", err);
+                return Err(err);
+            }
+        };
+
+        // Wait for a proving slot when concurrency is limited
+        let _permit = match &ctx.proving_queue {
+            Some(queue) => Some(queue.acquire(ProofPriority::default()).await),
+            None => None,
+        };
+        let mut proof_result = match ctx
+            .prover_backend
+            .prove(
+                &elf,
+                inputs,
+                &request.proving_type,
+                output_temp_dir.path(),
+                &options,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(index, "Proof generation failed: {:?}", e);
+                return Err(e);
+            }
+        };
+        proof_result.program_hash = request.program_hash.clone();
+        proof_result.inputs = inputs.clone();
+        proof_result.inputs_encoding = InputsEncoding::Hex;
+        if let Err(err) = set_proof_commitment(&mut proof_result) {
+            error!("{}", err);
+            return Err(err);
+        }
+        info!(index, result = ?proof_result, "Proof generation successful");
+        results.push(proof_result);
+    }
+
+    Ok(TangleResult(results))
+}
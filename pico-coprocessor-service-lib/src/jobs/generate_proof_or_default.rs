@@ -0,0 +1,39 @@
+// pico-coprocessor-service-lib/src/jobs/generate_proof_or_default.rs
+use crate::{
+    context::ServiceContext,
+    errors::ProofServiceError,
+    jobs::generate_proof,
+    types::{ProofRequest, ProofResult},
+};
+use blueprint_sdk::{
+    error,
+    extract::Context,
+    info,
+    tangle::extract::{Optional, TangleArg, TangleResult},
+};
+
+/// Like `generate_proof`, but the request argument may be left out, in which case the service's
+/// `default_proof_request` is proven instead (e.g. periodically re-proving a fixed program).
+pub async fn generate_proof_or_default(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(Optional(request)): TangleArg<Optional<ProofRequest>>,
+) -> Result<TangleResult<ProofResult>, ProofServiceError> {
+    let request = match request {
+        Some(request) => request,
+        None => match &ctx.default_proof_request {
+            Some(default) => {
+                info!(program_hash = %default.program_hash, "No request given, proving the service default");
+                default.clone()
+            }
+            None => {
+                let err = ProofServiceError::InvalidInput(
+                    "No proof request given and the service has no default_proof_request"
+                        .to_string(),
+                );
+                error!("{}", err);
+                return Err(err);
+            }
+        },
+    };
+    generate_proof(Context(ctx), TangleArg(request)).await
+}
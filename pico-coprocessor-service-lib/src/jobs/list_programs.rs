@@ -0,0 +1,26 @@
+// pico-coprocessor-service-lib/src/jobs/list_programs.rs
+use crate::{context::ServiceContext, errors::ProofServiceError, evm, types::ProgramEntry};
+use blueprint_sdk::{error, extract::Context, info, tangle::extract::TangleResult};
+
+/// Returns every program registered in the configured `ProgramRegistry`.
+pub async fn list_programs(
+    Context(ctx): Context<ServiceContext>,
+) -> Result<TangleResult<Vec<ProgramEntry>>, ProofServiceError> {
+    info!("Received list_programs job request");
+
+    match evm::list_programs(&ctx).await {
+        Ok(programs) => Ok(TangleResult(
+            programs
+                .into_iter()
+                .map(|(hash, location)| ProgramEntry {
+                    program_hash: hash.to_string(),
+                    location,
+                })
+                .collect(),
+        )),
+        Err(e) => {
+            error!("Failed to list programs: {:?}", e);
+            Err(e)
+        }
+    }
+}
@@ -1,6 +1,231 @@
 // pico-coprocessor-service-lib/src/jobs/mod.rs
+pub mod check_program;
 pub mod coprocessor;
+pub mod describe_program;
 pub mod generate_proof;
+pub mod generate_proof_multi;
+pub mod generate_proof_or_default;
+pub mod list_programs;
+pub mod prove;
+pub mod resume_proof;
+pub mod verify_proof;
+pub mod warm_program;
 
+pub use check_program::check_program_matches_registry;
 pub use coprocessor::generate_coprocessor_proof;
+pub use describe_program::describe_program;
 pub use generate_proof::generate_proof;
+pub use generate_proof_multi::generate_proof_multi;
+pub use generate_proof_or_default::generate_proof_or_default;
+pub use list_programs::list_programs;
+pub use prove::prove;
+pub use resume_proof::resume_proof;
+pub use verify_proof::{verify_proof, verify_proofs_stream};
+pub use warm_program::warm_program;
+
+use crate::context::ServiceContext;
+use crate::encoding;
+use crate::errors::ProofServiceError;
+use crate::evm;
+use crate::pico;
+use crate::types::{InputsEncoding, OutputSink, ProofRequest, ProofResult, ProvingType};
+use blueprint_sdk::alloy::primitives::Address;
+use blueprint_sdk::warn;
+use url::Url;
+
+/// Logged in place of the inputs of `private_inputs` requests.
+pub(crate) const REDACTED: &str = "<redacted>";
+
+/// Clears the echoed `inputs` of a result when its request asked for private inputs.
+pub(crate) fn withhold_private_inputs(
+    mut result: ProofResult,
+    private_inputs: bool,
+) -> ProofResult {
+    if private_inputs {
+        result.inputs.clear();
+    }
+    result
+}
+
+/// Refuses a `private_inputs` request the inputs would leave the service's own process for: a
+/// dump of them (`dump_inputs`) or a prover backend proving elsewhere. Checkpoints, which would
+/// persist them, are skipped for such requests instead.
+pub(crate) fn ensure_private_inputs_allowed(
+    ctx: &ServiceContext,
+    private_inputs: bool,
+    dump_inputs: bool,
+) -> Result<(), ProofServiceError> {
+    if !private_inputs {
+        return Ok(());
+    }
+    if dump_inputs {
+        return Err(ProofServiceError::InvalidInput(
+            "private_inputs can't be combined with dump_inputs_to".to_string(),
+        ));
+    }
+    if !ctx.prover_backend.keeps_inputs_in_process() {
+        return Err(ProofServiceError::InvalidInput(
+            "private_inputs needs a service proving in-process; this one hands inputs to a \
+             remote prover or prover subprocess"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Refuses prover options the configured backend wouldn't apply, before any work is done: a
+/// `shard_size` override (validated either way) and the `memory_limit_bytes` of a `FullWithEvm`
+/// proof only hold in a process started with them (see `ProverBackend::applies_process_env`).
+pub(crate) fn ensure_prove_options_applicable(
+    ctx: &ServiceContext,
+    proving_type: &ProvingType,
+    shard_size: Option<usize>,
+    memory_limit_bytes: Option<usize>,
+) -> Result<(), ProofServiceError> {
+    if let Some(shard_size) = shard_size {
+        pico::validate_shard_size(shard_size)?;
+    }
+    if ctx.prover_backend.applies_process_env() {
+        return Ok(());
+    }
+    pico::ensure_shard_size_applied(shard_size)?;
+    if *proving_type == ProvingType::FullWithEvm && memory_limit_bytes.is_some() {
+        return Err(ProofServiceError::InvalidInput(
+            "memory_limit_bytes can't be applied to FullWithEvm proofs in process; the service \
+             must prove in subprocesses (prover_subprocess)"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Records the result's `proof_commitment`, computed from its proof.
+pub(crate) fn set_proof_commitment(result: &mut ProofResult) -> Result<(), ProofServiceError> {
+    result.proof_commitment = result.compute_proof_commitment()?.to_string();
+    Ok(())
+}
+
+/// Compresses the public values of a result when its request asked for it.
+pub(crate) fn compress_if_requested(
+    result: ProofResult,
+    compress: bool,
+) -> Result<ProofResult, ProofServiceError> {
+    if compress {
+        encoding::compress_public_values(result)
+    } else {
+        Ok(result)
+    }
+}
+
+/// Rejects an S3 output sink up front when the service has no S3 store to upload to, or when it
+/// names a bucket or key prefix the store doesn't allow.
+pub(crate) fn check_output_sink(
+    ctx: &ServiceContext,
+    sink: Option<&OutputSink>,
+) -> Result<(), ProofServiceError> {
+    match sink {
+        Some(OutputSink::S3 { bucket, key_prefix }) => ctx
+            .s3_store
+            .as_ref()
+            .ok_or_else(no_s3_store)?
+            .check_destination(bucket, key_prefix),
+        _ => Ok(()),
+    }
+}
+
+fn no_s3_store() -> ProofServiceError {
+    ProofServiceError::InvalidInput(
+        "S3 output_sink requested but the service has no S3 store configured".to_string(),
+    )
+}
+
+/// Delivers the proof to the request's output sink: left inline, or uploaded and replaced by the
+/// object's URL. Applied last, so `proof_commitment` and the metadata describe the uploaded proof.
+pub(crate) async fn deliver_proof(
+    ctx: &ServiceContext,
+    mut result: ProofResult,
+    sink: Option<&OutputSink>,
+) -> Result<ProofResult, ProofServiceError> {
+    let Some(OutputSink::S3 { bucket, key_prefix }) = sink else {
+        return Ok(result);
+    };
+    let store = ctx.s3_store.as_ref().ok_or_else(no_s3_store)?;
+    store.check_destination(bucket, key_prefix)?;
+    let proof = result.output_encoding.decode(&result.proof)?;
+    let key = format!(
+        "{}{}.proof",
+        key_prefix,
+        encoding::strip_hex_prefix(&result.proof_commitment)
+    );
+    result.proof = store.put_object(bucket, &key, proof).await?.to_string();
+    result.proof_is_reference = true;
+    Ok(result)
+}
+
+/// Records the chain (`eth_chainId` of `rpc_url`, the RPC the job used) and the registry (the one
+/// that resolved the program, `None` when the job consulted none) a result was produced against.
+/// The chain is left unset when it can't be determined, which doesn't fail the proof.
+pub(crate) async fn record_chain_context(
+    ctx: &ServiceContext,
+    mut result: ProofResult,
+    rpc_url: &Url,
+    registry_address: Option<Address>,
+) -> ProofResult {
+    result.chain_id = match evm::get_cached_chain_id(rpc_url, &ctx.chain_ids).await {
+        Ok(chain_id) => Some(chain_id),
+        Err(e) => {
+            warn!(%rpc_url, "Proof result won't record its chain: {}", e);
+            None
+        }
+    };
+    result.registry_address = registry_address;
+    result
+}
+
+/// Completes a proof the prover returned for `request`: fills in what only the request knows,
+/// records the chain context and applies the request's output options, delivering the proof
+/// last. Freshly proven and checkpoint-resumed proofs both finish here; `registry_address` is the
+/// registry the program was resolved from, if any.
+pub(crate) async fn finish_proof(
+    ctx: &ServiceContext,
+    mut result: ProofResult,
+    request: ProofRequest,
+    proof_id: String,
+    rpc_url: &Url,
+    registry_address: Option<Address>,
+) -> Result<ProofResult, ProofServiceError> {
+    result.program_hash = request.program_hash;
+    result.proof_id = Some(proof_id);
+    result.nonce = request.nonce;
+    result.expires_at = request.expires_at;
+    result.tags = request.tags;
+    // The raw hex inputs as given, whatever the backend echoed back
+    result.inputs = request.inputs;
+    result.inputs_encoding = InputsEncoding::Hex;
+    set_proof_commitment(&mut result)?;
+    let result = record_chain_context(ctx, result, rpc_url, registry_address).await;
+
+    let result = encoding::reencode_result(result, request.output_encoding)?;
+    let result = withhold_private_inputs(result, request.private_inputs);
+    let result = attach_scale_metadata(result, request.include_scale_metadata);
+    let result = compress_if_requested(result, request.compress_public_values)?;
+    deliver_proof(ctx, result, request.output_sink.as_ref()).await
+}
+
+/// Attaches the SCALE-encoded metadata of a result when its request asked for it.
+///
+/// The metadata is an extra, so a result it can't be encoded for is returned without it rather
+/// than losing the proof.
+pub(crate) fn attach_scale_metadata(mut result: ProofResult, include: bool) -> ProofResult {
+    if include {
+        match result.to_scale() {
+            Ok(scale) => result.scale_metadata_hex = Some(hex::encode(scale)),
+            Err(e) => warn!(
+                program_hash = %result.program_hash,
+                "Failed to encode SCALE metadata, returning the proof without it: {}",
+                e
+            ),
+        }
+    }
+    result
+}
@@ -0,0 +1,30 @@
+// pico-coprocessor-service-lib/src/jobs/prove.rs
+use crate::{
+    context::ServiceContext,
+    errors::ProofServiceError,
+    jobs::{generate_coprocessor_proof, generate_proof},
+    types::{AnyProofRequest, ProofResult},
+};
+use blueprint_sdk::{
+    extract::Context,
+    info,
+    tangle::extract::{TangleArg, TangleResult},
+};
+
+/// Single proving entry point: hands a generic request to `generate_proof` and a coprocessor
+/// request to `generate_coprocessor_proof`.
+pub async fn prove(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(request): TangleArg<AnyProofRequest>,
+) -> Result<TangleResult<ProofResult>, ProofServiceError> {
+    match request {
+        AnyProofRequest::Generic(request) => {
+            info!("Dispatching prove job to generate_proof");
+            generate_proof(Context(ctx), TangleArg(request)).await
+        }
+        AnyProofRequest::Coprocessor(request) => {
+            info!("Dispatching prove job to generate_coprocessor_proof");
+            generate_coprocessor_proof(Context(ctx), TangleArg(request)).await
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// pico-coprocessor-service-lib/src/jobs/resume_proof.rs
+use crate::{
+    auth,
+    context::ServiceContext,
+    errors::ProofServiceError,
+    jobs::{finish_proof, generate_proof::prove_request},
+    types::{FailurePolicy, ProofRequest, ProofResult, ResumeProofRequest},
+    validation,
+};
+use blueprint_sdk::{
+    error,
+    extract::Context,
+    info,
+    tangle::extract::{TangleArg, TangleResult},
+    warn,
+};
+
+/// Resumes a proof from the checkpoint `generate_proof` persisted before an interruption.
+///
+/// A recorded RISCV phase completes requests that accept a `Fast` proof. Otherwise proving restarts
+/// from the RISCV phase, since pico-sdk runs RISCV and recursion as a single call. Only the caller
+/// that requested the proof may resume it, and the checkpoint is removed once the job finishes,
+/// whatever the outcome.
+pub async fn resume_proof(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(request): TangleArg<ResumeProofRequest>,
+) -> Result<TangleResult<ProofResult>, ProofServiceError> {
+    info!(proof_id = %request.proof_id, "Received resume_proof job request");
+
+    // Verify-only nodes never prove
+    if let Err(err) = ctx.ensure_can_prove() {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Track the job so shutdown waits for it; refused once shutdown has begun
+    let _in_flight = match ctx.in_flight.begin() {
+        Ok(guard) => guard,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Reject unsigned or unauthorized callers when authentication is enabled
+    let caller = match auth::authenticate(&ctx, &request) {
+        Ok(caller) => caller,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Resumed proofs count against the caller's limit like new ones, re-runs included: those skip
+    // generate_proof's own admission
+    if let Err(err) = ctx.check_rate_limit(caller.as_deref()) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    let Some(store) = ctx.checkpoints.clone() else {
+        let err = ProofServiceError::ConfigError("proof checkpoints are not enabled".to_string());
+        error!("{}", err);
+        return Err(err);
+    };
+    let checkpoint = match store.load(&request.proof_id) {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => {
+            let err = ProofServiceError::InvalidInput(format!(
+                "No checkpoint for proof_id {}",
+                request.proof_id
+            ));
+            error!("{}", err);
+            return Err(err);
+        }
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Another caller's proof is left alone, checkpoint included
+    if checkpoint.caller.is_some() && checkpoint.caller != caller {
+        let err = ProofServiceError::Unauthorized(format!(
+            "proof {} was requested by another caller",
+            request.proof_id
+        ));
+        error!("{}", err);
+        return Err(err);
+    }
+
+    let accepts_fast = checkpoint.request.proving_type.is_fast()
+        || checkpoint.request.on_full_failure == FailurePolicy::FallbackFast;
+    let outcome = match checkpoint.riscv_proof {
+        Some(riscv) if accepts_fast => {
+            info!(proof_id = %request.proof_id, "Completing proof from RISCV checkpoint");
+            complete_from_riscv(&ctx, riscv, checkpoint.request, &request.proof_id).await
+        }
+        _ => {
+            info!(proof_id = %request.proof_id, "Re-running proof from checkpointed request");
+            prove_request(ctx.clone(), checkpoint.request, caller).await
+        }
+    };
+
+    // A failed resume isn't retried from the checkpoint either
+    if let Err(e) = store.remove(&request.proof_id) {
+        warn!(proof_id = %request.proof_id, "Failed to remove proof checkpoint: {}", e);
+    }
+    match outcome {
+        Ok(result) => {
+            info!(proof_id = %request.proof_id, "Resumed proof completed");
+            Ok(TangleResult(result))
+        }
+        Err(err) => {
+            error!(proof_id = %request.proof_id, "Resumed proof failed: {}", err);
+            Err(err)
+        }
+    }
+}
+
+// Completes a checkpointed request from its recorded RISCV proof, finishing it like a freshly
+// proven one
+async fn complete_from_riscv(
+    ctx: &ServiceContext,
+    mut riscv: ProofResult,
+    request: ProofRequest,
+    proof_id: &str,
+) -> Result<ProofResult, ProofServiceError> {
+    // The request may have expired while the proof was interrupted
+    validation::validate_expiry(request.expires_at, validation::unix_now())?;
+    let rpc_url = ctx.rpc_url_for(request.eth_rpc_url_override.as_deref())?;
+    riscv.downgraded = !request.proving_type.is_fast();
+    // No registry is consulted to resume; the program was resolved before the interruption
+    let result = finish_proof(ctx, riscv, request, proof_id.to_string(), &rpc_url, None).await?;
+    ctx.metrics.record_proof("resume_proof", &result, false);
+    Ok(result)
+}
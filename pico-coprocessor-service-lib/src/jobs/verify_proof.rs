@@ -0,0 +1,154 @@
+// pico-coprocessor-service-lib/src/jobs/verify_proof.rs
+use crate::{
+    context::ServiceContext,
+    errors::ProofServiceError,
+    evm, pico, program,
+    sandbox::RequestSandbox,
+    types::{ProgramLocation, ProofPriority, ProofResult, VerifyProofRequest},
+};
+use blueprint_sdk::{
+    alloy::primitives::B256,
+    error,
+    extract::Context,
+    info,
+    tangle::extract::{TangleArg, TangleResult},
+    warn,
+};
+use futures::{Stream, StreamExt};
+use std::str::FromStr;
+
+/// Verifies a previously generated proof. Runs in both service modes.
+pub async fn verify_proof(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(request): TangleArg<VerifyProofRequest>,
+) -> Result<TangleResult<bool>, ProofServiceError> {
+    let program_hash = &request.proof.program_hash;
+    info!(%program_hash, proving_type = ?request.proof.proving_type, "Received verify_proof job request");
+
+    // Proofs from incompatible prover versions can't be checked meaningfully
+    if let Some(min_version) = &ctx.min_prover_version {
+        if let Err(err) = pico::ensure_prover_version(&request.proof.prover_version, min_version) {
+            error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    match check_proof(
+        &ctx,
+        &request.proof,
+        request.program_location_override.as_ref(),
+    )
+    .await
+    {
+        Ok(valid) => Ok(TangleResult(valid)),
+        Err(e) => {
+            error!("Proof verification failed: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Verifies a stream of proofs, at most `concurrency` at a time, yielding each proof's commitment
+/// (keccak256 of its proof bytes) and whether it verified, in completion order.
+///
+/// Each check also holds a slot of `ctx.proving_queue` when one is configured, so a verifier
+/// node's concurrency limit covers streamed checks too. Programs are located through the registry.
+/// A proof that can't be checked at all (unknown program, undecodable proof, unsupported proving
+/// type, incompatible prover version) yields `false`.
+pub fn verify_proofs_stream<'a, S>(
+    ctx: &'a ServiceContext,
+    proofs: S,
+    concurrency: usize,
+) -> impl Stream<Item = (String, bool)> + 'a
+where
+    S: Stream<Item = ProofResult> + 'a,
+{
+    proofs
+        .map(move |proof| async move {
+            let commitment = proof
+                .compute_proof_commitment()
+                .map(|commitment| commitment.to_string())
+                .unwrap_or_else(|_| proof.proof_commitment.clone());
+            let _permit = match &ctx.proving_queue {
+                Some(queue) => Some(queue.acquire(ProofPriority::default()).await),
+                None => None,
+            };
+            let valid = async {
+                if let Some(min_version) = &ctx.min_prover_version {
+                    pico::ensure_prover_version(&proof.prover_version, min_version)?;
+                }
+                check_proof(ctx, &proof, None).await
+            }
+            .await;
+            match valid {
+                Ok(valid) => (commitment, valid),
+                Err(err) => {
+                    warn!(%commitment, "Streamed proof could not be verified: {}", err);
+                    (commitment, false)
+                }
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+}
+
+// Fetches the proof's program (from the override or the registry) and verifies the proof against it
+async fn check_proof(
+    ctx: &ServiceContext,
+    proof: &ProofResult,
+    program_location_override: Option<&ProgramLocation>,
+) -> Result<bool, ProofServiceError> {
+    // The service doesn't fetch uploaded proofs back; the caller restores the bytes first
+    if proof.proof_is_reference {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "proof is a reference to {}; restore the uploaded proof to verify it",
+            proof.proof
+        )));
+    }
+    let program_hash = &proof.program_hash;
+    let program_hash_bytes = B256::from_str(program_hash).map_err(|_| {
+        ProofServiceError::InvalidInput(format!(
+            "Invalid program_hash format (expected 32-byte hex): {}",
+            program_hash
+        ))
+    })?;
+    program::check_location_override(ctx, program_location_override)?;
+
+    let sandbox = RequestSandbox::create(&ctx.temp_dir_base)?;
+
+    let cached = program::cached_program(
+        ctx,
+        program_hash,
+        program_location_override,
+        false,
+        sandbox.path(),
+    )
+    .await?;
+    let (_elf_temp_dir, elf_path) = match cached {
+        Some(cached) => cached,
+        None => {
+            let location = match program_location_override {
+                Some(loc) => {
+                    info!("Using program location override: {:?}", loc);
+                    loc.clone()
+                }
+                None => {
+                    evm::get_program_location_from_registry(
+                        ctx,
+                        &ctx.eth_rpc_url,
+                        None,
+                        &program_hash_bytes,
+                    )
+                    .await?
+                    .0
+                }
+            };
+            program::fetch_and_verify_program(ctx, &location, program_hash, sandbox.path()).await?
+        }
+    };
+
+    // Verification is CPU-bound; keep it off the async workers so streamed checks run in parallel
+    let (proof, cache) = (proof.clone(), ctx.prover_cache.clone());
+    tokio::task::spawn_blocking(move || pico::verify_pico_proof(&elf_path, &proof, &cache))
+        .await
+        .map_err(|e| ProofServiceError::InternalError(format!("Verification task failed: {}", e)))?
+}
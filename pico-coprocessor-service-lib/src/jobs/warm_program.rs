@@ -0,0 +1,144 @@
+// pico-coprocessor-service-lib/src/jobs/warm_program.rs
+use crate::{
+    auth,
+    context::ServiceContext,
+    elf_cache::ElfCache,
+    errors::ProofServiceError,
+    evm, program,
+    sandbox::RequestSandbox,
+    types::{WarmProgramRequest, WarmProgramResult},
+};
+use blueprint_sdk::{
+    alloy::primitives::B256,
+    error,
+    extract::Context,
+    info,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Fetches and verifies a program into the context's ELF cache and sets up its prover for the
+/// request's proving type in the context's prover cache, without proving anything, so the first
+/// such proof skips the registry lookup, the download and the prover setup. Only proving nodes run it, and it is authenticated and rate limited like a
+/// proof since it makes the node download.
+///
+/// The program is looked up in the service's registries. Requires an ELF cache (`elf_cache_dir`);
+/// a program already cached isn't fetched again.
+pub async fn warm_program(
+    Context(ctx): Context<ServiceContext>,
+    TangleArg(request): TangleArg<WarmProgramRequest>,
+) -> Result<TangleResult<WarmProgramResult>, ProofServiceError> {
+    info!(program_hash = %request.program_hash, "Received warm_program job request");
+
+    // Verify-only nodes never prove, so there is nothing to warm
+    if let Err(err) = ctx.ensure_can_prove() {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    // Track the job so shutdown waits for it; refused once shutdown has begun
+    let _in_flight = match ctx.in_flight.begin() {
+        Ok(guard) => guard,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Reject unsigned or unauthorized callers when authentication is enabled
+    let caller = match auth::authenticate(&ctx, &request) {
+        Ok(caller) => caller,
+        Err(err) => {
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    // Warming downloads like a proof does, so it counts against the caller's limit
+    if let Err(err) = ctx.check_rate_limit(caller.as_deref()) {
+        error!("{}", err);
+        return Err(err);
+    }
+
+    let Some(cache) = ctx.elf_cache.clone() else {
+        let err = ProofServiceError::ConfigError(
+            "warm_program requires an ELF cache (elf_cache_dir)".to_string(),
+        );
+        error!("{}", err);
+        return Err(err);
+    };
+
+    let program_hash_bytes = match B256::from_str(&request.program_hash) {
+        Ok(hash) => hash,
+        Err(_) => {
+            let err = ProofServiceError::InvalidInput(format!(
+                "Invalid program_hash format (expected 32-byte hex): {}",
+                request.program_hash
+            ));
+            error!("{}", err);
+            return Err(err);
+        }
+    };
+
+    match warm(&ctx, &cache, &request, &program_hash_bytes).await {
+        Ok(result) => {
+            info!(result = ?result, "Program warmed");
+            Ok(TangleResult(result))
+        }
+        Err(err) => {
+            error!("Failed to warm program: {:?}", err);
+            Err(err)
+        }
+    }
+}
+
+async fn warm(
+    ctx: &ServiceContext,
+    cache: &ElfCache,
+    request: &WarmProgramRequest,
+    program_hash_bytes: &B256,
+) -> Result<WarmProgramResult, ProofServiceError> {
+    let (cached, already_cached, fetch_ms) = match cache.get(&request.program_hash) {
+        Some(path) => (path, true, 0),
+        None => {
+            let sandbox = RequestSandbox::create(&ctx.temp_dir_base)?;
+            let started = Instant::now();
+            let (location, _registry) = evm::get_program_location_from_registry(
+                ctx,
+                &ctx.eth_rpc_url,
+                None,
+                program_hash_bytes,
+            )
+            .await?;
+            let (_elf_temp_dir, elf_path) = program::fetch_and_verify_program(
+                ctx,
+                &location,
+                &request.program_hash,
+                sandbox.path(),
+            )
+            .await?;
+            let fetch_ms = started.elapsed().as_millis() as u64;
+
+            // The fetch caches the verified ELF, but only warns if that fails; here it is the
+            // whole point
+            let cached = cache
+                .get_or_insert(&request.program_hash, &elf_path, None)
+                .await?;
+            (cached, false, fetch_ms)
+        }
+    };
+
+    let elf = tokio::fs::read(&cached).await?;
+    let prover_client_cached = ctx
+        .prover_backend
+        .warm(&elf, &request.proving_type, &ctx.prover_cache)
+        .await?;
+    Ok(WarmProgramResult {
+        program_hash: request.program_hash.clone(),
+        already_cached,
+        elf_bytes: elf.len() as u64,
+        fetch_ms,
+        prover_client_cached,
+    })
+}
@@ -1,37 +1,169 @@
 // pico-coprocessor-service-lib/src/lib.rs
 
 // Declare modules
+mod archive;
+mod auth;
+mod backend;
+mod brevis;
+mod cache;
+mod canonical;
+mod checkpoint;
+mod config;
 mod context;
+mod elf_cache;
+mod encoding;
+mod ens;
 mod errors;
 mod evm;
+mod field_mapping;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod fixtures;
 mod jobs;
+mod memory;
+mod metrics;
+mod mpt;
 mod pico;
+mod preprocess;
 mod program;
+mod proof_format;
+mod public_values;
+mod queue;
+mod rate_limit;
+mod s3;
+mod sandbox;
+mod service;
+mod shutdown;
+mod transport;
 mod types;
+mod validation;
 
 #[cfg(test)]
 mod tests;
 
 // Publicly export key types, errors, context, and job functions
-pub use context::ServiceContext;
-pub use errors::ProofServiceError;
+pub use archive::{
+    ARCHIVE_EVM_DIR, ARCHIVE_MANIFEST, BLOCKCHAIN_DATA_ENTRY, fetch_blockchain_data,
+    unpack_blockchain_data,
+};
+pub use auth::{SignedRequest, authenticate, sign_request};
+pub use backend::{
+    DEFAULT_REMOTE_PROVER_TIMEOUT, LocalProver, PROVER_WORKER_ENV, ProverBackend,
+    ProverWorkerRequest, ProverWorkerResponse, RemoteProveRequest, RemoteProver, SubprocessProver,
+    is_prover_worker, run_prover_worker,
+};
+pub use brevis::{
+    BREVIS_REQUEST_DATA_ENV, BREVIS_REQUEST_FILE, BrevisRequestClient, BrevisRequestOutcome,
+};
+pub use cache::{DEFAULT_PROOF_CACHE_ENTRIES, ProofCache, ProofCacheKey};
+pub use canonical::to_canonical_json;
+pub use checkpoint::{CheckpointStore, ProofCheckpoint};
+pub use config::{DEFAULT_TEMP_DIR_BASE, RateLimitConfig, SERVICE_CONFIG_ENV, ServiceConfig};
+pub use context::{
+    DEFAULT_ELF_CONTENT_TYPES, HardLimits, HttpProxyConfig, ServiceContext, ServiceMode,
+};
+pub use elf_cache::ElfCache;
+pub use encoding::{
+    MAX_PUBLIC_VALUES_BYTES, ProofMetadata, compress_public_values, decode_hex, reencode_result,
+    strip_hex_prefix,
+};
+pub use ens::{ENS_REGISTRY_ADDRESS, EnsCache, namehash};
+pub use errors::{JobError, ProofServiceError};
+pub use evm::ChainIdCache;
+pub use metrics::{DEFAULT_MAX_TAGGED_SERIES, ProofMetrics, TAG_LABEL_PREFIX, serve_metrics};
+pub use pico::{
+    FieldConfig, PHASE_EVM, PHASE_RECURSION, PHASE_RISCV, PROVER_CLIENT_CACHE_CAPACITY,
+    PROVER_VERSION, ProveOptions, ProverCache, RiscvProofCallback, verify_coprocessor_proof,
+};
+pub use preprocess::InputPreprocessor;
+pub use program::{
+    DownloadDedup, HASH_ANNOTATION_PREFIX, HashProgress, HashProgressCallback, MAX_SCHEMA_BYTES,
+    SUPPORTED_URL_SCHEMES, location_hash_algorithm,
+};
+pub use proof_format::{decode_recursion_proof, encode_recursion_proof};
+pub use public_values::{
+    MERKLE_LEAF_PREFIX, MERKLE_NODE_PREFIX, apply_public_values_policy, decode_public_values,
+    merkle_root, parse_merkle_commitment,
+};
+pub use queue::{ProvingPermit, ProvingQueue};
+pub use rate_limit::{MAX_RETRY_AFTER, RateLimiter, UNAUTHENTICATED_CALLER};
+pub use s3::{DEFAULT_S3_REGION, S3Config, S3Store};
+pub use service::ProofServiceBuilder;
+pub use shutdown::{
+    DEFAULT_SHUTDOWN_GRACE, InFlightGuard, InFlightTracker, ShutdownReport, graceful_shutdown,
+};
 // Export new job function and request type
-pub use jobs::{generate_coprocessor_proof, generate_proof};
+pub use jobs::{
+    check_program_matches_registry, describe_program, generate_coprocessor_proof, generate_proof,
+    generate_proof_multi, generate_proof_or_default, list_programs, prove, resume_proof,
+    verify_proof, verify_proofs_stream, warm_program,
+};
+pub use transport::{
+    PUBLIC_VALUES_LENGTH_HEADER, ProofResponseBody, decode_request_body, encode_proof_response,
+};
+pub use validation::{
+    MAX_TAG_KEY_BYTES, MAX_TAG_VALUE_BYTES, MAX_TAGS, unix_now, validate_expiry,
+    validate_hard_limits, validate_max_sizes, validate_receipt_blocks,
+    validate_receipt_log_structure, validate_tags, validate_topic_counts,
+};
 // Export new request type
 pub use types::{
+    AnyProofRequest,
     BlockchainData,
+    BlockchainDataSource,
+    CheckProgramRequest,
+    CheckProgramResult,
+    CommitMode,
+    CoprocessorCommitFormat,
     CoprocessorProofRequest,
+    CoprocessorVerification,
+    DescribeProgramRequest,
+    EvmInputsJson,
+    FailurePolicy,
+    FieldMapping,
+    FieldSource,
+    FieldSpec,
+    HashAlgorithm,
+    InputsEncoding,
+    MappedField,
     MaxSizes, // Export new types
+    MerkleCommitment,
+    MultiProofRequest,
+    OutputEncoding,
+    OutputSink,
+    ProgramEntry,
     ProgramLocation,
+    ProgramSchema,
+    ProofPriority,
     ProofRequest,
     ProofResult,
     ProvingType,
+    PublicValuesPolicy,
+    ReceiptBlock,
+    ReceiptInclusionProof,
+    ReceiptOrder,
+    RecursionProofFormat,
+    RegistryRef,
+    RequestSignature,
+    ResumeProofRequest,
     SerializableLog,
     SerializableReceipt,
     SerializableStorageSlot,
     SerializableTransaction, // Export data types
+    TransactionFields,
+    VerifyProofRequest,
+    WarmProgramRequest,
+    WarmProgramResult,
 };
 
 // Define Job IDs
 pub const GENERATE_PROOF_JOB_ID: u32 = 1;
 pub const GENERATE_COPROCESSOR_PROOF_JOB_ID: u32 = 2; // New Job ID
+pub const VERIFY_PROOF_JOB_ID: u32 = 3;
+pub const LIST_PROGRAMS_JOB_ID: u32 = 4;
+pub const RESUME_PROOF_JOB_ID: u32 = 5;
+pub const PROVE_JOB_ID: u32 = 6; // Dispatches either request kind
+pub const GENERATE_PROOF_OR_DEFAULT_JOB_ID: u32 = 7; // Request argument optional
+pub const GENERATE_PROOF_MULTI_JOB_ID: u32 = 8; // One program, several input sets
+pub const DESCRIBE_PROGRAM_JOB_ID: u32 = 9; // Program interface from the registry
+pub const WARM_PROGRAM_JOB_ID: u32 = 10; // Fetch a program into the ELF cache ahead of use
+pub const CHECK_PROGRAM_JOB_ID: u32 = 11; // Compare a local ELF with the registered program
@@ -0,0 +1,215 @@
+// pico-coprocessor-service-lib/src/memory.rs
+use crate::errors::ProofServiceError;
+use blueprint_sdk::{debug, warn};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Reads `MemAvailable` from `/proc/meminfo`, in bytes. `None` where the file is missing or unreadable.
+pub fn available_memory() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    parse_mem_available(&meminfo)
+}
+
+/// Extracts `MemAvailable` (reported in kB) from the text of `/proc/meminfo`, in bytes.
+pub fn parse_mem_available(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kib: u64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+    kib.checked_mul(1024)
+}
+
+/// Rejects a request whose memory ceiling exceeds what the host currently has available.
+///
+/// In-process proving can't be bounded, so the ceiling is taken as the estimate of what the proof
+/// needs. Hosts that don't report available memory are not checked.
+pub fn ensure_memory_available(limit_bytes: usize) -> Result<(), ProofServiceError> {
+    let Some(available) = available_memory() else {
+        warn!(
+            limit_bytes,
+            "Available memory is unknown on this host, not checking memory_limit_bytes"
+        );
+        return Ok(());
+    };
+    if (limit_bytes as u64) > available {
+        return Err(ProofServiceError::ConfigError(format!(
+            "Insufficient memory: request needs up to {} bytes but only {} are available",
+            limit_bytes, available
+        )));
+    }
+    Ok(())
+}
+
+/// Image repository Pico's `prove_evm` runs the gnark container from.
+pub const PICO_GNARK_IMAGE: &str = "brevishub/pico_gnark_cli";
+
+/// Adjustments the Docker shim applies to the commands `prove_evm` runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DockerOverrides {
+    /// Passed to `docker run` as `--memory`.
+    pub memory_limit_bytes: Option<usize>,
+    /// Image used in place of [`PICO_GNARK_IMAGE`] (any tag), e.g. a pinned digest or a mirror.
+    pub image: Option<String>,
+}
+
+impl DockerOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.memory_limit_bytes.is_none() && self.image.is_none()
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Shell script standing in for `docker` that adds `--memory` to `docker run` and forwards
+/// everything else to `real_docker` unchanged.
+pub fn docker_shim_script(real_docker: &Path, limit_bytes: usize) -> String {
+    docker_overrides_script(real_docker, &DockerOverrides {
+        memory_limit_bytes: Some(limit_bytes),
+        image: None,
+    })
+}
+
+/// Shell script standing in for `docker` that applies `overrides` and forwards the command to
+/// `real_docker`: the image replaces [`PICO_GNARK_IMAGE`] in every command (so `pull` fetches it
+/// too), and the memory limit is added to `docker run`.
+pub fn docker_overrides_script(real_docker: &Path, overrides: &DockerOverrides) -> String {
+    let real = shell_quote(&real_docker.display().to_string());
+    let mut script = String::from("#!/bin/sh\n");
+    if let Some(image) = &overrides.image {
+        script.push_str(&format!(
+            "for arg do\n  \
+               shift\n  \
+               case \"$arg\" in\n    \
+                 {default}|{default}:*) set -- \"$@\" {image} ;;\n    \
+                 *) set -- \"$@\" \"$arg\" ;;\n  \
+               esac\n\
+             done\n",
+            default = PICO_GNARK_IMAGE,
+            image = shell_quote(image),
+        ));
+    }
+    if let Some(limit_bytes) = overrides.memory_limit_bytes {
+        script.push_str(&format!(
+            "if [ \"$1\" = \"run\" ]; then\n  \
+               shift\n  \
+               exec {real} run --memory={limit_bytes} \"$@\"\n\
+             fi\n"
+        ));
+    }
+    script.push_str(&format!("exec {real} \"$@\"\n"));
+    script
+}
+
+/// Writes [`docker_shim_script`] as an executable `docker` in `dir` and returns its path.
+pub fn write_docker_shim(
+    dir: &Path,
+    real_docker: &Path,
+    limit_bytes: usize,
+) -> Result<PathBuf, ProofServiceError> {
+    write_docker_overrides_shim(dir, real_docker, &DockerOverrides {
+        memory_limit_bytes: Some(limit_bytes),
+        image: None,
+    })
+}
+
+/// Writes [`docker_overrides_script`] as an executable `docker` in `dir` and returns its path.
+pub fn write_docker_overrides_shim(
+    dir: &Path,
+    real_docker: &Path,
+    overrides: &DockerOverrides,
+) -> Result<PathBuf, ProofServiceError> {
+    let shim = dir.join("docker");
+    std::fs::write(&shim, docker_overrides_script(real_docker, overrides))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&shim, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(shim)
+}
+
+/// Environment variable set on a prover worker started with a [`DockerShim`] first on its `PATH`.
+pub const DOCKER_SHIM_ENV: &str = "PICO_DOCKER_SHIM";
+
+/// A `docker` shim applying [`DockerOverrides`] to the containers `prove_evm` starts.
+///
+/// Pico launches the gnark container through `docker` on `PATH` with no way to pass extra flags
+/// or choose the image. The service never rewrites its own `PATH`, which every thread shares;
+/// instead the shim's directory goes first on the `PATH` of the prover worker it starts (see
+/// [`DockerShim::child_env`]). The directory is removed when the shim is dropped.
+pub struct DockerShim {
+    search_path: Option<OsString>,
+    shim_dir: TempDir,
+}
+
+impl DockerShim {
+    /// Writes the shim to a temporary directory under `work_dir`, forwarding to the `docker` found
+    /// on this process's `PATH`.
+    pub fn create(work_dir: &Path, overrides: &DockerOverrides) -> Result<Self, ProofServiceError> {
+        Self::create_with_path(work_dir, overrides, std::env::var_os("PATH"))
+    }
+
+    /// Like [`DockerShim::create`], with `search_path` in place of this process's `PATH`: the real
+    /// `docker` is looked up on it, and the shim's directory is put in front of it for the child.
+    pub fn create_with_path(
+        work_dir: &Path,
+        overrides: &DockerOverrides,
+        search_path: Option<OsString>,
+    ) -> Result<Self, ProofServiceError> {
+        let real_docker = find_in_path(search_path.as_deref(), "docker").ok_or_else(|| {
+            ProofServiceError::ConfigError(
+                "memory_limit_bytes and evm_docker_image require docker on PATH for FullWithEvm proving"
+                    .to_string(),
+            )
+        })?;
+        let shim_dir = tempfile::Builder::new()
+            .prefix("docker_shim_")
+            .tempdir_in(work_dir)
+            .map_err(|e| {
+                ProofServiceError::TempDirError(format!("Failed to create Docker shim dir: {}", e))
+            })?;
+        write_docker_overrides_shim(shim_dir.path(), &real_docker, overrides)?;
+        debug!(?overrides, docker = ?real_docker, "Created Docker shim");
+        Ok(Self {
+            search_path,
+            shim_dir,
+        })
+    }
+
+    /// Environment for the child process the shim applies to: `PATH` with the shim first, and
+    /// [`DOCKER_SHIM_ENV`] naming its directory.
+    pub fn child_env(&self) -> Result<[(&'static str, OsString); 2], ProofServiceError> {
+        let mut paths = vec![self.shim_dir.path().to_path_buf()];
+        if let Some(path) = &self.search_path {
+            paths.extend(std::env::split_paths(path));
+        }
+        let path = std::env::join_paths(paths)
+            .map_err(|e| ProofServiceError::ConfigError(format!("Invalid PATH: {}", e)))?;
+        Ok([
+            ("PATH", path),
+            (DOCKER_SHIM_ENV, self.shim_dir.path().as_os_str().to_owned()),
+        ])
+    }
+}
+
+/// Checks that Docker calls made by this process get `overrides` applied, i.e. that it is a prover
+/// worker started with a [`DockerShim`]. An in-process proof can't apply them without rewriting
+/// the service's `PATH`.
+pub fn ensure_docker_shim(overrides: &DockerOverrides) -> Result<(), ProofServiceError> {
+    if overrides.is_empty() || std::env::var_os(DOCKER_SHIM_ENV).is_some() {
+        return Ok(());
+    }
+    Err(ProofServiceError::ConfigError(
+        "memory_limit_bytes and evm_docker_image for FullWithEvm proofs require the service to prove in subprocesses (prover_subprocess)"
+            .to_string(),
+    ))
+}
+
+fn find_in_path(path: Option<&std::ffi::OsStr>, program: &str) -> Option<PathBuf> {
+    std::env::split_paths(path?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
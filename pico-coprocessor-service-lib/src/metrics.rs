@@ -0,0 +1,207 @@
+// pico-coprocessor-service-lib/src/metrics.rs
+use crate::errors::ProofServiceError;
+use crate::types::ProofResult;
+use blueprint_sdk::{info, warn};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Default cap on distinct tagged series, see [`ProofMetrics::new`].
+pub const DEFAULT_MAX_TAGGED_SERIES: usize = 1000;
+/// Prefix of the label each request tag becomes, keeping tags apart from the built-in labels.
+pub const TAG_LABEL_PREFIX: &str = "tag_";
+
+// Label names and values of one series, sorted by name
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Series {
+    proofs: u64,
+    proving_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    series: BTreeMap<Labels, Series>,
+    tagged_series: usize,
+    dropped_tags: u64,
+}
+
+/// Proof counters in the Prometheus text format, shared by every clone of the context.
+///
+/// Every finished proof is counted under its job, proving type and whether it came from the cache,
+/// plus one `tag_<key>` label per request tag so operators can attribute proofs to customers.
+/// Tags are caller-chosen, so the number of distinct tagged series is capped: once the cap is
+/// reached, proofs with a new tag combination are counted without their tags (and in
+/// `pico_metric_tags_dropped_total`) instead of growing the series set.
+#[derive(Debug, Clone)]
+pub struct ProofMetrics {
+    state: Arc<Mutex<MetricsState>>,
+    max_tagged_series: usize,
+}
+
+impl Default for ProofMetrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_TAGGED_SERIES)
+    }
+}
+
+impl ProofMetrics {
+    pub fn new(max_tagged_series: usize) -> Self {
+        Self {
+            state: Arc::default(),
+            max_tagged_series,
+        }
+    }
+
+    /// Counts a proof returned by `job`, with the result's tags as labels. Cached proofs add no
+    /// proving time.
+    pub fn record_proof(&self, job: &str, result: &ProofResult, cached: bool) {
+        let proving_ms = match (&result.phase_timings, cached) {
+            (Some(timings), false) => timings.values().sum(),
+            _ => 0,
+        };
+        let mut labels = vec![
+            ("cached".to_string(), cached.to_string()),
+            ("job".to_string(), job.to_string()),
+            (
+                "proving_type".to_string(),
+                format!("{:?}", result.proving_type),
+            ),
+        ];
+        let tag_labels = tag_labels(&result.tags);
+
+        let mut state = self.state.lock().unwrap();
+        if !tag_labels.is_empty() {
+            let mut tagged = labels.clone();
+            tagged.extend(tag_labels);
+            tagged.sort();
+            if state.series.contains_key(&tagged) || state.tagged_series < self.max_tagged_series {
+                if !state.series.contains_key(&tagged) {
+                    state.tagged_series += 1;
+                }
+                labels = tagged;
+            } else {
+                warn!(
+                    job,
+                    "Metric series limit reached; counting proof without its tags"
+                );
+                state.dropped_tags += 1;
+            }
+        }
+        let series = state.series.entry(labels).or_default();
+        series.proofs += 1;
+        series.proving_ms += proving_ms;
+    }
+
+    /// Proofs counted under exactly `labels` (name, value pairs in any order).
+    pub fn proofs_total(&self, labels: &[(&str, &str)]) -> u64 {
+        let mut labels: Labels = labels
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        labels.sort();
+        let state = self.state.lock().unwrap();
+        state.series.get(&labels).map_or(0, |series| series.proofs)
+    }
+
+    /// Renders every series in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::new();
+        out.push_str("# HELP pico_proofs_total Proofs returned by the service.\n");
+        out.push_str("# TYPE pico_proofs_total counter\n");
+        for (labels, series) in &state.series {
+            let _ = writeln!(
+                out,
+                "pico_proofs_total{{{}}} {}",
+                render_labels(labels),
+                series.proofs
+            );
+        }
+        out.push_str(
+            "# HELP pico_proving_milliseconds_total Wall time spent proving the counted proofs.\n",
+        );
+        out.push_str("# TYPE pico_proving_milliseconds_total counter\n");
+        for (labels, series) in &state.series {
+            let _ = writeln!(
+                out,
+                "pico_proving_milliseconds_total{{{}}} {}",
+                render_labels(labels),
+                series.proving_ms
+            );
+        }
+        out.push_str(
+            "# HELP pico_metric_tags_dropped_total Proofs counted without their tags because the series limit was reached.\n",
+        );
+        out.push_str("# TYPE pico_metric_tags_dropped_total counter\n");
+        let _ = writeln!(out, "pico_metric_tags_dropped_total {}", state.dropped_tags);
+        out
+    }
+}
+
+// Tag keys become `tag_<key>` labels, with characters Prometheus doesn't allow in names replaced
+fn tag_labels(tags: &BTreeMap<String, String>) -> Labels {
+    tags.iter()
+        .map(|(key, value)| {
+            let name: String = key
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            (format!("{}{}", TAG_LABEL_PREFIX, name), value.clone())
+        })
+        .collect()
+}
+
+fn render_labels(labels: &Labels) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Serves `metrics` at `addr` as a Prometheus scrape target until the task is dropped. Every
+/// request is answered with the rendered metrics, whatever its path.
+pub async fn serve_metrics(
+    addr: SocketAddr,
+    metrics: ProofMetrics,
+) -> Result<(), ProofServiceError> {
+    let listener = TcpListener::bind(addr).await.map_err(|e| {
+        ProofServiceError::ConfigError(format!("Failed to bind metrics address {}: {}", addr, e))
+    })?;
+    info!(%addr, "Serving Prometheus metrics");
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!("Failed to accept metrics connection: {}", e);
+                continue;
+            }
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            // The request itself doesn't matter; read its head so the client sees a response
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            let body = metrics.render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                warn!(%peer, "Failed to write metrics response: {}", e);
+            }
+        });
+    }
+}
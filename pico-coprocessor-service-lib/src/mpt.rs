@@ -0,0 +1,158 @@
+// pico-coprocessor-service-lib/src/mpt.rs
+use blueprint_sdk::alloy::primitives::{B256, keccak256};
+
+// A decoded RLP item: the payload of a string, or the concatenated items of a list
+enum Rlp<'a> {
+    String(&'a [u8]),
+    List(&'a [u8]),
+}
+
+// Splits the first RLP item off `data`, returning it with its full encoding and what follows
+fn decode_item(data: &[u8]) -> Result<(Rlp<'_>, &[u8], &[u8]), String> {
+    let (&prefix, rest) = data.split_first().ok_or("empty RLP item")?;
+    let (offset, len, is_list) = match prefix {
+        0x00..=0x7f => return Ok((Rlp::String(&data[..1]), &data[..1], &data[1..])),
+        0x80..=0xb7 => (1, usize::from(prefix - 0x80), false),
+        0xc0..=0xf7 => (1, usize::from(prefix - 0xc0), true),
+        _ => {
+            let len_of_len = usize::from(if prefix < 0xc0 {
+                prefix - 0xb7
+            } else {
+                prefix - 0xf7
+            });
+            let len_bytes = rest.get(..len_of_len).ok_or("truncated RLP length")?;
+            if len_of_len > std::mem::size_of::<usize>() {
+                return Err("RLP length out of range".to_string());
+            }
+            let len = len_bytes
+                .iter()
+                .fold(0usize, |len, &byte| (len << 8) | usize::from(byte));
+            (1 + len_of_len, len, prefix >= 0xc0)
+        }
+    };
+    let end = offset
+        .checked_add(len)
+        .filter(|&end| end <= data.len())
+        .ok_or("truncated RLP item")?;
+    let payload = &data[offset..end];
+    let item = if is_list {
+        Rlp::List(payload)
+    } else {
+        Rlp::String(payload)
+    };
+    Ok((item, &data[..end], &data[end..]))
+}
+
+// Items of an RLP list payload, each with its full encoding
+fn decode_list(mut payload: &[u8]) -> Result<Vec<(Rlp<'_>, &[u8])>, String> {
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (item, raw, rest) = decode_item(payload)?;
+        items.push((item, raw));
+        payload = rest;
+    }
+    Ok(items)
+}
+
+/// Key of the `index`-th receipt (or transaction) in a block's trie: the RLP encoding of `index`.
+pub fn trie_index_key(index: u64) -> Vec<u8> {
+    match index {
+        0 => vec![0x80],
+        1..=0x7f => vec![index as u8],
+        _ => {
+            let bytes = index.to_be_bytes();
+            let significant = &bytes[bytes.iter().take_while(|&&b| b == 0).count()..];
+            let mut key = vec![0x80 + significant.len() as u8];
+            key.extend_from_slice(significant);
+            key
+        }
+    }
+}
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+// Next node on the path: referenced by hash, or embedded in its parent when shorter than 32 bytes
+enum NodeRef<'a> {
+    Hash(B256),
+    Inline(&'a [u8]),
+}
+
+fn child_ref<'a>(item: &Rlp<'a>, raw: &'a [u8]) -> Result<Option<NodeRef<'a>>, String> {
+    match item {
+        Rlp::String([]) => Ok(None),
+        Rlp::String(hash) if hash.len() == 32 => Ok(Some(NodeRef::Hash(B256::from_slice(hash)))),
+        Rlp::String(_) => Err("malformed child reference".to_string()),
+        Rlp::List(_) => Ok(Some(NodeRef::Inline(raw))),
+    }
+}
+
+/// Walks a Merkle-Patricia `proof` (RLP-encoded nodes, root first, as `eth_getProof` returns them)
+/// from `root` along `key`, returning the value stored there.
+///
+/// Fails when a node doesn't hash to its reference, the proof ends early or has unused nodes, or
+/// the key isn't in the trie.
+pub fn verify_proof(root: B256, key: &[u8], proof: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    let path = nibbles(key);
+    let mut position = 0;
+    let mut nodes = proof.iter();
+    let mut next = NodeRef::Hash(root);
+    let value = loop {
+        let node: &[u8] = match next {
+            NodeRef::Hash(hash) => {
+                let node = nodes.next().ok_or("proof ends before reaching the key")?;
+                if keccak256(node) != hash {
+                    return Err(format!("proof node does not hash to {}", hash));
+                }
+                node
+            }
+            NodeRef::Inline(node) => node,
+        };
+        let (item, _, rest) = decode_item(node)?;
+        let items = match item {
+            Rlp::List(payload) if rest.is_empty() => decode_list(payload)?,
+            _ => return Err("proof node is not an RLP list".to_string()),
+        };
+
+        match items.as_slice() {
+            [branch @ .., (value, _)] if branch.len() == 16 => {
+                let Some(&nibble) = path.get(position) else {
+                    match value {
+                        Rlp::String(value) if !value.is_empty() => break value.to_vec(),
+                        _ => return Err("key is not in the trie".to_string()),
+                    }
+                };
+                let (child, raw) = &branch[usize::from(nibble)];
+                position += 1;
+                next = child_ref(child, *raw)?.ok_or("key is not in the trie")?;
+            }
+            [(Rlp::String(encoded_path), _), (child, raw)] => {
+                let (&flags, _) = encoded_path.split_first().ok_or("empty node path")?;
+                // Hex-prefix encoding: a flag nibble, then a padding nibble when the path is even
+                let encoded_nibbles = nibbles(encoded_path);
+                let node_path = &encoded_nibbles[if flags & 0x10 != 0 { 1 } else { 2 }..];
+                let remaining = &path[position..];
+                if flags & 0x20 != 0 {
+                    if remaining != node_path {
+                        return Err("key is not in the trie".to_string());
+                    }
+                    match child {
+                        Rlp::String(value) => break value.to_vec(),
+                        Rlp::List(_) => return Err("malformed leaf value".to_string()),
+                    }
+                }
+                if !remaining.starts_with(node_path) {
+                    return Err("key is not in the trie".to_string());
+                }
+                position += node_path.len();
+                next = child_ref(child, *raw)?.ok_or("malformed extension node")?;
+            }
+            _ => return Err("proof node is neither a branch, extension nor leaf".to_string()),
+        }
+    };
+    if nodes.next().is_some() {
+        return Err("proof has nodes past the key's value".to_string());
+    }
+    Ok(value)
+}
@@ -1,13 +1,179 @@
+use crate::encoding::decode_hex;
 use crate::errors::ProofServiceError;
-use crate::types::{ProofResult, ProvingType};
-use blueprint_sdk::{debug, info};
+use crate::memory;
+use crate::proof_format::encode_recursion_proof;
+use crate::types::{
+    CoprocessorVerification, EvmInputsJson, FailurePolicy, ProofResult, ProvingType,
+    RecursionProofFormat,
+};
+use blueprint_sdk::{debug, info, warn};
+use memmap2::Mmap;
 use pico_sdk::client::DefaultProverClient;
+use pico_vm::compiler::riscv::program::Program;
+use pico_vm::configs::field_config::KoalaBearBn254;
 use pico_vm::configs::stark_config::{KoalaBearBn254Poseidon2, KoalaBearPoseidon2};
-use pico_vm::machine::proof::BaseProof;
+use pico_vm::emulator::stdin::EmulatorStdin;
+use pico_vm::instances::compiler::onchain_circuit::gnark::builder::OnchainVerifierCircuit;
+use pico_vm::instances::compiler::onchain_circuit::stdin::OnchainStdin;
+use pico_vm::instances::compiler::onchain_circuit::utils::{
+    build_gnark_config, save_embed_proof_data,
+};
+use pico_vm::machine::proof::{BaseProof, MetaProof};
+use pico_vm::proverchain::{
+    CombineProver, CompressProver, ConvertProver, EmbedProver, InitialProverSetup, MachineProver,
+    ProverChain, RiscvProver,
+};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Environment variable Pico reads its shard (chunk) size from when building the prover client.
+pub const SHARD_SIZE_ENV: &str = "CHUNK_SIZE";
+/// Revision of `pico-vm`/`pico-sdk` the workspace manifest pins.
+pub(crate) const PICO_VM_REV: &str = "ece7e7e8401e7907924bf829503000bce3b4e963";
+/// Version of the Pico VM/SDK this service is built against, recorded on every proof. It is the
+/// `pico-vm` version at [`PICO_VM_REV`]; update both together with the pinned dependency.
+pub const PROVER_VERSION: &str = "1.0.1";
+/// `ProofResult::phase_timings` key for the RISCV phase of a `Fast` or `Full` proof (including a
+/// `Full` proof's Fast fallback).
+pub const PHASE_RISCV: &str = "riscv";
+/// `ProofResult::phase_timings` key for the recursion phase of a `Full` proof: converting,
+/// combining, compressing and embedding its RISCV proof.
+pub const PHASE_RECURSION: &str = "recursion";
+/// `ProofResult::phase_timings` key for a `FullWithEvm` proof: the full proof plus the gnark
+/// wrapping run by `prove_evm`.
+pub const PHASE_EVM: &str = "evm";
+/// Smallest accepted `shard_size` override.
+pub const MIN_SHARD_SIZE: usize = 1 << 16;
+/// Largest accepted `shard_size` override.
+pub const MAX_SHARD_SIZE: usize = 1 << 24;
+/// ELFs a [`ProverCache`] keeps prover setups for when `prover_cache_capacity` isn't configured.
+pub const PROVER_CLIENT_CACHE_CAPACITY: usize = 4;
+
+/// Field the prover works over. Also selects the gnark circuit used for EVM wrapping.
+///
+/// Only KoalaBear: the service proves with `DefaultProverClient`, whose proofs are KoalaBear
+/// proofs, so wrapping them with another field's circuit could never verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldConfig {
+    KoalaBear,
+}
+
+impl FieldConfig {
+    /// Field of the proofs the service's prover client produces.
+    pub const fn selected() -> Self {
+        FieldConfig::KoalaBear
+    }
+
+    /// Field identifier `prove_evm` hands to the gnark container.
+    pub fn evm_field_type(self) -> &'static str {
+        match self {
+            FieldConfig::KoalaBear => "kb",
+        }
+    }
+}
+
+/// The `prove_evm` step of a prover client, separated so the arguments can be checked without Docker.
+#[cfg(any(feature = "evm-proving", test))]
+pub trait EvmProver {
+    fn prove_evm(
+        &self,
+        need_setup: bool,
+        output_dir: PathBuf,
+        field_type: &str,
+    ) -> Result<(), String>;
+}
+
+#[cfg(any(feature = "evm-proving", test))]
+impl EvmProver for DefaultProverClient {
+    fn prove_evm(
+        &self,
+        need_setup: bool,
+        output_dir: PathBuf,
+        field_type: &str,
+    ) -> Result<(), String> {
+        DefaultProverClient::prove_evm(self, need_setup, output_dir, field_type)
+            .map_err(|e| format!("{:?}", e))
+    }
+}
+
+/// Runs `prove_evm` with the field string matching `field`.
+#[cfg(any(feature = "evm-proving", test))]
+pub fn run_prove_evm(
+    prover: &impl EvmProver,
+    field: FieldConfig,
+    need_setup: bool,
+    output_dir: PathBuf,
+) -> Result<(), ProofServiceError> {
+    debug!(?field, need_setup, "Running prove_evm");
+    prover
+        .prove_evm(need_setup, output_dir, field.evm_field_type())
+        .map_err(|e| ProofServiceError::ProvingError(format!("EVM proving failed: {}", e)))
+}
+
+/// Per-request options applied to the prover client.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProveOptions {
+    /// Overrides the prover's shard (chunk) size.
+    pub shard_size: Option<usize>,
+    /// Behaviour when a `Full` proof fails during recursion.
+    pub on_full_failure: FailurePolicy,
+    /// Capture the prover's logs into `ProofResult::debug_log`.
+    pub capture_logs: bool,
+    /// Return a `Full` proof's intermediate RISCV proof in `ProofResult::riscv_proof`.
+    pub include_riscv_proof: bool,
+    /// Memory ceiling for the proof: checked against available memory, and passed to the gnark
+    /// container as `--memory` for `FullWithEvm`.
+    pub memory_limit_bytes: Option<usize>,
+    /// Fail the proof when the program commits no public values (see [`committed_public_values`]).
+    #[serde(default)]
+    pub require_public_values: bool,
+    /// Serialization of a `Full` proof's embed proof (see [`encode_recursion_proof`]).
+    #[serde(default)]
+    pub recursion_proof_format: RecursionProofFormat,
+    /// Directory `FullWithEvm` artifacts are written under instead of `output_base_dir`, so they
+    /// outlive the job. A path on this node, so it is never sent to a remote prover.
+    #[serde(skip)]
+    pub evm_output_dir: Option<PathBuf>,
+    /// Age after which earlier artifact dirs under `evm_output_dir` are removed as a new one is
+    /// created (see [`prune_evm_output_dirs`]). Node-local like the directory.
+    #[serde(skip)]
+    pub evm_output_retention: Option<Duration>,
+    /// Image the gnark container runs from instead of Pico's default, for pinned or air-gapped
+    /// hosts. Configured per node, so it is never sent to a remote prover either.
+    #[serde(skip)]
+    pub evm_docker_image: Option<String>,
+    /// Caller-managed directory artifacts are written to directly, instead of a fresh
+    /// subdirectory per run (see [`execute_pico_prove_in_dir`]). Local to this node as well.
+    #[serde(skip)]
+    pub output_dir: Option<PathBuf>,
+    /// Called once a `Full` proof's RISCV phase completes, e.g. to checkpoint it. Only proofs run
+    /// in this process report it.
+    #[serde(skip)]
+    pub on_riscv_proof: Option<RiscvProofCallback>,
+    /// Prover setups to reuse, and to keep for later proofs; unset sets them up for this proof
+    /// only. Held by this process, so never sent to a remote prover or prover subprocess.
+    #[serde(skip)]
+    pub prover_cache: Option<ProverCache>,
+}
+
+/// Receives a `Full` proof's RISCV phase, shaped like a `Fast` [`ProofResult`] (proof, public
+/// values, inputs and prover version only).
+#[derive(Clone)]
+pub struct RiscvProofCallback(pub Arc<dyn Fn(&ProofResult) + Send + Sync>);
+
+impl std::fmt::Debug for RiscvProofCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RiscvProofCallback")
+    }
+}
 
 /// Executes the Pico proving process for the given ELF file and inputs.
 pub async fn execute_pico_prove(
@@ -15,90 +181,384 @@ pub async fn execute_pico_prove(
     inputs_hex: &str,
     proving_type: &ProvingType,
     output_base_dir: &Path, // Base directory for prover outputs
+    options: &ProveOptions,
 ) -> Result<ProofResult, ProofServiceError> {
-    info!(elf = ?elf_path, type = ?proving_type, output_dir = ?output_base_dir, "Starting Pico proving process");
+    info!(elf = ?elf_path, type = ?proving_type, output_dir = ?output_base_dir, options = ?options, "Starting Pico proving process");
+
+    // Reject proving types compiled out of this build before doing any work
+    ensure_proving_type_supported(proving_type)?;
 
     // 1. Load ELF
     let elf_contents = read_elf_file(elf_path)?;
 
-    // 2. Initialize Prover Client (Default is KoalaBear)
-    // Explicit types might be needed if inference fails, but DefaultProverClient should work.
-    let client = DefaultProverClient::new(&elf_contents);
+    prove_elf(
+        &elf_contents,
+        inputs_hex,
+        proving_type,
+        output_base_dir,
+        options,
+    )
+    .await
+}
+
+/// Like [`execute_pico_prove`], but writes the proof's artifacts straight into `output_dir`, a
+/// persistent directory the caller manages, instead of a new subdirectory of a job's output dir.
+///
+/// `output_dir` must exist and be writable; it is neither created nor cleaned up. It takes
+/// precedence over `options.evm_output_dir`.
+pub async fn execute_pico_prove_in_dir(
+    elf_path: &Path,
+    inputs_hex: &str,
+    proving_type: &ProvingType,
+    output_dir: &Path,
+    options: &ProveOptions,
+) -> Result<ProofResult, ProofServiceError> {
+    if !output_dir.is_dir() {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "output_dir {:?} is not a directory",
+            output_dir
+        )));
+    }
+    tempfile::NamedTempFile::new_in(output_dir).map_err(|e| {
+        ProofServiceError::InvalidInput(format!(
+            "output_dir {:?} is not writable: {}",
+            output_dir, e
+        ))
+    })?;
+
+    let options = ProveOptions {
+        output_dir: Some(output_dir.to_path_buf()),
+        ..options.clone()
+    };
+    execute_pico_prove(elf_path, inputs_hex, proving_type, output_dir, &options).await
+}
+
+/// Like [`execute_pico_prove`], but proves directly from a memory-mapped ELF instead of
+/// reading it into a `Vec` first. Used for large trusted local programs.
+pub async fn execute_pico_prove_mmap(
+    elf: &Mmap,
+    inputs_hex: &str,
+    proving_type: &ProvingType,
+    output_base_dir: &Path,
+    options: &ProveOptions,
+) -> Result<ProofResult, ProofServiceError> {
+    info!(elf_len = elf.len(), type = ?proving_type, output_dir = ?output_base_dir, options = ?options, "Starting Pico proving process (memory-mapped ELF)");
+
+    ensure_proving_type_supported(proving_type)?;
+
+    prove_elf(elf, inputs_hex, proving_type, output_base_dir, options).await
+}
+
+/// Like [`execute_pico_prove`], but proves an ELF already in memory (used by
+/// [`crate::backend::LocalProver`]).
+pub async fn execute_pico_prove_elf(
+    elf: &[u8],
+    inputs_hex: &str,
+    proving_type: &ProvingType,
+    output_base_dir: &Path,
+    options: &ProveOptions,
+) -> Result<ProofResult, ProofServiceError> {
+    info!(elf_len = elf.len(), type = ?proving_type, output_dir = ?output_base_dir, options = ?options, "Starting Pico proving process (in-memory ELF)");
+
+    ensure_proving_type_supported(proving_type)?;
+
+    prove_elf(elf, inputs_hex, proving_type, output_base_dir, options).await
+}
+
+async fn prove_elf(
+    elf_contents: &[u8],
+    inputs_hex: &str,
+    proving_type: &ProvingType,
+    output_base_dir: &Path,
+    options: &ProveOptions,
+) -> Result<ProofResult, ProofServiceError> {
+    let prove = || {
+        prove_elf_blocking(
+            elf_contents,
+            inputs_hex,
+            proving_type,
+            output_base_dir,
+            options,
+        )
+    };
+    if !options.capture_logs {
+        return prove();
+    }
+    let (outcome, debug_log) = capture_logs(prove);
+    let mut result = outcome?;
+    result.debug_log = Some(debug_log);
+    Ok(result)
+}
+
+/// Runs `f` with the tracing events it emits on this thread written to a buffer, and returns
+/// them alongside its output.
+///
+/// While capturing, those events go to the buffer instead of the service's subscriber. Events
+/// from threads the prover spawns internally are not captured.
+pub fn capture_logs<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(move || LogBuffer(writer.clone()))
+        .finish();
+    let output = tracing::subscriber::with_default(subscriber, f);
+    let log = buffer.lock().unwrap_or_else(|e| e.into_inner());
+    (output, String::from_utf8_lossy(&log).into_owned())
+}
+
+// `io::Write` handle onto the shared buffer of `capture_logs`
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// A prover client waiting in a ProverCache for the next proof of its ELF
+struct ParkedProverClient(DefaultProverClient);
+
+// SAFETY: `DefaultProverClient` is only `!Send` because it keeps its stdin builder in an
+// `Rc<RefCell<_>>`. The clones `get_stdin_builder` hands out never outlive the proof that checked
+// the client out, so a parked client holds the only handle and moves to another thread whole, and
+// a client is checked out by one proof at a time.
+unsafe impl Send for ParkedProverClient {}
+
+// Idle entries keyed by the SHA-256 of their ELF, least recently used first
+type ParkedEntries<V> = Arc<Mutex<Vec<([u8; 32], V)>>>;
+
+/// Prover setups kept for later proofs of the same ELF, so those skip setting up the machine: idle
+/// prover clients (`Fast`, `FastWithVerify` and `FullWithEvm` proofs, and verification) and idle
+/// `Full` prover chains. Each kind holds up to `capacity` ELFs, evicting the least recently used;
+/// a capacity of 0 keeps nothing.
+///
+/// Clones share the entries. The service keeps one on `ServiceContext::prover_cache` and hands it
+/// to the prover through [`ProveOptions::prover_cache`].
+#[derive(Clone)]
+pub struct ProverCache {
+    clients: ParkedEntries<ParkedProverClient>,
+    chains: ParkedEntries<FullProverChain>,
+    capacity: usize,
+}
+
+impl Default for ProverCache {
+    fn default() -> Self {
+        Self::new(PROVER_CLIENT_CACHE_CAPACITY)
+    }
+}
+
+impl std::fmt::Debug for ProverCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProverCache")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProverCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            clients: Default::default(),
+            chains: Default::default(),
+            capacity,
+        }
+    }
+
+    /// ELFs kept per kind of setup.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Whether the setup proofs of `elf` as `proving_type` run on is kept.
+    pub fn contains(&self, elf: &[u8], proving_type: &ProvingType) -> bool {
+        let key: [u8; 32] = Sha256::digest(elf).into();
+        match proving_type {
+            ProvingType::Full => lock_entries(&self.chains).iter().any(|(k, _)| k == &key),
+            _ => lock_entries(&self.clients).iter().any(|(k, _)| k == &key),
+        }
+    }
+
+    /// Sets up what proofs of `elf` as `proving_type` run on, its prover client or for `Full`
+    /// proofs its prover chain, unless that is kept already. Returns whether it is kept now.
+    pub fn warm(&self, elf: &[u8], proving_type: &ProvingType) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        if self.contains(elf, proving_type) {
+            return true;
+        }
+        let key: [u8; 32] = Sha256::digest(elf).into();
+        match proving_type {
+            ProvingType::Full => self.park(&self.chains, key, FullProverChain::new(elf)),
+            _ => self.park(
+                &self.clients,
+                key,
+                ParkedProverClient(DefaultProverClient::new(elf)),
+            ),
+        }
+        true
+    }
+
+    /// Runs `f` with a prover client for `elf`, reusing one an earlier proof of the same ELF left
+    /// (with its stdin cleared) instead of setting up the machine again, and keeps the client for
+    /// the next proof afterwards. Concurrent proofs of one ELF each get a client of their own.
+    fn with_client<T>(&self, elf: &[u8], f: impl FnOnce(&DefaultProverClient) -> T) -> T {
+        let key: [u8; 32] = Sha256::digest(elf).into();
+        let client = match take_entry(&self.clients, &key) {
+            Some(ParkedProverClient(client)) => {
+                debug!("Reusing cached prover client");
+                *client.get_stdin_builder().borrow_mut() =
+                    EmulatorStdin::<Program, Vec<u8>>::new_builder();
+                client
+            }
+            None => DefaultProverClient::new(elf),
+        };
+        let output = f(&client);
+        self.park(&self.clients, key, ParkedProverClient(client));
+        output
+    }
+
+    /// Runs `f` with the Full prover chain for `elf`, reusing an idle one like
+    /// [`with_client`](Self::with_client) does for clients.
+    fn with_full_chain<T>(&self, elf: &[u8], f: impl FnOnce(&FullProverChain) -> T) -> T {
+        let key: [u8; 32] = Sha256::digest(elf).into();
+        let chain = match take_entry(&self.chains, &key) {
+            Some(chain) => {
+                debug!("Reusing cached Full prover chain");
+                chain
+            }
+            None => FullProverChain::new(elf),
+        };
+        let output = f(&chain);
+        self.park(&self.chains, key, chain);
+        output
+    }
+
+    fn park<V>(&self, entries: &ParkedEntries<V>, key: [u8; 32], value: V) {
+        let mut entries = lock_entries(entries);
+        entries.push((key, value));
+        while entries.len() > self.capacity {
+            entries.remove(0);
+        }
+    }
+}
+
+fn lock_entries<V>(entries: &ParkedEntries<V>) -> std::sync::MutexGuard<'_, Vec<([u8; 32], V)>> {
+    entries.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn take_entry<V>(entries: &ParkedEntries<V>, key: &[u8; 32]) -> Option<V> {
+    let mut entries = lock_entries(entries);
+    let index = entries.iter().rposition(|(k, _)| k == key)?;
+    Some(entries.remove(index).1)
+}
+
+// Wall time of each proving phase, recorded through a shared reference so that both closures
+// handed to `with_failure_policy` can time their phase
+#[derive(Default)]
+struct PhaseTimer(RefCell<HashMap<String, u64>>);
+
+impl PhaseTimer {
+    fn time<T>(&self, phase: &str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let output = f();
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        debug!(phase, elapsed_ms, "Proving phase finished");
+        *self.0.borrow_mut().entry(phase.to_string()).or_default() += elapsed_ms;
+        output
+    }
+
+    fn into_timings(self) -> HashMap<String, u64> {
+        self.0.into_inner()
+    }
+}
+
+fn prove_elf_blocking(
+    elf_contents: &[u8],
+    inputs_hex: &str,
+    proving_type: &ProvingType,
+    output_base_dir: &Path,
+    options: &ProveOptions,
+) -> Result<ProofResult, ProofServiceError> {
+    // Refuse to start a proof the host can't hold
+    if let Some(limit) = options.memory_limit_bytes {
+        memory::ensure_memory_available(limit)?;
+    }
+
+    // 2. Initialize the prover (Default is KoalaBear)
+    // The shard size is picked up from the environment when a prover is constructed, and the
+    // environment is the same for every prover this process builds, so cached ones fit too.
+    ensure_shard_size_applied(options.shard_size)?;
 
     // 3. Prepare Inputs
-    let input_bytes = hex::decode(inputs_hex)?;
-    let stdin_builder = client.get_stdin_builder();
-    stdin_builder.borrow_mut().write(&input_bytes);
-    debug!("Inputs written to prover stdin");
+    let input_bytes = decode_hex(inputs_hex)?;
+    let cache = options
+        .prover_cache
+        .clone()
+        .unwrap_or_else(|| ProverCache::new(0));
 
     // 4. Execute Proving based on type
-    let (proof_bytes, public_values_bytes, maybe_output_dir) = match proving_type {
+    let mut downgraded = false;
+    let mut self_verified = false;
+    let mut recursion_proof_format = RecursionProofFormat::default();
+    let timer = PhaseTimer::default();
+    let (proof_bytes, public_values_bytes, maybe_output_dir, riscv_proof_bytes) = match proving_type
+    {
         ProvingType::Fast => {
-            info!("Executing fast proof (RISCV phase only)");
-            // prove_fast returns Result<MetaProof<KoalaBearPoseidon2>, Error>
-            let riscv_proof = client.prove_fast().map_err(|e| {
-                ProofServiceError::ProvingError(format!("Fast proving failed: {:?}", e))
+            let (proof_data, pv) = cache.with_client(elf_contents, |client| {
+                write_prover_inputs(client, &input_bytes);
+                timer.time(PHASE_RISCV, || prove_fast_phase(client, options))
             })?;
-
-            // Extract public values (likely from riscv_proof.pv_stream)
-            let pv = riscv_proof.pv_stream.clone().ok_or_else(|| {
-                ProofServiceError::ProvingError(
-                    "Fast proof missing public values stream".to_string(),
-                )
+            (proof_data, pv, None, None)
+        }
+        ProvingType::FastWithVerify => {
+            let (proof_data, pv) = cache.with_client(elf_contents, |client| {
+                write_prover_inputs(client, &input_bytes);
+                let (proof_data, pv) =
+                    timer.time(PHASE_RISCV, || prove_fast_phase(client, options))?;
+                self_verify_fast_proof(client, &proof_data, &pv)?;
+                Ok::<_, ProofServiceError>((proof_data, pv))
             })?;
-
-            // Extract proof data (likely the first proof in the MetaProof)
-            // Assume the proof object itself can be SCALE encoded for serialization.
-            let proof: BaseProof<KoalaBearPoseidon2> = riscv_proof
-                .proofs()
-                .first()
-                .ok_or_else(|| {
-                    ProofServiceError::ProvingError(
-                        "Fast proof MetaProof contained no proofs".to_string(),
-                    )
-                })?
-                .clone();
-            // Serialize the proof
-            let proof_data = serde_json::to_vec(&proof)?;
-
-            info!("Fast proof generated successfully.");
-            (proof_data, pv, None)
+            self_verified = true;
+            (proof_data, pv, None, None)
         }
+        // The SDK client proves RISCV and recursion in one call, so Full proofs run the stages
+        // of a chain of their own to time (and report) the RISCV phase apart from recursion, and
+        // to fall back on a recursion failure without proving RISCV again
         ProvingType::Full => {
-            info!("Executing full proof (RECURSION phase)");
-            // Create a specific output dir for this proof run
-            let proof_output_dir = create_proof_output_dir(output_base_dir, "full")?;
-            // prove returns Result<(MetaProof<KoalaBearPoseidon2>, MetaProof<KoalaBearBn254Poseidon2>), Error>
-            let (riscv_proof, embed_proof) =
-                client.prove(proof_output_dir.clone()).map_err(|e| {
-                    ProofServiceError::ProvingError(format!("Full proving failed: {:?}", e))
-                })?;
-
-            // Extract public values from the RISCV proof part
-            let pv = riscv_proof.pv_stream.clone().ok_or_else(|| {
-                ProofServiceError::ProvingError(
-                    "Full proof (RISCV part) missing public values stream".to_string(),
+            let (output, fell_back) = cache.with_full_chain(elf_contents, |chain| {
+                prove_full_phase(
+                    chain,
+                    &input_bytes,
+                    inputs_hex,
+                    output_base_dir,
+                    options,
+                    &timer,
                 )
             })?;
-
-            // Extract proof data from the Embed proof part
-            let proof: BaseProof<KoalaBearBn254Poseidon2> = embed_proof
-                .proofs()
-                .first()
-                .ok_or_else(|| {
-                    ProofServiceError::ProvingError(
-                        "Full proof (Embed part) MetaProof contained no proofs".to_string(),
-                    )
-                })?
-                .clone();
-            let proof_data = serde_json::to_vec(&proof)?;
-
-            info!("Full proof generated successfully.");
-            (proof_data, pv, Some(proof_output_dir))
+            downgraded = fell_back;
+            // A Fast fallback is serialized as usual
+            if !fell_back {
+                recursion_proof_format = options.recursion_proof_format;
+            }
+            output
         }
+        #[cfg(not(feature = "evm-proving"))]
+        ProvingType::FullWithEvm => return Err(evm_proving_disabled()),
+        #[cfg(feature = "evm-proving")]
         ProvingType::FullWithEvm => {
             info!("Executing full proof with EVM phase");
-            let proof_output_dir = create_proof_output_dir(output_base_dir, "evm")?;
+            let proof_output_dir = evm_proof_output_dir(output_base_dir, options)?;
 
             // Check if setup is needed (basic check, still relies on Docker call robustness)
             let need_setup = !check_if_evm_setup_exists(&proof_output_dir);
@@ -109,81 +569,537 @@ pub async fn execute_pico_prove(
                 );
             }
 
+            // The gnark container's memory bound and image are applied by the Docker shim this
+            // process was started with
+            memory::ensure_docker_shim(&docker_overrides(options))?;
+
             // Call prove_evm - this internally calls .prove() and then runs Docker commands.
-            // DefaultProverClient is KoalaBear, so field_type is "kb".
-            client
-                .prove_evm(need_setup, proof_output_dir.clone(), "kb")
-                .map_err(|e| {
-                    ProofServiceError::ProvingError(format!("EVM proving failed: {:?}", e))
-                })?;
+            cache.with_client(elf_contents, |client| {
+                write_prover_inputs(client, &input_bytes);
+                timer.time(PHASE_EVM, || {
+                    run_prove_evm(
+                        client,
+                        FieldConfig::selected(),
+                        need_setup,
+                        proof_output_dir.clone(),
+                    )
+                })
+            })?;
 
             info!("EVM Docker commands completed (assumed). Reading artifacts...");
 
             // Read artifacts generated by Docker container in proof_output_dir.
-            let proof_path = proof_output_dir.join("proof.data");
-            // Docs mention pv_file, CLI output might use inputs.json. Check both.
-            let pv_path_primary = proof_output_dir.join("pv_file");
-            let pv_path_alt = proof_output_dir.join("inputs.json");
-
-            let proof_data = tokio::fs::read(&proof_path).await.map_err(|e| {
-                ProofServiceError::ProvingError(format!(
-                    "Failed to read EVM proof file {:?}: {}",
-                    proof_path, e
-                ))
-            })?;
-
-            // Read public values file (try pv_file first, then inputs.json)
-            let pv_content = std::fs::read_to_string(&pv_path_primary)
-                .or_else(|_| std::fs::read_to_string(&pv_path_alt))
-                .map_err(|e| {
-                    ProofServiceError::ProvingError(format!(
-                        "Failed to read EVM public values file ({:?} or {:?}): {}",
-                        pv_path_primary, pv_path_alt, e
-                    ))
-                })?;
-
-            // Public values can be hex in pv_file or JSON in inputs.json. Handle both.
-            let pv_bytes = if pv_path_alt.exists() && pv_content.trim().starts_with('{') {
-                // Assume inputs.json format: {"riscvVKey": "...", "proof": "...", "publicValues": "0x..."}
-                let json_val: serde_json::Value =
-                    serde_json::from_str(&pv_content).map_err(|e| {
-                        ProofServiceError::ProvingError(format!(
-                            "Failed to parse EVM public values JSON {:?}: {}",
-                            pv_path_alt, e
-                        ))
-                    })?;
-                let pv_hex = json_val["publicValues"].as_str().ok_or_else(|| {
-                    ProofServiceError::ProvingError(
-                        "Missing 'publicValues' field in inputs.json".to_string(),
-                    )
-                })?;
-                // Remove "0x" prefix if present
-                hex::decode(pv_hex.trim_start_matches("0x"))?
-            } else {
-                // Assume pv_file format (raw hex string)
-                hex::decode(pv_content.trim())?
-            };
+            let (proof_data, pv_bytes) = read_evm_artifacts(&proof_output_dir)?;
+            let pv_bytes = committed_public_values(
+                Some(pv_bytes),
+                "FullWithEvm",
+                options.require_public_values,
+            )?;
 
             info!("EVM proof generated and artifacts read successfully.");
-            (proof_data, pv_bytes, Some(proof_output_dir))
+            // prove_evm doesn't hand back its intermediate proofs
+            (proof_data, pv_bytes, Some(proof_output_dir), None)
         }
     };
 
+    // The verifier calldata sits next to the EVM proof it was written for
+    #[cfg(feature = "evm-proving")]
+    let evm_inputs = match (proving_type, &maybe_output_dir) {
+        (ProvingType::FullWithEvm, Some(dir)) => read_evm_inputs(dir)?,
+        _ => None,
+    };
+    #[cfg(not(feature = "evm-proving"))]
+    let evm_inputs = None;
+
     let result = ProofResult {
         public_values: hex::encode(&public_values_bytes),
         proof: hex::encode(&proof_bytes), // Proof data is SCALE encoded then hex encoded
-        proving_type: proving_type.clone(),
+        proving_type: if downgraded {
+            ProvingType::Fast
+        } else {
+            proving_type.clone()
+        },
+        downgraded,
+        self_verified,
         output_dir: maybe_output_dir.map(|p| p.to_string_lossy().to_string()),
+        riscv_proof: riscv_proof_bytes.map(hex::encode),
         // Populate other fields later in generate_proof job
         program_hash: String::new(), // Placeholder - To be filled by caller (generate_proof job)
         inputs: inputs_hex.to_string(), // Store original hex input
+        prover_version: PROVER_VERSION.to_string(),
+        phase_timings: Some(timer.into_timings()),
+        evm_inputs,
+        recursion_proof_format,
+        ..Default::default() // Job-specific metadata is filled in by the caller
     };
 
     info!("Pico proving process completed successfully.");
     Ok(result)
 }
 
-fn read_elf_file(elf_path: &Path) -> Result<Vec<u8>, ProofServiceError> {
+/// Public values a proof committed, taken from its MetaProof's `pv_stream`.
+///
+/// `None` means the prover didn't capture the stream, so the result can't be trusted and is always
+/// an error. `Some` but empty means the program committed nothing, which is legitimate for a program
+/// proving only that it ran: accepted unless `require_public_values` is set.
+pub fn committed_public_values(
+    pv_stream: Option<Vec<u8>>,
+    proof_kind: &str,
+    require_public_values: bool,
+) -> Result<Vec<u8>, ProofServiceError> {
+    match pv_stream {
+        None => Err(ProofServiceError::ProvingError(format!(
+            "{} proof missing public values stream (the prover did not capture it)",
+            proof_kind
+        ))),
+        Some(pv) if pv.is_empty() && require_public_values => {
+            Err(ProofServiceError::ProvingError(format!(
+                "{} proof committed no public values, and the request requires them",
+                proof_kind
+            )))
+        }
+        Some(pv) if pv.is_empty() => {
+            info!("{} proof committed no public values", proof_kind);
+            Ok(pv)
+        }
+        Some(pv) => Ok(pv),
+    }
+}
+
+// Writes `input_bytes` to the stdin of `client`, which `ProverCache::with_client` hands out
+// cleared
+fn write_prover_inputs(client: &DefaultProverClient, input_bytes: &[u8]) {
+    client
+        .get_stdin_builder()
+        .borrow_mut()
+        .write(&input_bytes.to_vec());
+    debug!("Inputs written to prover stdin");
+}
+
+fn prove_fast_phase(
+    client: &DefaultProverClient,
+    options: &ProveOptions,
+) -> Result<(Vec<u8>, Vec<u8>), ProofServiceError> {
+    info!("Executing fast proof (RISCV phase only)");
+    // prove_fast returns Result<MetaProof<KoalaBearPoseidon2>, Error>
+    let riscv_proof = client
+        .prove_fast()
+        .map_err(|e| ProofServiceError::ProvingError(format!("Fast proving failed: {:?}", e)))?;
+    let output = fast_proof_output(&riscv_proof, "Fast", options)?;
+    info!("Fast proof generated successfully.");
+    Ok(output)
+}
+
+// Serialized proof and committed public values of a RISCV MetaProof, as returned for Fast proofs
+fn fast_proof_output(
+    riscv_proof: &MetaProof<KoalaBearPoseidon2>,
+    proof_kind: &str,
+    options: &ProveOptions,
+) -> Result<(Vec<u8>, Vec<u8>), ProofServiceError> {
+    // Public values come from riscv_proof.pv_stream
+    let pv = committed_public_values(
+        riscv_proof.pv_stream.clone(),
+        proof_kind,
+        options.require_public_values,
+    )?;
+
+    // Extract proof data (the first proof in the MetaProof)
+    let proof: BaseProof<KoalaBearPoseidon2> = riscv_proof
+        .proofs()
+        .first()
+        .ok_or_else(|| {
+            ProofServiceError::ProvingError(format!(
+                "{} proof MetaProof contained no proofs",
+                proof_kind
+            ))
+        })?
+        .clone();
+    // Serialize the proof
+    let proof_data = serde_json::to_vec(&proof)?;
+    Ok((proof_data, pv))
+}
+
+// Proof bytes, public values, artifact dir and the separately requested RISCV proof
+type FullPhaseOutput = (Vec<u8>, Vec<u8>, Option<PathBuf>, Option<Vec<u8>>);
+
+// Returns the proof outputs together with whether `on_full_failure` fell back to the RISCV proof.
+// Only a recursion failure falls back: a RISCV failure leaves no proof to fall back to.
+fn prove_full_phase(
+    chain: &FullProverChain,
+    input_bytes: &[u8],
+    inputs_hex: &str,
+    output_base_dir: &Path,
+    options: &ProveOptions,
+    timer: &PhaseTimer,
+) -> Result<(FullPhaseOutput, bool), ProofServiceError> {
+    info!("Executing full proof (RISCV and RECURSION phases)");
+    let riscv_proof = timer.time(PHASE_RISCV, || chain.prove_riscv(input_bytes))?;
+    // Serialized like a Fast proof so the same verifier path accepts it
+    let (riscv_proof_data, pv) = fast_proof_output(&riscv_proof, "Full (RISCV part)", options)?;
+    if let Some(on_riscv_proof) = &options.on_riscv_proof {
+        (on_riscv_proof.0)(&ProofResult {
+            public_values: hex::encode(&pv),
+            proof: hex::encode(&riscv_proof_data),
+            proving_type: ProvingType::Fast,
+            inputs: inputs_hex.to_string(),
+            prover_version: PROVER_VERSION.to_string(),
+            ..Default::default()
+        });
+    }
+
+    with_failure_policy(
+        options.on_full_failure,
+        || {
+            // Create a specific output dir for this proof run, unless the caller provided one
+            let proof_output_dir = match &options.output_dir {
+                Some(dir) => dir.clone(),
+                None => create_proof_output_dir(output_base_dir, "full")?,
+            };
+            let embed_proof = timer.time(PHASE_RECURSION, || {
+                chain.prove_recursion(&riscv_proof, &proof_output_dir)
+            })?;
+
+            // Extract proof data from the Embed proof part
+            let proof: BaseProof<KoalaBearBn254Poseidon2> = embed_proof
+                .proofs()
+                .first()
+                .ok_or_else(|| {
+                    ProofServiceError::ProvingError(
+                        "Full proof (Embed part) MetaProof contained no proofs".to_string(),
+                    )
+                })?
+                .clone();
+            let proof_data = encode_recursion_proof(&proof, options.recursion_proof_format)?;
+
+            info!("Full proof generated successfully.");
+            let riscv_proof_data = options
+                .include_riscv_proof
+                .then(|| riscv_proof_data.clone());
+            Ok((
+                proof_data,
+                pv.clone(),
+                Some(proof_output_dir),
+                riscv_proof_data,
+            ))
+        },
+        // The RISCV proof just made is the Fast proof, so none is returned separately
+        || Ok((riscv_proof_data.clone(), pv.clone(), None, None)),
+    )
+}
+
+// The prover stages `DefaultProverClient::prove` runs in one call, held apart so that a Full proof
+// can time its RISCV and recursion phases separately
+struct FullProverChain {
+    riscv: RiscvProver<KoalaBearPoseidon2, Program>,
+    convert: ConvertProver<KoalaBearPoseidon2, KoalaBearPoseidon2>,
+    combine: CombineProver<KoalaBearPoseidon2, KoalaBearPoseidon2>,
+    compress: CompressProver<KoalaBearPoseidon2, KoalaBearPoseidon2>,
+    embed: EmbedProver<KoalaBearPoseidon2, KoalaBearBn254Poseidon2, Vec<u8>>,
+}
+
+impl FullProverChain {
+    // Sets up the same stages as `DefaultProverClient::new`
+    fn new(elf: &[u8]) -> Self {
+        let riscv = RiscvProver::new_initial_prover(
+            (KoalaBearPoseidon2::new(), elf),
+            Default::default(),
+            None,
+        );
+        let convert = ConvertProver::new_with_prev(&riscv, Default::default(), None);
+        let combine = CombineProver::new_with_prev(&convert, Default::default(), None);
+        let compress = CompressProver::new_with_prev(&combine, (), None);
+        let embed = EmbedProver::new_with_prev(&compress, (), None);
+        FullProverChain {
+            riscv,
+            convert,
+            combine,
+            compress,
+            embed,
+        }
+    }
+
+    // RISCV phase: proves the program's execution on `input_bytes`
+    fn prove_riscv(
+        &self,
+        input_bytes: &[u8],
+    ) -> Result<MetaProof<KoalaBearPoseidon2>, ProofServiceError> {
+        let mut stdin_builder = EmulatorStdin::<Program, Vec<u8>>::new_builder();
+        stdin_builder.write(&input_bytes.to_vec());
+        let riscv_proof = self.riscv.prove(stdin_builder.finalize());
+        check_stage(self.riscv.verify(&riscv_proof, self.riscv.vk()), "riscv")?;
+        Ok(riscv_proof)
+    }
+
+    // Recursion phase: converts, combines, compresses and embeds `riscv_proof`, leaving the files
+    // `DefaultProverClient::prove` writes for the gnark verifier in `output_dir`
+    fn prove_recursion(
+        &self,
+        riscv_proof: &MetaProof<KoalaBearPoseidon2>,
+        output_dir: &Path,
+    ) -> Result<MetaProof<KoalaBearBn254Poseidon2>, ProofServiceError> {
+        let riscv_vk = self.riscv.vk();
+        let proof = self.convert.prove(riscv_proof.clone());
+        check_stage(self.convert.verify(&proof, riscv_vk), "convert")?;
+        let proof = self.combine.prove(proof);
+        check_stage(self.combine.verify(&proof, riscv_vk), "combine")?;
+        let proof = self.compress.prove(proof);
+        check_stage(self.compress.verify(&proof, riscv_vk), "compress")?;
+        let proof = self.embed.prove(proof);
+        check_stage(self.embed.verify(&proof, riscv_vk), "embed")?;
+
+        let onchain_stdin = OnchainStdin {
+            machine: self.embed.machine().clone(),
+            vk: proof.vks().first().cloned().ok_or_else(|| {
+                ProofServiceError::ProvingError("Embed proof carried no verifying key".to_string())
+            })?,
+            proof: proof.proofs().first().cloned().ok_or_else(|| {
+                ProofServiceError::ProvingError(
+                    "Full proof (Embed part) MetaProof contained no proofs".to_string(),
+                )
+            })?,
+            flag_complete: true,
+        };
+        let (constraints, witness) = OnchainVerifierCircuit::<
+            KoalaBearBn254,
+            KoalaBearBn254Poseidon2,
+        >::build(&onchain_stdin);
+        save_embed_proof_data(riscv_proof, &proof, output_dir.to_path_buf()).map_err(|e| {
+            ProofServiceError::IoError(format!(
+                "Failed to write embed proof data to {:?}: {:?}",
+                output_dir, e
+            ))
+        })?;
+        build_gnark_config(constraints, witness, output_dir.to_path_buf());
+        Ok(proof)
+    }
+}
+
+// Fails the Full proof when a stage's own output doesn't verify
+fn check_stage(verified: bool, stage: &str) -> Result<(), ProofServiceError> {
+    if verified {
+        Ok(())
+    } else {
+        Err(ProofServiceError::ProvingError(format!(
+            "Full proving failed: {} proof did not verify",
+            stage
+        )))
+    }
+}
+
+/// Runs `full`, falling back to `fast` when it fails and `policy` is `FallbackFast`.
+///
+/// Full proofs only wrap their recursion phase in it, with `fast` returning the RISCV proof the
+/// recursion started from.
+///
+/// Returns the output together with whether the fallback was used. If the fallback fails as well,
+/// the original `full` error is returned.
+pub fn with_failure_policy<T>(
+    policy: FailurePolicy,
+    full: impl FnOnce() -> Result<T, ProofServiceError>,
+    fast: impl FnOnce() -> Result<T, ProofServiceError>,
+) -> Result<(T, bool), ProofServiceError> {
+    match full() {
+        Ok(output) => Ok((output, false)),
+        Err(err) if policy == FailurePolicy::FallbackFast => {
+            warn!(
+                "Full proving failed ({}), falling back to a Fast proof",
+                err
+            );
+            match fast() {
+                Ok(output) => Ok((output, true)),
+                Err(fallback_err) => {
+                    warn!("Fast fallback failed as well: {}", fallback_err);
+                    Err(err)
+                }
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Verifies a proof produced by `execute_pico_prove` against the verifying key derived from the ELF.
+///
+/// Only `Fast` (RISCV) proofs can be checked in-process; `FullWithEvm` proofs are checked by the
+/// generated Groth16 verifier contract. The prover client verifying it comes from, and is kept in,
+/// `cache`.
+pub fn verify_pico_proof(
+    elf_path: &Path,
+    proof_result: &ProofResult,
+    cache: &ProverCache,
+) -> Result<bool, ProofServiceError> {
+    let elf_contents = read_elf_file(elf_path)?;
+    verify_pico_proof_elf(&elf_contents, proof_result, cache)
+}
+
+/// Like [`verify_pico_proof`], with the ELF already in memory.
+pub fn verify_pico_proof_elf(
+    elf_contents: &[u8],
+    proof_result: &ProofResult,
+    cache: &ProverCache,
+) -> Result<bool, ProofServiceError> {
+    if !proof_result.proving_type.is_fast() {
+        return Err(ProofServiceError::UnsupportedProvingType(format!(
+            "Verification of {:?} proofs is not supported",
+            proof_result.proving_type
+        )));
+    }
+
+    let proof_data = proof_result.output_encoding.decode(&proof_result.proof)?;
+    let public_values = proof_result.public_values_bytes()?;
+    let valid = cache.with_client(elf_contents, |client| {
+        verify_fast_proof(client, &proof_data, public_values)
+    })?;
+    info!(valid, program_hash = %proof_result.program_hash, "Fast proof verification finished");
+    Ok(valid)
+}
+
+/// Checks a freshly generated `Fast` proof (serialized as by `execute_pico_prove`) with the client
+/// that produced it, failing with [`ProofServiceError::ProvingError`] unless it verifies.
+pub fn self_verify_fast_proof(
+    client: &DefaultProverClient,
+    proof_data: &[u8],
+    public_values: &[u8],
+) -> Result<(), ProofServiceError> {
+    let failed = |reason: String| {
+        ProofServiceError::ProvingError(format!("Fast proof self-verification failed: {}", reason))
+    };
+    match verify_fast_proof(client, proof_data, public_values.to_vec()) {
+        Ok(true) => {
+            info!("Fast proof self-verified");
+            Ok(())
+        }
+        Ok(false) => Err(failed("proof rejected by the verifier".to_string())),
+        Err(e) => Err(failed(e.to_string())),
+    }
+}
+
+// Verifies a serialized RISCV proof against the client's verifying key
+fn verify_fast_proof(
+    client: &DefaultProverClient,
+    proof_data: &[u8],
+    public_values: Vec<u8>,
+) -> Result<bool, ProofServiceError> {
+    let proof: BaseProof<KoalaBearPoseidon2> = serde_json::from_slice(proof_data)?;
+    let riscv_vk = client.riscv_vk().clone();
+    let meta_proof = MetaProof::new(Arc::new([proof]), Arc::new([riscv_vk]), Some(public_values));
+    Ok(client.verify_riscv(&meta_proof))
+}
+
+/// Verifies a coprocessor proof and compares its public values with `expected_public_values_hex`,
+/// recomputed independently by the caller.
+///
+/// The two checks are reported separately: a proof can be valid for the program yet commit a result
+/// other than the expected one. The comparison is over the raw public values, whatever the result's
+/// `output_encoding`.
+pub fn verify_coprocessor_proof(
+    elf_path: &Path,
+    proof_result: &ProofResult,
+    expected_public_values_hex: &str,
+) -> Result<CoprocessorVerification, ProofServiceError> {
+    let expected = decode_hex(expected_public_values_hex).map_err(|e| {
+        ProofServiceError::InvalidInput(format!(
+            "Invalid expected public values (expected hex): {}",
+            e
+        ))
+    })?;
+    let public_values = proof_result.public_values_bytes()?;
+
+    // A one-off check, with no later proof to keep the client for
+    let crypto_valid = verify_pico_proof(elf_path, proof_result, &ProverCache::new(0))?;
+    let value_matches = public_values == expected;
+    info!(crypto_valid, value_matches, program_hash = %proof_result.program_hash, "Coprocessor proof verification finished");
+    Ok(CoprocessorVerification {
+        crypto_valid,
+        value_matches,
+    })
+}
+
+/// Rejects proofs whose `prover_version` is older than `min_version` (dotted numeric versions,
+/// compared component by component, with missing components counting as zero so that "1.2" and
+/// "1.2.0" are the same version). Proofs without a recorded version are rejected.
+pub fn ensure_prover_version(
+    prover_version: &str,
+    min_version: &str,
+) -> Result<(), ProofServiceError> {
+    fn parse(version: &str) -> Option<Vec<u64>> {
+        let mut parts: Vec<u64> = version
+            .trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?;
+        // Trailing zeros don't change the version
+        while parts.last() == Some(&0) {
+            parts.pop();
+        }
+        Some(parts)
+    }
+    let min = parse(min_version).ok_or_else(|| {
+        ProofServiceError::ConfigError(format!("Invalid min_prover_version: {:?}", min_version))
+    })?;
+    match parse(prover_version) {
+        Some(version) if version >= min => Ok(()),
+        _ => Err(ProofServiceError::InvalidInput(format!(
+            "Proof prover version {:?} is incompatible, at least {} is required",
+            prover_version, min_version
+        ))),
+    }
+}
+
+/// Rejects proving types that were compiled out of this build.
+pub fn ensure_proving_type_supported(proving_type: &ProvingType) -> Result<(), ProofServiceError> {
+    match proving_type {
+        #[cfg(not(feature = "evm-proving"))]
+        ProvingType::FullWithEvm => Err(evm_proving_disabled()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "evm-proving"))]
+fn evm_proving_disabled() -> ProofServiceError {
+    ProofServiceError::UnsupportedProvingType("FullWithEvm not enabled in this build".to_string())
+}
+
+/// Adjustments a `FullWithEvm` proof with `options` makes to the gnark container.
+pub fn docker_overrides(options: &ProveOptions) -> memory::DockerOverrides {
+    memory::DockerOverrides {
+        memory_limit_bytes: options.memory_limit_bytes,
+        image: options.evm_docker_image.clone(),
+    }
+}
+
+/// Checks that a shard size override is a power of two within [`MIN_SHARD_SIZE`, `MAX_SHARD_SIZE`].
+pub fn validate_shard_size(shard_size: usize) -> Result<(), ProofServiceError> {
+    if !shard_size.is_power_of_two() || !(MIN_SHARD_SIZE..=MAX_SHARD_SIZE).contains(&shard_size) {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Invalid shard_size {}: must be a power of two between {} and {}",
+            shard_size, MIN_SHARD_SIZE, MAX_SHARD_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that prover clients built in this process use a request's shard size override.
+///
+/// Pico only reads the shard size from `SHARD_SIZE_ENV`, and the service never changes its own
+/// environment while jobs run, so an override holds only in a process started with it: a
+/// [`SubprocessProver`](crate::backend::SubprocessProver) worker.
+pub fn ensure_shard_size_applied(shard_size: Option<usize>) -> Result<(), ProofServiceError> {
+    let Some(size) = shard_size else {
+        return Ok(());
+    };
+    validate_shard_size(size)?;
+    if std::env::var(SHARD_SIZE_ENV).ok() != Some(size.to_string()) {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "shard_size {} can't be applied in process; the service must prove in subprocesses (prover_subprocess)",
+            size
+        )));
+    }
+    debug!(shard_size = size, "Using prover shard size");
+    Ok(())
+}
+
+pub(crate) fn read_elf_file(elf_path: &Path) -> Result<Vec<u8>, ProofServiceError> {
     let file = File::open(elf_path)?; // Use std::fs::File for blocking read is ok here
     let mut reader = std::io::BufReader::new(file);
     let mut buffer = Vec::new();
@@ -212,7 +1128,153 @@ fn create_proof_output_dir(
     Ok(output_dir)
 }
 
+/// Creates the directory a `FullWithEvm` proof writes its artifacts to (`ProofResult::output_dir`):
+/// under `options.evm_output_dir` when set, where it persists, else under the job's `output_base_dir`.
+/// A caller-managed `options.output_dir` is used as-is.
+#[cfg(any(feature = "evm-proving", test))]
+pub fn evm_proof_output_dir(
+    output_base_dir: &Path,
+    options: &ProveOptions,
+) -> Result<PathBuf, ProofServiceError> {
+    if let Some(dir) = &options.output_dir {
+        return Ok(dir.clone());
+    }
+    let Some(evm_output_dir) = &options.evm_output_dir else {
+        return create_proof_output_dir(output_base_dir, "evm");
+    };
+    if let Some(retention) = options.evm_output_retention {
+        prune_evm_output_dirs(evm_output_dir, retention);
+    }
+    create_proof_output_dir(evm_output_dir, "evm")
+}
+
+/// Removes the artifact dirs [`evm_proof_output_dir`] created under `base_dir` that were last
+/// modified more than `retention` ago, so a persistent `evm_output_dir` doesn't grow without
+/// bound. Other entries are left alone, and failures are only logged.
+///
+/// The retention should exceed the longest `FullWithEvm` proof, whose dir may otherwise be
+/// removed while the gnark container still writes to it.
+#[cfg(any(feature = "evm-proving", test))]
+pub fn prune_evm_output_dirs(base_dir: &Path, retention: Duration) {
+    let entries = match std::fs::read_dir(base_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!(dir = ?base_dir, "Failed to list EVM output dirs for pruning: {}", e);
+            return;
+        }
+    };
+    let now = std::time::SystemTime::now();
+    for entry in entries.flatten() {
+        let is_evm_output = entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("proof_evm_");
+        let expired = entry.metadata().is_ok_and(|metadata| {
+            metadata.is_dir()
+                && metadata.modified().is_ok_and(|modified| {
+                    now.duration_since(modified).unwrap_or_default() > retention
+                })
+        });
+        if !is_evm_output || !expired {
+            continue;
+        }
+        match std::fs::remove_dir_all(entry.path()) {
+            Ok(()) => debug!(dir = ?entry.path(), "Removed expired EVM output dir"),
+            Err(e) => warn!(dir = ?entry.path(), "Failed to remove expired EVM output dir: {}", e),
+        }
+    }
+}
+
+/// Reads the proof and public values the gnark container wrote to `proof_output_dir`.
+///
+/// A file that isn't there is reported as [`ProofServiceError::ArtifactMissing`], so a Docker run
+/// that produced nothing is distinguishable from a failed proof.
+#[cfg(any(feature = "evm-proving", test))]
+pub fn read_evm_artifacts(
+    proof_output_dir: &Path,
+) -> Result<(Vec<u8>, Vec<u8>), ProofServiceError> {
+    let proof_path = proof_output_dir.join("proof.data");
+    // Docs mention pv_file, CLI output might use inputs.json. Check both.
+    let pv_path_primary = proof_output_dir.join("pv_file");
+    let pv_path_alt = proof_output_dir.join("inputs.json");
+
+    let proof_data = std::fs::read(&proof_path).map_err(|e| {
+        evm_artifact_error(e, "proof.data", proof_output_dir, || {
+            format!("Failed to read EVM proof file {:?}", proof_path)
+        })
+    })?;
+
+    // Read public values file (try pv_file first, then inputs.json)
+    let pv_content = std::fs::read_to_string(&pv_path_primary)
+        .or_else(|_| std::fs::read_to_string(&pv_path_alt))
+        .map_err(|e| {
+            evm_artifact_error(e, "pv_file or inputs.json", proof_output_dir, || {
+                format!(
+                    "Failed to read EVM public values file ({:?} or {:?})",
+                    pv_path_primary, pv_path_alt
+                )
+            })
+        })?;
+
+    // Public values can be hex in pv_file or JSON in inputs.json. Handle both.
+    let pv_bytes = if pv_path_alt.exists() && pv_content.trim().starts_with('{') {
+        parse_evm_inputs_json(&pv_content, &pv_path_alt)?.public_values_bytes()?
+    } else {
+        // Assume pv_file format (raw hex string)
+        decode_hex(pv_content.trim())?
+    };
+
+    Ok((proof_data, pv_bytes))
+}
+
+/// Reads the verifier calldata (`inputs.json`) the gnark container wrote to `proof_output_dir`,
+/// if it wrote one.
+#[cfg(any(feature = "evm-proving", test))]
+pub fn read_evm_inputs(
+    proof_output_dir: &Path,
+) -> Result<Option<EvmInputsJson>, ProofServiceError> {
+    let path = proof_output_dir.join("inputs.json");
+    match std::fs::read_to_string(&path) {
+        Ok(content) => parse_evm_inputs_json(&content, &path).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(ProofServiceError::ProvingError(format!(
+            "Failed to read EVM inputs {:?}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Parses the contents of an `inputs.json` EVM artifact read from `path`.
+#[cfg(any(feature = "evm-proving", test))]
+pub fn parse_evm_inputs_json(
+    content: &str,
+    path: &Path,
+) -> Result<EvmInputsJson, ProofServiceError> {
+    serde_json::from_str(content).map_err(|e| {
+        ProofServiceError::ProvingError(format!("Failed to parse EVM inputs {:?}: {}", path, e))
+    })
+}
+
+// Missing files become ArtifactMissing; any other read failure stays a ProvingError
+#[cfg(any(feature = "evm-proving", test))]
+fn evm_artifact_error(
+    e: std::io::Error,
+    artifact: &str,
+    dir: &Path,
+    context: impl FnOnce() -> String,
+) -> ProofServiceError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        ProofServiceError::ArtifactMissing {
+            artifact: artifact.to_string(),
+            dir: dir.display().to_string(),
+        }
+    } else {
+        ProofServiceError::ProvingError(format!("{}: {}", context(), e))
+    }
+}
+
 // Basic placeholder check if EVM setup artifacts exist
+#[cfg(feature = "evm-proving")]
 fn check_if_evm_setup_exists(output_dir: &Path) -> bool {
     // Gnark PK/VK are generated according to docs (`prove --evm --setup`).
     // The `prove_evm` docker command likely checks for these itself.
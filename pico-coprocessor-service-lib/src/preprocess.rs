@@ -0,0 +1,12 @@
+// pico-coprocessor-service-lib/src/preprocess.rs
+use crate::errors::ProofServiceError;
+use crate::types::BlockchainData;
+
+/// Transforms coprocessor `BlockchainData` (filtering, aggregating, ...) before it is serialized
+/// for the zkVM program.
+///
+/// Preprocessors are registered on `ServiceContext::input_preprocessors` and run in registration
+/// order; an error aborts the job before any proving work starts.
+pub trait InputPreprocessor: Send + Sync {
+    fn process(&self, data: BlockchainData) -> Result<BlockchainData, ProofServiceError>;
+}
@@ -1,35 +1,248 @@
 // pico-coprocessor-service-lib/src/program.rs
 use crate::context::ServiceContext;
 use crate::errors::ProofServiceError;
-use crate::types::ProgramLocation;
-use blueprint_sdk::{debug, error, info};
+use crate::types::{HashAlgorithm, ProgramLocation, ProgramSchema};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::alloy::primitives::B256;
+use blueprint_sdk::{debug, error, info, warn};
 use futures::StreamExt;
+use memmap2::Mmap;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use tempfile::{self, TempDir};
 use tokio::fs::File;
 use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::OnceCell;
 use url::Url;
 
-/// Fetches the program ELF binary, verifies its hash, saves it to a temporary directory.
+/// Hashing progress of a program ELF, reported as each chunk is hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashProgress {
+    /// Bytes hashed so far.
+    pub bytes_processed: u64,
+    /// Size of the program, when known up front (file size or download `Content-Length`).
+    pub total_bytes: Option<u64>,
+}
+
+/// Receives [`HashProgress`] updates, e.g. forwarding them to a UI progress channel.
+pub type HashProgressCallback = Arc<dyn Fn(HashProgress) + Send + Sync>;
+
+/// Shares one download among concurrent fetches of the same program hash.
+///
+/// The first fetch downloads and verifies the program into a staging directory under
+/// `temp_dir_base`; fetches arriving meanwhile wait for it and link the verified file into their
+/// own directories. Once the download finishes the entry is dropped, so later fetches download
+/// again unless the context keeps an [`ElfCache`](crate::ElfCache). A failed download is not
+/// shared: each waiter retries it in turn and gets its own error.
+#[derive(Clone, Default)]
+pub struct DownloadDedup {
+    in_flight: Arc<Mutex<HashMap<String, Arc<OnceCell<Arc<StagedElf>>>>>>,
+}
+
+// A verified download, removed once every fetch sharing it has linked it
+struct StagedElf {
+    _dir: TempDir,
+    path: PathBuf,
+    hash_hex: String,
+}
+
+impl DownloadDedup {
+    /// Number of program hashes currently being downloaded.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+
+    async fn fetch(
+        &self,
+        ctx: &ServiceContext,
+        url: &Url,
+        expected_hash_hex: &str,
+        algorithm: HashAlgorithm,
+    ) -> Result<Arc<StagedElf>, ProofServiceError> {
+        let key = expected_hash_hex.to_ascii_lowercase();
+        let entry = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let result = entry
+            .get_or_try_init(|| stage_download(ctx, url, expected_hash_hex, algorithm))
+            .await
+            .map(Arc::clone);
+
+        // Whoever finishes first retires the entry; fetches still waiting hold their own handle
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &entry))
+        {
+            in_flight.remove(&key);
+        }
+        result
+    }
+}
+
+// Downloads and verifies a program outside any request sandbox, so it outlives the first request
+async fn stage_download(
+    ctx: &ServiceContext,
+    url: &Url,
+    expected_hash_hex: &str,
+    algorithm: HashAlgorithm,
+) -> Result<Arc<StagedElf>, ProofServiceError> {
+    let dir = tempfile::Builder::new()
+        .prefix("pico_download_")
+        .tempdir_in(&ctx.temp_dir_base)
+        .map_err(|e| {
+            ProofServiceError::TempDirError(format!("Failed to create download staging dir: {}", e))
+        })?;
+    let path = dir.path().join("program.elf");
+    let hash_hex = download_and_hash(ctx, url, &path, algorithm).await?;
+    verify_program_hash(expected_hash_hex, hash_hex.clone(), &path)?;
+    Ok(Arc::new(StagedElf {
+        _dir: dir,
+        path,
+        hash_hex,
+    }))
+}
+
+/// Prefix of the location annotation naming the hash algorithm a program is registered under: a
+/// `hash=<algorithm>` URL fragment (`https://example.com/p.elf#hash=blake3`) or `data:` URI
+/// parameter (`data:application/octet-stream;hash=blake3;base64,...`). Fragments aren't sent with
+/// the download, so annotated URLs fetch as before.
+pub const HASH_ANNOTATION_PREFIX: &str = "hash=";
+
+/// Hash algorithm a program location is annotated with; [`HashAlgorithm::Sha256`] for
+/// unannotated locations and local paths.
+pub fn location_hash_algorithm(
+    location: &ProgramLocation,
+) -> Result<HashAlgorithm, ProofServiceError> {
+    let annotation = match location {
+        ProgramLocation::RemoteUrl(url) => url.fragment().and_then(|fragment| {
+            fragment
+                .split('&')
+                .find_map(|part| part.strip_prefix(HASH_ANNOTATION_PREFIX))
+        }),
+        ProgramLocation::DataUri(uri) => uri
+            .strip_prefix(DATA_URI_SCHEME)
+            .and_then(|rest| rest.split_once(','))
+            .and_then(|(metadata, _)| {
+                metadata
+                    .split(';')
+                    .find_map(|part| part.strip_prefix(HASH_ANNOTATION_PREFIX))
+            }),
+        ProgramLocation::LocalPath(_) => None,
+    };
+    annotation.map_or(Ok(HashAlgorithm::default()), HashAlgorithm::from_str)
+}
+
+// Incremental hasher for the algorithm a program is registered under
+enum ElfHasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl ElfHasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            HashAlgorithm::Blake3 => Self::Blake3(Box::default()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(bytes),
+            Self::Blake3(hasher) => {
+                hasher.update(bytes);
+            }
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Self::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Self::Blake3(hasher) => hex::encode(hasher.finalize().as_bytes()),
+        }
+    }
+}
+
+/// Hex digest of `bytes` under `algorithm`.
+pub fn hash_hex(algorithm: HashAlgorithm, bytes: &[u8]) -> String {
+    let mut hasher = ElfHasher::new(algorithm);
+    hasher.update(bytes);
+    hasher.finalize_hex()
+}
+
+/// Fetches the program ELF binary, verifies its hash, saves it to a temporary directory under `work_dir`.
 /// Returns the TempDir handle (for cleanup) and the path to the temporary file.
 pub async fn fetch_and_verify_program(
     ctx: &ServiceContext,
     location: &ProgramLocation,
     expected_hash_hex: &str,
+    work_dir: &Path,
 ) -> Result<(TempDir, PathBuf), ProofServiceError> {
-    // Return tuple
-    let temp_dir = tempfile::Builder::new()
-        .prefix("pico_elf_")
-        .tempdir_in(&ctx.temp_dir_base)
-        .map_err(|e| {
-            ProofServiceError::TempDirError(format!("Failed to create temp dir for ELF: {}", e))
-        })?;
+    fetch_program(ctx, location, expected_hash_hex, work_dir, false).await
+}
+
+/// Whether a request's `insecure_skip_hash_verify` is honored: only for `LocalPath` programs on a
+/// service that sets `allow_insecure`.
+pub fn skips_hash_verify(
+    ctx: &ServiceContext,
+    location: &ProgramLocation,
+    insecure_skip_hash_verify: bool,
+) -> bool {
+    insecure_skip_hash_verify
+        && ctx.allow_insecure
+        && matches!(location, ProgramLocation::LocalPath(_))
+}
 
+/// [`fetch_and_verify_program`], except that a local program is used without hashing it when
+/// [`skips_hash_verify`] allows it, for iterating on an ELF during development.
+pub async fn fetch_program(
+    ctx: &ServiceContext,
+    location: &ProgramLocation,
+    expected_hash_hex: &str,
+    work_dir: &Path,
+    insecure_skip_hash_verify: bool,
+) -> Result<(TempDir, PathBuf), ProofServiceError> {
+    let skip_hash_verify = skips_hash_verify(ctx, location, insecure_skip_hash_verify);
+    let algorithm = location_hash_algorithm(location)?;
+    // Return tuple
+    let temp_dir = elf_temp_dir(work_dir)?;
     let elf_path = temp_dir.path().join("program.elf");
 
     let actual_hash_hex = match location {
-        ProgramLocation::RemoteUrl(url) => download_and_hash(ctx, url, &elf_path).await?,
+        ProgramLocation::RemoteUrl(url) => match &ctx.download_dedup {
+            Some(dedup) => {
+                let staged = dedup.fetch(ctx, url, expected_hash_hex, algorithm).await?;
+                link_or_copy(&staged.path, &elf_path).await?;
+                staged.hash_hex.clone()
+            }
+            None => download_and_hash(ctx, url, &elf_path, algorithm).await?,
+        },
+        ProgramLocation::DataUri(uri) => {
+            let bytes = decode_data_uri(uri)?;
+            if bytes.len() as u64 > ctx.max_elf_bytes {
+                return Err(ProofServiceError::InvalidInput(format!(
+                    "Data URI program of {} bytes exceeds max_elf_bytes ({})",
+                    bytes.len(),
+                    ctx.max_elf_bytes
+                )));
+            }
+            tokio::fs::write(&elf_path, &bytes).await?;
+            debug!(
+                "Wrote {} bytes from data URI to {:?}",
+                bytes.len(),
+                elf_path
+            );
+            hash_hex(algorithm, &bytes)
+        }
         ProgramLocation::LocalPath(path) => {
             if !path.exists() {
                 return Err(ProofServiceError::IoError(format!(
@@ -43,18 +256,242 @@ pub async fn fetch_and_verify_program(
                 "Copied {} bytes from local path {:?} to {:?}",
                 bytes_copied, path, elf_path
             );
-            calculate_file_hash(&elf_path).await?
+            if skip_hash_verify {
+                warn!(
+                    path = ?path,
+                    expected_hash = %expected_hash_hex,
+                    "INSECURE: skipping program hash verification (insecure_skip_hash_verify); \
+                     the proof is not bound to the requested program hash"
+                );
+                return Ok((temp_dir, elf_path));
+            }
+            calculate_file_hash_with_algorithm(&elf_path, algorithm, ctx.hash_progress.as_ref())
+                .await?
         }
     };
 
     // Verify hash
-    if actual_hash_hex.eq_ignore_ascii_case(expected_hash_hex) {
-        info!(expected = %expected_hash_hex, actual = %actual_hash_hex, path = ?elf_path, "Program hash verified successfully");
-        // Return the TempDir handle AND the path
-        Ok((temp_dir, elf_path))
+    // TempDir cleans up automatically when dropped if verification fails
+    // The cache stores ELFs by SHA-256, so a SHA-256 verification saves hashing the file again
+    let sha256_hex = (algorithm == HashAlgorithm::Sha256).then(|| actual_hash_hex.clone());
+    verify_program_hash(expected_hash_hex, actual_hash_hex, &elf_path)?;
+    // Later requests for the program are served from the cache
+    if let Some(cache) = &ctx.elf_cache {
+        if let Err(e) = cache
+            .get_or_insert(expected_hash_hex, &elf_path, sha256_hex.as_deref())
+            .await
+        {
+            warn!(program_hash = %expected_hash_hex, "Failed to cache program ELF: {}", e);
+        }
+    }
+    // Return the TempDir handle AND the path
+    Ok((temp_dir, elf_path))
+}
+
+/// The program cached in the context's [`ElfCache`] for `expected_hash_hex`, placed in a temp dir
+/// under `work_dir` as [`fetch_program`] would, so the caller can skip the registry lookup and
+/// download. `None` when there is no cache or entry, or when the request's local program is used
+/// without hash verification (it may differ from the cached one).
+pub async fn cached_program(
+    ctx: &ServiceContext,
+    expected_hash_hex: &str,
+    location_override: Option<&ProgramLocation>,
+    insecure_skip_hash_verify: bool,
+    work_dir: &Path,
+) -> Result<Option<(TempDir, PathBuf)>, ProofServiceError> {
+    if location_override
+        .is_some_and(|location| skips_hash_verify(ctx, location, insecure_skip_hash_verify))
+    {
+        return Ok(None);
+    }
+    let Some(cached) = ctx
+        .elf_cache
+        .as_ref()
+        .and_then(|cache| cache.get(expected_hash_hex))
+    else {
+        return Ok(None);
+    };
+    let temp_dir = elf_temp_dir(work_dir)?;
+    let elf_path = temp_dir.path().join("program.elf");
+    link_or_copy(&cached, &elf_path).await?;
+    info!(program_hash = %expected_hash_hex, "Using cached program ELF");
+    Ok(Some((temp_dir, elf_path)))
+}
+
+fn elf_temp_dir(work_dir: &Path) -> Result<TempDir, ProofServiceError> {
+    tempfile::Builder::new()
+        .prefix("pico_elf_")
+        .tempdir_in(work_dir)
+        .map_err(|e| {
+            ProofServiceError::TempDirError(format!("Failed to create temp dir for ELF: {}", e))
+        })
+}
+
+// Hard links are free; fall back to copying across filesystems
+async fn link_or_copy(from: &Path, to: &Path) -> Result<(), ProofServiceError> {
+    if std::fs::hard_link(from, to).is_err() {
+        tokio::fs::copy(from, to).await?;
+    }
+    Ok(())
+}
+
+/// Memory-maps a program ELF that [`fetch_program`] or [`cached_program`] placed in a job's temp
+/// dir, so it is proven without being read into memory.
+///
+/// Only those copies are mapped: they were verified when fetched and nothing but the job writes
+/// to them. A request's `LocalPath` is never mapped in place, since whoever owns it could change
+/// or truncate it under the mapping.
+pub async fn map_fetched_program(elf_path: &Path) -> Result<Mmap, ProofServiceError> {
+    let path = elf_path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&path).map_err(|e| {
+            ProofServiceError::IoError(format!("Fetched program {:?} unreadable: {}", path, e))
+        })?;
+        // SAFETY: the file is the job's own copy in its sandbox, not modified while proving.
+        let mmap = unsafe { Mmap::map(&file) }?;
+        debug!(
+            "Mapped {} bytes from fetched program {:?}",
+            mmap.len(),
+            path
+        );
+        Ok(mmap)
+    })
+    .await
+    .map_err(|e| ProofServiceError::InternalError(format!("Program mapping failed: {}", e)))?
+}
+
+/// Largest program schema document accepted by [`fetch_program_schema`].
+pub const MAX_SCHEMA_BYTES: usize = 64 * 1024;
+
+/// Fetches the schema document published at `uri` (an http(s) or `data:` URI), checks its SHA256
+/// against the `schema_hash` recorded in the registry, and parses it.
+pub async fn fetch_program_schema(
+    ctx: &ServiceContext,
+    uri: &str,
+    schema_hash: &B256,
+) -> Result<ProgramSchema, ProofServiceError> {
+    let bytes = if uri.starts_with(DATA_URI_SCHEME) {
+        decode_data_uri(uri)?
+    } else {
+        let url = Url::parse(uri)?;
+        info!(%url, "Downloading program schema");
+        let response = ctx.http_client.get(url.clone()).send().await?;
+        if !response.status().is_success() {
+            return Err(ProofServiceError::ProgramDownloadFailed(format!(
+                "Failed to download schema from {}: Status {}",
+                url,
+                response.status()
+            )));
+        }
+        let mut bytes = Vec::new();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            bytes.extend_from_slice(&chunk?);
+            if bytes.len() > MAX_SCHEMA_BYTES {
+                return Err(ProofServiceError::ProgramDownloadFailed(format!(
+                    "Schema at {} exceeds {} bytes",
+                    url, MAX_SCHEMA_BYTES
+                )));
+            }
+        }
+        bytes
+    };
+
+    let actual_hash = B256::from_slice(&Sha256::digest(&bytes));
+    if actual_hash != *schema_hash {
+        error!(%uri, expected = %schema_hash, got = %actual_hash, "Program schema hash mismatch!");
+        return Err(ProofServiceError::ProgramDownloadFailed(format!(
+            "Schema at {} has hash {}, registry records {}",
+            uri, actual_hash, schema_hash
+        )));
+    }
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Scheme prefix of inline [`ProgramLocation::DataUri`] programs.
+pub const DATA_URI_SCHEME: &str = "data:";
+
+/// URL schemes [`ProgramLocation::RemoteUrl`] programs are downloaded over.
+pub const SUPPORTED_URL_SCHEMES: &[&str] = &["http", "https"];
+
+impl ProgramLocation {
+    /// Whether this build can fetch the program: `RemoteUrl`s over one of
+    /// [`SUPPORTED_URL_SCHEMES`], `data:` URIs and local paths. Locations needing a fetcher this
+    /// build lacks, such as `ipfs://` or `s3://` URLs, are not. Takes the context for locations
+    /// whose support depends on the service's configuration; none do yet.
+    pub fn is_supported(&self, _ctx: &ServiceContext) -> bool {
+        match self {
+            ProgramLocation::RemoteUrl(url) => SUPPORTED_URL_SCHEMES.contains(&url.scheme()),
+            ProgramLocation::DataUri(uri) => uri.starts_with(DATA_URI_SCHEME),
+            ProgramLocation::LocalPath(_) => true,
+        }
+    }
+}
+
+/// Rejects a request's `program_location_override` that [`ProgramLocation::is_supported`] refuses,
+/// so the job fails as it starts rather than once it gets to fetching the program.
+pub fn check_location_override(
+    ctx: &ServiceContext,
+    location_override: Option<&ProgramLocation>,
+) -> Result<(), ProofServiceError> {
+    match location_override {
+        Some(location) if !location.is_supported(ctx) => {
+            let reason = match location {
+                ProgramLocation::RemoteUrl(url) => format!(
+                    "URL scheme {:?} is not supported (expected one of {})",
+                    url.scheme(),
+                    SUPPORTED_URL_SCHEMES.join(", ")
+                ),
+                ProgramLocation::DataUri(_) => {
+                    format!("data URI must start with {:?}", DATA_URI_SCHEME)
+                }
+                ProgramLocation::LocalPath(path) => format!("{:?} is not supported", path),
+            };
+            Err(ProofServiceError::InvalidInput(format!(
+                "Unsupported program_location_override: {}",
+                reason
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Decodes the payload of a `data:[<media type>];base64,<payload>` URI. Only base64 payloads are
+/// accepted, since ELF binaries aren't text.
+pub fn decode_data_uri(uri: &str) -> Result<Vec<u8>, ProofServiceError> {
+    let invalid = |reason: &str| {
+        ProofServiceError::InvalidInput(format!("Invalid program data URI: {}", reason))
+    };
+    let rest = uri
+        .strip_prefix(DATA_URI_SCHEME)
+        .ok_or_else(|| invalid("missing data: scheme"))?;
+    let (metadata, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| invalid("missing ',' before payload"))?;
+    if !metadata.ends_with(";base64") {
+        return Err(invalid("payload must be base64 encoded"));
+    }
+    STANDARD
+        .decode(payload.trim())
+        .map_err(|e| invalid(&e.to_string()))
+}
+
+fn verify_program_hash(
+    expected_hash_hex: &str,
+    actual_hash_hex: String,
+    path: &Path,
+) -> Result<(), ProofServiceError> {
+    // Hashes compare as hex digits; a `0x` prefix on either side is not part of the hash
+    let digits = |hex: &str| {
+        hex.strip_prefix("0x")
+            .or_else(|| hex.strip_prefix("0X"))
+            .unwrap_or(hex)
+    };
+    if digits(&actual_hash_hex).eq_ignore_ascii_case(digits(expected_hash_hex)) {
+        info!(expected = %expected_hash_hex, actual = %actual_hash_hex, path = ?path, "Program hash verified successfully");
+        Ok(())
     } else {
         error!(expected = %expected_hash_hex, actual = %actual_hash_hex, "Program hash mismatch!");
-        // TempDir cleans up automatically when dropped, no need for manual remove_dir_all here
         Err(ProofServiceError::ProgramHashMismatch {
             expected: expected_hash_hex.to_string(),
             got: actual_hash_hex,
@@ -67,6 +504,7 @@ async fn download_and_hash(
     ctx: &ServiceContext,
     url: &Url,
     dest_path: &Path,
+    algorithm: HashAlgorithm,
 ) -> Result<String, ProofServiceError> {
     info!(%url, dest = ?dest_path, "Downloading program ELF");
     let response = ctx.http_client.get(url.clone()).send().await?;
@@ -79,28 +517,94 @@ async fn download_and_hash(
         )));
     }
 
+    // Refuse e.g. an HTML login page before spending time hashing it
+    if let Some(allowed) = &ctx.allowed_elf_content_types {
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        check_content_type(content_type, allowed, url)?;
+    }
+
+    let total_bytes = response.content_length();
+    let too_large = || {
+        ProofServiceError::ProgramDownloadFailed(format!(
+            "Program at {} exceeds max_elf_bytes ({})",
+            url, ctx.max_elf_bytes
+        ))
+    };
+    if total_bytes.is_some_and(|len| len > ctx.max_elf_bytes) {
+        return Err(too_large());
+    }
     let mut file = BufWriter::new(File::create(dest_path).await?);
-    let mut hasher = Sha256::new();
+    let mut hasher = ElfHasher::new(algorithm);
     let mut stream = response.bytes_stream();
+    let mut bytes_processed = 0u64;
 
     while let Some(chunk_result) = stream.next().await {
         let chunk = chunk_result?;
         hasher.update(&chunk);
         file.write_all(&chunk).await?;
+        bytes_processed += chunk.len() as u64;
+        // Content-Length may be missing or wrong, so the bound holds for the bytes received
+        if bytes_processed > ctx.max_elf_bytes {
+            return Err(too_large());
+        }
+        if let Some(progress) = &ctx.hash_progress {
+            progress(HashProgress {
+                bytes_processed,
+                total_bytes,
+            });
+        }
     }
 
     file.flush().await?; // Ensure all bytes are written
 
-    let hash_bytes = hasher.finalize();
-    let hash_hex = hex::encode(hash_bytes);
-    debug!(%url, %hash_hex, "Finished downloading and hashing");
+    let hash_hex = hasher.finalize_hex();
+    debug!(%url, %hash_hex, ?algorithm, "Finished downloading and hashing");
     Ok(hash_hex)
 }
 
-async fn calculate_file_hash(path: &Path) -> Result<String, ProofServiceError> {
+// Compares the media type (ignoring parameters such as charset) against the allow-list
+fn check_content_type(
+    content_type: &str,
+    allowed: &[String],
+    url: &Url,
+) -> Result<(), ProofServiceError> {
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    if allowed.iter().any(|a| a.eq_ignore_ascii_case(media_type)) {
+        return Ok(());
+    }
+    Err(ProofServiceError::ProgramDownloadFailed(format!(
+        "unexpected content-type {:?} from {}",
+        content_type, url
+    )))
+}
+
+pub(crate) async fn calculate_file_hash(path: &Path) -> Result<String, ProofServiceError> {
+    calculate_file_hash_with_progress(path, None).await
+}
+
+/// Hex SHA-256 of the file at `path`, reporting progress after each chunk when `progress` is set.
+pub async fn calculate_file_hash_with_progress(
+    path: &Path,
+    progress: Option<&HashProgressCallback>,
+) -> Result<String, ProofServiceError> {
+    calculate_file_hash_with_algorithm(path, HashAlgorithm::Sha256, progress).await
+}
+
+/// [`calculate_file_hash_with_progress`] under `algorithm`.
+pub async fn calculate_file_hash_with_algorithm(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    progress: Option<&HashProgressCallback>,
+) -> Result<String, ProofServiceError> {
     let mut file = tokio::fs::File::open(path).await?;
-    let mut hasher = Sha256::new();
+    let total_bytes = file.metadata().await.ok().map(|m| m.len());
+    let mut hasher = ElfHasher::new(algorithm);
     let mut buffer = [0; 4096]; // Slightly larger buffer
+    let mut bytes_processed = 0u64;
 
     loop {
         let n = tokio::io::AsyncReadExt::read(&mut file, &mut buffer).await?;
@@ -108,8 +612,14 @@ async fn calculate_file_hash(path: &Path) -> Result<String, ProofServiceError> {
             break;
         }
         hasher.update(&buffer[..n]);
+        bytes_processed += n as u64;
+        if let Some(progress) = progress {
+            progress(HashProgress {
+                bytes_processed,
+                total_bytes,
+            });
+        }
     }
 
-    let hash_bytes = hasher.finalize();
-    Ok(hex::encode(hash_bytes))
+    Ok(hasher.finalize_hex())
 }
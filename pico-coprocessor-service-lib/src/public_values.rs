@@ -0,0 +1,140 @@
+// pico-coprocessor-service-lib/src/public_values.rs
+use crate::errors::ProofServiceError;
+use crate::types::{CoprocessorCommitFormat, MerkleCommitment, PublicValuesPolicy};
+use blueprint_sdk::alloy::{
+    primitives::{B256, Bytes, keccak256},
+    sol_types::SolValue,
+};
+
+/// Extracts the committed payload from a proof's public values according to `format`.
+pub fn decode_public_values(
+    format: CoprocessorCommitFormat,
+    public_values: &[u8],
+) -> Result<Vec<u8>, ProofServiceError> {
+    match format {
+        CoprocessorCommitFormat::Raw => Ok(public_values.to_vec()),
+        CoprocessorCommitFormat::LengthPrefixed => decode_length_prefixed(public_values),
+        CoprocessorCommitFormat::AbiEncoded => Bytes::abi_decode(public_values, true)
+            .map(Vec::from)
+            .map_err(|e| {
+                ProofServiceError::InvalidInput(format!(
+                    "Public values are not ABI-encoded bytes: {}",
+                    e
+                ))
+            }),
+    }
+}
+
+// u64 little-endian length followed by exactly that many bytes (bincode's `Vec<u8>` layout)
+fn decode_length_prefixed(public_values: &[u8]) -> Result<Vec<u8>, ProofServiceError> {
+    let (prefix, payload) = public_values.split_first_chunk::<8>().ok_or_else(|| {
+        ProofServiceError::InvalidInput(format!(
+            "Length-prefixed public values too short: {} bytes",
+            public_values.len()
+        ))
+    })?;
+    let declared = u64::from_le_bytes(*prefix);
+    if declared != payload.len() as u64 {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Length-prefixed public values declare {} bytes but carry {}",
+            declared,
+            payload.len()
+        )));
+    }
+    Ok(payload.to_vec())
+}
+
+/// Sizes decoded public values according to `policy`.
+pub fn apply_public_values_policy(
+    policy: PublicValuesPolicy,
+    values: Vec<u8>,
+) -> Result<Vec<u8>, ProofServiceError> {
+    match policy {
+        PublicValuesPolicy::AsIs => Ok(values),
+        PublicValuesPolicy::Exact32 if values.len() == 32 => Ok(values),
+        PublicValuesPolicy::LeftPad32 if values.len() <= 32 => {
+            let mut padded = vec![0u8; 32 - values.len()];
+            padded.extend(values);
+            Ok(padded)
+        }
+        _ => Err(ProofServiceError::InvalidInput(format!(
+            "Public values are {} bytes, {:?} requires {} 32",
+            values.len(),
+            policy,
+            if policy == PublicValuesPolicy::Exact32 {
+                "exactly"
+            } else {
+                "at most"
+            }
+        ))),
+    }
+}
+
+/// Byte prepended to a leaf before hashing it into the Merkle tree.
+pub const MERKLE_LEAF_PREFIX: u8 = 0x00;
+/// Byte prepended to a pair of child hashes before hashing them into their parent.
+pub const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+/// Root of the Merkle tree over `leaves`: each leaf is hashed as `keccak256(0x00 || leaf)`,
+/// parents are `keccak256(0x01 || left || right)`, and an odd node is carried up to the next level
+/// unhashed. The prefixes keep a pair of leaves from passing for an internal node (and one leaf
+/// from passing for the root). No leaves give zero.
+pub fn merkle_root(leaves: &[B256]) -> B256 {
+    if leaves.is_empty() {
+        return B256::ZERO;
+    }
+    let mut level: Vec<B256> = leaves
+        .iter()
+        .map(|leaf| keccak256([&[MERKLE_LEAF_PREFIX][..], leaf.as_slice()].concat()))
+        .collect();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => keccak256(
+                    [&[MERKLE_NODE_PREFIX][..], left.as_slice(), right.as_slice()].concat(),
+                ),
+                [odd] => *odd,
+                _ => unreachable!("chunks(2) yields one or two nodes"),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Parses a `CommitMode::MerkleRoot` payload: the 32-byte root, optionally followed by one 32-byte
+/// leaf per receipt. Committed leaves must number `receipt_count` and hash to the root.
+pub fn parse_merkle_commitment(
+    payload: &[u8],
+    receipt_count: usize,
+) -> Result<MerkleCommitment, ProofServiceError> {
+    if payload.len() < 32 || payload.len() % 32 != 0 {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Merkle commitment must be a 32-byte root plus 32-byte leaves, got {} bytes",
+            payload.len()
+        )));
+    }
+    let (root, leaves) = payload.split_at(32);
+    let root = B256::from_slice(root);
+    let leaves: Vec<B256> = leaves.chunks_exact(32).map(B256::from_slice).collect();
+    if !leaves.is_empty() {
+        if leaves.len() != receipt_count {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Merkle commitment carries {} leaves for {} receipts",
+                leaves.len(),
+                receipt_count
+            )));
+        }
+        let computed = merkle_root(&leaves);
+        if computed != root {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Committed Merkle root {} does not match its leaves (computed {})",
+                root, computed
+            )));
+        }
+    }
+    Ok(MerkleCommitment {
+        root: root.to_string(),
+        leaves: leaves.iter().map(ToString::to_string).collect(),
+    })
+}
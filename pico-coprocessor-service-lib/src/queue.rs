@@ -0,0 +1,140 @@
+// pico-coprocessor-service-lib/src/queue.rs
+use crate::types::ProofPriority;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Bounds how many proofs run at once. When all permits are taken, waiting jobs are granted the
+/// next free permit by priority, first come first served within a priority.
+#[derive(Debug, Clone)]
+pub struct ProvingQueue {
+    inner: Arc<Mutex<QueueState>>,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    available: usize,
+    next_seq: u64,
+    waiters: BinaryHeap<Waiter>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    priority: ProofPriority,
+    seq: u64,
+    grant: oneshot::Sender<ProvingPermit>,
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Max-heap: higher priority first, then the earlier arrival
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+/// A proving slot, returned to the queue when dropped.
+#[derive(Debug)]
+pub struct ProvingPermit {
+    // None once the slot has been returned without going through Drop
+    queue: Option<ProvingQueue>,
+}
+
+impl ProvingQueue {
+    pub fn new(permits: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(QueueState {
+                available: permits,
+                next_seq: 0,
+                waiters: BinaryHeap::new(),
+            })),
+        }
+    }
+
+    /// Waits for a proving slot.
+    pub async fn acquire(&self, priority: ProofPriority) -> ProvingPermit {
+        let granted = {
+            let mut state = self.lock();
+            if state.available > 0 && state.waiters.is_empty() {
+                state.available -= 1;
+                None
+            } else {
+                let (grant, granted) = oneshot::channel();
+                let seq = state.next_seq;
+                state.next_seq += 1;
+                state.waiters.push(Waiter {
+                    priority,
+                    seq,
+                    grant,
+                });
+                Some(granted)
+            }
+        };
+        match granted {
+            // The permit travels through the channel, so a job cancelled after it was sent drops
+            // it along with the receiver, returning it to the queue. The sender is only consumed
+            // by `release`, which always sends.
+            Some(granted) => granted
+                .await
+                .expect("queued waiters are always granted a permit"),
+            None => ProvingPermit {
+                queue: Some(self.clone()),
+            },
+        }
+    }
+
+    /// Free permits.
+    pub fn available(&self) -> usize {
+        self.lock().available
+    }
+
+    /// Jobs waiting for a permit.
+    pub fn waiting(&self) -> usize {
+        self.lock().waiters.len()
+    }
+
+    fn release(&self) {
+        let mut state = self.lock();
+        let mut permit = ProvingPermit {
+            queue: Some(self.clone()),
+        };
+        // Skip waiters whose job was cancelled while queued
+        while let Some(waiter) = state.waiters.pop() {
+            match waiter.grant.send(permit) {
+                Ok(()) => return,
+                Err(returned) => permit = returned,
+            }
+        }
+        // Nobody took the slot; dropping the permit here would release it again
+        permit.queue = None;
+        state.available += 1;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for ProvingPermit {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue.take() {
+            queue.release();
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// pico-coprocessor-service-lib/src/rate_limit.rs
+use crate::errors::ProofServiceError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Bucket shared by every caller the service can't identify, i.e. all callers when
+/// authentication is disabled. Can't collide with a caller's hex public key.
+pub const UNAUTHENTICATED_CALLER: &str = "unauthenticated";
+
+/// Longest `retry_after` a [`RateLimiter`] reports. Waits that don't fit a `Duration`, from
+/// refill rates close to zero, are reported as this too.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Token-bucket rate limiter keyed by authenticated caller (hex public key), with
+/// unauthenticated callers sharing [`UNAUTHENTICATED_CALLER`].
+///
+/// Each caller may submit up to `capacity` jobs in a burst; tokens then refill at
+/// `refill_per_sec`. Clones share the same buckets.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// `capacity` and `refill_per_sec` must be positive.
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Result<Self, ProofServiceError> {
+        if capacity == 0 || !refill_per_sec.is_finite() || refill_per_sec <= 0.0 {
+            return Err(ProofServiceError::ConfigError(format!(
+                "Rate limit needs a positive capacity and refill rate, got {} and {}",
+                capacity, refill_per_sec
+            )));
+        }
+        Ok(Self {
+            capacity: f64::from(capacity),
+            refill_per_sec,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Takes a token from `caller`'s bucket, or fails with `RateLimited` and the time until the
+    /// next token is available.
+    pub fn check(&self, caller: &str) -> Result<(), ProofServiceError> {
+        self.check_at(caller, Instant::now())
+    }
+
+    /// [`check`](Self::check) at a given instant.
+    pub fn check_at(&self, caller: &str, now: Instant) -> Result<(), ProofServiceError> {
+        self.check_n_at(caller, 1, now)
+    }
+
+    /// Takes `n` tokens from `caller`'s bucket at once, or none: fails with `RateLimited` and the
+    /// time until all `n` are available, leaving the bucket as it was. More tokens than the
+    /// bucket holds are never available, which fails with `InvalidInput`.
+    pub fn check_n(&self, caller: &str, n: u32) -> Result<(), ProofServiceError> {
+        self.check_n_at(caller, n, Instant::now())
+    }
+
+    /// [`check_n`](Self::check_n) at a given instant.
+    pub fn check_n_at(&self, caller: &str, n: u32, now: Instant) -> Result<(), ProofServiceError> {
+        let needed = f64::from(n);
+        if needed > self.capacity {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "{} proofs exceed the rate limit's burst of {}",
+                n, self.capacity
+            )));
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets.entry(caller.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= needed {
+            bucket.tokens -= needed;
+            return Ok(());
+        }
+        let retry_after =
+            Duration::try_from_secs_f64((needed - bucket.tokens) / self.refill_per_sec)
+                .map_or(MAX_RETRY_AFTER, |wait| wait.min(MAX_RETRY_AFTER));
+        Err(ProofServiceError::RateLimited { retry_after })
+    }
+}
@@ -0,0 +1,242 @@
+// pico-coprocessor-service-lib/src/s3.rs
+use crate::errors::ProofServiceError;
+use blueprint_sdk::info;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// S3-compatible endpoint proofs are uploaded to, with the credentials requests are signed with
+/// (AWS Signature Version 4) and the part of the store requests may upload to. The secret is left
+/// out of `Debug`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct S3Config {
+    /// Base URL of the service, e.g. `https://s3.us-east-1.amazonaws.com`; objects are addressed
+    /// path-style as `{endpoint}/{bucket}/{key}`.
+    pub endpoint: Url,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Only bucket an `output_sink` may name.
+    pub bucket: String,
+    /// Prefix an `output_sink`'s `key_prefix` must start with; empty allows the whole bucket.
+    #[serde(default)]
+    pub key_prefix: String,
+}
+
+/// Region requests are signed for when the configuration doesn't name one.
+pub const DEFAULT_S3_REGION: &str = "us-east-1";
+
+fn default_region() -> String {
+    DEFAULT_S3_REGION.to_string()
+}
+
+impl std::fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("region", &self.region)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field("bucket", &self.bucket)
+            .field("key_prefix", &self.key_prefix)
+            .finish()
+    }
+}
+
+/// Uploads objects to the bucket of an [`S3Config`] endpoint with signed `PUT` requests.
+#[derive(Debug, Clone)]
+pub struct S3Store {
+    config: S3Config,
+    client: reqwest::Client,
+}
+
+impl S3Store {
+    pub fn new(config: S3Config) -> Self {
+        Self::with_client(config, reqwest::Client::new())
+    }
+
+    pub fn with_client(config: S3Config, client: reqwest::Client) -> Self {
+        Self { config, client }
+    }
+
+    /// Rejects uploads outside the configured bucket and key prefix, so requests can't write to
+    /// other objects the credentials reach.
+    pub fn check_destination(
+        &self,
+        bucket: &str,
+        key_prefix: &str,
+    ) -> Result<(), ProofServiceError> {
+        if bucket != self.config.bucket {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "S3 bucket {:?} is not allowed; proofs are uploaded to {:?}",
+                bucket, self.config.bucket
+            )));
+        }
+        if !key_prefix.starts_with(&self.config.key_prefix) {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "S3 key_prefix {:?} must start with {:?}",
+                key_prefix, self.config.key_prefix
+            )));
+        }
+        Ok(())
+    }
+
+    /// URL of `key` in `bucket`, as returned by [`S3Store::put_object`].
+    pub fn object_url(&self, bucket: &str, key: &str) -> Result<Url, ProofServiceError> {
+        if bucket.is_empty()
+            || !bucket
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Invalid S3 bucket name: {:?}",
+                bucket
+            )));
+        }
+        // Dot segments would be resolved away by the URL, so the object would land elsewhere
+        if key.is_empty()
+            || key
+                .split('/')
+                .any(|segment| segment == "." || segment == "..")
+        {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Invalid S3 object key: {:?}",
+                key
+            )));
+        }
+        let base = self.config.endpoint.as_str().trim_end_matches('/');
+        Url::parse(&format!("{}/{}/{}", base, bucket, uri_encode(key))).map_err(|e| {
+            ProofServiceError::InvalidInput(format!("Invalid S3 object key {:?}: {}", key, e))
+        })
+    }
+
+    /// Stores `body` as `key` in `bucket`, returning the object's URL.
+    pub async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: Vec<u8>,
+    ) -> Result<Url, ProofServiceError> {
+        let url = self.object_url(bucket, key)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let headers = self.sign_put(&url, &body, now);
+
+        let mut request = self.client.put(url.clone()).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(ProofServiceError::InternalError(format!(
+                "S3 upload to {} failed with {}: {}",
+                url,
+                status,
+                detail.trim()
+            )));
+        }
+        info!(%url, "Uploaded object to S3");
+        Ok(url)
+    }
+
+    // Signature Version 4 headers for a single-chunk PUT of `body` to `url` at unix time `now`
+    fn sign_put(&self, url: &Url, body: &[u8], now: u64) -> Vec<(&'static str, String)> {
+        let (date, timestamp) = amz_timestamp(now);
+        let payload_hash = hex::encode(Sha256::digest(body));
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            url.path(),
+            host,
+            payload_hash,
+            timestamp,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let secret = format!("AWS4{}", self.config.secret_access_key);
+        let mut key = hmac_sha256(secret.as_bytes(), date.as_bytes());
+        for part in [self.config.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        vec![
+            ("x-amz-content-sha256", payload_hash),
+            ("x-amz-date", timestamp),
+            (
+                "authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.config.access_key_id, scope, signed_headers, signature
+                ),
+            ),
+        ]
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// Percent-encodes everything but RFC 3986 unreserved characters and `/`, as Signature Version 4
+// canonical URIs require
+fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// (`YYYYMMDD`, `YYYYMMDDTHHMMSSZ`) of a unix time, in UTC
+pub(crate) fn amz_timestamp(unix_secs: u64) -> (String, String) {
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+    // Civil date of a day count since 1970-01-01 (Howard Hinnant's days_to_civil)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let timestamp = format!(
+        "{}T{:02}{:02}{:02}Z",
+        date,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    );
+    (date, timestamp)
+}
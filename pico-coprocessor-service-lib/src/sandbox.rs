@@ -0,0 +1,173 @@
+// pico-coprocessor-service-lib/src/sandbox.rs
+use crate::errors::ProofServiceError;
+use blueprint_sdk::{debug, warn};
+use rand::Rng;
+use std::path::{Path, PathBuf};
+
+/// Directory name prefix for per-request sandboxes under `temp_dir_base`.
+pub const SANDBOX_PREFIX: &str = "req_";
+
+/// Per-request working directory (`temp_dir_base/req_{proof_id}/`).
+///
+/// Every file a job writes (downloaded ELF, prover outputs) lives beneath it, so concurrent jobs never
+/// share a directory. The whole subtree is removed when the sandbox is dropped.
+#[derive(Debug)]
+pub struct RequestSandbox {
+    proof_id: String,
+    path: PathBuf,
+}
+
+impl RequestSandbox {
+    /// Creates a fresh sandbox under `base_dir`. Fails instead of reusing a directory that already exists.
+    pub fn create(base_dir: &Path) -> Result<Self, ProofServiceError> {
+        let proof_id = new_proof_id();
+        let path = base_dir.join(format!("{}{}", SANDBOX_PREFIX, proof_id));
+        std::fs::create_dir(&path).map_err(|e| {
+            ProofServiceError::TempDirError(format!(
+                "Failed to create request sandbox {:?}: {}",
+                path, e
+            ))
+        })?;
+        debug!(%proof_id, path = ?path, "Created request sandbox");
+        Ok(Self { proof_id, path })
+    }
+
+    pub fn proof_id(&self) -> &str {
+        &self.proof_id
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RequestSandbox {
+    fn drop(&mut self) {
+        match std::fs::remove_dir_all(&self.path) {
+            Ok(()) => debug!(proof_id = %self.proof_id, "Removed request sandbox"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!(path = ?self.path, "Failed to remove request sandbox: {}", e),
+        }
+    }
+}
+
+/// Directory a request's sandbox goes under: the caller's `temp_dir_override` when set, otherwise
+/// `temp_dir_base`.
+///
+/// Overrides are refused unless the operator allows some directories (`allowed_temp_dirs`), and
+/// must then be one of them or beneath one, after resolving symlinks and `..`. The override must
+/// also be an existing directory the service can write to; it is probed the way `temp_dir_base`
+/// is at startup, so a bad path fails the request before any work is done.
+pub fn sandbox_base(
+    temp_dir_base: &Path,
+    allowed_temp_dirs: &[PathBuf],
+    temp_dir_override: Option<&Path>,
+) -> Result<PathBuf, ProofServiceError> {
+    let Some(dir) = temp_dir_override else {
+        return Ok(temp_dir_base.to_path_buf());
+    };
+    if allowed_temp_dirs.is_empty() {
+        return Err(ProofServiceError::InvalidInput(
+            "temp_dir_override requires the service to configure allowed_temp_dirs".to_string(),
+        ));
+    }
+    if !dir.is_dir() {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "temp_dir_override {:?} is not a directory",
+            dir
+        )));
+    }
+    let resolved = dir.canonicalize().map_err(|e| {
+        ProofServiceError::InvalidInput(format!(
+            "temp_dir_override {:?} can't be resolved: {}",
+            dir, e
+        ))
+    })?;
+    let allowed = allowed_temp_dirs.iter().any(|root| {
+        root.canonicalize()
+            .is_ok_and(|root| resolved.starts_with(root))
+    });
+    if !allowed {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "temp_dir_override {:?} is outside the service's allowed_temp_dirs",
+            dir
+        )));
+    }
+    tempfile::NamedTempFile::new_in(&resolved).map_err(|e| {
+        ProofServiceError::InvalidInput(format!(
+            "temp_dir_override {:?} is not writable: {}",
+            dir, e
+        ))
+    })?;
+    Ok(resolved)
+}
+
+/// Path a request's `dump_inputs_to` file is written to: the bare file name inside the
+/// operator's `dump_inputs_dir`.
+///
+/// Dumps are refused unless the service configures the directory. The name may not contain a path
+/// separator or be `.`/`..`, and may not name a file that already exists, so a request can neither
+/// escape the directory nor overwrite an earlier dump.
+pub fn dump_path(dump_dir: Option<&Path>, file_name: &str) -> Result<PathBuf, ProofServiceError> {
+    let Some(dir) = dump_dir else {
+        return Err(ProofServiceError::InvalidInput(
+            "dump_inputs_to requires the service to configure dump_inputs_dir".to_string(),
+        ));
+    };
+    let is_bare_name = !file_name.is_empty()
+        && file_name != "."
+        && file_name != ".."
+        && !file_name.contains(['/', '\\', '\0'])
+        && Path::new(file_name).file_name() == Some(file_name.as_ref());
+    if !is_bare_name {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "dump_inputs_to {:?} must be a bare file name",
+            file_name
+        )));
+    }
+    let path = dir.join(file_name);
+    if path.symlink_metadata().is_ok() {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "dump_inputs_to {:?} already exists",
+            file_name
+        )));
+    }
+    Ok(path)
+}
+
+/// Removes every request sandbox under `base_dir`, returning how many were removed.
+///
+/// Only meant for shutdown, once no job can still be using its sandbox.
+pub fn remove_all_sandboxes(base_dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(base_dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let is_sandbox = entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(SANDBOX_PREFIX);
+        if is_sandbox && entry.path().is_dir() {
+            match std::fs::remove_dir_all(entry.path()) {
+                Ok(()) => removed += 1,
+                Err(e) => warn!(path = ?entry.path(), "Failed to remove request sandbox: {}", e),
+            }
+        }
+    }
+    removed
+}
+
+/// Generates an identifier for a proof request (millisecond timestamp plus a random suffix).
+pub fn new_proof_id() -> String {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let random_suffix: String = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(8)
+        .map(char::from)
+        .collect();
+    format!("{}_{}", timestamp, random_suffix.to_lowercase())
+}
@@ -0,0 +1,197 @@
+// pico-coprocessor-service-lib/src/service.rs
+use crate::{
+    CHECK_PROGRAM_JOB_ID, DESCRIBE_PROGRAM_JOB_ID, GENERATE_COPROCESSOR_PROOF_JOB_ID,
+    GENERATE_PROOF_JOB_ID, GENERATE_PROOF_MULTI_JOB_ID, GENERATE_PROOF_OR_DEFAULT_JOB_ID,
+    LIST_PROGRAMS_JOB_ID, PROVE_JOB_ID, RESUME_PROOF_JOB_ID, VERIFY_PROOF_JOB_ID,
+    WARM_PROGRAM_JOB_ID,
+    context::{ServiceContext, ServiceMode},
+    errors::{JobError, ProofServiceError},
+    jobs::{
+        check_program_matches_registry, describe_program, generate_coprocessor_proof,
+        generate_proof, generate_proof_multi, generate_proof_or_default, list_programs, prove,
+        resume_proof, verify_proof, warm_program,
+    },
+    types::{
+        AnyProofRequest, CheckProgramRequest, CheckProgramResult, CoprocessorProofRequest,
+        DescribeProgramRequest, MultiProofRequest, ProgramEntry, ProgramSchema, ProofRequest,
+        ProofResult, ResumeProofRequest, VerifyProofRequest, WarmProgramRequest, WarmProgramResult,
+    },
+};
+use blueprint_sdk::{
+    Job, Router,
+    extract::Context,
+    tangle::{
+        extract::{Optional, TangleArg, TangleResult},
+        layers::TangleLayer,
+    },
+};
+
+/// Registers the service's jobs on a [`Router`], each under its job ID exactly once.
+///
+/// Verification, registry queries and program checks are registered in every mode; proving jobs
+/// and program warming only for [`ServiceMode::ProveAndVerify`]. Every ID registered through the builder is remembered, so a
+/// second route for the same ID fails with a [`ProofServiceError::ConfigError`] at startup
+/// instead of silently replacing the first.
+#[derive(Debug, Clone)]
+pub struct ProofServiceBuilder {
+    mode: ServiceMode,
+    job_ids: Vec<u32>,
+}
+
+impl ProofServiceBuilder {
+    pub fn new(mode: ServiceMode) -> Self {
+        Self {
+            mode,
+            job_ids: Vec::new(),
+        }
+    }
+
+    /// Job IDs registered so far, in registration order.
+    pub fn job_ids(&self) -> &[u32] {
+        &self.job_ids
+    }
+
+    /// Reserves `job_id` for a route, failing if it was registered before.
+    pub fn claim(&mut self, job_id: u32) -> Result<(), ProofServiceError> {
+        if self.job_ids.contains(&job_id) {
+            return Err(ProofServiceError::ConfigError(format!(
+                "Job ID {} registered twice",
+                job_id
+            )));
+        }
+        self.job_ids.push(job_id);
+        Ok(())
+    }
+
+    /// Adds a route for each of the mode's jobs to `router`.
+    pub fn register_jobs(
+        &mut self,
+        router: Router<ServiceContext>,
+    ) -> Result<Router<ServiceContext>, ProofServiceError> {
+        self.claim(VERIFY_PROOF_JOB_ID)?;
+        self.claim(LIST_PROGRAMS_JOB_ID)?;
+        self.claim(DESCRIBE_PROGRAM_JOB_ID)?;
+        self.claim(CHECK_PROGRAM_JOB_ID)?;
+        let mut router = router
+            .route(VERIFY_PROOF_JOB_ID, verify_proof_job.layer(TangleLayer))
+            .route(LIST_PROGRAMS_JOB_ID, list_programs_job.layer(TangleLayer))
+            .route(
+                DESCRIBE_PROGRAM_JOB_ID,
+                describe_program_job.layer(TangleLayer),
+            )
+            .route(CHECK_PROGRAM_JOB_ID, check_program_job.layer(TangleLayer));
+        if self.mode != ServiceMode::ProveAndVerify {
+            return Ok(router);
+        }
+
+        self.claim(GENERATE_PROOF_JOB_ID)?;
+        router = router.route(GENERATE_PROOF_JOB_ID, generate_proof_job.layer(TangleLayer));
+        self.claim(RESUME_PROOF_JOB_ID)?;
+        router = router.route(RESUME_PROOF_JOB_ID, resume_proof_job.layer(TangleLayer));
+        self.claim(GENERATE_COPROCESSOR_PROOF_JOB_ID)?;
+        router = router.route(
+            GENERATE_COPROCESSOR_PROOF_JOB_ID,
+            generate_coprocessor_proof_job.layer(TangleLayer),
+        );
+        self.claim(PROVE_JOB_ID)?;
+        router = router.route(PROVE_JOB_ID, prove_job.layer(TangleLayer));
+        self.claim(GENERATE_PROOF_OR_DEFAULT_JOB_ID)?;
+        router = router.route(
+            GENERATE_PROOF_OR_DEFAULT_JOB_ID,
+            generate_proof_or_default_job.layer(TangleLayer),
+        );
+        self.claim(GENERATE_PROOF_MULTI_JOB_ID)?;
+        router = router.route(
+            GENERATE_PROOF_MULTI_JOB_ID,
+            generate_proof_multi_job.layer(TangleLayer),
+        );
+        self.claim(WARM_PROGRAM_JOB_ID)?;
+        router = router.route(WARM_PROGRAM_JOB_ID, warm_program_job.layer(TangleLayer));
+        Ok(router)
+    }
+}
+
+// Route adapters: every job reports failures to the chain as a JobError with its stable code
+async fn generate_proof_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<ProofRequest>,
+) -> Result<TangleResult<ProofResult>, JobError> {
+    generate_proof(ctx, request).await.map_err(JobError::from)
+}
+
+async fn generate_coprocessor_proof_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<CoprocessorProofRequest>,
+) -> Result<TangleResult<ProofResult>, JobError> {
+    generate_coprocessor_proof(ctx, request)
+        .await
+        .map_err(JobError::from)
+}
+
+async fn generate_proof_or_default_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<Optional<ProofRequest>>,
+) -> Result<TangleResult<ProofResult>, JobError> {
+    generate_proof_or_default(ctx, request)
+        .await
+        .map_err(JobError::from)
+}
+
+async fn generate_proof_multi_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<MultiProofRequest>,
+) -> Result<TangleResult<Vec<ProofResult>>, JobError> {
+    generate_proof_multi(ctx, request)
+        .await
+        .map_err(JobError::from)
+}
+
+async fn prove_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<AnyProofRequest>,
+) -> Result<TangleResult<ProofResult>, JobError> {
+    prove(ctx, request).await.map_err(JobError::from)
+}
+
+async fn verify_proof_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<VerifyProofRequest>,
+) -> Result<TangleResult<bool>, JobError> {
+    verify_proof(ctx, request).await.map_err(JobError::from)
+}
+
+async fn list_programs_job(
+    ctx: Context<ServiceContext>,
+) -> Result<TangleResult<Vec<ProgramEntry>>, JobError> {
+    list_programs(ctx).await.map_err(JobError::from)
+}
+
+async fn describe_program_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<DescribeProgramRequest>,
+) -> Result<TangleResult<ProgramSchema>, JobError> {
+    describe_program(ctx, request).await.map_err(JobError::from)
+}
+
+async fn warm_program_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<WarmProgramRequest>,
+) -> Result<TangleResult<WarmProgramResult>, JobError> {
+    warm_program(ctx, request).await.map_err(JobError::from)
+}
+
+async fn check_program_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<CheckProgramRequest>,
+) -> Result<TangleResult<CheckProgramResult>, JobError> {
+    check_program_matches_registry(ctx, request)
+        .await
+        .map_err(JobError::from)
+}
+
+async fn resume_proof_job(
+    ctx: Context<ServiceContext>,
+    request: TangleArg<ResumeProofRequest>,
+) -> Result<TangleResult<ProofResult>, JobError> {
+    resume_proof(ctx, request).await.map_err(JobError::from)
+}
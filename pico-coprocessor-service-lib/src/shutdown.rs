@@ -0,0 +1,127 @@
+// pico-coprocessor-service-lib/src/shutdown.rs
+use crate::context::ServiceContext;
+use crate::errors::ProofServiceError;
+use crate::sandbox;
+use blueprint_sdk::{info, warn};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Default time `graceful_shutdown` waits for in-flight proofs.
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(300);
+
+/// Counts proving jobs in progress and stops admitting new ones once closed.
+#[derive(Debug, Clone, Default)]
+pub struct InFlightTracker {
+    inner: Arc<TrackerState>,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    count: AtomicUsize,
+    closed: AtomicBool,
+    idle: Notify,
+}
+
+/// Marks one job as in flight until dropped.
+#[derive(Debug)]
+pub struct InFlightGuard {
+    inner: Arc<TrackerState>,
+}
+
+impl InFlightTracker {
+    /// Registers a new job, or fails with `ShuttingDown` once the tracker is closed.
+    pub fn begin(&self) -> Result<InFlightGuard, ProofServiceError> {
+        self.inner.count.fetch_add(1, Ordering::SeqCst);
+        // Checked after incrementing so `drain` can't miss a job admitted concurrently with `close`
+        if self.inner.closed.load(Ordering::SeqCst) {
+            release(&self.inner);
+            return Err(ProofServiceError::ShuttingDown);
+        }
+        Ok(InFlightGuard {
+            inner: self.inner.clone(),
+        })
+    }
+
+    /// Number of jobs currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.inner.count.load(Ordering::SeqCst)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Stops admitting new jobs.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Closes the tracker and waits up to `grace` for in-flight jobs to finish.
+    ///
+    /// Returns `true` if every job finished in time.
+    pub async fn drain(&self, grace: Duration) -> bool {
+        self.close();
+        let wait_idle = async {
+            loop {
+                let idle = self.inner.idle.notified();
+                tokio::pin!(idle);
+                idle.as_mut().enable();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+        tokio::time::timeout(grace, wait_idle).await.is_ok()
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        release(&self.inner);
+    }
+}
+
+fn release(state: &TrackerState) {
+    if state.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+        state.idle.notify_waiters();
+    }
+}
+
+/// Outcome of `graceful_shutdown`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Jobs still running when the grace period ran out.
+    pub abandoned: usize,
+    /// Request sandboxes removed from `temp_dir_base` afterwards.
+    pub removed_sandboxes: usize,
+}
+
+/// Stops admitting jobs, waits up to `grace` for in-flight proofs, then removes leftover request
+/// sandboxes.
+pub async fn graceful_shutdown(ctx: &ServiceContext, grace: Duration) -> ShutdownReport {
+    info!(
+        in_flight = ctx.in_flight.in_flight(),
+        ?grace,
+        "Shutting down, waiting for in-flight proofs"
+    );
+    let abandoned = if ctx.in_flight.drain(grace).await {
+        0
+    } else {
+        let abandoned = ctx.in_flight.in_flight();
+        warn!(
+            abandoned,
+            "Grace period elapsed with proofs still in flight"
+        );
+        abandoned
+    };
+
+    let removed_sandboxes = sandbox::remove_all_sandboxes(&ctx.temp_dir_base);
+    info!(abandoned, removed_sandboxes, "Shutdown complete");
+    ShutdownReport {
+        abandoned,
+        removed_sandboxes,
+    }
+}
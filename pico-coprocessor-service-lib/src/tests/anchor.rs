@@ -0,0 +1,311 @@
+use super::mock::{revert, start_mock_rpc};
+use crate::{
+    CoprocessorProofRequest, MaxSizes, ProgramLocation, ProofRequest, ProofResult,
+    ProofServiceError, ProveOptions, ProverBackend, ProvingType, RegistryRef, ServiceContext,
+    evm::ProgramRegistry, generate_coprocessor_proof, generate_proof,
+    jobs::coprocessor::build_input_bundle,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::alloy::sol_types::{SolError, SolValue};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use futures::future::BoxFuture;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use url::Url;
+
+const ANCHOR_NUMBER: u64 = 0x1234;
+
+fn context_for(rpc_url: Url) -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(rpc_url, Address::ZERO, temp_base)
+        .expect("Failed to create test ServiceContext")
+}
+
+fn anchored_request(anchor_hash: B256) -> CoprocessorProofRequest {
+    CoprocessorProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        anchor_block_hash: Some(anchor_hash),
+        anchor_block_number: Some(ANCHOR_NUMBER),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_anchor_is_serialized_into_bundle() {
+    let ctx = context_for(Url::parse("http://localhost:8545").unwrap());
+    let anchor_hash = B256::repeat_byte(0xab);
+
+    let bundle = build_input_bundle(&ctx, &anchored_request(anchor_hash)).unwrap();
+    let encoded: serde_json::Value = serde_json::to_value(&bundle).unwrap();
+    assert_eq!(encoded["anchor_block_hash"], json!(anchor_hash.to_string()));
+    assert_eq!(encoded["anchor_block_number"], json!(ANCHOR_NUMBER));
+
+    // Unanchored bundles keep their previous encoding
+    let unanchored = build_input_bundle(&ctx, &CoprocessorProofRequest::default()).unwrap();
+    let encoded = serde_json::to_value(&unanchored).unwrap();
+    assert!(encoded.get("anchor_block_hash").is_none());
+}
+
+#[tokio::test]
+async fn test_anchor_hash_mismatch_is_rejected() {
+    let chain_hash = B256::repeat_byte(0x01);
+    let rpc = start_mock_rpc(move |method, params| match method {
+        "eth_getBlockByNumber" if params[0] == json!(format!("0x{:x}", ANCHOR_NUMBER)) => {
+            Ok(json!({ "number": params[0], "hash": chain_hash.to_string() }))
+        }
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+
+    let mut request = anchored_request(B256::repeat_byte(0x02));
+    request.verify_anchor = true;
+    let result =
+        generate_coprocessor_proof(Context(context_for(rpc.url())), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::ChainDataMismatch(msg) if msg.contains("Anchor mismatch"))
+    );
+    assert_eq!(rpc.hits(), 1);
+}
+
+#[tokio::test]
+async fn test_anchor_is_checked_on_the_rpc_override() {
+    let chain_hash = B256::repeat_byte(0x01);
+    let service_rpc =
+        start_mock_rpc(|method, _params| Err(format!("unexpected method {}", method))).await;
+    let override_rpc = start_mock_rpc(move |method, params| match method {
+        "eth_getBlockByNumber" => {
+            Ok(json!({ "number": params[0], "hash": chain_hash.to_string() }))
+        }
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+
+    let mut request = anchored_request(B256::repeat_byte(0x02));
+    request.verify_anchor = true;
+    request.eth_rpc_url_override = Some(override_rpc.url().to_string());
+    let result =
+        generate_coprocessor_proof(Context(context_for(service_rpc.url())), TangleArg(request))
+            .await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::ChainDataMismatch(msg) if msg.contains("Anchor mismatch"))
+    );
+    assert_eq!(override_rpc.hits(), 1);
+    assert_eq!(service_rpc.hits(), 0);
+}
+
+#[tokio::test]
+async fn test_verify_anchor_requires_hash_and_number() {
+    let mut request = anchored_request(B256::repeat_byte(0x02));
+    request.anchor_block_number = None;
+    request.verify_anchor = true;
+
+    let ctx = context_for(Url::parse("http://localhost:8545").unwrap());
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::InvalidInput(_)
+    ));
+}
+
+// Answers every proof without running the VM
+struct StubProver;
+
+impl ProverBackend for StubProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            Ok(ProofResult {
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                inputs: inputs_hex.to_string(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_result_records_effective_chain_and_registry() {
+    let chain = |chain_id: u64| {
+        start_mock_rpc(move |method, _params| match method {
+            "eth_chainId" => Ok(json!(format!("0x{:x}", chain_id))),
+            other => Err(format!("unexpected method {}", other)),
+        })
+    };
+    let service_rpc = chain(1).await;
+    let override_rpc = chain(8453).await;
+    let service_registry = Address::repeat_byte(0x11);
+    let override_registry = Address::repeat_byte(0x22);
+
+    let temp_base = tempdir().unwrap();
+    let mut ctx = ServiceContext::new(
+        service_rpc.url(),
+        service_registry,
+        temp_base.path().join("service"),
+    )
+    .unwrap();
+    ctx.prover_backend = Arc::new(StubProver);
+    let elf_path = temp_base.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+    let program_hash = hex::encode(Sha256::digest(b"\x7fELF"));
+    let location = ProgramLocation::LocalPath(elf_path);
+
+    // Without overrides the result names the service's chain; the program's location was given,
+    // so no registry was consulted
+    let request = ProofRequest {
+        program_hash: program_hash.clone(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location.clone()),
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+    assert_eq!(result.chain_id, Some(1));
+    assert_eq!(result.registry_address, None);
+
+    // Per-request overrides are what the result records
+    let request = ProofRequest {
+        eth_rpc_url_override: Some(override_rpc.url().to_string()),
+        registry_address_override: Some(RegistryRef::Address(override_registry)),
+        ..request
+    };
+    let TangleResult(result) = generate_proof(Context(ctx.clone()), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.chain_id, Some(8453));
+    assert_eq!(result.registry_address, None);
+
+    let request = CoprocessorProofRequest {
+        program_hash,
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        eth_rpc_url_override: Some(override_rpc.url().to_string()),
+        registry_address_override: Some(RegistryRef::Address(override_registry)),
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.chain_id, Some(8453));
+    assert_eq!(result.registry_address, None);
+
+    // Each endpoint's chain is asked for once, whichever job records it
+    assert_eq!(service_rpc.hits(), 1);
+    assert_eq!(override_rpc.hits(), 1);
+    assert_eq!(ctx.chain_ids.get(&service_rpc.url()), Some(1));
+}
+
+#[tokio::test]
+async fn test_result_records_the_registry_that_resolved_the_program() {
+    const PRIMARY: Address = Address::repeat_byte(0x11);
+    const FALLBACK: Address = Address::repeat_byte(0x12);
+    let elf = b"\x7fELF served by the fallback registry";
+    let program_hash = B256::from_slice(&Sha256::digest(elf));
+    let location = format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(elf)
+    );
+    let rpc = start_mock_rpc(move |method, params| match method {
+        "eth_chainId" => Ok(json!("0x1")),
+        "eth_call" => {
+            let to: Address = params[0]["to"]
+                .as_str()
+                .unwrap_or_default()
+                .parse()
+                .unwrap();
+            match to {
+                PRIMARY => Err(revert(
+                    &ProgramRegistry::ProgramRegistry__ProgramNotFound {}.abi_encode(),
+                )),
+                FALLBACK => Ok(json!(format!(
+                    "0x{}",
+                    hex::encode((program_hash, location.clone()).abi_encode_params())
+                ))),
+                other => Err(format!("unexpected call to {}", other)),
+            }
+        }
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let temp_base = tempdir().unwrap();
+    let mut ctx = ServiceContext::new(rpc.url(), PRIMARY, temp_base.path().to_path_buf()).unwrap();
+    ctx.prover_backend = Arc::new(StubProver);
+    ctx.fallback_registries = vec![RegistryRef::Address(FALLBACK)];
+
+    let request = ProofRequest {
+        program_hash: program_hash.to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.registry_address, Some(FALLBACK));
+}
+
+#[tokio::test]
+async fn test_registry_lookup_uses_the_rpc_override() {
+    let elf = b"\x7fELF from the override chain";
+    let program_hash = B256::from_slice(&Sha256::digest(elf));
+    let location = format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(elf)
+    );
+    let service_rpc =
+        start_mock_rpc(|method, _params| Err(format!("unexpected method {}", method))).await;
+    let override_rpc = start_mock_rpc(move |method, _params| match method {
+        "eth_call" => Ok(json!(format!(
+            "0x{}",
+            hex::encode((program_hash, location.clone()).abi_encode_params())
+        ))),
+        "eth_chainId" => Ok(json!("0x2105")),
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let mut ctx = context_for(service_rpc.url());
+    ctx.prover_backend = Arc::new(StubProver);
+
+    let request = ProofRequest {
+        program_hash: program_hash.to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        eth_rpc_url_override: Some(override_rpc.url().to_string()),
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.chain_id, Some(8453));
+    assert_eq!(override_rpc.hits(), 2);
+    assert_eq!(service_rpc.hits(), 0);
+}
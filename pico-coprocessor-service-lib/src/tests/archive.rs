@@ -0,0 +1,259 @@
+use super::mock::{MockResponse, MockServer};
+use crate::{
+    ARCHIVE_EVM_DIR, ARCHIVE_MANIFEST, BLOCKCHAIN_DATA_ENTRY, BlockchainData, BlockchainDataSource,
+    CoprocessorProofRequest, InputsEncoding, ProofResult, ProofServiceError, ProvingType,
+    ServiceContext, context::DEFAULT_MAX_INPUT_BYTES, fixtures::CoprocessorFixtureBuilder,
+    generate_coprocessor_proof, unpack_blockchain_data,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256, U256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use flate2::{Compression, write::GzEncoder};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use tempfile::tempdir;
+use url::Url;
+
+fn archived_result(output_dir: Option<String>) -> ProofResult {
+    ProofResult {
+        public_values: hex::encode([7u8; 32]),
+        proof: hex::encode(b"proof bytes"),
+        proving_type: ProvingType::FullWithEvm,
+        output_dir,
+        program_hash: hex::encode([0xabu8; 32]),
+        inputs: hex::encode(br#"{"receipts":[]}"#),
+        inputs_encoding: InputsEncoding::HexOfJson,
+        sdk_init_params: Some((1, 0, 0)),
+        prover_version: "1.1.0".to_string(),
+        ..Default::default()
+    }
+}
+
+fn archive_entries(path: &std::path::Path) -> Vec<(String, Vec<u8>)> {
+    let mut archive = tar::Archive::new(std::fs::File::open(path).unwrap());
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).unwrap();
+            (name, contents)
+        })
+        .collect()
+}
+
+#[test]
+fn test_archive_round_trips_result_and_evm_artifacts() {
+    let dir = tempdir().unwrap();
+    let evm_dir = dir.path().join("evm_out");
+    std::fs::create_dir(&evm_dir).unwrap();
+    std::fs::write(evm_dir.join("proof.data"), b"groth16").unwrap();
+    std::fs::write(evm_dir.join("pv_file"), b"0x07").unwrap();
+    let result = archived_result(Some(evm_dir.to_string_lossy().into_owned()));
+
+    let path = dir.path().join("proof.tar");
+    result.to_archive(&path).unwrap();
+    let reloaded = ProofResult::from_archive(&path, DEFAULT_MAX_INPUT_BYTES).unwrap();
+
+    assert_eq!(reloaded.proof, result.proof);
+    assert_eq!(reloaded.public_values, result.public_values);
+    assert_eq!(reloaded.program_hash, result.program_hash);
+    assert_eq!(reloaded.inputs, result.inputs);
+    assert_eq!(reloaded.proving_type, result.proving_type);
+    assert_eq!(reloaded.prover_version, result.prover_version);
+    assert_eq!(
+        serde_json::to_value(&reloaded).unwrap(),
+        serde_json::to_value(&result).unwrap()
+    );
+
+    // The artifacts travel with the manifest, independent of the original output_dir
+    std::fs::remove_dir_all(&evm_dir).unwrap();
+    let entries = archive_entries(&path);
+    let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, [
+        ARCHIVE_MANIFEST.to_string(),
+        format!("{}/proof.data", ARCHIVE_EVM_DIR),
+        format!("{}/pv_file", ARCHIVE_EVM_DIR),
+    ]);
+    assert_eq!(entries[1].1, b"groth16");
+}
+
+#[test]
+fn test_archive_without_manifest_is_rejected() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("empty.tar");
+    tar::Builder::new(std::fs::File::create(&path).unwrap())
+        .into_inner()
+        .unwrap();
+
+    assert!(matches!(
+        ProofResult::from_archive(&path, DEFAULT_MAX_INPUT_BYTES),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains(ARCHIVE_MANIFEST)
+    ));
+    // Results without EVM artifacts archive just the manifest
+    let path = dir.path().join("fast.tar");
+    archived_result(None).to_archive(&path).unwrap();
+    assert_eq!(archive_entries(&path).len(), 1);
+}
+
+// A tar archive of `data` as published by a client, gzip-compressed when `gzip` is set
+fn blockchain_data_archive(data: &BlockchainData, gzip: bool) -> Vec<u8> {
+    let json = serde_json::to_vec(data).unwrap();
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, BLOCKCHAIN_DATA_ENTRY, json.as_slice())
+        .unwrap();
+    let tar = builder.into_inner().unwrap();
+    if !gzip {
+        return tar;
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+#[test]
+fn test_blockchain_data_unpacked_from_plain_and_gzipped_archives() {
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(3).build();
+    for gzip in [false, true] {
+        let archive = blockchain_data_archive(&fixture.blockchain_data, gzip);
+        assert_eq!(
+            unpack_blockchain_data(&archive, DEFAULT_MAX_INPUT_BYTES).unwrap(),
+            fixture.blockchain_data
+        );
+    }
+
+    let empty = tar::Builder::new(Vec::new()).into_inner().unwrap();
+    assert!(matches!(
+        unpack_blockchain_data(&empty, DEFAULT_MAX_INPUT_BYTES),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains(BLOCKCHAIN_DATA_ENTRY)
+    ));
+}
+
+#[test]
+fn test_archive_entries_expanding_past_the_limit_are_refused() {
+    // Zeros compress to almost nothing, so the gzipped archive is far smaller than its entry
+    let padded = BlockchainData {
+        receipts: Some(Vec::new()),
+        ..Default::default()
+    };
+    let mut json = serde_json::to_vec(&padded).unwrap();
+    json.extend(std::iter::repeat_n(b' ', 1 << 20));
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, BLOCKCHAIN_DATA_ENTRY, json.as_slice())
+        .unwrap();
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&builder.into_inner().unwrap()).unwrap();
+    let archive = encoder.finish().unwrap();
+    assert!(archive.len() < 64 * 1024);
+
+    assert!(matches!(
+        unpack_blockchain_data(&archive, 64 * 1024),
+        Err(ProofServiceError::InputsTooLarge { size, limit: 65536 }) if size == json.len()
+    ));
+    assert_eq!(
+        unpack_blockchain_data(&archive, json.len()).unwrap(),
+        padded
+    );
+
+    // The manifest of a proof archive is bounded the same way
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("proof.tar");
+    archived_result(None).to_archive(&path).unwrap();
+    assert!(matches!(
+        ProofResult::from_archive(&path, 16),
+        Err(ProofServiceError::InputsTooLarge { limit: 16, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_archived_blockchain_data_proves_to_expected_volume() {
+    let ctx = setup_test_context();
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let elf_bytes = std::fs::read(&elf_path)
+        .expect("Failed to read test ELF file at tests/fixtures/trading_volume.elf");
+    let program_hash = hex::encode(Sha256::digest(&elf_bytes));
+
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(2).build();
+    let archive = blockchain_data_archive(&fixture.blockchain_data, true);
+    let archive_hash = B256::from_slice(&Sha256::digest(&archive));
+    let server = MockServer::start(move |_request| MockResponse::ok(archive.clone())).await;
+
+    let mut request = fixture.request(program_hash, elf_path);
+    request.blockchain_data = BlockchainData::default();
+    request.blockchain_data_source = Some(BlockchainDataSource::Archive(
+        server.url_for("inputs.tar.gz"),
+    ));
+    request.blockchain_data_hash = Some(archive_hash);
+
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request))
+        .await
+        .expect("Job failed")
+        .0;
+    assert_eq!(
+        U256::from_be_slice(&hex::decode(&result.public_values).unwrap()),
+        fixture.expected_volume
+    );
+    // The proof commits to the unpacked data exactly as if it had been sent inline
+    assert_eq!(result.inputs, fixture.inputs_hex());
+}
+
+#[tokio::test]
+async fn test_archived_blockchain_data_with_wrong_hash_is_refused() {
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(2).build();
+    let archive = blockchain_data_archive(&fixture.blockchain_data, false);
+    let server = MockServer::start(move |_request| MockResponse::ok(archive.clone())).await;
+    let request = |hash: Option<B256>| CoprocessorProofRequest {
+        program_hash: B256::repeat_byte(0x01).to_string(),
+        blockchain_data_source: Some(BlockchainDataSource::Archive(server.url_for("inputs.tar"))),
+        blockchain_data_hash: hash,
+        max_sizes: fixture.max_sizes.clone(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+
+    let result = generate_coprocessor_proof(
+        Context(setup_test_context()),
+        TangleArg(request(Some(B256::repeat_byte(0xee)))),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("has hash")
+    ));
+
+    let result =
+        generate_coprocessor_proof(Context(setup_test_context()), TangleArg(request(None))).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("requires blockchain_data_hash")
+    ));
+    // Without a hash there's nothing to check the archive against, so it isn't downloaded
+    assert_eq!(server.hits(), 1);
+}
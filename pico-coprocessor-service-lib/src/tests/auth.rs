@@ -0,0 +1,130 @@
+use crate::{
+    ProofRequest, ProofServiceError, ProvingType, RequestSignature, ServiceContext, authenticate,
+    generate_proof, sign_request, to_canonical_json,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use std::collections::HashSet;
+use tempfile::tempdir;
+use url::Url;
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn auth_context(authorized: &[&SigningKey]) -> ServiceContext {
+    let mut ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        tempdir().unwrap().into_path(),
+    )
+    .unwrap();
+    ctx.authorized_keys = Some(
+        authorized
+            .iter()
+            .map(|key| key.verifying_key().to_bytes())
+            .collect::<HashSet<_>>(),
+    );
+    ctx
+}
+
+fn request() -> ProofRequest {
+    ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    }
+}
+
+fn assert_unauthorized(result: Result<Option<String>, ProofServiceError>, expected: &str) {
+    match result {
+        Err(ProofServiceError::Unauthorized(msg)) => assert_eq!(msg, expected),
+        other => panic!("Expected Unauthorized({}), got {:?}", expected, other),
+    }
+}
+
+#[test]
+fn test_authentication_disabled_without_authorized_keys() {
+    let mut ctx = auth_context(&[]);
+    ctx.authorized_keys = None;
+    assert_eq!(authenticate(&ctx, &request()).unwrap(), None);
+}
+
+#[test]
+fn test_valid_signature_is_accepted() {
+    let key = signing_key(1);
+    let ctx = auth_context(&[&key]);
+    let signed = sign_request(request(), &key).unwrap();
+
+    let caller = authenticate(&ctx, &signed).unwrap();
+    assert_eq!(caller, Some(hex::encode(key.verifying_key().as_bytes())));
+}
+
+#[test]
+fn test_signature_covers_the_canonical_encoding() {
+    let key = signing_key(1);
+    let ctx = auth_context(&[&key]);
+
+    // A client in another language signs the unsigned request's sorted-key compact JSON
+    let unsigned = request();
+    let payload = to_canonical_json(&serde_json::to_value(&unsigned).unwrap()).unwrap();
+    let text = String::from_utf8(payload.clone()).unwrap();
+    assert!(text.starts_with("{\"") && !text.contains(' ') && !text.contains("signature"));
+    let signed = ProofRequest {
+        signature: Some(RequestSignature {
+            public_key: hex::encode(key.verifying_key().as_bytes()),
+            signature: hex::encode(key.sign(&payload).to_bytes()),
+        }),
+        ..unsigned
+    };
+
+    let caller = authenticate(&ctx, &signed).unwrap();
+    assert_eq!(caller, Some(hex::encode(key.verifying_key().as_bytes())));
+}
+
+#[test]
+fn test_invalid_signatures_are_rejected() {
+    let key = signing_key(1);
+    let ctx = auth_context(&[&key]);
+
+    // Request altered after signing
+    let mut tampered = sign_request(request(), &key).unwrap();
+    tampered.inputs = "01".to_string();
+    assert_unauthorized(authenticate(&ctx, &tampered), "invalid signature");
+
+    // Valid signature from a key that isn't allow-listed
+    let outsider = sign_request(request(), &signing_key(2)).unwrap();
+    assert_unauthorized(authenticate(&ctx, &outsider), "signer is not authorized");
+
+    // Garbage signature bytes
+    let mut garbled = sign_request(request(), &key).unwrap();
+    garbled.signature = Some(RequestSignature {
+        signature: "zz".to_string(),
+        ..garbled.signature.unwrap()
+    });
+    assert_unauthorized(authenticate(&ctx, &garbled), "malformed signature");
+}
+
+#[test]
+fn test_missing_signature_is_rejected() {
+    let key = signing_key(1);
+    let ctx = auth_context(&[&key]);
+    assert_unauthorized(authenticate(&ctx, &request()), "request is not signed");
+}
+
+#[tokio::test]
+async fn test_generate_proof_rejects_unsigned_request() {
+    let key = signing_key(1);
+    let ctx = auth_context(&[&key]);
+
+    let result = generate_proof(Context(ctx), TangleArg(request())).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::Unauthorized(_)
+    ));
+}
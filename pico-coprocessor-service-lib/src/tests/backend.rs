@@ -0,0 +1,389 @@
+use super::mock::{MockResponse, MockServer};
+use crate::{
+    PROVER_WORKER_ENV, ProgramLocation, ProofRequest, ProofResult, ProofServiceError, ProveOptions,
+    ProverBackend, ProverWorkerRequest, ProverWorkerResponse, ProvingType, RemoteProveRequest,
+    RemoteProver, ServiceContext, SubprocessProver, generate_proof,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::{
+    alloy::primitives::Address,
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+// What the fake provers answer with, shaped like a Fast result
+fn fixture_result() -> ProofResult {
+    ProofResult {
+        public_values: hex::encode([0u8, 0, 0, 42]),
+        proof: hex::encode(br#"{"commitments":[1,2,3]}"#),
+        proving_type: ProvingType::Fast,
+        inputs: "0102".to_string(),
+        ..Default::default()
+    }
+}
+
+// Remote prover recording each request body and answering with `fixture_result` as a Full
+// proof; a Fast one would be verified against the ELF, which none of these fakes is
+async fn mock_remote_prover() -> (MockServer, Arc<Mutex<Vec<RemoteProveRequest>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+    let server = MockServer::start(move |request| {
+        log.lock()
+            .unwrap()
+            .push(serde_json::from_slice(&request.body).unwrap());
+        let result = ProofResult {
+            proving_type: ProvingType::Full,
+            ..fixture_result()
+        };
+        MockResponse::json(&serde_json::to_value(result).unwrap())
+    })
+    .await;
+    (server, received)
+}
+
+#[tokio::test]
+async fn test_remote_prover_posts_elf_and_inputs() {
+    let (server, received) = mock_remote_prover().await;
+    let prover = RemoteProver::new(server.url_for("prove"));
+    let options = ProveOptions {
+        shard_size: Some(1 << 20),
+        ..Default::default()
+    };
+
+    let result = prover
+        .prove(
+            b"\x7fELF",
+            "0102",
+            &ProvingType::Full,
+            Path::new("/unused"),
+            &options,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.proof, fixture_result().proof);
+    assert_eq!(result.public_values, fixture_result().public_values);
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(STANDARD.decode(&received[0].elf).unwrap(), b"\x7fELF");
+    assert_eq!(received[0].inputs, "0102");
+    assert_eq!(received[0].proving_type, ProvingType::Full);
+    assert_eq!(received[0].options.shard_size, Some(1 << 20));
+}
+
+#[tokio::test]
+async fn test_remote_prover_must_answer_fast_requests_with_fast_proofs() {
+    // A Full result would skip the local verification a Fast one gets
+    let (server, _received) = mock_remote_prover().await;
+    let prover = RemoteProver::new(server.url_for("prove"));
+
+    let result = prover
+        .prove(
+            b"\x7fELF",
+            "0102",
+            &ProvingType::Fast,
+            Path::new("/unused"),
+            &ProveOptions::default(),
+        )
+        .await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("answered a Fast request"))
+    );
+}
+
+#[tokio::test]
+async fn test_remote_prover_error_status_fails_proof() {
+    let server =
+        MockServer::start(|_| MockResponse::status(503).with_header("Content-Type", "text/plain"))
+            .await;
+    let prover = RemoteProver::new(server.url());
+
+    let result = prover
+        .prove(
+            b"\x7fELF",
+            "00",
+            &ProvingType::Fast,
+            Path::new("/unused"),
+            &ProveOptions::default(),
+        )
+        .await;
+    assert!(matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("503")),);
+}
+
+#[tokio::test]
+async fn test_generate_proof_delegates_to_remote_backend() {
+    let (server, received) = mock_remote_prover().await;
+    let mut ctx = setup_test_context();
+    ctx.prover_backend = Arc::new(RemoteProver::new(server.url_for("prove")));
+
+    let program_dir = tempdir().unwrap();
+    let elf_path = program_dir.path().join("program.elf");
+    let elf = b"\x7fELF delegated".to_vec();
+    std::fs::write(&elf_path, &elf).unwrap();
+
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(&elf)),
+        inputs: "0102".to_string(),
+        proving_type: ProvingType::Full,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request.clone()))
+        .await
+        .unwrap();
+
+    // The job still fills in the request's program hash around the remote result
+    assert_eq!(result.program_hash, request.program_hash);
+    assert_eq!(result.proof, fixture_result().proof);
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(STANDARD.decode(&received[0].elf).unwrap(), elf);
+    assert_eq!(received[0].inputs, "0102");
+}
+
+// Executable shell script standing in for the service binary run as a prover worker
+#[cfg(unix)]
+fn fake_worker(dir: &Path, script: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("prover-worker");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_subprocess_prover_crash_fails_only_the_proof() {
+    let dir = tempdir().unwrap();
+    let mut ctx = setup_test_context();
+    // The worker dies of a segfault, as a crash in the native prover would
+    let crashing = fake_worker(dir.path(), "kill -SEGV $$");
+    ctx.prover_backend = Arc::new(SubprocessProver::new(crashing));
+
+    let elf_path = dir.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        inputs: "0102".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        ..Default::default()
+    };
+    let result = generate_proof(Context(ctx.clone()), TangleArg(request.clone())).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("prover process crashed"))
+    );
+
+    // The service carries on: the next worker gets the request on stdin and answers on stdout
+    let received = dir.path().join("received.json");
+    let response = dir.path().join("response.json");
+    std::fs::write(
+        &response,
+        serde_json::to_string(&ProverWorkerResponse::Proof(fixture_result())).unwrap(),
+    )
+    .unwrap();
+    let worker = fake_worker(
+        dir.path(),
+        &format!(
+            "[ \"${}\" = 1 ] || exit 2\ncat > '{}'\necho 'prover chatter'\ncat '{}'",
+            PROVER_WORKER_ENV,
+            received.display(),
+            response.display()
+        ),
+    );
+    ctx.prover_backend = Arc::new(SubprocessProver::new(worker));
+    let TangleResult(proof) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(proof.proof, fixture_result().proof);
+
+    let sent: ProverWorkerRequest =
+        serde_json::from_slice(&std::fs::read(received).unwrap()).unwrap();
+    assert_eq!(STANDARD.decode(&sent.prove.elf).unwrap(), b"\x7fELF");
+    assert_eq!(sent.prove.inputs, "0102");
+    assert!(sent.output_base_dir.is_dir());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_subprocess_prover_reports_worker_errors() {
+    let dir = tempdir().unwrap();
+    let response = dir.path().join("response.json");
+    std::fs::write(
+        &response,
+        serde_json::to_string(&ProverWorkerResponse::Error("out of memory".to_string())).unwrap(),
+    )
+    .unwrap();
+    let worker = fake_worker(
+        dir.path(),
+        &format!("cat > /dev/null\ncat '{}'\nexit 1", response.display()),
+    );
+
+    let result = SubprocessProver::new(worker)
+        .prove(
+            b"\x7fELF",
+            "00",
+            &ProvingType::Fast,
+            dir.path(),
+            &ProveOptions::default(),
+        )
+        .await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("out of memory"))
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_subprocess_prover_starts_worker_with_shard_size() {
+    let dir = tempdir().unwrap();
+    let response = dir.path().join("response.json");
+    std::fs::write(
+        &response,
+        serde_json::to_string(&ProverWorkerResponse::Proof(fixture_result())).unwrap(),
+    )
+    .unwrap();
+    // Only a worker started with the requested shard size answers with a proof
+    let worker = fake_worker(
+        dir.path(),
+        &format!(
+            "cat > /dev/null\n[ \"${}\" = {} ] || exit 2\ncat '{}'",
+            crate::pico::SHARD_SIZE_ENV,
+            1 << 20,
+            response.display()
+        ),
+    );
+    let prover = SubprocessProver::new(worker);
+    let options = ProveOptions {
+        shard_size: Some(1 << 20),
+        ..Default::default()
+    };
+    prover
+        .prove(b"\x7fELF", "00", &ProvingType::Fast, dir.path(), &options)
+        .await
+        .unwrap();
+    // The service's own environment is left alone
+    assert!(std::env::var_os(crate::pico::SHARD_SIZE_ENV).is_none());
+
+    let invalid = ProveOptions {
+        shard_size: Some((1 << 20) + 1),
+        ..Default::default()
+    };
+    assert!(matches!(
+        prover
+            .prove(b"\x7fELF", "00", &ProvingType::Fast, dir.path(), &invalid)
+            .await,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("Invalid shard_size")
+    ));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_subprocess_prover_hands_node_local_options_to_worker() {
+    let dir = tempdir().unwrap();
+    let received = dir.path().join("received.json");
+    let response = dir.path().join("response.json");
+    std::fs::write(
+        &response,
+        serde_json::to_string(&ProverWorkerResponse::Proof(fixture_result())).unwrap(),
+    )
+    .unwrap();
+    let worker = fake_worker(
+        dir.path(),
+        &format!(
+            "cat > '{}'\ncat '{}'",
+            received.display(),
+            response.display()
+        ),
+    );
+    let options = ProveOptions {
+        evm_output_dir: Some(dir.path().join("evm")),
+        evm_docker_image: Some("registry.local/gnark:pinned".to_string()),
+        output_dir: Some(dir.path().join("run")),
+        ..Default::default()
+    };
+    SubprocessProver::new(worker)
+        .prove(b"\x7fELF", "00", &ProvingType::Fast, dir.path(), &options)
+        .await
+        .unwrap();
+
+    // The worker proves on this node, so it gets the options a remote prover never sees
+    let sent: ProverWorkerRequest =
+        serde_json::from_slice(&std::fs::read(received).unwrap()).unwrap();
+    let worker_options = sent.options();
+    assert_eq!(worker_options.evm_output_dir, options.evm_output_dir);
+    assert_eq!(worker_options.evm_docker_image, options.evm_docker_image);
+    assert_eq!(worker_options.output_dir, options.output_dir);
+}
+
+// Writes a proof artifact like the EVM path does and reports its directory
+#[cfg(feature = "evm-proving")]
+struct EvmArtifactProver;
+
+#[cfg(feature = "evm-proving")]
+impl ProverBackend for EvmArtifactProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        _inputs_hex: &'a str,
+        _proving_type: &'a ProvingType,
+        output_base_dir: &'a Path,
+        options: &'a ProveOptions,
+    ) -> futures::future::BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            let dir = crate::pico::evm_proof_output_dir(output_base_dir, options)?;
+            std::fs::write(dir.join("proof.data"), b"proof")?;
+            Ok(ProofResult {
+                proving_type: ProvingType::FullWithEvm,
+                output_dir: Some(dir.to_string_lossy().to_string()),
+                ..fixture_result()
+            })
+        })
+    }
+}
+
+#[cfg(feature = "evm-proving")]
+#[tokio::test]
+async fn test_evm_output_dir_exists_after_job_returns() {
+    let evm_output = tempdir().unwrap();
+    let mut ctx = setup_test_context();
+    ctx.prover_backend = Arc::new(EvmArtifactProver);
+    ctx.evm_output_dir = Some(evm_output.path().to_path_buf());
+
+    let program_dir = tempdir().unwrap();
+    let elf_path = program_dir.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        inputs: "0102".to_string(),
+        proving_type: ProvingType::FullWithEvm,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+
+    // The job's temp dirs are gone, but the EVM artifacts are not
+    let output_dir = std::path::PathBuf::from(result.output_dir.unwrap());
+    assert!(output_dir.starts_with(evm_output.path()));
+    assert!(output_dir.join("proof.data").is_file());
+}
@@ -0,0 +1,68 @@
+use crate::{BREVIS_REQUEST_FILE, BrevisRequestClient, ProofServiceError};
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+// Written by the SDK's save_inputs for the trading volume example
+const SAVED_INPUTS: &str =
+    include_str!("../../../trading-volume-example/requests/inputs/request_prove_inputs.json");
+
+// Executable shell script standing in for brevis-request
+#[cfg(unix)]
+fn fake_binary(dir: &Path, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("brevis-request");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn test_request_file_matches_save_inputs_output() {
+    let dir = tempdir().unwrap();
+    let client = BrevisRequestClient::default();
+
+    let path = client.write_request(SAVED_INPUTS, dir.path()).unwrap();
+    assert_eq!(path, dir.path().join(BREVIS_REQUEST_FILE));
+    assert_eq!(std::fs::read_to_string(path).unwrap(), SAVED_INPUTS);
+
+    let result = client.write_request(r#"{"chain_id":1}"#, dir.path());
+    assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_submit_runs_binary_on_request_file() {
+    let dir = tempdir().unwrap();
+    let received = dir.path().join("received.json");
+    let binary = fake_binary(
+        dir.path(),
+        &format!(
+            "cat \"$REQUEST_DATA_FILE\" > '{}'\necho '{{\"request_id\":\"0x01\"}}'",
+            received.display()
+        ),
+    );
+
+    let outcome = BrevisRequestClient::new(binary)
+        .submit(SAVED_INPUTS, dir.path())
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome.response,
+        Some(serde_json::json!({ "request_id": "0x01" }))
+    );
+    assert_eq!(std::fs::read_to_string(received).unwrap(), SAVED_INPUTS);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_submit_reports_failed_run() {
+    let dir = tempdir().unwrap();
+    let binary = fake_binary(dir.path(), "echo 'gateway unreachable' >&2\nexit 3");
+
+    let result = BrevisRequestClient::new(binary)
+        .submit(SAVED_INPUTS, dir.path())
+        .await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("gateway unreachable"))
+    );
+}
@@ -0,0 +1,212 @@
+use crate::{
+    CoprocessorProofRequest, MaxSizes, ProofCache, ProofCacheKey, ProofResult, ProofServiceError,
+    ProvingType, ServiceContext, generate_coprocessor_proof, jobs::coprocessor::build_input_bundle,
+    to_canonical_json,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use tempfile::tempdir;
+use url::Url;
+
+const BLOCK_A: B256 = B256::repeat_byte(0xaa);
+const BLOCK_B: B256 = B256::repeat_byte(0xbb);
+
+fn cached_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    // Nothing listens here, so any cache miss fails on the registry lookup
+    let rpc_url = Url::parse("http://127.0.0.1:1").unwrap();
+    let mut ctx = ServiceContext::new(rpc_url, Address::ZERO, temp_base)
+        .expect("Failed to create test ServiceContext");
+    ctx.proof_cache = Some(ProofCache::default());
+    ctx
+}
+
+fn request_at(anchor: B256) -> CoprocessorProofRequest {
+    CoprocessorProofRequest {
+        program_hash: B256::repeat_byte(0x01).to_string(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        anchor_block_hash: Some(anchor),
+        anchor_block_number: Some(100),
+        ..Default::default()
+    }
+}
+
+fn cache_key_for(ctx: &ServiceContext, request: &CoprocessorProofRequest) -> ProofCacheKey {
+    let bundle = build_input_bundle(ctx, request).unwrap();
+    let inputs = hex::encode(to_canonical_json(&bundle).unwrap());
+    ProofCacheKey::new(
+        request.program_hash.parse().unwrap(),
+        request.proving_type.clone(),
+        request.anchor_block_hash,
+        inputs.as_bytes(),
+    )
+}
+
+#[tokio::test]
+async fn test_proof_cache_is_keyed_by_anchor_block() {
+    let ctx = cached_context();
+    let cache = ctx.proof_cache.clone().unwrap();
+
+    // Proof at block A, as stored by a previous successful run
+    let proved_at_a = ProofResult {
+        proof: "c0ffee".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    cache.insert(cache_key_for(&ctx, &request_at(BLOCK_A)), proved_at_a);
+
+    // Identical data at block A is served from the cache
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request_at(BLOCK_A)))
+        .await
+        .unwrap();
+    assert_eq!(result.0.proof, "c0ffee");
+
+    // The same data at block B misses and goes on to fetch the program
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request_at(BLOCK_B))).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::ContractCallError(_)
+    ));
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_proof_cache_key_covers_anchor_and_proving_type() {
+    let ctx = cached_context();
+    let key_a = cache_key_for(&ctx, &request_at(BLOCK_A));
+    assert_eq!(key_a, cache_key_for(&ctx, &request_at(BLOCK_A)));
+    assert_ne!(key_a, cache_key_for(&ctx, &request_at(BLOCK_B)));
+
+    let mut full = request_at(BLOCK_A);
+    full.proving_type = ProvingType::Full;
+    assert_ne!(key_a, cache_key_for(&ctx, &full));
+}
+
+#[tokio::test]
+async fn test_cached_proof_of_another_proving_type_is_not_served() {
+    let ctx = cached_context();
+    let cache = ctx.proof_cache.clone().unwrap();
+    let fast = request_at(BLOCK_A);
+    let mut full = request_at(BLOCK_A);
+    full.proving_type = ProvingType::Full;
+
+    // A Fast proof of the data, also stored (wrongly) under the Full request's key
+    let fast_result = ProofResult {
+        proof: "c0ffee".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    cache.insert(cache_key_for(&ctx, &fast), fast_result.clone());
+    cache.insert(cache_key_for(&ctx, &full), fast_result);
+
+    // The Full request misses and goes on to fetch the program to prove it
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(full)).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::ContractCallError(_)
+    ));
+
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(fast))
+        .await
+        .unwrap();
+    assert_eq!(result.0.proof, "c0ffee");
+    assert_eq!(result.0.proving_type, ProvingType::Fast);
+}
+
+#[tokio::test]
+async fn test_cached_proof_without_public_values_honours_require_public_values() {
+    let ctx = cached_context();
+    let cache = ctx.proof_cache.clone().unwrap();
+
+    // A proof of a run that committed nothing, cached for a request that accepted that
+    let empty_result = ProofResult {
+        proof: "c0ffee".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    cache.insert(cache_key_for(&ctx, &request_at(BLOCK_A)), empty_result);
+
+    // A request that requires public values is refused the cached proof
+    let mut strict = request_at(BLOCK_A);
+    strict.require_public_values = true;
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(strict)).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::ProvingError(msg) if msg.contains("no public values")
+    ));
+
+    // ...while one that doesn't is still served it
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request_at(BLOCK_A)))
+        .await
+        .unwrap();
+    assert_eq!(result.0.proof, "c0ffee");
+}
+
+#[test]
+fn test_proof_cache_evicts_least_recently_used() {
+    let ctx = cached_context();
+    let cache = ProofCache::new(2);
+    let key = |anchor| cache_key_for(&ctx, &request_at(anchor));
+    let proof = ProofResult {
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    cache.insert(key(BLOCK_A), proof.clone());
+    cache.insert(key(BLOCK_B), proof.clone());
+
+    // Serving A makes B the least recently used, so it goes when C arrives
+    assert!(cache.get(&key(BLOCK_A)).is_some());
+    cache.insert(key(B256::repeat_byte(0xcc)), proof);
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&key(BLOCK_A)).is_some());
+    assert!(cache.get(&key(BLOCK_B)).is_none());
+}
+
+#[tokio::test]
+async fn test_cached_proof_carries_nothing_of_the_run_that_produced_it() {
+    let ctx = cached_context();
+    let cache = ctx.proof_cache.clone().unwrap();
+    cache.insert(cache_key_for(&ctx, &request_at(BLOCK_A)), ProofResult {
+        proof: "c0ffee".to_string(),
+        proving_type: ProvingType::Fast,
+        proof_id: Some("1700000000000_abcd1234".to_string()),
+        debug_log: Some("first request's logs".to_string()),
+        riscv_proof: Some("beef".to_string()),
+        phase_timings: Some([("riscv".to_string(), 5)].into()),
+        output_dir: Some("/tmp/proof_evm_first".to_string()),
+        ..Default::default()
+    });
+
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request_at(BLOCK_A)))
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(result.proof, "c0ffee");
+    assert!(result.proof_id.is_none());
+    assert!(result.debug_log.is_none());
+    assert!(result.riscv_proof.is_none());
+    assert!(result.phase_timings.is_none());
+    assert!(result.output_dir.is_none());
+
+    // Logs and RISCV proofs are only had by proving, so those requests miss
+    for capture_logs in [true, false] {
+        let mut request = request_at(BLOCK_A);
+        request.capture_logs = capture_logs;
+        request.include_riscv_proof = !capture_logs;
+        let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request)).await;
+        assert!(matches!(
+            result.err().unwrap(),
+            ProofServiceError::ContractCallError(_)
+        ));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    FieldMapping, FieldSource, FieldSpec, MaxSizes, jobs::coprocessor::CoprocessorInputBundle,
+    to_canonical_json,
+};
+use blueprint_sdk::alloy::primitives::B256;
+
+// Canonical JSON of `golden_bundle()`, hex encoded as in `ProofResult::inputs`. Changing it changes
+// every coprocessor cache key and the bytes programs read, so update it only deliberately.
+const GOLDEN_INPUTS_HEX: &str = "7b22616e63686f725f626c6f636b5f68617368223a22307861626162616261626162616261626162616261626162616261626162616261626162616261626162616261626162616261626162616261626162616261626162222c22616e63686f725f626c6f636b5f6e756d626572223a372c2264617461223a7b7d2c2273697a6573223a7b226d61785f726563656970745f73697a65223a33322c226d61785f73746f726167655f73697a65223a36342c226d61785f74785f73697a65223a39367d7d";
+
+fn golden_bundle() -> CoprocessorInputBundle {
+    CoprocessorInputBundle {
+        sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 64,
+            max_tx_size: 96,
+        },
+        anchor_block_hash: Some(B256::repeat_byte(0xab)),
+        anchor_block_number: Some(7),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_input_bundle_canonical_inputs_hex_is_pinned() {
+    let canonical = to_canonical_json(&golden_bundle()).unwrap();
+    assert_eq!(hex::encode(&canonical), GOLDEN_INPUTS_HEX);
+    // Keys are sorted rather than in field order
+    assert!(
+        String::from_utf8(canonical)
+            .unwrap()
+            .starts_with(r#"{"anchor_block_hash":"#)
+    );
+}
+
+#[test]
+fn test_canonical_json_sorts_map_keys() {
+    let spec = |name: &str| {
+        vec![FieldSpec {
+            name: name.to_string(),
+            source: FieldSource::Topic(1),
+        }]
+    };
+    let (first, second) = (B256::repeat_byte(0x01), B256::repeat_byte(0x02));
+
+    let mut forward = FieldMapping::default();
+    forward.events.insert(first, spec("a"));
+    forward.events.insert(second, spec("b"));
+    let mut backward = FieldMapping::default();
+    backward.events.insert(second, spec("b"));
+    backward.events.insert(first, spec("a"));
+
+    let expected = format!(
+        r#"{{"events":{{"{}":[{{"name":"a","source":{{"topic":1}}}}],"{}":[{{"name":"b","source":{{"topic":1}}}}]}}}}"#,
+        first, second
+    );
+    assert_eq!(to_canonical_json(&forward).unwrap(), expected.as_bytes());
+    assert_eq!(to_canonical_json(&backward).unwrap(), expected.as_bytes());
+}
@@ -0,0 +1,290 @@
+use crate::{
+    CheckpointStore, FailurePolicy, ProgramLocation, ProofCheckpoint, ProofRequest, ProofResult,
+    ProofServiceError, ProveOptions, ProverBackend, ProvingType, ResumeProofRequest,
+    ServiceContext, ServiceMode, generate_proof, resume_proof, sign_request,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use ed25519_dalek::SigningKey;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use url::Url;
+
+fn context_with_checkpoints(dir: &Path) -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    let mut ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext");
+    ctx.checkpoints = Some(CheckpointStore::new(dir).unwrap());
+    ctx
+}
+
+#[tokio::test]
+async fn test_resume_from_riscv_checkpoint_after_restart() {
+    let checkpoint_dir = tempdir().unwrap();
+    let proof_id = "1700000000000_abcd1234".to_string();
+
+    // Before the restart: the RISCV phase of a Full proof completed and was persisted
+    {
+        let ctx = context_with_checkpoints(checkpoint_dir.path());
+        let checkpoint = ProofCheckpoint {
+            proof_id: proof_id.clone(),
+            request: ProofRequest {
+                program_hash: B256::repeat_byte(0x01).to_string(),
+                inputs: "00".to_string(),
+                proving_type: ProvingType::Full,
+                on_full_failure: FailurePolicy::FallbackFast,
+                nonce: Some(B256::repeat_byte(0x07)),
+                expires_at: Some(u64::MAX),
+                ..Default::default()
+            },
+            caller: None,
+            riscv_proof: Some(ProofResult {
+                public_values: "2a".to_string(),
+                proof: "c0ffee".to_string(),
+                proving_type: ProvingType::Fast,
+                inputs: "00".to_string(),
+                ..Default::default()
+            }),
+        };
+        ctx.checkpoints.as_ref().unwrap().save(&checkpoint).unwrap();
+    }
+
+    // After the restart: a fresh context over the same directory picks it up
+    let ctx = context_with_checkpoints(checkpoint_dir.path());
+    let store = ctx.checkpoints.clone().unwrap();
+    assert_eq!(store.pending().unwrap(), vec![proof_id.clone()]);
+
+    let request = ResumeProofRequest {
+        proof_id: proof_id.clone(),
+    };
+    let result = resume_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(result.proof, "c0ffee");
+    assert_eq!(result.program_hash, B256::repeat_byte(0x01).to_string());
+    assert!(result.downgraded);
+    // Finished like a freshly proven result
+    assert_eq!(result.nonce, Some(B256::repeat_byte(0x07)));
+    assert_eq!(result.expires_at, Some(u64::MAX));
+    assert!(!result.proof_commitment.is_empty());
+    assert!(store.load(&proof_id).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_resume_from_riscv_checkpoint_rejects_expired_request() {
+    let checkpoint_dir = tempdir().unwrap();
+    let ctx = context_with_checkpoints(checkpoint_dir.path());
+    let store = ctx.checkpoints.clone().unwrap();
+    let proof_id = "1700000000000_expired0".to_string();
+    let checkpoint = ProofCheckpoint {
+        proof_id: proof_id.clone(),
+        request: ProofRequest {
+            program_hash: B256::repeat_byte(0x01).to_string(),
+            inputs: "00".to_string(),
+            proving_type: ProvingType::Fast,
+            expires_at: Some(1),
+            ..Default::default()
+        },
+        caller: None,
+        riscv_proof: Some(ProofResult {
+            proof: "c0ffee".to_string(),
+            proving_type: ProvingType::Fast,
+            ..Default::default()
+        }),
+    };
+    store.save(&checkpoint).unwrap();
+
+    let request = ResumeProofRequest {
+        proof_id: proof_id.clone(),
+    };
+    let result = resume_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("expired"))
+    );
+    assert!(store.load(&proof_id).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_resume_unknown_proof_id_is_rejected() {
+    let checkpoint_dir = tempdir().unwrap();
+    let ctx = context_with_checkpoints(checkpoint_dir.path());
+
+    let request = ResumeProofRequest {
+        proof_id: "1700000000000_missing0".to_string(),
+    };
+    let result = resume_proof(Context(ctx.clone()), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("No checkpoint"))
+    );
+
+    // Ids that could escape the checkpoint directory are refused outright
+    let store = ctx.checkpoints.unwrap();
+    assert!(matches!(
+        store.load("../escape"),
+        Err(ProofServiceError::InvalidInput(_))
+    ));
+}
+
+// Reports a RISCV phase like an in-process Full proof, then finishes (or fails) the proof,
+// recording the checkpoints pending at that point
+struct CheckpointingProver {
+    store: CheckpointStore,
+    fail: bool,
+    seen: Mutex<Vec<ProofCheckpoint>>,
+}
+
+impl ProverBackend for CheckpointingProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        inputs_hex: &'a str,
+        _proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        options: &'a ProveOptions,
+    ) -> futures::future::BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            let riscv = ProofResult {
+                public_values: "2a".to_string(),
+                proof: "c0ffee".to_string(),
+                proving_type: ProvingType::Fast,
+                inputs: inputs_hex.to_string(),
+                ..Default::default()
+            };
+            (options.on_riscv_proof.as_ref().unwrap().0)(&riscv);
+            for proof_id in self.store.pending().unwrap() {
+                let checkpoint = self.store.load(&proof_id).unwrap().unwrap();
+                self.seen.lock().unwrap().push(checkpoint);
+            }
+            if self.fail {
+                return Err(ProofServiceError::ProvingError(
+                    "recursion failed".to_string(),
+                ));
+            }
+            Ok(ProofResult {
+                public_values: "2a".to_string(),
+                proof: "beef".to_string(),
+                proving_type: ProvingType::Full,
+                ..Default::default()
+            })
+        })
+    }
+}
+
+fn full_request(dir: &Path) -> ProofRequest {
+    let elf_path = dir.join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+    ProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Full,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_riscv_phase_is_checkpointed_until_the_proof_finishes() {
+    let checkpoint_dir = tempdir().unwrap();
+    let programs = tempdir().unwrap();
+    for fail in [false, true] {
+        let mut ctx = context_with_checkpoints(checkpoint_dir.path());
+        let store = ctx.checkpoints.clone().unwrap();
+        let prover = Arc::new(CheckpointingProver {
+            store: store.clone(),
+            fail,
+            seen: Mutex::new(Vec::new()),
+        });
+        ctx.prover_backend = prover.clone();
+
+        let result = generate_proof(Context(ctx), TangleArg(full_request(programs.path()))).await;
+
+        // While proving, the checkpoint held the request and its RISCV phase
+        let seen = prover.seen.lock().unwrap().clone();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].request.proving_type, ProvingType::Full);
+        assert_eq!(seen[0].riscv_proof.as_ref().unwrap().proof, "c0ffee");
+        match result {
+            // The returned id is the one to resume, and the finished proof's checkpoint is gone
+            Ok(result) => assert_eq!(result.0.proof_id, Some(seen[0].proof_id.clone())),
+            Err(err) => assert!(matches!(err, ProofServiceError::ProvingError(_))),
+        }
+        // Failed proofs aren't left behind for resume_proof either
+        assert!(store.pending().unwrap().is_empty(), "fail = {}", fail);
+    }
+}
+
+#[tokio::test]
+async fn test_resume_is_limited_to_the_original_caller() {
+    let checkpoint_dir = tempdir().unwrap();
+    let owner = SigningKey::from_bytes(&[1; 32]);
+    let other = SigningKey::from_bytes(&[2; 32]);
+    let mut ctx = context_with_checkpoints(checkpoint_dir.path());
+    ctx.authorized_keys = Some(
+        [&owner, &other]
+            .iter()
+            .map(|key| key.verifying_key().to_bytes())
+            .collect(),
+    );
+    let store = ctx.checkpoints.clone().unwrap();
+    let proof_id = "1700000000000_owned000".to_string();
+    store
+        .save(&ProofCheckpoint {
+            proof_id: proof_id.clone(),
+            request: ProofRequest {
+                program_hash: B256::repeat_byte(0x01).to_string(),
+                inputs: "00".to_string(),
+                proving_type: ProvingType::Fast,
+                ..Default::default()
+            },
+            caller: Some(hex::encode(owner.verifying_key().as_bytes())),
+            riscv_proof: Some(ProofResult {
+                public_values: "2a".to_string(),
+                proof: "c0ffee".to_string(),
+                proving_type: ProvingType::Fast,
+                ..Default::default()
+            }),
+        })
+        .unwrap();
+    let request = ResumeProofRequest {
+        proof_id: proof_id.clone(),
+        ..Default::default()
+    };
+
+    // Unsigned requests, other callers and verify-only nodes are refused, checkpoint kept
+    let unsigned = resume_proof(Context(ctx.clone()), TangleArg(request.clone())).await;
+    assert!(matches!(unsigned, Err(ProofServiceError::Unauthorized(_))));
+    let by_other = sign_request(request.clone(), &other).unwrap();
+    let result = resume_proof(Context(ctx.clone()), TangleArg(by_other)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::Unauthorized(msg)) if msg.contains("another caller"))
+    );
+    let by_owner = sign_request(request, &owner).unwrap();
+    let mut verify_only = ctx.clone();
+    verify_only.mode = ServiceMode::VerifyOnly;
+    let result = resume_proof(Context(verify_only), TangleArg(by_owner.clone())).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::UnsupportedProvingType(_))
+    ));
+    assert_eq!(store.pending().unwrap(), vec![proof_id.clone()]);
+
+    let result = resume_proof(Context(ctx), TangleArg(by_owner))
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(result.proof, "c0ffee");
+    assert_eq!(result.proof_id, Some(proof_id));
+    assert!(store.pending().unwrap().is_empty());
+}
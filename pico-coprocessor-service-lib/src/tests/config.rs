@@ -0,0 +1,347 @@
+use super::mock::{MockResponse, MockServer};
+use crate::{
+    HardLimits, ProofResult, ProofServiceError, ProveOptions, ProvingType, RateLimitConfig,
+    RegistryRef, ServiceConfig, ServiceMode,
+};
+use blueprint_sdk::alloy::primitives::Address;
+use ed25519_dalek::SigningKey;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_config_file_sets_every_context_field() {
+    let dir = tempdir().unwrap();
+    let remote = MockServer::start(|_request| {
+        MockResponse::json(
+            &serde_json::to_value(ProofResult {
+                proving_type: ProvingType::Full,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    })
+    .await;
+    let authorized_key = SigningKey::from_bytes(&[3; 32]).verifying_key().to_bytes();
+    let config_path = dir.path().join("service.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+eth_rpc_url = "http://rpc.example.com:8545/"
+registry_contract_address = "{registry}"
+fallback_registries = ["{fallback}", "registry.example.eth"]
+temp_dir_base = "{temp}"
+service_mode = "verify-only"
+shutdown_grace_secs = 42
+checkpoint_dir = "{checkpoints}"
+elf_cache_dir = "{elf_cache}"
+elf_cache_max_bytes = 4096
+prover_cache_capacity = 2
+proving_concurrency = 3
+remote_prover_url = "{remote}"
+remote_prover_timeout_secs = 30
+evm_output_dir = "{evm}"
+dump_inputs_dir = "{dumps}"
+allowed_temp_dirs = ["{scratch}"]
+allow_insecure = true
+max_input_bytes = 1024
+max_prover_input_bytes = 2048
+max_elf_bytes = 8192
+allowed_elf_content_types = ["application/x-elf"]
+allowed_rpc_hosts = ["RPC.example.com"]
+min_prover_version = "1.0.0"
+proof_cache = true
+proof_cache_max_entries = 2
+authorized_keys = ["0x{authorized}"]
+metrics_addr = "127.0.0.1:9100"
+max_tagged_series = 5
+
+[hard_limits]
+max_receipts_hard = 10
+max_logs_per_receipt_hard = 11
+max_storage_slots_hard = 12
+max_transactions_hard = 13
+
+[rate_limit]
+capacity = 2
+refill_per_sec = 0.001
+
+[default_proof_request]
+program_hash = "0x0000000000000000000000000000000000000000000000000000000000000001"
+inputs = "00"
+proving_type = "Fast"
+"#,
+            registry = Address::repeat_byte(0x11),
+            fallback = Address::repeat_byte(0x12),
+            temp = dir.path().join("tmp").display(),
+            checkpoints = dir.path().join("checkpoints").display(),
+            elf_cache = dir.path().join("elf_cache").display(),
+            remote = remote.url_for("prove"),
+            evm = dir.path().join("evm").display(),
+            dumps = dir.path().join("dumps").display(),
+            scratch = dir.path().join("scratch").display(),
+            authorized = hex::encode(authorized_key),
+        ),
+    )
+    .unwrap();
+
+    let config = ServiceConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.shutdown_grace(), Duration::from_secs(42));
+    assert_eq!(config.metrics_addr, Some("127.0.0.1:9100".parse().unwrap()));
+    let ctx = config.build_context().unwrap();
+
+    assert_eq!(ctx.eth_rpc_url.as_str(), "http://rpc.example.com:8545/");
+    assert_eq!(
+        ctx.registry_contract_address,
+        RegistryRef::Address(Address::repeat_byte(0x11))
+    );
+    assert_eq!(ctx.fallback_registries, vec![
+        RegistryRef::Address(Address::repeat_byte(0x12)),
+        RegistryRef::Ens("registry.example.eth".to_string()),
+    ]);
+    assert_eq!(ctx.temp_dir_base, dir.path().join("tmp"));
+    assert!(ctx.temp_dir_base.is_dir());
+    assert_eq!(ctx.mode, ServiceMode::VerifyOnly);
+    assert_eq!(
+        ctx.checkpoints.as_ref().unwrap().dir(),
+        dir.path().join("checkpoints")
+    );
+    assert_eq!(
+        ctx.elf_cache.as_ref().unwrap().dir(),
+        dir.path().join("elf_cache")
+    );
+    assert_eq!(ctx.elf_cache.as_ref().unwrap().max_bytes(), Some(4096));
+    assert_eq!(ctx.prover_cache.capacity(), 2);
+    assert_eq!(ctx.proving_queue.as_ref().unwrap().available(), 3);
+    assert_eq!(ctx.evm_output_dir, Some(dir.path().join("evm")));
+    assert_eq!(ctx.dump_inputs_dir, Some(dir.path().join("dumps")));
+    assert_eq!(ctx.allowed_temp_dirs, vec![dir.path().join("scratch")]);
+    assert!(ctx.allow_insecure);
+    assert_eq!(ctx.max_input_bytes, 1024);
+    assert_eq!(ctx.max_prover_input_bytes, 2048);
+    assert_eq!(ctx.max_elf_bytes, 8192);
+    assert_eq!(ctx.hard_limits, HardLimits {
+        max_receipts_hard: 10,
+        max_logs_per_receipt_hard: 11,
+        max_storage_slots_hard: 12,
+        max_transactions_hard: 13,
+    });
+    assert_eq!(
+        ctx.allowed_elf_content_types,
+        Some(vec!["application/x-elf".to_string()])
+    );
+    assert_eq!(
+        ctx.allowed_rpc_hosts,
+        Some(HashSet::from(["rpc.example.com".to_string()]))
+    );
+    assert_eq!(ctx.min_prover_version.as_deref(), Some("1.0.0"));
+    assert_eq!(ctx.proof_cache.as_ref().unwrap().max_entries(), 2);
+    assert_eq!(ctx.authorized_keys, Some(HashSet::from([authorized_key])));
+
+    let limiter = ctx.rate_limiter.as_ref().unwrap();
+    limiter.check("caller").unwrap();
+    limiter.check("caller").unwrap();
+    assert!(matches!(
+        limiter.check("caller"),
+        Err(ProofServiceError::RateLimited { .. })
+    ));
+
+    let default_request = ctx.default_proof_request.as_ref().unwrap();
+    assert_eq!(default_request.proving_type, ProvingType::Fast);
+    assert_eq!(default_request.inputs, "00");
+
+    // Proofs go to the configured remote prover
+    ctx.prover_backend
+        .prove(
+            b"elf",
+            "00",
+            &ProvingType::Full,
+            dir.path(),
+            &ProveOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(remote.hits(), 1);
+}
+
+#[test]
+fn test_environment_overrides_config_file() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("service.json");
+    std::fs::write(
+        &config_path,
+        serde_json::json!({
+            "eth_rpc_url": "http://file.example.com/",
+            "registry_contract_address": Address::repeat_byte(0x11).to_string(),
+            "temp_dir_base": dir.path().join("file"),
+            "service_mode": "verify-only",
+            "proving_concurrency": 2,
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let env: HashMap<&str, String> = HashMap::from([
+        ("ETH_RPC_URL", "http://env.example.com/".to_string()),
+        (
+            "TEMP_DIR_BASE",
+            dir.path().join("env").display().to_string(),
+        ),
+        ("PROVING_CONCURRENCY", "5".to_string()),
+    ]);
+    let mut config = ServiceConfig::from_file(&config_path).unwrap();
+    config
+        .apply_env_overrides(|name| env.get(name).cloned())
+        .unwrap();
+    let ctx = config.build_context().unwrap();
+
+    assert_eq!(ctx.eth_rpc_url.as_str(), "http://env.example.com/");
+    assert_eq!(ctx.temp_dir_base, dir.path().join("env"));
+    assert_eq!(ctx.proving_queue.as_ref().unwrap().available(), 5);
+    // Values the environment doesn't set keep the file's
+    assert_eq!(ctx.mode, ServiceMode::VerifyOnly);
+    assert_eq!(
+        ctx.registry_contract_address,
+        RegistryRef::Address(Address::repeat_byte(0x11))
+    );
+
+    // Proxy credentials come from the environment, and never show up in Debug output
+    let proxy_env = HashMap::from([
+        ("OUTBOUND_PROXY_URL", "http://proxy.example.com:3128/"),
+        ("OUTBOUND_PROXY_USERNAME", "svc"),
+        ("OUTBOUND_PROXY_PASSWORD", "s3cret"),
+    ]);
+    let mut proxied = ServiceConfig::default();
+    proxied
+        .apply_env_overrides(|name| proxy_env.get(name).map(|value| value.to_string()))
+        .unwrap();
+    let proxy = proxied.http_proxy.as_ref().unwrap();
+    assert_eq!(proxy.url.as_str(), "http://proxy.example.com:3128/");
+    assert_eq!(proxy.username.as_deref(), Some("svc"));
+    assert_eq!(proxy.password.as_deref(), Some("s3cret"));
+    assert!(!format!("{:?}", proxied).contains("s3cret"));
+    assert!(matches!(
+        ServiceConfig::default()
+            .apply_env_overrides(|name| (name == "OUTBOUND_PROXY_USERNAME").then(|| "svc".to_string())),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("without a proxy URL")
+    ));
+
+    // AWS credentials only apply once an S3 endpoint is configured
+    let s3_env = HashMap::from([
+        ("S3_ENDPOINT", "http://minio.example.com:9000/"),
+        ("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE"),
+        ("AWS_SECRET_ACCESS_KEY", "s3cret"),
+        ("S3_BUCKET", "proofs"),
+        ("S3_KEY_PREFIX", "jobs/"),
+    ]);
+    let mut with_s3 = ServiceConfig::default();
+    with_s3
+        .apply_env_overrides(|name| s3_env.get(name).map(|value| value.to_string()))
+        .unwrap();
+    let s3 = with_s3.s3.as_ref().unwrap();
+    assert_eq!(s3.endpoint.as_str(), "http://minio.example.com:9000/");
+    assert_eq!(s3.region, "us-east-1");
+    assert_eq!(s3.access_key_id, "AKIDEXAMPLE");
+    assert_eq!(s3.bucket, "proofs");
+    assert_eq!(s3.key_prefix, "jobs/");
+    assert!(!format!("{:?}", with_s3).contains("s3cret"));
+    let mut without_s3 = ServiceConfig::default();
+    without_s3
+        .apply_env_overrides(|name| (name == "AWS_ACCESS_KEY_ID").then(|| "AKID".to_string()))
+        .unwrap();
+    assert!(without_s3.s3.is_none());
+
+    // Subprocess proving and a remote prover are alternative backends
+    let mut isolated = ServiceConfig {
+        eth_rpc_url: Some("http://rpc.example.com/".parse().unwrap()),
+        registry_contract_address: Some(Address::repeat_byte(0x11).to_string()),
+        temp_dir_base: Some(dir.path().join("isolated")),
+        remote_prover_url: Some("http://prover.example.com/".parse().unwrap()),
+        ..Default::default()
+    };
+    isolated
+        .apply_env_overrides(|name| (name == "PROVER_SUBPROCESS").then(|| "true".to_string()))
+        .unwrap();
+    assert!(isolated.prover_subprocess);
+    assert!(matches!(
+        isolated.build_context(),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("remote_prover_url")
+    ));
+
+    // The gnark image is only applied by prover subprocesses
+    let mut pinned_image = ServiceConfig {
+        eth_rpc_url: Some("http://rpc.example.com/".parse().unwrap()),
+        registry_contract_address: Some(Address::repeat_byte(0x11).to_string()),
+        temp_dir_base: Some(dir.path().join("pinned-image")),
+        evm_docker_image: Some("registry.example.com/gnark@sha256:abcd".to_string()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        pinned_image.build_context(),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("prover_subprocess")
+    ));
+    pinned_image.prover_subprocess = true;
+    assert_eq!(
+        pinned_image
+            .build_context()
+            .unwrap()
+            .evm_docker_image
+            .as_deref(),
+        Some("registry.example.com/gnark@sha256:abcd")
+    );
+
+    // Authorized keys come from the environment too, and must be ed25519 public keys
+    let key = SigningKey::from_bytes(&[4; 32]).verifying_key().to_bytes();
+    let mut authenticated = ServiceConfig {
+        eth_rpc_url: Some("http://rpc.example.com/".parse().unwrap()),
+        registry_contract_address: Some(Address::repeat_byte(0x11).to_string()),
+        temp_dir_base: Some(dir.path().join("authenticated")),
+        ..Default::default()
+    };
+    authenticated
+        .apply_env_overrides(|name| {
+            (name == "AUTHORIZED_KEYS").then(|| format!(" {} ,", hex::encode(key)))
+        })
+        .unwrap();
+    assert_eq!(
+        authenticated.build_context().unwrap().authorized_keys,
+        Some(HashSet::from([key]))
+    );
+    authenticated.authorized_keys = Some(vec!["abcd".to_string()]);
+    assert!(matches!(
+        authenticated.build_context(),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("Invalid authorized key")
+    ));
+    authenticated.authorized_keys = Some(Vec::new());
+    assert!(matches!(
+        authenticated.build_context(),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("authorized_keys is empty")
+    ));
+
+    // A rate limit has to refill
+    let rate_limited = ServiceConfig {
+        eth_rpc_url: Some("http://rpc.example.com/".parse().unwrap()),
+        registry_contract_address: Some(Address::repeat_byte(0x11).to_string()),
+        temp_dir_base: Some(dir.path().join("rate-limited")),
+        rate_limit: Some(RateLimitConfig {
+            capacity: 1,
+            refill_per_sec: 0.0,
+        }),
+        ..Default::default()
+    };
+    assert!(matches!(
+        rate_limited.build_context(),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("refill rate")
+    ));
+
+    let mut invalid = ServiceConfig::default();
+    assert!(matches!(
+        invalid.apply_env_overrides(|name| (name == "SHUTDOWN_GRACE_SECS").then(|| "soon".to_string())),
+        Err(ProofServiceError::ConfigError(msg)) if msg.starts_with("Invalid SHUTDOWN_GRACE_SECS")
+    ));
+    assert!(matches!(
+        ServiceConfig::default().build_context(),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("ETH_RPC_URL")
+    ));
+}
@@ -0,0 +1,157 @@
+// Request types are deserialized from untrusted input: malformed payloads must come back as
+// errors, never as panics
+use crate::{
+    CoprocessorProofRequest, MaxSizes, ProofServiceError, ProvingType, ServiceContext,
+    generate_coprocessor_proof,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use proptest::prelude::*;
+use serde_json::Value;
+use std::str::FromStr;
+use tempfile::{TempDir, tempdir};
+use url::Url;
+
+// The context plus the guard of its temp base, which is removed when the guard drops
+fn setup_test_context() -> (ServiceContext, TempDir) {
+    let temp_base = tempdir().expect("Failed to create base temp dir for tests");
+    let rpc_url = Url::parse("http://localhost:8545").unwrap();
+    let ctx = ServiceContext::new(rpc_url, Address::ZERO, temp_base.path().to_path_buf())
+        .expect("Failed to create test ServiceContext");
+    (ctx, temp_base)
+}
+
+fn valid_request() -> CoprocessorProofRequest {
+    CoprocessorProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    }
+}
+
+// Runs the job to completion on a fresh single-threaded runtime
+fn run_job(request: CoprocessorProofRequest) -> Result<(), ProofServiceError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let (ctx, _temp_base) = setup_test_context();
+    runtime
+        .block_on(generate_coprocessor_proof(Context(ctx), TangleArg(request)))
+        .map(|_| ())
+}
+
+fn arbitrary_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        ".*".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map(".*", inner, 0..4)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+// Structs deserialize from JSON objects and from arrays of their fields in order
+fn is_struct_like(value: &Value) -> bool {
+    value.is_object() || value.is_array()
+}
+
+// Top-level fields of a valid request, each with a check for values that may be of the field's
+// type; anything else has to be rejected
+const TYPED_FIELDS: [(&str, fn(&Value) -> bool); 4] = [
+    ("program_hash", Value::is_string),
+    ("blockchain_data", is_struct_like),
+    ("max_sizes", is_struct_like),
+    ("proving_type", Value::is_string),
+];
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn prop_random_bytes_never_deserialize(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        prop_assert!(serde_json::from_slice::<CoprocessorProofRequest>(&bytes).is_err());
+    }
+
+    #[test]
+    fn prop_mistyped_fields_are_rejected(
+        field in 0..TYPED_FIELDS.len(),
+        value in arbitrary_json(),
+    ) {
+        let (name, expected_type) = TYPED_FIELDS[field];
+        prop_assume!(!expected_type(&value));
+        let mut json = serde_json::to_value(valid_request()).unwrap();
+        json[name] = value;
+        let bytes = serde_json::to_vec(&json).unwrap();
+        prop_assert!(serde_json::from_slice::<CoprocessorProofRequest>(&bytes).is_err());
+    }
+
+    #[test]
+    fn prop_job_rejects_malformed_program_hash(program_hash in ".*") {
+        prop_assume!(B256::from_str(&program_hash).is_err());
+        let result = run_job(CoprocessorProofRequest {
+            program_hash,
+            ..valid_request()
+        });
+        prop_assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn prop_job_rejects_invalid_max_sizes(
+        max_receipt_size in any::<usize>(),
+        max_storage_size in any::<usize>(),
+        max_tx_size in any::<usize>(),
+    ) {
+        let sizes = [max_receipt_size, max_storage_size, max_tx_size];
+        prop_assume!(sizes.iter().any(|&size| size == 0 || size % 32 != 0));
+        let result = run_job(CoprocessorProofRequest {
+            max_sizes: MaxSizes {
+                max_receipt_size,
+                max_storage_size,
+                max_tx_size,
+            },
+            ..valid_request()
+        });
+        prop_assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+    }
+}
+
+#[test]
+fn test_deeply_nested_blockchain_data_is_rejected() {
+    // Structs also deserialize from JSON arrays, so nested brackets recurse into the receipts;
+    // they have to hit serde_json's recursion limit and fail instead of overflowing the stack
+    let depth = 100_000;
+    let payload = format!(
+        r#"{{"program_hash":"{}","blockchain_data":{}{},"max_sizes":{{"max_receipt_size":32,"max_storage_size":32,"max_tx_size":32}},"proving_type":"Fast"}}"#,
+        B256::ZERO,
+        "[".repeat(depth),
+        "]".repeat(depth)
+    );
+    assert!(serde_json::from_str::<CoprocessorProofRequest>(&payload).is_err());
+}
+
+#[test]
+fn test_multibyte_program_hash_is_rejected() {
+    // 64 bytes after the 0x prefix, but not 64 hex digits
+    let program_hash = format!("0x{}", "é".repeat(32));
+    assert_eq!(program_hash.len(), 66);
+    let result = run_job(CoprocessorProofRequest {
+        program_hash,
+        ..valid_request()
+    });
+    assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+}
@@ -0,0 +1,819 @@
+use super::mock::{MockResponse, MockServer, start_mock_rpc};
+use crate::{
+    DEFAULT_ELF_CONTENT_TYPES, ElfCache, HashAlgorithm, HashProgress, HashProgressCallback,
+    HttpProxyConfig, ProgramLocation, ProofRequest, ProofResult, ProofServiceError, ProveOptions,
+    ProverBackend, ProverCache, ProvingType, ServiceContext, ServiceMode, WarmProgramRequest,
+    generate_proof, location_hash_algorithm, program, warm_program,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::{
+    alloy::{
+        primitives::{Address, B256},
+        sol_types::SolValue,
+    },
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+fn progress_collector() -> (HashProgressCallback, Arc<Mutex<Vec<HashProgress>>>) {
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let sink = updates.clone();
+    let callback: HashProgressCallback = Arc::new(move |p| sink.lock().unwrap().push(p));
+    (callback, updates)
+}
+
+fn assert_progress_reaches(updates: &[HashProgress], size: u64) {
+    assert!(!updates.is_empty());
+    assert!(
+        updates
+            .windows(2)
+            .all(|w| w[0].bytes_processed < w[1].bytes_processed)
+    );
+    assert_eq!(updates.last().unwrap().bytes_processed, size);
+    assert!(updates.iter().all(|p| p.total_bytes == Some(size)));
+}
+
+async fn login_page_server() -> MockServer {
+    MockServer::start(|_| {
+        MockResponse::ok("<html>Please sign in</html>")
+            .with_header("Content-Type", "text/html; charset=utf-8")
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_html_download_rejected_when_content_types_enforced() {
+    let server = login_page_server().await;
+    let mut ctx = setup_test_context();
+    ctx.allowed_elf_content_types = Some(
+        DEFAULT_ELF_CONTENT_TYPES
+            .iter()
+            .map(|t| t.to_string())
+            .collect(),
+    );
+
+    let location = ProgramLocation::RemoteUrl(server.url_for("program.elf"));
+    let work_dir = tempdir().unwrap();
+    let result = program::fetch_and_verify_program(
+        &ctx,
+        &location,
+        &B256::ZERO.to_string(),
+        work_dir.path(),
+    )
+    .await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProgramDownloadFailed(msg)) if msg.contains("unexpected content-type") && msg.contains("text/html"))
+    );
+}
+
+#[tokio::test]
+async fn test_content_type_check_is_opt_in() {
+    let server = login_page_server().await;
+    let ctx = setup_test_context();
+
+    // Without an allow-list the page is downloaded and only fails hash verification
+    let location = ProgramLocation::RemoteUrl(server.url_for("program.elf"));
+    let work_dir = tempdir().unwrap();
+    let result = program::fetch_and_verify_program(
+        &ctx,
+        &location,
+        &B256::ZERO.to_string(),
+        work_dir.path(),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::ProgramHashMismatch { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_data_uri_program_is_decoded_and_verified() {
+    let ctx = setup_test_context();
+    let elf = b"\x7fELF not really a program".to_vec();
+    let uri = format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(&elf)
+    );
+    let work_dir = tempdir().unwrap();
+
+    let (_dir, elf_path) = program::fetch_and_verify_program(
+        &ctx,
+        &ProgramLocation::DataUri(uri.clone()),
+        &hex::encode(Sha256::digest(&elf)),
+        work_dir.path(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(elf_path).unwrap(), elf);
+
+    let result = program::fetch_and_verify_program(
+        &ctx,
+        &ProgramLocation::DataUri(uri),
+        &B256::ZERO.to_string(),
+        work_dir.path(),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::ProgramHashMismatch { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_program_hash_matches_with_or_without_0x_prefix() {
+    let ctx = setup_test_context();
+    let elf = b"\x7fELF not really a program".to_vec();
+    let uri = format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(&elf)
+    );
+    let work_dir = tempdir().unwrap();
+
+    // A program hash as a B256 displays it, and the same digits upper-cased
+    let prefixed = B256::from_slice(&Sha256::digest(&elf)).to_string();
+    assert!(prefixed.starts_with("0x"));
+    let upper = format!("0X{}", hex::encode_upper(Sha256::digest(&elf)));
+    for expected in [prefixed, upper] {
+        let (_dir, elf_path) = program::fetch_and_verify_program(
+            &ctx,
+            &ProgramLocation::DataUri(uri.clone()),
+            &expected,
+            work_dir.path(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(elf_path).unwrap(), elf);
+    }
+}
+
+#[tokio::test]
+async fn test_blake3_annotated_program_is_verified() {
+    // BLAKE3 test vector for the empty input
+    assert_eq!(
+        program::hash_hex(HashAlgorithm::Blake3, b""),
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+    );
+
+    let elf = b"\x7fELF hashed with blake3".to_vec();
+    let blake3_hash = hex::encode(blake3::hash(&elf).as_bytes());
+    let body = elf.clone();
+    let server = MockServer::start(move |_| MockResponse::ok(body.clone())).await;
+    let ctx = setup_test_context();
+    let work_dir = tempdir().unwrap();
+
+    let mut url = server.url_for("program.elf");
+    url.set_fragment(Some("hash=blake3"));
+    let data_uri = format!(
+        "data:application/octet-stream;hash=blake3;base64,{}",
+        STANDARD.encode(&elf)
+    );
+    for location in [
+        ProgramLocation::RemoteUrl(url),
+        ProgramLocation::DataUri(data_uri),
+    ] {
+        assert_eq!(
+            location_hash_algorithm(&location).unwrap(),
+            HashAlgorithm::Blake3
+        );
+        let (_dir, elf_path) =
+            program::fetch_and_verify_program(&ctx, &location, &blake3_hash, work_dir.path())
+                .await
+                .unwrap();
+        assert_eq!(std::fs::read(elf_path).unwrap(), elf);
+
+        // The SHA256 of the program doesn't match under the annotated algorithm
+        let result = program::fetch_and_verify_program(
+            &ctx,
+            &location,
+            &hex::encode(Sha256::digest(&elf)),
+            work_dir.path(),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ProofServiceError::ProgramHashMismatch { got, .. }) if got == blake3_hash
+        ));
+    }
+
+    // Unannotated locations keep SHA256; unknown algorithms are rejected
+    assert_eq!(
+        location_hash_algorithm(&ProgramLocation::RemoteUrl(server.url_for("program.elf")))
+            .unwrap(),
+        HashAlgorithm::Sha256
+    );
+    let mut unknown = server.url_for("program.elf");
+    unknown.set_fragment(Some("hash=md5"));
+    assert!(matches!(
+        location_hash_algorithm(&ProgramLocation::RemoteUrl(unknown)),
+        Err(ProofServiceError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_data_uri_must_be_base64() {
+    assert_eq!(
+        program::decode_data_uri("data:;base64,AAEC").unwrap(),
+        vec![0, 1, 2]
+    );
+    for uri in [
+        "data:text/plain,hello",
+        "data:application/octet-stream;base64",
+        "data:application/octet-stream;base64,not base64!",
+        "https://example.com/program.elf",
+    ] {
+        assert!(
+            matches!(
+                program::decode_data_uri(uri),
+                Err(ProofServiceError::InvalidInput(_))
+            ),
+            "{}",
+            uri
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_local_hash_reports_progress_up_to_file_size() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("program.elf");
+    let elf: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    std::fs::write(&path, &elf).unwrap();
+    let (callback, updates) = progress_collector();
+
+    let hash = program::calculate_file_hash_with_progress(&path, Some(&callback))
+        .await
+        .unwrap();
+    assert_eq!(hash, hex::encode(Sha256::digest(&elf)));
+
+    let updates = updates.lock().unwrap();
+    // Hashed in 4 KiB chunks
+    assert!(updates.len() > 1);
+    assert_progress_reaches(&updates, elf.len() as u64);
+}
+
+#[tokio::test]
+async fn test_download_reports_progress_up_to_content_length() {
+    let elf: Vec<u8> = (0..50_000u32).map(|i| (i % 251) as u8).collect();
+    let body = elf.clone();
+    let server = MockServer::start(move |_| MockResponse::ok(body.clone())).await;
+    let (callback, updates) = progress_collector();
+    let mut ctx = setup_test_context();
+    ctx.hash_progress = Some(callback);
+
+    let work_dir = tempdir().unwrap();
+    program::fetch_and_verify_program(
+        &ctx,
+        &ProgramLocation::RemoteUrl(server.url_for("program.elf")),
+        &hex::encode(Sha256::digest(&elf)),
+        work_dir.path(),
+    )
+    .await
+    .unwrap();
+
+    assert_progress_reaches(&updates.lock().unwrap(), elf.len() as u64);
+}
+
+#[tokio::test]
+async fn test_concurrent_fetches_of_same_program_share_one_download() {
+    let elf = b"\x7fELF shared program".to_vec();
+    let body = elf.clone();
+    let server = MockServer::start(move |_| MockResponse::ok(body.clone())).await;
+    let ctx = setup_test_context();
+    let location = ProgramLocation::RemoteUrl(server.url_for("program.elf"));
+    let hash = hex::encode(Sha256::digest(&elf));
+
+    let work_dirs: Vec<_> = (0..10).map(|_| tempdir().unwrap()).collect();
+    let fetches = work_dirs
+        .iter()
+        .map(|dir| program::fetch_and_verify_program(&ctx, &location, &hash, dir.path()));
+    let results = futures::future::join_all(fetches).await;
+
+    assert_eq!(server.hits(), 1);
+    for result in results {
+        let (_dir, elf_path) = result.unwrap();
+        assert_eq!(std::fs::read(elf_path).unwrap(), elf);
+    }
+    assert_eq!(ctx.download_dedup.as_ref().unwrap().in_flight(), 0);
+
+    // Without dedup every fetch downloads the program itself
+    let mut ctx = ctx;
+    ctx.download_dedup = None;
+    let fetches = work_dirs
+        .iter()
+        .map(|dir| program::fetch_and_verify_program(&ctx, &location, &hash, dir.path()));
+    for result in futures::future::join_all(fetches).await {
+        result.unwrap();
+    }
+    assert_eq!(server.hits(), 11);
+}
+
+#[tokio::test]
+async fn test_download_routes_through_authenticated_proxy() {
+    let elf = b"\x7fELF behind a proxy".to_vec();
+    let body = elf.clone();
+    let expected_auth = format!("Basic {}", STANDARD.encode("svc:s3cret"));
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let seen = requested.clone();
+    let proxy = MockServer::start(move |req| {
+        seen.lock().unwrap().push(req.path.clone());
+        if req.header("proxy-authorization") != Some(expected_auth.as_str()) {
+            return MockResponse::status(407);
+        }
+        MockResponse::ok(body.clone())
+    })
+    .await;
+    let mut ctx = setup_test_context();
+    ctx.use_http_proxy(&HttpProxyConfig {
+        url: proxy.url(),
+        username: Some("svc".to_string()),
+        password: Some("s3cret".to_string()),
+    })
+    .unwrap();
+
+    // The origin doesn't resolve, so only a proxied download can succeed
+    let work_dir = tempdir().unwrap();
+    let (_dir, elf_path) = program::fetch_and_verify_program(
+        &ctx,
+        &ProgramLocation::RemoteUrl(Url::parse("http://programs.invalid/program.elf").unwrap()),
+        &hex::encode(Sha256::digest(&elf)),
+        work_dir.path(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(elf_path).unwrap(), elf);
+    assert_eq!(proxy.hits(), 1);
+    assert_eq!(*requested.lock().unwrap(), vec![
+        "http://programs.invalid/program.elf".to_string()
+    ]);
+}
+
+#[tokio::test]
+async fn test_hash_verification_skipped_only_for_insecure_local_programs() {
+    let elf = b"\x7fELF local build".to_vec();
+    let work_dir = tempdir().unwrap();
+    let local_path = work_dir.path().join("program.elf");
+    std::fs::write(&local_path, &elf).unwrap();
+    let local = ProgramLocation::LocalPath(local_path);
+    let stale_hash = B256::ZERO.to_string();
+
+    // Either flag alone leaves the hash check in place
+    for (allow_insecure, skip_requested) in [(false, false), (false, true), (true, false)] {
+        let mut ctx = setup_test_context();
+        ctx.allow_insecure = allow_insecure;
+        let result =
+            program::fetch_program(&ctx, &local, &stale_hash, work_dir.path(), skip_requested)
+                .await;
+        assert!(
+            matches!(result, Err(ProofServiceError::ProgramHashMismatch { .. })),
+            "allow_insecure={} skip_requested={}",
+            allow_insecure,
+            skip_requested
+        );
+    }
+
+    let mut ctx = setup_test_context();
+    ctx.allow_insecure = true;
+    let (_dir, elf_path) = program::fetch_program(&ctx, &local, &stale_hash, work_dir.path(), true)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(elf_path).unwrap(), elf);
+
+    // Programs from anywhere but a local path are always verified
+    let uri = format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(&elf)
+    );
+    let result = program::fetch_program(
+        &ctx,
+        &ProgramLocation::DataUri(uri),
+        &stale_hash,
+        work_dir.path(),
+        true,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::ProgramHashMismatch { .. })
+    ));
+}
+
+// Records the ELFs it was asked to prove or warm (and for which proving type) instead of doing
+// either
+#[derive(Default)]
+struct RecordingProver {
+    elves: Mutex<Vec<Vec<u8>>>,
+    warmed: Mutex<Vec<(Vec<u8>, ProvingType)>>,
+}
+
+impl ProverBackend for RecordingProver {
+    fn prove<'a>(
+        &'a self,
+        elf: &'a [u8],
+        _inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> futures::future::BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        self.elves.lock().unwrap().push(elf.to_vec());
+        Box::pin(async move {
+            Ok(ProofResult {
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                ..Default::default()
+            })
+        })
+    }
+
+    fn warm<'a>(
+        &'a self,
+        elf: &'a [u8],
+        proving_type: &'a ProvingType,
+        _cache: &'a ProverCache,
+    ) -> futures::future::BoxFuture<'a, Result<bool, ProofServiceError>> {
+        self.warmed
+            .lock()
+            .unwrap()
+            .push((elf.to_vec(), proving_type.clone()));
+        Box::pin(async { Ok(true) })
+    }
+}
+
+// Overwrites the request's program file mid-proof, then records the ELF it was handed
+struct RewritingProver {
+    source: std::path::PathBuf,
+    elves: Mutex<Vec<Vec<u8>>>,
+}
+
+impl ProverBackend for RewritingProver {
+    fn prove<'a>(
+        &'a self,
+        elf: &'a [u8],
+        _inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> futures::future::BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        std::fs::write(&self.source, b"\x7fELF swapped under the prover").unwrap();
+        self.elves.lock().unwrap().push(elf.to_vec());
+        Box::pin(async move {
+            Ok(ProofResult {
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_mapped_programs_are_the_jobs_own_copy() {
+    let dir = tempdir().unwrap();
+    let elf_path = dir.path().join("program.elf");
+    let elf = b"\x7fELF mapped from the sandbox".to_vec();
+    std::fs::write(&elf_path, &elf).unwrap();
+    let prover = Arc::new(RewritingProver {
+        source: elf_path.clone(),
+        elves: Mutex::new(Vec::new()),
+    });
+    let mut ctx = setup_test_context();
+    ctx.mmap_programs = true;
+    ctx.prover_backend = prover.clone();
+
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(&elf)),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path.clone())),
+        ..Default::default()
+    };
+    generate_proof(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+
+    // The request's file changed while the job proved, the mapped copy didn't
+    assert_eq!(*prover.elves.lock().unwrap(), vec![elf.clone()]);
+    assert_ne!(std::fs::read(&elf_path).unwrap(), elf);
+    // The changed file no longer matches the program hash
+    assert!(matches!(
+        generate_proof(Context(ctx), TangleArg(request)).await,
+        Err(ProofServiceError::ProgramHashMismatch { .. })
+    ));
+}
+
+// Serves a registry listing the program with ELF `elf` at `location`
+async fn registry_listing(elf: &[u8], location: Url) -> MockServer {
+    let program_hash = B256::from_slice(&Sha256::digest(elf));
+    start_mock_rpc(move |method, _params| match method {
+        "eth_call" => Ok(json!(format!(
+            "0x{}",
+            hex::encode((program_hash, location.to_string()).abi_encode_params())
+        ))),
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_warmed_program_is_proven_without_fetching_it_again() {
+    let elf = b"\x7fELF warm me up".to_vec();
+    let program_hash = hex::encode(Sha256::digest(&elf));
+    let served = elf.clone();
+    let server = MockServer::start(move |_| MockResponse::ok(served.clone())).await;
+    let rpc = registry_listing(&elf, server.url_for("program.elf")).await;
+    let prover = Arc::new(RecordingProver::default());
+    let cache_dir = tempdir().unwrap();
+    let mut ctx = setup_test_context();
+    ctx.eth_rpc_url = rpc.url();
+    ctx.elf_cache = Some(ElfCache::new(cache_dir.path()).unwrap());
+    ctx.prover_backend = prover.clone();
+
+    let request = WarmProgramRequest {
+        program_hash: program_hash.clone(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    let TangleResult(warmed) = warm_program(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+    assert!(!warmed.already_cached);
+    assert!(warmed.prover_client_cached);
+    assert_eq!(warmed.elf_bytes, elf.len() as u64);
+    assert_eq!(server.hits(), 1);
+    assert_eq!(*prover.warmed.lock().unwrap(), vec![(
+        elf.clone(),
+        ProvingType::Fast
+    )]);
+
+    // No override and the registry gone: only the cache can supply the program
+    ctx.eth_rpc_url = Url::parse("http://127.0.0.1:1").unwrap();
+    let proof_request = ProofRequest {
+        program_hash: program_hash.clone(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    generate_proof(Context(ctx.clone()), TangleArg(proof_request))
+        .await
+        .unwrap();
+    assert_eq!(server.hits(), 1);
+    assert_eq!(*prover.elves.lock().unwrap(), vec![elf]);
+
+    let TangleResult(rewarmed) = warm_program(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert!(rewarmed.already_cached);
+    assert_eq!(server.hits(), 1);
+}
+
+#[test]
+fn test_identical_elves_are_stored_once_across_program_hashes() {
+    let cache_dir = tempdir().unwrap();
+    let cache = ElfCache::new(cache_dir.path()).unwrap();
+    let sources = tempdir().unwrap();
+    let elf_path = sources.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF shared").unwrap();
+
+    // The same program registered under two keys, e.g. hashed with two algorithms
+    let first = cache.insert(&"aa".repeat(32), &elf_path).unwrap();
+    let second = cache.insert(&"bb".repeat(32), &elf_path).unwrap();
+    assert_eq!(cache.get(&"aa".repeat(32)), Some(first.clone()));
+    assert_eq!(cache.get(&"bb".repeat(32)), Some(second.clone()));
+    assert_eq!(std::fs::read(&second).unwrap(), b"\x7fELF shared");
+
+    let stored = |cache: &ElfCache| std::fs::read_dir(cache.content_dir()).unwrap().count();
+    assert_eq!(stored(&cache), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let inode = |path: &Path| std::fs::metadata(path).unwrap().ino();
+        assert_eq!(inode(&first), inode(&second));
+    }
+
+    // Another ELF gets its own copy
+    std::fs::write(&elf_path, b"\x7fELF other").unwrap();
+    cache.insert(&"cc".repeat(32), &elf_path).unwrap();
+    assert_eq!(stored(&cache), 2);
+    assert_eq!(
+        std::fs::read(cache.get(&"aa".repeat(32)).unwrap()).unwrap(),
+        b"\x7fELF shared"
+    );
+}
+
+#[tokio::test]
+async fn test_elf_cache_reuses_a_known_digest_and_existing_entries() {
+    let cache_dir = tempdir().unwrap();
+    let cache = ElfCache::new(cache_dir.path()).unwrap();
+    let sources = tempdir().unwrap();
+    let elf_path = sources.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF digested").unwrap();
+    let sha256_hex = hex::encode(Sha256::digest(b"\x7fELF digested"));
+
+    // The stored copy is named by the digest handed in, not one computed again
+    let path = cache
+        .get_or_insert(&"aa".repeat(32), &elf_path, Some(&sha256_hex))
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"\x7fELF digested");
+    assert!(
+        cache
+            .content_dir()
+            .join(format!("{}.elf", sha256_hex))
+            .is_file()
+    );
+
+    // An existing entry is returned as is
+    std::fs::write(&elf_path, b"\x7fELF changed").unwrap();
+    let again = cache
+        .get_or_insert(&"aa".repeat(32), &elf_path, None)
+        .await
+        .unwrap();
+    assert_eq!(again, path);
+    assert_eq!(std::fs::read(&again).unwrap(), b"\x7fELF digested");
+
+    assert!(matches!(
+        cache.insert_with_digest(&"bb".repeat(32), &elf_path, Some("not hex")),
+        Err(ProofServiceError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_warm_program_requires_an_elf_cache() {
+    let request = WarmProgramRequest {
+        program_hash: B256::ZERO.to_string(),
+        ..Default::default()
+    };
+    let result = warm_program(Context(setup_test_context()), TangleArg(request)).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("ELF cache")
+    ));
+}
+
+#[tokio::test]
+async fn test_warm_program_is_refused_to_verify_only_nodes_and_unsigned_callers() {
+    let cache_dir = tempdir().unwrap();
+    let mut ctx = setup_test_context();
+    ctx.elf_cache = Some(ElfCache::new(cache_dir.path()).unwrap());
+    let request = WarmProgramRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        ..Default::default()
+    };
+
+    ctx.mode = ServiceMode::VerifyOnly;
+    let result = warm_program(Context(ctx.clone()), TangleArg(request.clone())).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::UnsupportedProvingType(msg)) if msg.contains("verify-only")
+    ));
+
+    ctx.mode = ServiceMode::ProveAndVerify;
+    ctx.authorized_keys = Some(HashSet::from([[7u8; 32]]));
+    let result = warm_program(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(result, Err(ProofServiceError::Unauthorized(_))));
+}
+
+#[test]
+fn test_elf_cache_evicts_least_recently_used_programs() {
+    let cache_dir = tempdir().unwrap();
+    let cache = ElfCache::new(cache_dir.path()).unwrap().with_max_bytes(20);
+    let sources = tempdir().unwrap();
+    let write = |name: &str, bytes: &[u8]| {
+        let path = sources.path().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    };
+    let hash = |byte: &str| byte.repeat(32);
+    let set_age = |path: &Path, secs_ago: u64| {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(secs_ago))
+            .unwrap();
+    };
+
+    let first = cache
+        .insert(&hash("aa"), &write("a.elf", b"\x7fELF aaaa"))
+        .unwrap();
+    cache
+        .insert(&hash("ab"), &write("a2.elf", b"\x7fELF aaaa"))
+        .unwrap();
+    set_age(&first, 60);
+    let second = cache
+        .insert(&hash("bb"), &write("b.elf", b"\x7fELF bbbb"))
+        .unwrap();
+    set_age(&second, 30);
+
+    // Using the older program makes the other one the least recently used
+    assert!(cache.get(&hash("aa")).is_some());
+    cache
+        .insert(&hash("cc"), &write("c.elf", b"\x7fELF cccc"))
+        .unwrap();
+    assert!(cache.get(&hash("aa")).is_some());
+    assert!(cache.get(&hash("ab")).is_some());
+    assert_eq!(cache.get(&hash("bb")), None);
+    assert!(cache.get(&hash("cc")).is_some());
+    assert_eq!(std::fs::read_dir(cache.content_dir()).unwrap().count(), 2);
+
+    // An ELF that can never fit is refused rather than emptying the cache
+    let result = cache.insert(&hash("dd"), &write("d.elf", &[0u8; 21]));
+    assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+    assert!(cache.get(&hash("cc")).is_some());
+}
+
+#[tokio::test]
+async fn test_programs_larger_than_max_elf_bytes_are_refused() {
+    let elf = vec![0x7fu8; 64];
+    let program_hash = hex::encode(Sha256::digest(&elf));
+    let served = elf.clone();
+    let server = MockServer::start(move |_| MockResponse::ok(served.clone())).await;
+    let mut ctx = setup_test_context();
+    ctx.max_elf_bytes = 63;
+    let work_dir = tempdir().unwrap();
+
+    let location = ProgramLocation::RemoteUrl(server.url_for("program.elf"));
+    let result =
+        program::fetch_and_verify_program(&ctx, &location, &program_hash, work_dir.path()).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::ProgramDownloadFailed(msg)) if msg.contains("max_elf_bytes")
+    ));
+    let data_uri = ProgramLocation::DataUri(format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(&elf)
+    ));
+    let result =
+        program::fetch_and_verify_program(&ctx, &data_uri, &program_hash, work_dir.path()).await;
+    assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+
+    ctx.max_elf_bytes = 64;
+    program::fetch_and_verify_program(&ctx, &location, &program_hash, work_dir.path())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_unsupported_location_override_fails_before_fetching() {
+    let elf = b"\x7fELF over http".to_vec();
+    let program_hash = hex::encode(Sha256::digest(&elf));
+    let served = elf.clone();
+    let server = MockServer::start(move |_| MockResponse::ok(served.clone())).await;
+    let prover = Arc::new(RecordingProver::default());
+    let mut ctx = setup_test_context();
+    ctx.prover_backend = prover.clone();
+
+    // Downloads go over HTTP(S); data URIs and local paths need no fetcher
+    let supported = ProgramLocation::RemoteUrl(server.url_for("program.elf"));
+    assert!(supported.is_supported(&ctx));
+    assert!(ProgramLocation::DataUri("data:;base64,AA==".to_string()).is_supported(&ctx));
+    assert!(ProgramLocation::LocalPath("program.elf".into()).is_supported(&ctx));
+    let request = |location: ProgramLocation| ProofRequest {
+        program_hash: program_hash.clone(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    };
+    generate_proof(Context(ctx.clone()), TangleArg(request(supported)))
+        .await
+        .unwrap();
+    assert_eq!(server.hits(), 1);
+
+    // This build has no IPFS or S3 fetcher
+    for url in [
+        "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+        "s3://programs/p.elf",
+    ] {
+        let unsupported = ProgramLocation::RemoteUrl(Url::parse(url).unwrap());
+        assert!(!unsupported.is_supported(&ctx));
+        let result = generate_proof(Context(ctx.clone()), TangleArg(request(unsupported))).await;
+        assert!(matches!(
+            result,
+            Err(ProofServiceError::InvalidInput(msg))
+                if msg.starts_with("Unsupported program_location_override") && msg.contains("http, https")
+        ));
+    }
+    assert_eq!(prover.elves.lock().unwrap().len(), 1);
+}
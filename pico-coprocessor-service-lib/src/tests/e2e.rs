@@ -1,18 +1,25 @@
 // tests/integration_tests.rs
 use crate::{
     BlockchainData, CoprocessorProofRequest, GENERATE_COPROCESSOR_PROOF_JOB_ID,
-    GENERATE_PROOF_JOB_ID, MaxSizes, ProgramLocation, ProofRequest, ProofResult, ProofServiceError,
-    ProvingType, SerializableLog, SerializableReceipt, ServiceContext, generate_coprocessor_proof,
-    generate_proof, jobs::coprocessor::CoprocessorInputBundle,
+    GENERATE_PROOF_JOB_ID, InputPreprocessor, MaxSizes, ProgramLocation, ProofRequest, ProofResult,
+    ProofServiceError, ProvingType, SerializableLog, SerializableReceipt, ServiceContext,
+    fixtures::CoprocessorFixtureBuilder,
+    generate_coprocessor_proof, generate_proof,
+    jobs::coprocessor::{CoprocessorInputBundle, build_input_bundle},
+    validate_max_sizes, validate_receipt_log_structure,
 };
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use blueprint_sdk::{
     alloy::primitives::{Address, B256, U256, keccak256}, // Import alloy types
     extract::Context,
     tangle::extract::{Optional, TangleArg, TangleResult},
 };
 use hex::FromHex;
+use sha2::{Digest, Sha256};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 use tempfile::tempdir;
 use url::Url; // For checking input encoding
 
@@ -39,6 +46,7 @@ async fn test_generate_proof_job_invalid_hash_format() {
         program_location_override: None,
         eth_rpc_url_override: None,
         registry_address_override: None,
+        ..Default::default()
     };
     let tangle_arg = TangleArg(request);
     let job_context = Context(ctx);
@@ -59,6 +67,7 @@ async fn test_generate_proof_job_invalid_input_hex() {
         program_location_override: None,
         eth_rpc_url_override: None,
         registry_address_override: None,
+        ..Default::default()
     };
     let tangle_arg = TangleArg(request);
     let job_context = Context(ctx);
@@ -110,6 +119,7 @@ async fn test_coprocessor_job_invalid_hash_format() {
         program_location_override: None,
         eth_rpc_url_override: None,
         registry_address_override: None,
+        ..Default::default()
     };
     let tangle_arg = TangleArg(request);
     let job_context = Context(ctx);
@@ -135,6 +145,7 @@ async fn test_coprocessor_job_invalid_max_sizes_zero() {
         program_location_override: None,
         eth_rpc_url_override: None,
         registry_address_override: None,
+        ..Default::default()
     };
     let tangle_arg = TangleArg(request);
     let job_context = Context(ctx);
@@ -160,6 +171,7 @@ async fn test_coprocessor_job_invalid_max_sizes_multiple() {
         program_location_override: None,
         eth_rpc_url_override: None,
         registry_address_override: None,
+        ..Default::default()
     };
     let tangle_arg = TangleArg(request);
     let job_context = Context(ctx);
@@ -182,8 +194,11 @@ fn test_coprocessor_input_bundle_serialization() {
                     address: Address::from_str("0x...").unwrap_or_default(),
                     topics: vec![B256::from_str("0x...").unwrap_or_default()],
                     data_hex: "0123".to_string(),
+                    log_pos: None,
                 }],
                 raw_data_hex: "f8...".to_string(),
+                inclusion_proof: None,
+                block_number: None,
             }]),
             storage_slots: None,
             transactions: None,
@@ -193,6 +208,7 @@ fn test_coprocessor_input_bundle_serialization() {
             max_storage_size: 32,
             max_tx_size: 32,
         },
+        ..Default::default()
     };
 
     let encoded = serde_json::to_vec(&bundle).unwrap();
@@ -203,6 +219,83 @@ fn test_coprocessor_input_bundle_serialization() {
     assert_eq!(bundle, decoded);
 }
 
+// Drops receipts whose status is not 1
+struct SuccessfulReceiptsOnly;
+
+impl InputPreprocessor for SuccessfulReceiptsOnly {
+    fn process(&self, mut data: BlockchainData) -> Result<BlockchainData, ProofServiceError> {
+        if let Some(receipts) = data.receipts.as_mut() {
+            receipts.retain(|r| r.status == Some(U256::from(1)));
+        }
+        Ok(data)
+    }
+}
+
+#[test]
+fn test_input_preprocessor_filters_bundle() {
+    let mut ctx = setup_test_context();
+    ctx.input_preprocessors
+        .push(Arc::new(SuccessfulReceiptsOnly));
+
+    let mut blockchain_data = CoprocessorFixtureBuilder::new()
+        .num_receipts(3)
+        .build()
+        .blockchain_data;
+    blockchain_data.receipts.as_mut().unwrap()[1].status = Some(U256::ZERO);
+    let request = CoprocessorProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        blockchain_data,
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        ..Default::default()
+    };
+
+    let bundle = build_input_bundle(&ctx, &request).unwrap();
+    let receipts = bundle.data.receipts.unwrap();
+    assert_eq!(receipts.len(), 2);
+    assert!(receipts.iter().all(|r| r.status == Some(U256::from(1))));
+    // The request itself is left untouched
+    assert_eq!(request.blockchain_data.receipts.unwrap().len(), 3);
+}
+
+#[test]
+fn test_sdk_init_params_match_blockchain_data() {
+    let mut blockchain_data = CoprocessorFixtureBuilder::new()
+        .num_receipts(5)
+        .build()
+        .blockchain_data;
+    assert_eq!(blockchain_data.sdk_init_params(), (5, 0, 0));
+
+    blockchain_data.storage_slots = Some(Vec::new());
+    blockchain_data.transactions = None;
+    assert_eq!(blockchain_data.sdk_init_params(), (5, 0, 0));
+    assert_eq!(BlockchainData::default().sdk_init_params(), (0, 0, 0));
+}
+
+#[test]
+fn test_fixture_builder_output_is_consistent() {
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(40).build();
+    assert_eq!(fixture.blockchain_data.sdk_init_params(), (40, 0, 0));
+    assert_eq!(fixture.max_sizes.max_receipt_size, 64);
+    assert!(validate_max_sizes(&fixture.max_sizes, &fixture.blockchain_data).is_ok());
+    assert!(validate_receipt_log_structure(&fixture.blockchain_data).is_ok());
+
+    let single = CoprocessorFixtureBuilder::new().build();
+    assert_eq!(
+        fixture.expected_volume,
+        single.expected_volume * U256::from(40)
+    );
+    let ctx = setup_test_context();
+    let request = fixture.request(B256::ZERO.to_string(), PathBuf::from("program.elf"));
+    assert_eq!(
+        build_input_bundle(&ctx, &request).unwrap(),
+        fixture.input_bundle()
+    );
+}
+
 // --- process_coprocessor_proof Full E2E Test ---
 
 #[tokio::test]
@@ -219,28 +312,14 @@ async fn test_coprocessor_job_trading_volume_e2e() {
     let program_hash = hex::encode(elf_hash);
     println!("Test ELF Hash: {}", program_hash);
 
-    // 2. Prepare Input Data (mimicking prepare_test_receipts)
+    // 2. Prepare Input Data from the shared trading-volume fixture
     let max_receipts_for_test = 4; // Use a smaller number for faster testing
-    let (blockchain_data, expected_volume) = prepare_test_blockchain_data(max_receipts_for_test);
+    let fixture = CoprocessorFixtureBuilder::new()
+        .num_receipts(max_receipts_for_test)
+        .build();
 
-    let max_sizes = MaxSizes {
-        max_receipt_size: max_receipts_for_test * 32, // Example sizing, needs adjustment based on actual data
-        max_storage_size: 32,                         // Minimal size if not used
-        max_tx_size: 32,                              // Minimal size if not used
-    };
-    // Ensure sizes are valid
-    assert!(max_sizes.max_receipt_size > 0 && max_sizes.max_receipt_size % 32 == 0);
-
-    // 3. Construct Request using LocalPath override
-    let request = CoprocessorProofRequest {
-        program_hash,
-        blockchain_data: blockchain_data.clone(), // Clone data for potential later use/assertion
-        max_sizes: max_sizes.clone(),             // Clone sizes
-        proving_type: ProvingType::Fast,          // Use Fast for testing (no Docker needed)
-        program_location_override: Some(ProgramLocation::LocalPath(elf_path)), // Override location
-        eth_rpc_url_override: None,
-        registry_address_override: None,
-    };
+    // 3. Construct Request using LocalPath override, Fast proving (no Docker needed)
+    let request = fixture.request(program_hash, elf_path);
 
     // --- Execute Job ---
     let tangle_arg = TangleArg(request.clone()); // Clone request
@@ -256,14 +335,13 @@ async fn test_coprocessor_job_trading_volume_e2e() {
     // Verify Proving Type and Hash
     assert_eq!(proof_result.proving_type, ProvingType::Fast);
     assert_eq!(proof_result.program_hash, request.program_hash);
+    assert_eq!(
+        proof_result.sdk_init_params,
+        Some((max_receipts_for_test as u32, 0, 0))
+    );
 
     // Verify Inputs field (should be hex of SCALE encoded CoprocessorInputBundle)
-    let expected_input_bundle = CoprocessorInputBundle {
-        data: blockchain_data, // Use the same data used in the request
-        sizes: max_sizes,      // Use the same sizes used in the request
-    };
-    let expected_input_hex = hex::encode(serde_json::to_vec(&expected_input_bundle).unwrap());
-    assert_eq!(proof_result.inputs, expected_input_hex);
+    assert_eq!(proof_result.inputs, fixture.inputs_hex());
 
     // Verify Public Values (should be hex of volume.to_be_bytes())
     // Decode the hex public values back into bytes, then into U256
@@ -276,10 +354,10 @@ async fn test_coprocessor_job_trading_volume_e2e() {
     );
     let result_volume = U256::from_be_slice(&public_value_bytes);
 
-    println!("Expected Volume: {}", expected_volume);
+    println!("Expected Volume: {}", fixture.expected_volume);
     println!("Result Volume:   {}", result_volume);
     assert_eq!(
-        result_volume, expected_volume,
+        result_volume, fixture.expected_volume,
         "Public value (volume) does not match expected"
     );
 
@@ -288,57 +366,24 @@ async fn test_coprocessor_job_trading_volume_e2e() {
     assert!(hex::decode(&proof_result.proof).is_ok());
 }
 
-// Helper function to create test BlockchainData mimicking trading_volumn_lib
-// Returns the data and the expected final volume U256
-fn prepare_test_blockchain_data(num_receipts: usize) -> (BlockchainData, U256) {
-    // Data from trading_volumn_lib
-    let transaction_hash_hex = "0xd97c7863076f6b8a2430f3cc363220a1d67ee990d2673c927c93822fa541d39c";
-    let transaction_hash = B256::from_str(transaction_hash_hex).unwrap();
-    let usdc_pool_hex = "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640";
-    let usdc_pool = Address::from_str(usdc_pool_hex).unwrap();
-    let event_swap_hex = "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67";
-    let event_swap = B256::from_str(event_swap_hex).unwrap();
-    let value_hex = "0000000000000000000000000000000000000000000000010d12bdb167e201e0";
-    let value = U256::from_str(value_hex).unwrap();
-    let user_addr_hex = "0000000000000000000000006a000f20005980200259b80c5102003040001068";
-    let user_addr = B256::from_str(user_addr_hex).unwrap();
-
-    // Create the log data roughly matching field_0 and field_1 structure from the example
-    // Note: We need SerializableLog, not SdkLogFieldData here if using wrappers.
-    let log_0 = SerializableLog {
-        address: usdc_pool,
-        topics: vec![event_swap],        // Assuming topic is in topics vec
-        data_hex: value_hex.to_string(), // Assuming value maps to data_hex
-    };
-    let log_1 = SerializableLog {
-        address: usdc_pool,
-        topics: vec![event_swap, user_addr], // Assuming user addr is topic 1
-        data_hex: "".to_string(),            // No data part for this log field in example
-    };
-
-    let mut test_receipts = Vec::with_capacity(num_receipts);
-    for _ in 0..num_receipts {
-        // Reconstruct SerializableReceipt based on what the test program expects
-        // We need to ensure the structure matches what the zkVM program converts from/to.
-        // This mapping is crucial and depends on the exact fields used.
-        test_receipts.push(SerializableReceipt {
-            transaction_hash: transaction_hash,
-            status: Some(U256::from(1)), // Assume success
-            // Combine logs similar to how fields were combined in original example
-            // The test zkVM program expects specific logs/fields.
-            logs: vec![log_0.clone(), log_1.clone()], // Example pairing
-            raw_data_hex: "".to_string(),             // Not used in simplified zkVM logic
-        });
-    }
-
-    let blockchain_data = BlockchainData {
-        receipts: Some(test_receipts),
-        storage_slots: None,
-        transactions: None,
-    };
-
-    // Calculate expected volume (sum of 'value' from log_0 for each receipt)
-    let expected_volume = value.checked_mul(U256::from(num_receipts as u64)).unwrap();
-
-    (blockchain_data, expected_volume)
+#[tokio::test]
+async fn test_coprocessor_job_proves_program_from_data_uri() {
+    let ctx = setup_test_context();
+    let elf_bytes = std::fs::read("./tests/fixtures/trading_volume.elf")
+        .expect("Failed to read test ELF file at tests/fixtures/trading_volume.elf");
+    let program_hash = hex::encode(Sha256::digest(&elf_bytes));
+
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(2).build();
+    let mut request = fixture.request(program_hash, PathBuf::new());
+    request.program_location_override = Some(ProgramLocation::DataUri(format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(&elf_bytes)
+    )));
+
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    let proof_result = result.expect("Job failed").0;
+    assert_eq!(
+        U256::from_be_slice(&hex::decode(&proof_result.public_values).unwrap()),
+        fixture.expected_volume
+    );
 }
@@ -0,0 +1,551 @@
+use crate::{
+    CheckpointStore, CoprocessorProofRequest, InputsEncoding, MaxSizes, OutputEncoding,
+    ProgramLocation, ProofMetadata, ProofRequest, ProofResult, ProofServiceError, ProveOptions,
+    ProverBackend, ProvingType, RemoteProver, ServiceContext, compress_public_values, decode_hex,
+    fixtures::CoprocessorFixtureBuilder,
+    generate_coprocessor_proof, generate_proof,
+    jobs::{
+        attach_scale_metadata,
+        coprocessor::{CoprocessorInputBundle, build_input_bundle},
+    },
+    reencode_result,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256, keccak256},
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::{TempDir, tempdir};
+use url::Url;
+
+// Shaped like an `execute_pico_prove` Fast result, which is always produced hex encoded
+fn fast_result() -> ProofResult {
+    ProofResult {
+        public_values: hex::encode([0u8, 0, 0, 42]),
+        proof: hex::encode(br#"{"commitments":[1,2,3]}"#),
+        proving_type: ProvingType::Fast,
+        program_hash: "0x01".to_string(),
+        inputs: hex::encode([7u8; 5]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_hex_output_is_default_and_unchanged() {
+    let result = reencode_result(fast_result(), OutputEncoding::default()).unwrap();
+    assert_eq!(result.output_encoding, OutputEncoding::Hex);
+    assert_eq!(result.proof, fast_result().proof);
+}
+
+#[test]
+fn test_unset_optional_result_fields_are_serialized_as_null() {
+    let value = serde_json::to_value(fast_result()).unwrap();
+    for field in [
+        "output_dir",
+        "proof_id",
+        "sdk_init_params",
+        "used_sizes",
+        "public_values_decoded",
+        "merkle_commitment",
+        "riscv_proof",
+        "nonce",
+        "expires_at",
+        "scale_metadata_hex",
+        "debug_log",
+        "phase_timings",
+        "evm_inputs",
+        "chain_id",
+        "registry_address",
+    ] {
+        assert_eq!(
+            value.get(field),
+            Some(&serde_json::Value::Null),
+            "{}",
+            field
+        );
+    }
+    assert_eq!(value["tags"], serde_json::json!({}));
+
+    // Results serialized before the fields were always present still parse
+    let mut legacy = value;
+    legacy.as_object_mut().unwrap().remove("output_dir");
+    legacy.as_object_mut().unwrap().remove("riscv_proof");
+    legacy.as_object_mut().unwrap().remove("tags");
+    let parsed: ProofResult = serde_json::from_value(legacy).unwrap();
+    assert_eq!(parsed.output_dir, None);
+    assert_eq!(parsed.riscv_proof, None);
+    assert!(parsed.tags.is_empty());
+}
+
+#[test]
+fn test_base64_output_round_trips() {
+    let original = fast_result();
+    let encoded = reencode_result(original.clone(), OutputEncoding::Base64).unwrap();
+    assert_eq!(encoded.output_encoding, OutputEncoding::Base64);
+    assert_eq!(encoded.public_values, "AAAAKg==");
+    assert!(encoded.proof.len() < original.proof.len());
+
+    // Decoding with the recorded encoding recovers the raw bytes
+    let encoding = encoded.output_encoding;
+    assert_eq!(
+        encoding.decode(&encoded.proof).unwrap(),
+        hex::decode(&original.proof).unwrap()
+    );
+    assert_eq!(encoding.decode(&encoded.inputs).unwrap(), vec![7u8; 5]);
+
+    // ...and survives serialization plus conversion back to hex
+    let json = serde_json::to_string(&encoded).unwrap();
+    let decoded: ProofResult = serde_json::from_str(&json).unwrap();
+    let back = reencode_result(decoded, OutputEncoding::Hex).unwrap();
+    assert_eq!(back.proof, original.proof);
+    assert_eq!(back.public_values, original.public_values);
+    assert_eq!(back.inputs, original.inputs);
+}
+
+// Backend answering every proof with `fast_result`, standing in for the prover
+struct FixedProver;
+
+impl ProverBackend for FixedProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        _inputs_hex: &'a str,
+        _proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async { Ok(fast_result()) })
+    }
+}
+
+// Context proving with `FixedProver`, and a local program its requests can point at
+fn fixed_prover_context() -> (ServiceContext, TempDir, ProgramLocation, String) {
+    let temp_base = tempdir().unwrap();
+    let mut ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base.path().join("service"),
+    )
+    .unwrap();
+    ctx.prover_backend = Arc::new(FixedProver);
+
+    let elf_path = temp_base.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+    let program_hash = hex::encode(Sha256::digest(b"\x7fELF"));
+    (
+        ctx,
+        temp_base,
+        ProgramLocation::LocalPath(elf_path),
+        program_hash,
+    )
+}
+
+#[tokio::test]
+async fn test_generate_proof_reports_hex_inputs() {
+    let (ctx, _temp, location, program_hash) = fixed_prover_context();
+    let request = ProofRequest {
+        program_hash,
+        inputs: "0a0b".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    };
+
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.inputs_encoding, InputsEncoding::Hex);
+    assert_eq!(result.decode_inputs().unwrap(), vec![0x0a, 0x0b]);
+}
+
+#[test]
+fn test_hex_decodes_the_same_with_or_without_0x_prefix() {
+    for prefixed in ["0x0a0b", "0X0a0b"] {
+        assert_eq!(decode_hex(prefixed).unwrap(), decode_hex("0a0b").unwrap());
+    }
+    assert_eq!(decode_hex("0x").unwrap(), Vec::<u8>::new());
+    assert!(decode_hex("0x0g").is_err());
+    assert_eq!(OutputEncoding::Hex.decode("0x0a0b").unwrap(), vec![
+        0x0a, 0x0b
+    ]);
+}
+
+#[tokio::test]
+async fn test_generate_proof_accepts_0x_prefixed_inputs() {
+    let (ctx, _temp, location, program_hash) = fixed_prover_context();
+    let request = ProofRequest {
+        program_hash,
+        inputs: "0x0a0b".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    };
+
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.decode_inputs().unwrap(), vec![0x0a, 0x0b]);
+}
+
+#[test]
+fn test_0x_prefixed_blockchain_data_builds_the_same_bundle() {
+    let ctx = fixed_prover_context().0;
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(2).build();
+    let request = fixture.request(B256::ZERO.to_string(), "program.elf".into());
+
+    let mut prefixed = request.clone();
+    for receipt in prefixed.blockchain_data.receipts.iter_mut().flatten() {
+        receipt.raw_data_hex = format!("0x{}", receipt.raw_data_hex);
+        for log in &mut receipt.logs {
+            log.data_hex = format!("0x{}", log.data_hex);
+        }
+    }
+    assert_ne!(prefixed.blockchain_data, request.blockchain_data);
+    assert_eq!(
+        build_input_bundle(&ctx, &prefixed).unwrap(),
+        build_input_bundle(&ctx, &request).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_coprocessor_proof_reports_hex_of_json_inputs() {
+    let (ctx, _temp, location, program_hash) = fixed_prover_context();
+    let request = CoprocessorProofRequest {
+        program_hash,
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        output_encoding: OutputEncoding::Base64,
+        ..Default::default()
+    };
+
+    let TangleResult(result) = generate_coprocessor_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.inputs_encoding, InputsEncoding::HexOfJson);
+    // Whatever the output encoding, the decoded inputs are the bundle's JSON
+    let bundle: CoprocessorInputBundle =
+        serde_json::from_slice(&result.decode_inputs().unwrap()).unwrap();
+    assert_eq!(bundle.sizes.max_receipt_size, 32);
+}
+
+#[test]
+fn test_json_text_inputs_are_not_reencoded() {
+    let result = ProofResult {
+        inputs: r#"{"data":{}}"#.to_string(),
+        inputs_encoding: InputsEncoding::JsonUtf8,
+        ..fast_result()
+    };
+    let encoded = reencode_result(result, OutputEncoding::Base64).unwrap();
+    assert_eq!(encoded.inputs, r#"{"data":{}}"#);
+    assert_eq!(encoded.decode_inputs().unwrap(), br#"{"data":{}}"#);
+}
+
+#[tokio::test]
+async fn test_private_inputs_are_not_echoed() {
+    let (ctx, _temp, location, program_hash) = fixed_prover_context();
+    let request = CoprocessorProofRequest {
+        program_hash: program_hash.clone(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location.clone()),
+        private_inputs: true,
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request))
+        .await
+        .unwrap();
+    assert!(result.inputs.is_empty());
+    // Only the public values are revealed
+    assert_eq!(result.public_values, fast_result().public_values);
+
+    let request = ProofRequest {
+        program_hash,
+        inputs: "0a0b".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        private_inputs: true,
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert!(result.inputs.is_empty());
+}
+
+#[tokio::test]
+async fn test_private_inputs_never_leave_the_process() {
+    let (mut ctx, temp, location, program_hash) = fixed_prover_context();
+    let dumps = temp.path().join("dumps");
+    std::fs::create_dir(&dumps).unwrap();
+    ctx.dump_inputs_dir = Some(dumps.clone());
+    let coprocessor_request = CoprocessorProofRequest {
+        program_hash: program_hash.clone(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location.clone()),
+        private_inputs: true,
+        dump_inputs_to: Some("inputs.json".to_string()),
+        ..Default::default()
+    };
+    let result =
+        generate_coprocessor_proof(Context(ctx.clone()), TangleArg(coprocessor_request)).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("dump_inputs_to")
+    ));
+    assert!(!dumps.join("inputs.json").exists());
+
+    // Nor are they handed to a prover elsewhere
+    ctx.prover_backend = Arc::new(RemoteProver::new(
+        Url::parse("http://127.0.0.1:1/prove").unwrap(),
+    ));
+    let request = ProofRequest {
+        program_hash,
+        inputs: "0a0b".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        private_inputs: true,
+        ..Default::default()
+    };
+    let result = generate_proof(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("private_inputs")
+    ));
+}
+
+// `FixedProver` that records how many checkpoints were pending while it proved
+struct CheckpointProbe {
+    store: CheckpointStore,
+    pending: Mutex<Vec<usize>>,
+}
+
+impl ProverBackend for CheckpointProbe {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        _inputs_hex: &'a str,
+        _proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        let pending = self.store.pending().unwrap().len();
+        self.pending.lock().unwrap().push(pending);
+        Box::pin(async {
+            Ok(ProofResult {
+                proving_type: ProvingType::Full,
+                ..fast_result()
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_private_inputs_are_not_checkpointed() {
+    let (mut ctx, temp, location, program_hash) = fixed_prover_context();
+    let store = CheckpointStore::new(temp.path().join("checkpoints")).unwrap();
+    let probe = Arc::new(CheckpointProbe {
+        store: store.clone(),
+        pending: Mutex::new(Vec::new()),
+    });
+    ctx.checkpoints = Some(store);
+    ctx.prover_backend = probe.clone();
+
+    for private_inputs in [false, true] {
+        let request = ProofRequest {
+            program_hash: program_hash.clone(),
+            inputs: "0a0b".to_string(),
+            proving_type: ProvingType::Full,
+            program_location_override: Some(location.clone()),
+            private_inputs,
+            ..Default::default()
+        };
+        generate_proof(Context(ctx.clone()), TangleArg(request))
+            .await
+            .unwrap();
+    }
+    // Checkpointed while proving, except with private inputs
+    assert_eq!(*probe.pending.lock().unwrap(), vec![1, 0]);
+}
+
+#[test]
+fn test_scale_metadata_round_trips() {
+    let result = ProofResult {
+        program_hash: B256::repeat_byte(0xab).to_string(),
+        ..fast_result()
+    };
+    let scale = result.to_scale().unwrap();
+    let metadata = ProofMetadata::from_scale(&scale).unwrap();
+    assert_eq!(metadata.program_hash, [0xab; 32]);
+    assert_eq!(metadata.public_values, vec![0, 0, 0, 42]);
+    assert_eq!(
+        metadata.proof_commitment,
+        keccak256(br#"{"commitments":[1,2,3]}"#).0
+    );
+
+    // The metadata describes the proof, not how the result happens to be encoded
+    let base64 = reencode_result(result, OutputEncoding::Base64).unwrap();
+    assert_eq!(base64.to_scale().unwrap(), scale);
+
+    assert!(matches!(
+        ProofMetadata::from_scale(&scale[..40]),
+        Err(ProofServiceError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_scale_metadata_attached_on_request() {
+    let (ctx, _temp, location, program_hash) = fixed_prover_context();
+    let request = ProofRequest {
+        program_hash,
+        inputs: "0a0b".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    };
+
+    let TangleResult(plain) = generate_proof(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+    assert!(plain.scale_metadata_hex.is_none());
+
+    let request = ProofRequest {
+        include_scale_metadata: true,
+        ..request
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    let scale = hex::decode(result.scale_metadata_hex.as_ref().unwrap()).unwrap();
+    let metadata = ProofMetadata::from_scale(&scale).unwrap();
+    assert_eq!(hex::encode(metadata.program_hash), result.program_hash);
+    assert_eq!(metadata.public_values, vec![0, 0, 0, 42]);
+}
+
+#[test]
+fn test_unencodable_scale_metadata_leaves_the_proof_intact() {
+    // fast_result's program_hash isn't 32 bytes, so it has no SCALE encoding
+    let result = attach_scale_metadata(fast_result(), true);
+    assert!(result.scale_metadata_hex.is_none());
+    assert_eq!(result.proof, fast_result().proof);
+    assert_eq!(result.public_values, fast_result().public_values);
+}
+
+#[test]
+fn test_compressed_public_values_round_trip() {
+    // Many labeled 32-byte fields, as a large coprocessor commit would hold
+    let raw: Vec<u8> = (0..512u32)
+        .flat_map(|i| {
+            let mut field = [0u8; 32];
+            field[28..].copy_from_slice(&i.to_be_bytes());
+            field
+        })
+        .collect();
+    let result = ProofResult {
+        public_values: hex::encode(&raw),
+        program_hash: B256::repeat_byte(0xab).to_string(),
+        ..fast_result()
+    };
+    assert!(!result.public_values_compressed);
+
+    let compressed = compress_public_values(result.clone()).unwrap();
+    assert!(compressed.public_values_compressed);
+    assert!(compressed.public_values.len() < result.public_values.len() / 4);
+    assert_eq!(compressed.public_values_bytes().unwrap(), raw);
+    // Compressing twice is a no-op, and the flag survives serialization and re-encoding
+    let again = compress_public_values(compressed.clone()).unwrap();
+    assert_eq!(again.public_values, compressed.public_values);
+    let json = serde_json::to_string(&compressed).unwrap();
+    let decoded: ProofResult = serde_json::from_str(&json).unwrap();
+    let base64 = reencode_result(decoded, OutputEncoding::Base64).unwrap();
+    assert_eq!(base64.public_values_bytes().unwrap(), raw);
+    // SCALE metadata carries the committed bytes, not their compression
+    assert_eq!(base64.to_scale().unwrap(), result.to_scale().unwrap());
+
+    // Decompression stops at the limit instead of expanding whatever a result holds
+    assert_eq!(
+        compressed.public_values_bytes_within(raw.len()).unwrap(),
+        raw
+    );
+    assert!(matches!(
+        compressed.public_values_bytes_within(raw.len() - 1),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("expand past")
+    ));
+}
+
+#[tokio::test]
+async fn test_public_values_compressed_only_on_request() {
+    let (ctx, _temp, location, program_hash) = fixed_prover_context();
+    let request = ProofRequest {
+        program_hash,
+        inputs: "0a0b".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    };
+
+    let TangleResult(plain) = generate_proof(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+    assert!(!plain.public_values_compressed);
+    assert_eq!(plain.public_values, hex::encode([0u8, 0, 0, 42]));
+
+    let request = ProofRequest {
+        compress_public_values: true,
+        ..request
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert!(result.public_values_compressed);
+    assert_eq!(result.public_values_bytes().unwrap(), vec![0, 0, 0, 42]);
+}
+
+#[tokio::test]
+async fn test_results_carry_keccak_commitment_of_the_proof() {
+    let (ctx, _temp, location, program_hash) = fixed_prover_context();
+    let request = ProofRequest {
+        program_hash,
+        inputs: "0a0b".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    };
+
+    let TangleResult(result) = generate_proof(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+    let proof_bytes = hex::decode(&result.proof).unwrap();
+    assert_eq!(result.proof_commitment, keccak256(&proof_bytes).to_string());
+
+    // The commitment identifies the proof, not the encoding it was returned in
+    let request = ProofRequest {
+        output_encoding: OutputEncoding::Base64,
+        ..request
+    };
+    let TangleResult(base64) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(base64.proof_commitment, result.proof_commitment);
+    assert_eq!(
+        base64.compute_proof_commitment().unwrap().to_string(),
+        result.proof_commitment
+    );
+}
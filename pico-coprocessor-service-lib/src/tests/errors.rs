@@ -0,0 +1,239 @@
+use crate::{JobError, ProofServiceError};
+
+fn network_error() -> reqwest::Error {
+    reqwest::Client::new().get("not a url").build().unwrap_err()
+}
+
+fn blueprint_sdk_error() -> blueprint_sdk::Error {
+    blueprint_sdk::Error::Other("x".into())
+}
+
+// Number of `ProofServiceError` variants, each with an index below
+const VARIANT_COUNT: usize = 23;
+
+// No wildcard arm, so a new variant doesn't compile until it is given an index here and a case
+// in every table checked with `assert_every_variant_covered`
+fn variant_index(err: &ProofServiceError) -> usize {
+    match err {
+        ProofServiceError::ConfigError(_) => 0,
+        ProofServiceError::ContractCallError(_) => 1,
+        ProofServiceError::NetworkError(_) => 2,
+        ProofServiceError::IoError(_) => 3,
+        ProofServiceError::ProgramNotFoundInRegistry(_) => 4,
+        ProofServiceError::ProgramDownloadFailed(_) => 5,
+        ProofServiceError::ProgramHashMismatch { .. } => 6,
+        ProofServiceError::InvalidInput(_) => 7,
+        ProofServiceError::InputsTooLarge { .. } => 8,
+        ProofServiceError::ProvingError(_) => 9,
+        ProofServiceError::ArtifactMissing { .. } => 10,
+        ProofServiceError::SerdeError(_) => 11,
+        ProofServiceError::BlockchainError(_) => 12,
+        ProofServiceError::ChainDataMismatch(_) => 13,
+        ProofServiceError::InvalidUrl(_) => 14,
+        ProofServiceError::UnsupportedProvingType(_) => 15,
+        ProofServiceError::TempDirError(_) => 16,
+        ProofServiceError::HexError(_) => 17,
+        ProofServiceError::Unauthorized(_) => 18,
+        ProofServiceError::RateLimited { .. } => 19,
+        ProofServiceError::ShuttingDown => 20,
+        ProofServiceError::InternalError(_) => 21,
+        ProofServiceError::BlueprintSdkError(_) => 22,
+    }
+}
+
+fn assert_every_variant_covered<T>(cases: &[(ProofServiceError, T)]) {
+    let mut covered = [false; VARIANT_COUNT];
+    for (err, _) in cases {
+        covered[variant_index(err)] = true;
+    }
+    let missing: Vec<usize> = (0..VARIANT_COUNT).filter(|&i| !covered[i]).collect();
+    assert!(missing.is_empty(), "variants without a case: {:?}", missing);
+}
+
+#[test]
+fn test_error_codes_are_stable() {
+    let cases = [
+        (ProofServiceError::InvalidInput("x".into()), 1000),
+        (
+            ProofServiceError::InputsTooLarge { size: 2, limit: 1 },
+            1001,
+        ),
+        (ProofServiceError::UnsupportedProvingType("x".into()), 1002),
+        (
+            ProofServiceError::SerdeError(serde_json::from_str::<u8>("x").unwrap_err()),
+            1003,
+        ),
+        (
+            ProofServiceError::HexError(hex::FromHexError::OddLength),
+            1004,
+        ),
+        (
+            ProofServiceError::InvalidUrl(url::ParseError::EmptyHost),
+            1005,
+        ),
+        (ProofServiceError::Unauthorized("x".into()), 1006),
+        (
+            ProofServiceError::RateLimited {
+                retry_after: std::time::Duration::from_secs(1),
+            },
+            1007,
+        ),
+        (
+            ProofServiceError::ProgramNotFoundInRegistry("x".into()),
+            2000,
+        ),
+        (ProofServiceError::ProgramDownloadFailed("x".into()), 2001),
+        (
+            ProofServiceError::ProgramHashMismatch {
+                expected: "a".into(),
+                got: "b".into(),
+            },
+            2002,
+        ),
+        (ProofServiceError::NetworkError(network_error()), 2003),
+        (ProofServiceError::ProvingError("x".into()), 3000),
+        (
+            ProofServiceError::ArtifactMissing {
+                artifact: "proof.data".into(),
+                dir: "/tmp".into(),
+            },
+            3001,
+        ),
+        (
+            ProofServiceError::ContractCallError(
+                blueprint_sdk::alloy::contract::Error::ContractNotDeployed,
+            ),
+            4000,
+        ),
+        (ProofServiceError::BlockchainError("x".into()), 4001),
+        (ProofServiceError::ChainDataMismatch("x".into()), 4002),
+        (ProofServiceError::ConfigError("x".into()), 5000),
+        (ProofServiceError::IoError("x".into()), 5001),
+        (ProofServiceError::TempDirError("x".into()), 5002),
+        (ProofServiceError::ShuttingDown, 5003),
+        (ProofServiceError::InternalError("x".into()), 5004),
+        (
+            ProofServiceError::BlueprintSdkError(blueprint_sdk_error()),
+            5005,
+        ),
+    ];
+    assert_every_variant_covered(&cases);
+    for (err, code) in cases {
+        assert_eq!(err.code(), code, "unexpected code for {:?}", err);
+    }
+}
+
+#[test]
+fn test_retryable_errors_are_classified() {
+    let cases = [
+        (ProofServiceError::NetworkError(network_error()), true),
+        (
+            ProofServiceError::RateLimited {
+                retry_after: std::time::Duration::from_secs(1),
+            },
+            true,
+        ),
+        (ProofServiceError::ShuttingDown, true),
+        (
+            ProofServiceError::BlockchainError("Failed to fetch block 1: timeout".into()),
+            true,
+        ),
+        (
+            ProofServiceError::BlockchainError("Block 1 not found".into()),
+            true,
+        ),
+        (
+            ProofServiceError::ChainDataMismatch(
+                "Anchor mismatch: block 1 has hash 0x01, request claims 0x02".into(),
+            ),
+            false,
+        ),
+        (
+            ProofServiceError::ChainDataMismatch(
+                "Receipt 0 is not included in block 1 at index 0: bad node".into(),
+            ),
+            false,
+        ),
+        (
+            ProofServiceError::ContractCallError(
+                blueprint_sdk::alloy::contract::Error::TransportError(
+                    blueprint_sdk::alloy::transports::TransportErrorKind::custom_str("timeout"),
+                ),
+            ),
+            true,
+        ),
+        (
+            ProofServiceError::ContractCallError(
+                blueprint_sdk::alloy::contract::Error::ContractNotDeployed,
+            ),
+            false,
+        ),
+        (ProofServiceError::ConfigError("x".into()), false),
+        (ProofServiceError::IoError("x".into()), false),
+        (
+            ProofServiceError::ProgramNotFoundInRegistry("x".into()),
+            false,
+        ),
+        (ProofServiceError::ProgramDownloadFailed("x".into()), false),
+        (
+            ProofServiceError::ProgramHashMismatch {
+                expected: "a".into(),
+                got: "b".into(),
+            },
+            false,
+        ),
+        (ProofServiceError::InvalidInput("x".into()), false),
+        (
+            ProofServiceError::InputsTooLarge { size: 2, limit: 1 },
+            false,
+        ),
+        (ProofServiceError::ProvingError("x".into()), false),
+        (
+            ProofServiceError::ArtifactMissing {
+                artifact: "proof.data".into(),
+                dir: "/tmp".into(),
+            },
+            false,
+        ),
+        (
+            ProofServiceError::SerdeError(serde_json::from_str::<u8>("x").unwrap_err()),
+            false,
+        ),
+        (
+            ProofServiceError::InvalidUrl(url::ParseError::EmptyHost),
+            false,
+        ),
+        (ProofServiceError::UnsupportedProvingType("x".into()), false),
+        (ProofServiceError::TempDirError("x".into()), false),
+        (
+            ProofServiceError::HexError(hex::FromHexError::OddLength),
+            false,
+        ),
+        (ProofServiceError::Unauthorized("x".into()), false),
+        (ProofServiceError::InternalError("x".into()), false),
+        (
+            ProofServiceError::BlueprintSdkError(blueprint_sdk_error()),
+            false,
+        ),
+    ];
+    assert_every_variant_covered(&cases);
+    for (err, retryable) in cases {
+        assert_eq!(
+            err.is_retryable(),
+            retryable,
+            "unexpected classification of {:?}",
+            err
+        );
+    }
+}
+
+#[test]
+fn test_job_error_carries_code_and_message() {
+    let job_error = JobError::from(ProofServiceError::InvalidInput("bad hash".into()));
+    assert_eq!(job_error.code, 1000);
+    assert_eq!(job_error.message, "Invalid Input Data: bad hash");
+    assert_eq!(
+        job_error.to_string(),
+        "[E1000] Invalid Input Data: bad hash"
+    );
+}
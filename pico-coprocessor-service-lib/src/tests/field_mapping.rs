@@ -0,0 +1,121 @@
+use crate::jobs::coprocessor::build_input_bundle;
+use crate::{
+    BlockchainData, CoprocessorProofRequest, FieldMapping, FieldSource, FieldSpec,
+    ProofServiceError, SerializableLog, SerializableReceipt, ServiceContext,
+};
+use blueprint_sdk::alloy::primitives::{Address, B256, U256, address, b256};
+use std::collections::HashMap;
+use tempfile::tempdir;
+use url::Url;
+
+const USDC_POOL: Address = address!("88e6a0c2ddd26feeb64f039a2c41296fcb3f5640");
+const SWAP_EVENT: B256 = b256!("c42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67");
+const USER: B256 = b256!("0000000000000000000000006a000f20005980200259b80c5102003040001068");
+const AMOUNT: B256 = b256!("0000000000000000000000000000000000000000000000010d12bdb167e201e0");
+
+fn swap_data(topics: Vec<B256>) -> BlockchainData {
+    BlockchainData {
+        receipts: Some(vec![SerializableReceipt {
+            transaction_hash: B256::ZERO,
+            status: Some(U256::from(1)),
+            logs: vec![SerializableLog {
+                address: USDC_POOL,
+                topics,
+                data_hex: hex::encode(AMOUNT),
+                log_pos: Some(17),
+            }],
+            raw_data_hex: String::new(),
+            inclusion_proof: None,
+            block_number: None,
+        }]),
+        storage_slots: None,
+        transactions: None,
+    }
+}
+
+fn swap_mapping() -> FieldMapping {
+    FieldMapping {
+        events: HashMap::from([(SWAP_EVENT, vec![
+            FieldSpec {
+                name: "user".to_string(),
+                source: FieldSource::Topic(1),
+            },
+            FieldSpec {
+                name: "amount".to_string(),
+                source: FieldSource::Data(0),
+            },
+        ])]),
+    }
+}
+
+#[test]
+fn test_field_mapping_extracts_user_from_topic_one() {
+    let fields = swap_mapping()
+        .extract(&swap_data(vec![SWAP_EVENT, USER]))
+        .unwrap();
+    assert_eq!(fields.len(), 2);
+
+    let user = &fields[0];
+    assert_eq!(user.name, "user");
+    assert!(user.is_topic);
+    assert_eq!(user.field_index, 1);
+    assert_eq!(user.value, USER);
+    assert_eq!(user.contract, USDC_POOL);
+    assert_eq!(user.event, SWAP_EVENT);
+    assert_eq!(user.log_pos, Some(17));
+
+    let amount = &fields[1];
+    assert!(!amount.is_topic);
+    assert_eq!(amount.value, AMOUNT);
+
+    // Unmapped events yield no fields
+    let other = FieldMapping::default()
+        .extract(&swap_data(vec![SWAP_EVENT, USER]))
+        .unwrap();
+    assert!(other.is_empty());
+}
+
+#[test]
+fn test_field_mapping_rejects_missing_topic() {
+    let result = swap_mapping().extract(&swap_data(vec![SWAP_EVENT]));
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("field user expects topic 1, log has 1 topics"))
+    );
+}
+
+#[test]
+fn test_field_mapping_rejects_data_offset_past_the_end() {
+    for offset in [1, 32, usize::MAX - 16, usize::MAX] {
+        let mapping = FieldMapping {
+            events: HashMap::from([(SWAP_EVENT, vec![FieldSpec {
+                name: "amount".to_string(),
+                source: FieldSource::Data(offset),
+            }])]),
+        };
+        let result = mapping.extract(&swap_data(vec![SWAP_EVENT, USER]));
+        assert!(
+            matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains(&format!("field amount expects a word at data offset {}", offset))),
+            "offset {}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn test_input_bundle_carries_mapped_fields() {
+    let ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        tempdir().unwrap().into_path(),
+    )
+    .unwrap();
+    let mut request = CoprocessorProofRequest {
+        blockchain_data: swap_data(vec![SWAP_EVENT, USER]),
+        ..Default::default()
+    };
+    assert!(build_input_bundle(&ctx, &request).unwrap().fields.is_none());
+
+    request.field_mapping = Some(swap_mapping());
+    let fields = build_input_bundle(&ctx, &request).unwrap().fields.unwrap();
+    assert_eq!(fields[0].value, USER);
+}
@@ -0,0 +1,209 @@
+use super::mock::start_mock_rpc;
+use crate::{
+    BlockchainData, CoprocessorProofRequest, MaxSizes, ProgramLocation, ProofResult,
+    ProofServiceError, ProveOptions, ProverBackend, ProvingType, ReceiptInclusionProof,
+    SerializableReceipt, ServiceContext, generate_coprocessor_proof,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256, U256, keccak256},
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use futures::future::BoxFuture;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::{TempDir, tempdir};
+use url::Url;
+
+const ANCHOR_NUMBER: u64 = 0x1234;
+const ANCHOR_HASH: B256 = B256::new([0xab; 32]);
+
+// Counts proofs instead of running the VM
+#[derive(Default)]
+struct CountingProver {
+    proofs: AtomicUsize,
+}
+
+impl ProverBackend for CountingProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        self.proofs.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            Ok(ProofResult {
+                public_values: hex::encode([42u8]),
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                inputs: inputs_hex.to_string(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+fn rlp_string(bytes: &[u8]) -> Vec<u8> {
+    match bytes {
+        [byte] if *byte < 0x80 => vec![*byte],
+        _ => rlp_with_prefix(0x80, bytes),
+    }
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    rlp_with_prefix(0xc0, &items.concat())
+}
+
+fn rlp_with_prefix(short: u8, payload: &[u8]) -> Vec<u8> {
+    let mut encoded = if payload.len() < 56 {
+        vec![short + payload.len() as u8]
+    } else {
+        vec![short + 55 + 1, payload.len() as u8]
+    };
+    encoded.extend_from_slice(payload);
+    encoded
+}
+
+// Consensus encoding of a typed receipt, distinct for each `index`
+fn receipt_encoding(index: u8) -> Vec<u8> {
+    [vec![0x02], vec![index; 70]].concat()
+}
+
+// A block's receipts trie holding two receipts, keyed 0x80 (index 0) and 0x01 (index 1): a
+// branch root with leaves under nibbles 8 and 0. Returns the root and each receipt's proof.
+fn receipts_trie() -> (B256, [Vec<String>; 2]) {
+    // Hex-prefix paths of the single nibble left below the branch (odd leaf flag 3)
+    let leaf_0 = rlp_list(&[rlp_string(&[0x30]), rlp_string(&receipt_encoding(0))]);
+    let leaf_1 = rlp_list(&[rlp_string(&[0x31]), rlp_string(&receipt_encoding(1))]);
+    let mut children = vec![rlp_string(&[]); 17];
+    children[8] = rlp_string(keccak256(&leaf_0).as_slice());
+    children[0] = rlp_string(keccak256(&leaf_1).as_slice());
+    let branch = rlp_list(&children);
+
+    let proof = |leaf: &[u8]| vec![hex::encode(&branch), hex::encode(leaf)];
+    (keccak256(&branch), [proof(&leaf_0), proof(&leaf_1)])
+}
+
+fn proven_receipt(index: u8, proof: Vec<String>) -> SerializableReceipt {
+    SerializableReceipt {
+        transaction_hash: B256::repeat_byte(index + 1),
+        status: Some(U256::from(1)),
+        logs: vec![],
+        raw_data_hex: hex::encode(receipt_encoding(index)),
+        inclusion_proof: Some(ReceiptInclusionProof {
+            transaction_index: u64::from(index),
+            proof,
+        }),
+        block_number: None,
+    }
+}
+
+fn inclusion_context(rpc_url: Url) -> (ServiceContext, Arc<CountingProver>, TempDir) {
+    let temp_base = tempdir().unwrap();
+    let mut ctx = ServiceContext::new(rpc_url, Address::ZERO, temp_base.path().join("service"))
+        .expect("Failed to create test ServiceContext");
+    let prover = Arc::new(CountingProver::default());
+    ctx.prover_backend = prover.clone();
+    std::fs::write(temp_base.path().join("program.elf"), b"\x7fELF").unwrap();
+    (ctx, prover, temp_base)
+}
+
+fn inclusion_request(
+    elf_dir: &Path,
+    receipts: Vec<SerializableReceipt>,
+) -> CoprocessorProofRequest {
+    CoprocessorProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        blockchain_data: BlockchainData {
+            receipts: Some(receipts),
+            ..Default::default()
+        },
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_dir.join("program.elf"))),
+        anchor_block_hash: Some(ANCHOR_HASH),
+        anchor_block_number: Some(ANCHOR_NUMBER),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_receipt_inclusion_proofs_are_checked_against_the_receipts_root() {
+    let (receipts_root, [proof_0, proof_1]) = receipts_trie();
+    let rpc = start_mock_rpc(move |method, params| match method {
+        "eth_getBlockByNumber" if params[0] == json!(format!("0x{:x}", ANCHOR_NUMBER)) => {
+            Ok(json!({
+                "number": params[0],
+                "hash": ANCHOR_HASH.to_string(),
+                "receiptsRoot": receipts_root.to_string(),
+            }))
+        }
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let (ctx, prover, temp) = inclusion_context(rpc.url());
+
+    let request = inclusion_request(temp.path(), vec![
+        proven_receipt(0, proof_0.clone()),
+        proven_receipt(1, proof_1.clone()),
+    ]);
+    let TangleResult(result) = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.proof, hex::encode(b"proof"));
+    assert_eq!(prover.proofs.load(Ordering::SeqCst), 1);
+
+    // A receipt the block doesn't hold at that index is rejected before proving
+    let mut tampered = proven_receipt(1, proof_1.clone());
+    tampered.raw_data_hex = hex::encode(receipt_encoding(2));
+    let request = inclusion_request(temp.path(), vec![tampered]);
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ChainDataMismatch(msg)) if msg.contains("does not match"))
+    );
+
+    // ...as is a proof whose nodes don't hash up to the receipts root
+    let mut tampered_proof = proof_1;
+    let mut leaf = hex::decode(&tampered_proof[1]).unwrap();
+    *leaf.last_mut().unwrap() ^= 0xff;
+    tampered_proof[1] = hex::encode(leaf);
+    let request = inclusion_request(temp.path(), vec![proven_receipt(1, tampered_proof)]);
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ChainDataMismatch(msg)) if msg.contains("not included"))
+    );
+
+    // The proof of another receipt doesn't lead to this one's index
+    let misplaced = proven_receipt(1, proof_0);
+    let request = inclusion_request(temp.path(), vec![misplaced]);
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::ChainDataMismatch(_))
+    ));
+    assert_eq!(prover.proofs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_receipt_inclusion_proofs_require_an_anchor() {
+    let (_, [proof_0, _]) = receipts_trie();
+    let (ctx, prover, temp) = inclusion_context(Url::parse("http://localhost:8545").unwrap());
+
+    let mut request = inclusion_request(temp.path(), vec![proven_receipt(0, proof_0)]);
+    request.anchor_block_hash = None;
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("anchor_block_hash"))
+    );
+    assert_eq!(prover.proofs.load(Ordering::SeqCst), 0);
+}
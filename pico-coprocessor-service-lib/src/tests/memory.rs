@@ -0,0 +1,228 @@
+use crate::ProofServiceError;
+use crate::memory;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[cfg(unix)]
+#[test]
+fn test_docker_shim_passes_memory_flag_to_run() {
+    let dir = tempdir().unwrap();
+    let args_file = dir.path().join("args");
+    // Stand-in for the real docker binary that records its arguments
+    let fake_docker = dir.path().join("real-docker");
+    std::fs::write(
+        &fake_docker,
+        format!("#!/bin/sh\necho \"$@\" >> '{}'\n", args_file.display()),
+    )
+    .unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&fake_docker, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let shim_dir = dir.path().join("shim");
+    std::fs::create_dir(&shim_dir).unwrap();
+    let shim = memory::write_docker_shim(&shim_dir, &fake_docker, 8 << 30).unwrap();
+
+    let status = std::process::Command::new(&shim)
+        .args([
+            "run",
+            "--rm",
+            "-v",
+            "/out:/data",
+            "brevishub/pico_gnark_cli",
+        ])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let status = std::process::Command::new(&shim)
+        .args(["pull", "brevishub/pico_gnark_cli"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let recorded = std::fs::read_to_string(&args_file).unwrap();
+    let lines: Vec<&str> = recorded.lines().collect();
+    assert_eq!(lines, vec![
+        "run --memory=8589934592 --rm -v /out:/data brevishub/pico_gnark_cli",
+        "pull brevishub/pico_gnark_cli",
+    ]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_docker_shim_substitutes_configured_image() {
+    let dir = tempdir().unwrap();
+    let args_file = dir.path().join("args");
+    let fake_docker = dir.path().join("real-docker");
+    std::fs::write(
+        &fake_docker,
+        format!("#!/bin/sh\necho \"$@\" >> '{}'\n", args_file.display()),
+    )
+    .unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&fake_docker, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let shim_dir = dir.path().join("shim");
+    std::fs::create_dir(&shim_dir).unwrap();
+    let overrides = memory::DockerOverrides {
+        memory_limit_bytes: None,
+        image: Some("registry.internal/gnark@sha256:abcd".to_string()),
+    };
+    let shim = memory::write_docker_overrides_shim(&shim_dir, &fake_docker, &overrides).unwrap();
+
+    for args in [
+        vec![
+            "run",
+            "--rm",
+            "-v",
+            "/out:/data",
+            "brevishub/pico_gnark_cli:1.1",
+            "prove",
+        ],
+        vec!["pull", "brevishub/pico_gnark_cli"],
+        vec!["ps"],
+    ] {
+        assert!(
+            std::process::Command::new(&shim)
+                .args(&args)
+                .status()
+                .unwrap()
+                .success()
+        );
+    }
+
+    let recorded = std::fs::read_to_string(&args_file).unwrap();
+    let lines: Vec<&str> = recorded.lines().collect();
+    assert_eq!(lines, vec![
+        "run --rm -v /out:/data registry.internal/gnark@sha256:abcd prove",
+        "pull registry.internal/gnark@sha256:abcd",
+        "ps",
+    ]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_docker_shim_applies_only_to_the_child_path() {
+    let dir = tempdir().unwrap();
+    let args_file = dir.path().join("args");
+    let bin = dir.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    std::fs::write(
+        bin.join("docker"),
+        format!("#!/bin/sh\necho \"$@\" >> '{}'\n", args_file.display()),
+    )
+    .unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(bin.join("docker"), std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+    }
+
+    let overrides = memory::DockerOverrides {
+        memory_limit_bytes: Some(1 << 30),
+        image: None,
+    };
+    let path_before = std::env::var_os("PATH");
+    let shim =
+        memory::DockerShim::create_with_path(dir.path(), &overrides, Some(bin.clone().into()))
+            .unwrap();
+    // A child started with the shim's environment runs `docker` through it
+    let status = std::process::Command::new("/bin/sh")
+        .args(["-c", "docker run --rm brevishub/pico_gnark_cli"])
+        .envs(shim.child_env().unwrap())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(
+        std::fs::read_to_string(&args_file).unwrap(),
+        "run --memory=1073741824 --rm brevishub/pico_gnark_cli\n"
+    );
+    // ...while this process's PATH is untouched, so it can't apply the overrides itself
+    assert_eq!(std::env::var_os("PATH"), path_before);
+    assert!(matches!(
+        memory::ensure_docker_shim(&overrides),
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("prover_subprocess")
+    ));
+    memory::ensure_docker_shim(&memory::DockerOverrides::default()).unwrap();
+
+    let shim_dir = PathBuf::from(&shim.child_env().unwrap()[1].1);
+    drop(shim);
+    assert!(!shim_dir.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_docker_shim_image_override_reaches_only_the_child() {
+    let dir = tempdir().unwrap();
+    let args_file = dir.path().join("args");
+    let bin = dir.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    std::fs::write(
+        bin.join("docker"),
+        format!("#!/bin/sh\necho \"$@\" >> '{}'\n", args_file.display()),
+    )
+    .unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(bin.join("docker"), std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+    }
+
+    let overrides = memory::DockerOverrides {
+        memory_limit_bytes: None,
+        image: Some("registry.internal/gnark@sha256:abcd".to_string()),
+    };
+    let shim =
+        memory::DockerShim::create_with_path(dir.path(), &overrides, Some(bin.clone().into()))
+            .unwrap();
+    // Two workers proving at once each see their own image; neither changes this process
+    let other = memory::DockerShim::create_with_path(
+        dir.path(),
+        &memory::DockerOverrides {
+            memory_limit_bytes: None,
+            image: Some("mirror.internal/gnark:1.1".to_string()),
+        },
+        Some(bin.clone().into()),
+    )
+    .unwrap();
+    for shim in [&shim, &other] {
+        let status = std::process::Command::new("/bin/sh")
+            .args(["-c", "docker pull brevishub/pico_gnark_cli"])
+            .envs(shim.child_env().unwrap())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    assert_eq!(
+        std::fs::read_to_string(&args_file).unwrap(),
+        "pull registry.internal/gnark@sha256:abcd\npull mirror.internal/gnark:1.1\n"
+    );
+    assert!(std::env::var_os(memory::DOCKER_SHIM_ENV).is_none());
+}
+
+#[test]
+fn test_parse_mem_available() {
+    let meminfo =
+        "MemTotal:       16318472 kB\nMemFree:         1234567 kB\nMemAvailable:    8000000 kB\n";
+    assert_eq!(memory::parse_mem_available(meminfo), Some(8_000_000 * 1024));
+    assert_eq!(memory::parse_mem_available("MemTotal: 1 kB\n"), None);
+}
+
+#[test]
+fn test_memory_limit_above_available_is_rejected() {
+    let Some(available) = memory::available_memory() else {
+        return; // Host doesn't report available memory
+    };
+    let err = memory::ensure_memory_available(usize::MAX).unwrap_err();
+    assert!(matches!(err, ProofServiceError::ConfigError(_)), "{}", err);
+    assert!(memory::ensure_memory_available((available / 2) as usize).is_ok());
+}
+
+#[test]
+fn test_docker_shim_quotes_real_docker_path() {
+    let script = memory::docker_shim_script(&PathBuf::from("/opt/it's/docker"), 1024);
+    assert!(script.contains(r"exec '/opt/it'\''s/docker' run --memory=1024 "));
+}
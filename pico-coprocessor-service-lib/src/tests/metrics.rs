@@ -0,0 +1,215 @@
+use crate::{
+    CoprocessorProofRequest, MAX_TAGS, MaxSizes, ProgramLocation, ProofCache, ProofCacheKey,
+    ProofMetrics, ProofRequest, ProofResult, ProofServiceError, ProveOptions, ProverBackend,
+    ProvingType, ServiceContext, generate_coprocessor_proof, generate_proof,
+    jobs::coprocessor::build_input_bundle, to_canonical_json, validate_tags,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+fn tags(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+// Answers every proof with a Fast result that took 25ms to prove
+struct TimedProver;
+
+impl ProverBackend for TimedProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        inputs_hex: &'a str,
+        _proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> futures::future::BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            Ok(ProofResult {
+                public_values: hex::encode([0u8, 0, 0, 42]),
+                proof: hex::encode(br#"{"commitments":[1,2,3]}"#),
+                proving_type: ProvingType::Fast,
+                inputs: inputs_hex.to_string(),
+                phase_timings: Some(HashMap::from([("riscv".to_string(), 25)])),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_tags_round_trip_into_result_and_metrics() {
+    let mut ctx = setup_test_context();
+    ctx.prover_backend = Arc::new(TimedProver);
+    let metrics = ctx.metrics.clone();
+
+    let program_dir = tempdir().unwrap();
+    let elf_path = program_dir.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF tagged").unwrap();
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF tagged")),
+        inputs: "0102".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        tags: tags(&[("customer", "acme"), ("billing_plan", "pro")]),
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request.clone()))
+        .await
+        .unwrap();
+
+    assert_eq!(result.tags, request.tags);
+    let labels = [
+        ("cached", "false"),
+        ("job", "generate_proof"),
+        ("proving_type", "Fast"),
+        ("tag_billing_plan", "pro"),
+        ("tag_customer", "acme"),
+    ];
+    assert_eq!(metrics.proofs_total(&labels), 1);
+    let rendered = metrics.render();
+    assert!(rendered.contains(
+        r#"pico_proofs_total{cached="false",job="generate_proof",proving_type="Fast",tag_billing_plan="pro",tag_customer="acme"} 1"#
+    ));
+    assert!(rendered.contains(
+        r#"pico_proving_milliseconds_total{cached="false",job="generate_proof",proving_type="Fast",tag_billing_plan="pro",tag_customer="acme"} 25"#
+    ));
+}
+
+#[tokio::test]
+async fn test_cached_coprocessor_proof_carries_the_new_requests_tags() {
+    let mut ctx = setup_test_context();
+    ctx.proof_cache = Some(ProofCache::default());
+    let request = |customer: &str| CoprocessorProofRequest {
+        program_hash: B256::repeat_byte(0x01).to_string(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        tags: tags(&[("customer", customer)]),
+        ..Default::default()
+    };
+
+    // Proof stored by an earlier request from another customer
+    let bundle = build_input_bundle(&ctx, &request("acme")).unwrap();
+    let inputs = hex::encode(to_canonical_json(&bundle).unwrap());
+    ctx.proof_cache.as_ref().unwrap().insert(
+        ProofCacheKey::new(
+            B256::repeat_byte(0x01),
+            ProvingType::Fast,
+            None,
+            inputs.as_bytes(),
+        ),
+        ProofResult {
+            proof: "c0ffee".to_string(),
+            proving_type: ProvingType::Fast,
+            tags: tags(&[("customer", "acme")]),
+            ..Default::default()
+        },
+    );
+
+    let TangleResult(result) =
+        generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request("globex")))
+            .await
+            .unwrap();
+    assert_eq!(result.tags, tags(&[("customer", "globex")]));
+    assert_eq!(
+        ctx.metrics.proofs_total(&[
+            ("cached", "true"),
+            ("job", "generate_coprocessor_proof"),
+            ("proving_type", "Fast"),
+            ("tag_customer", "globex"),
+        ]),
+        1
+    );
+}
+
+#[test]
+fn test_tagged_series_are_capped() {
+    let metrics = ProofMetrics::new(1);
+    let result = |customer: &str| ProofResult {
+        proving_type: ProvingType::Fast,
+        tags: tags(&[("customer", customer)]),
+        ..Default::default()
+    };
+    metrics.record_proof("generate_proof", &result("acme"), false);
+    metrics.record_proof("generate_proof", &result("acme"), false);
+    // A new combination past the cap is counted without its tags
+    metrics.record_proof("generate_proof", &result("globex"), false);
+
+    let base = [
+        ("cached", "false"),
+        ("job", "generate_proof"),
+        ("proving_type", "Fast"),
+    ];
+    assert_eq!(
+        metrics.proofs_total(&[base[0], base[1], base[2], ("tag_customer", "acme")]),
+        2
+    );
+    assert_eq!(
+        metrics.proofs_total(&[base[0], base[1], base[2], ("tag_customer", "globex")]),
+        0
+    );
+    assert_eq!(metrics.proofs_total(&base), 1);
+    assert!(
+        metrics
+            .render()
+            .contains("pico_metric_tags_dropped_total 1")
+    );
+}
+
+#[tokio::test]
+async fn test_job_rejects_too_many_tags() {
+    let ctx = setup_test_context();
+    let request = ProofRequest {
+        program_hash: B256::repeat_byte(0x01).to_string(),
+        inputs: "0102".to_string(),
+        proving_type: ProvingType::Fast,
+        tags: (0..=MAX_TAGS)
+            .map(|i| (format!("key{}", i), "value".to_string()))
+            .collect(),
+        ..Default::default()
+    };
+    let result = generate_proof(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("tags")
+    ));
+}
+
+#[test]
+fn test_tag_keys_are_limited_to_label_characters() {
+    validate_tags(&tags(&[("customer_2", "acme"), ("Plan", "pro")])).unwrap();
+    // Keys that would be rewritten into another key's label are refused
+    for key in ["billing-plan", "billing.plan", "billing plan", "plän"] {
+        assert!(matches!(
+            validate_tags(&tags(&[(key, "pro")])),
+            Err(ProofServiceError::InvalidInput(msg)) if msg.contains("may only contain")
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+// Minimal HTTP/1.1 and JSON-RPC mock servers for tests that would otherwise need a live endpoint.
+use serde_json::{Value, json};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn json(value: &Value) -> Self {
+        Self::ok(value.to_string()).with_header("Content-Type", "application/json")
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+/// HTTP server on an ephemeral local port answering every request with `handler`.
+pub struct MockServer {
+    url: Url,
+    hits: Arc<AtomicUsize>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let handler: Arc<Handler> = Arc::new(handler);
+        let hits = Arc::new(AtomicUsize::new(0));
+
+        let task_hits = hits.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let hits = task_hits.clone();
+                tokio::spawn(async move {
+                    let _ = serve_connection(stream, handler, hits).await;
+                });
+            }
+        });
+
+        Self { url, hits, task }
+    }
+
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    pub fn url_for(&self, path: &str) -> Url {
+        self.url.join(path).unwrap()
+    }
+
+    /// Number of requests served so far.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// JSON-RPC server dispatching each call to `handler(method, params)`; `Err` becomes a JSON-RPC error.
+/// Errors built with [`revert`] are returned as an `eth_call` revert carrying the data.
+pub async fn start_mock_rpc<F>(handler: F) -> MockServer
+where
+    F: Fn(&str, &Value) -> Result<Value, String> + Send + Sync + 'static,
+{
+    MockServer::start(move |request| {
+        let body: Value = serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+        let answer = |call: &Value| {
+            let method = call["method"].as_str().unwrap_or_default();
+            match handler(method, &call["params"]) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+                Err(message) => match message.strip_prefix(REVERT_PREFIX) {
+                    Some(data) => json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "error": { "code": 3, "message": "execution reverted", "data": data }
+                    }),
+                    None => json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "error": { "code": -32000, "message": message }
+                    }),
+                },
+            }
+        };
+        let response = match &body {
+            Value::Array(calls) => Value::Array(calls.iter().map(answer).collect()),
+            call => answer(call),
+        };
+        MockResponse::json(&response)
+    })
+    .await
+}
+
+const REVERT_PREFIX: &str = "execution reverted: ";
+
+/// A mock RPC error reverting with `data` (e.g. an encoded custom error).
+pub fn revert(data: &[u8]) -> String {
+    format!("{}0x{}", REVERT_PREFIX, hex::encode(data))
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    handler: Arc<Handler>,
+    hits: Arc<AtomicUsize>,
+) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let body_start = header_end + 4;
+    while buf.len() < body_start + content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body_end = buf.len().min(body_start + content_length);
+
+    let request = MockRequest {
+        method,
+        path,
+        headers,
+        body: buf[body_start..body_end].to_vec(),
+    };
+    hits.fetch_add(1, Ordering::SeqCst);
+    let response = handler(&request);
+
+    let mut out = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        out.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    out.push_str("\r\n");
+    stream.write_all(out.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.shutdown().await
+}
@@ -1,2 +1,36 @@
+pub mod anchor;
+pub mod archive;
+pub mod auth;
+pub mod backend;
+pub mod brevis;
+pub mod cache;
+pub mod canonical;
+pub mod checkpoint;
+pub mod config;
+pub mod deserialization;
+pub mod download;
 pub mod e2e;
+pub mod encoding;
+pub mod errors;
+pub mod field_mapping;
+pub mod inclusion;
+pub mod memory;
+pub mod metrics;
+pub mod mock;
+pub mod multi;
+pub mod ordering;
+pub mod proof_format;
+pub mod prove;
+pub mod public_values;
+pub mod queue;
+pub mod rate_limit;
+pub mod registry;
+pub mod s3;
+pub mod sandbox;
+pub mod sdk_inputs;
+pub mod service;
+pub mod shutdown;
+pub mod transaction;
+pub mod transport;
+pub mod validation;
 pub mod vm;
@@ -0,0 +1,151 @@
+use super::mock::{MockResponse, MockServer};
+use crate::{
+    MultiProofRequest, ProgramLocation, ProofResult, ProofServiceError, ProveOptions,
+    ProverBackend, ProvingType, RateLimiter, ServiceContext, UNAUTHENTICATED_CALLER,
+    generate_proof_multi,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+// Backend committing the hash of its ELF and inputs, so every input set yields its own result
+struct EchoProver;
+
+impl ProverBackend for EchoProver {
+    fn prove<'a>(
+        &'a self,
+        elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            let committed = Sha256::new()
+                .chain_update(elf)
+                .chain_update(inputs_hex)
+                .finalize();
+            Ok(ProofResult {
+                public_values: hex::encode(committed),
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_one_program_proven_against_each_input_set_after_one_fetch() {
+    let elf = b"\x7fELF multi".to_vec();
+    let body = elf.clone();
+    let server = MockServer::start(move |_| MockResponse::ok(body.clone())).await;
+    let mut ctx = setup_test_context();
+    ctx.prover_backend = Arc::new(EchoProver);
+
+    let request = MultiProofRequest {
+        program_hash: hex::encode(Sha256::digest(&elf)),
+        inputs: vec!["0a".to_string(), "0b0c".to_string()],
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::RemoteUrl(server.url_for("program.elf"))),
+        ..Default::default()
+    };
+    let TangleResult(results) = generate_proof_multi(Context(ctx), TangleArg(request.clone()))
+        .await
+        .unwrap();
+
+    assert_eq!(server.hits(), 1);
+    assert_eq!(results.len(), 2);
+    assert_ne!(results[0].public_values, results[1].public_values);
+    for (result, inputs) in results.iter().zip(&request.inputs) {
+        assert_eq!(&result.inputs, inputs);
+        assert_eq!(result.program_hash, request.program_hash);
+    }
+}
+
+#[tokio::test]
+async fn test_bad_input_set_rejected_before_fetching() {
+    let server = MockServer::start(|_| MockResponse::ok("unused")).await;
+    let request = MultiProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: vec!["0a".to_string(), "zz".to_string()],
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::RemoteUrl(server.url_for("program.elf"))),
+        ..Default::default()
+    };
+    let result = generate_proof_multi(Context(setup_test_context()), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("input set 1"))
+    );
+    assert_eq!(server.hits(), 0);
+
+    let request = MultiProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    let result = generate_proof_multi(Context(setup_test_context()), TangleArg(request)).await;
+    assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+}
+
+#[tokio::test]
+async fn test_rejected_request_consumes_no_rate_limit_tokens() {
+    let mut ctx = setup_test_context();
+    let limiter = RateLimiter::new(2, 0.001).unwrap();
+    ctx.rate_limiter = Some(limiter.clone());
+
+    let request = MultiProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: vec!["0a".to_string(), "zz".to_string()],
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    let result = generate_proof_multi(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+
+    // Both tokens are still there for the caller's next request
+    limiter.check(UNAUTHENTICATED_CALLER).unwrap();
+    limiter.check(UNAUTHENTICATED_CALLER).unwrap();
+}
+
+#[tokio::test]
+async fn test_request_beyond_remaining_tokens_takes_none() {
+    let mut ctx = setup_test_context();
+    let limiter = RateLimiter::new(3, 0.001).unwrap();
+    limiter.check(UNAUTHENTICATED_CALLER).unwrap();
+    ctx.rate_limiter = Some(limiter.clone());
+
+    // Three input sets against the two tokens left
+    let request = MultiProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: vec!["0a".to_string(), "0b".to_string(), "0c".to_string()],
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    let result = generate_proof_multi(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(result, Err(ProofServiceError::RateLimited { .. })));
+
+    // The partial bucket wasn't drained
+    limiter.check(UNAUTHENTICATED_CALLER).unwrap();
+    limiter.check(UNAUTHENTICATED_CALLER).unwrap();
+}
@@ -0,0 +1,183 @@
+use crate::{
+    BlockchainData, CoprocessorProofRequest, ProofServiceError, ReceiptBlock, ReceiptOrder,
+    SerializableLog, SerializableReceipt, ServiceContext, jobs::coprocessor::build_input_bundle,
+    to_canonical_json,
+};
+use blueprint_sdk::alloy::primitives::{Address, B256, U256};
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+fn receipt(hash_byte: u8, log_pos: Option<u64>) -> SerializableReceipt {
+    SerializableReceipt {
+        transaction_hash: B256::repeat_byte(hash_byte),
+        status: Some(U256::from(1)),
+        logs: vec![SerializableLog {
+            address: Address::repeat_byte(0x88),
+            topics: vec![],
+            data_hex: String::new(),
+            log_pos,
+        }],
+        raw_data_hex: String::new(),
+        inclusion_proof: None,
+        block_number: None,
+    }
+}
+
+// Submitted out of hash and log order on purpose
+fn submitted() -> Vec<SerializableReceipt> {
+    vec![
+        receipt(0x03, Some(5)),
+        receipt(0x01, None),
+        receipt(0x02, Some(9)),
+        receipt(0x04, Some(2)),
+    ]
+}
+
+fn request(sort_by: Option<ReceiptOrder>) -> CoprocessorProofRequest {
+    CoprocessorProofRequest {
+        blockchain_data: BlockchainData {
+            receipts: Some(submitted()),
+            ..Default::default()
+        },
+        sort_by,
+        ..Default::default()
+    }
+}
+
+fn bundled_hash_bytes(request: &CoprocessorProofRequest) -> Vec<u8> {
+    let bundle = build_input_bundle(&setup_test_context(), request).unwrap();
+    bundle
+        .data
+        .receipts
+        .unwrap()
+        .iter()
+        .map(|r| r.transaction_hash[0])
+        .collect()
+}
+
+#[test]
+fn test_receipts_keep_submission_order_by_default() {
+    let request = request(None);
+    assert_eq!(bundled_hash_bytes(&request), vec![0x03, 0x01, 0x02, 0x04]);
+
+    // ...all the way into the serialized bundle the program reads
+    let bundle = build_input_bundle(&setup_test_context(), &request).unwrap();
+    let encoded: serde_json::Value =
+        serde_json::from_slice(&to_canonical_json(&bundle).unwrap()).unwrap();
+    let hashes: Vec<String> = encoded["data"]["receipts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["transaction_hash"].as_str().unwrap().to_string())
+        .collect();
+    let expected: Vec<String> = submitted()
+        .iter()
+        .map(|r| r.transaction_hash.to_string())
+        .collect();
+    assert_eq!(hashes, expected);
+}
+
+#[test]
+fn test_sort_by_puts_receipts_in_canonical_order() {
+    assert_eq!(
+        bundled_hash_bytes(&request(Some(ReceiptOrder::TransactionHash))),
+        vec![0x01, 0x02, 0x03, 0x04]
+    );
+    // Receipts without a log position go last
+    assert_eq!(
+        bundled_hash_bytes(&request(Some(ReceiptOrder::LogPos))),
+        vec![0x04, 0x03, 0x02, 0x01]
+    );
+}
+
+fn in_block(hash_byte: u8, log_pos: Option<u64>, block_number: u64) -> SerializableReceipt {
+    SerializableReceipt {
+        block_number: Some(block_number),
+        ..receipt(hash_byte, log_pos)
+    }
+}
+
+#[test]
+fn test_receipts_across_blocks_are_grouped_in_the_bundle() {
+    let mut request = request(None);
+    request.anchor_block_number = Some(101);
+    request.blockchain_data.receipts = Some(vec![
+        in_block(0x03, Some(5), 100),
+        in_block(0x01, Some(9), 100),
+        in_block(0x04, Some(2), 101),
+        in_block(0x02, Some(1), 101),
+        in_block(0x05, None, 101),
+    ]);
+
+    let bundle = build_input_bundle(&setup_test_context(), &request).unwrap();
+    let expected = vec![
+        ReceiptBlock {
+            block_number: 100,
+            first_receipt: 0,
+            receipt_count: 2,
+        },
+        ReceiptBlock {
+            block_number: 101,
+            first_receipt: 2,
+            receipt_count: 3,
+        },
+    ];
+    assert_eq!(bundle.receipt_blocks, Some(expected));
+
+    // The program reads the grouping next to the receipts it indexes
+    let encoded: serde_json::Value =
+        serde_json::from_slice(&to_canonical_json(&bundle).unwrap()).unwrap();
+    assert_eq!(
+        encoded["receipt_blocks"],
+        serde_json::json!([
+            {"block_number": 100, "first_receipt": 0, "receipt_count": 2},
+            {"block_number": 101, "first_receipt": 2, "receipt_count": 3},
+        ])
+    );
+    let blocks: Vec<u64> = encoded["data"]["receipts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["block_number"].as_u64().unwrap())
+        .collect();
+    assert_eq!(blocks, vec![100, 100, 101, 101, 101]);
+
+    // Sorting reorders receipts within their block only
+    request.sort_by = Some(ReceiptOrder::TransactionHash);
+    assert_eq!(bundled_hash_bytes(&request), vec![
+        0x01, 0x03, 0x02, 0x04, 0x05
+    ]);
+    request.sort_by = Some(ReceiptOrder::LogPos);
+    assert_eq!(bundled_hash_bytes(&request), vec![
+        0x03, 0x01, 0x02, 0x04, 0x05
+    ]);
+    let sorted = build_input_bundle(&setup_test_context(), &request).unwrap();
+    assert_eq!(sorted.receipt_blocks, bundle.receipt_blocks);
+
+    // Single-block data has no grouping
+    assert_eq!(
+        build_input_bundle(&setup_test_context(), &self::request(None))
+            .unwrap()
+            .receipt_blocks,
+        None
+    );
+
+    // Receipts from after the anchor block are refused
+    request.anchor_block_number = Some(100);
+    assert!(matches!(
+        build_input_bundle(&setup_test_context(), &request),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("after the anchor block 100")
+    ));
+}
@@ -0,0 +1,90 @@
+use crate::{RecursionProofFormat, decode_recursion_proof, encode_recursion_proof};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Montgomery forms of the KoalaBear elements 1 and 2 (1·2^32 and 2·2^32 mod p)
+const MONTY_ONE: u32 = 0x01ff_fffe;
+const MONTY_TWO: u32 = 0x03ff_fffc;
+
+// Stands in for an embed proof: field elements as u32s, degrees and chip indices as usizes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct StandInProof {
+    commitments: Vec<[u32; 2]>,
+    log_main_degrees: Vec<usize>,
+    main_chip_ordering: BTreeMap<String, usize>,
+    pow_witness: Option<u32>,
+    opening: Opening,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum Opening {
+    Values(Vec<u32>),
+    Empty,
+}
+
+fn stand_in_proof() -> StandInProof {
+    StandInProof {
+        commitments: vec![[MONTY_ONE, MONTY_TWO], [0, MONTY_ONE]],
+        log_main_degrees: vec![MONTY_ONE as usize, 20],
+        main_chip_ordering: BTreeMap::from([("Cpu".to_string(), 0), ("Memory".to_string(), 1)]),
+        pow_witness: Some(MONTY_TWO),
+        opening: Opening::Values(vec![MONTY_TWO, MONTY_ONE]),
+    }
+}
+
+#[test]
+fn test_recursion_proof_formats_differ_and_round_trip() {
+    let proof = stand_in_proof();
+
+    let json = encode_recursion_proof(&proof, RecursionProofFormat::Json).unwrap();
+    assert_eq!(json, serde_json::to_vec(&proof).unwrap());
+
+    let canonical = encode_recursion_proof(&proof, RecursionProofFormat::JsonCanonical).unwrap();
+    assert_ne!(canonical, json);
+    let value: serde_json::Value = serde_json::from_slice(&canonical).unwrap();
+    assert_eq!(value["commitments"], serde_json::json!([[1, 2], [0, 1]]));
+    assert_eq!(value["pow_witness"], 2);
+    assert_eq!(value["opening"]["Values"], serde_json::json!([2, 1]));
+    // Only field elements are converted
+    assert_eq!(
+        value["log_main_degrees"],
+        serde_json::json!([MONTY_ONE, 20])
+    );
+    assert_eq!(value["main_chip_ordering"]["Memory"], 1);
+
+    for (format, bytes) in [
+        (RecursionProofFormat::Json, &json),
+        (RecursionProofFormat::JsonCanonical, &canonical),
+    ] {
+        let decoded: StandInProof = decode_recursion_proof(bytes, format).unwrap();
+        assert_eq!(decoded, proof, "{:?} round trip", format);
+    }
+
+    // Unit variants and absent options pass through both ways
+    let sparse = StandInProof {
+        pow_witness: None,
+        opening: Opening::Empty,
+        ..proof
+    };
+    let encoded = encode_recursion_proof(&sparse, RecursionProofFormat::JsonCanonical).unwrap();
+    let decoded: StandInProof =
+        decode_recursion_proof(&encoded, RecursionProofFormat::JsonCanonical).unwrap();
+    assert_eq!(decoded, sparse);
+}
+
+#[test]
+fn test_recursion_proof_rejects_values_outside_the_field() {
+    let mut proof = stand_in_proof();
+    proof.pow_witness = Some(u32::MAX);
+    assert!(encode_recursion_proof(&proof, RecursionProofFormat::JsonCanonical).is_err());
+
+    // A canonical proof can't hold p or more
+    let mut value = serde_json::to_value(stand_in_proof()).unwrap();
+    value["pow_witness"] = serde_json::json!(0x7f00_0001u32);
+    let bytes = serde_json::to_vec(&value).unwrap();
+    assert!(
+        decode_recursion_proof::<StandInProof>(&bytes, RecursionProofFormat::JsonCanonical)
+            .is_err()
+    );
+    assert!(decode_recursion_proof::<StandInProof>(&bytes, RecursionProofFormat::Json).is_ok());
+}
@@ -0,0 +1,75 @@
+use crate::{
+    AnyProofRequest, CoprocessorProofRequest, MaxSizes, ProofRequest, ProofServiceError,
+    ProvingType, ServiceContext, prove,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+#[tokio::test]
+async fn test_prove_dispatches_generic_request_to_generate_proof() {
+    let mut ctx = setup_test_context();
+    ctx.max_input_bytes = 4;
+    // Only generate_proof checks the size of raw inputs
+    let request = AnyProofRequest::Generic(ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".repeat(8),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    });
+
+    let result = prove(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::InputsTooLarge { size: 8, limit: 4 }
+    ));
+}
+
+#[tokio::test]
+async fn test_prove_dispatches_coprocessor_request_to_generate_coprocessor_proof() {
+    let ctx = setup_test_context();
+    // Only generate_coprocessor_proof validates max_sizes
+    let request = AnyProofRequest::Coprocessor(CoprocessorProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 0,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    });
+
+    let result = prove(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("Invalid max_sizes"))
+    );
+}
+
+#[test]
+fn test_any_proof_request_is_tagged_by_kind() {
+    let request: AnyProofRequest = serde_json::from_value(serde_json::json!({
+        "coprocessor": serde_json::to_value(CoprocessorProofRequest::default()).unwrap()
+    }))
+    .unwrap();
+    assert!(matches!(request, AnyProofRequest::Coprocessor(_)));
+
+    let json = serde_json::to_value(AnyProofRequest::Generic(ProofRequest::default())).unwrap();
+    assert!(json.get("generic").is_some());
+}
@@ -0,0 +1,252 @@
+use crate::{
+    BlockchainData, CommitMode, CoprocessorCommitFormat, CoprocessorProofRequest, MaxSizes,
+    ProgramLocation, ProofResult, ProofServiceError, ProveOptions, ProverBackend, ProvingType,
+    PublicValuesPolicy, SerializableReceipt, ServiceContext, apply_public_values_policy,
+    decode_public_values, generate_coprocessor_proof, merkle_root, parse_merkle_commitment,
+};
+use blueprint_sdk::alloy::{
+    primitives::{Address, B256, Bytes, U256, keccak256},
+    sol_types::SolValue,
+};
+use blueprint_sdk::{
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use url::Url;
+
+fn length_prefixed(payload: &[u8]) -> Vec<u8> {
+    let mut encoded = (payload.len() as u64).to_le_bytes().to_vec();
+    encoded.extend_from_slice(payload);
+    encoded
+}
+
+#[test]
+fn test_length_prefixed_commit_extracts_payload() {
+    let payload = b"trading volume: 42".to_vec();
+    let decoded = decode_public_values(
+        CoprocessorCommitFormat::LengthPrefixed,
+        &length_prefixed(&payload),
+    )
+    .unwrap();
+    assert_eq!(decoded, payload);
+
+    // Empty payloads are valid
+    let decoded = decode_public_values(
+        CoprocessorCommitFormat::LengthPrefixed,
+        &length_prefixed(&[]),
+    )
+    .unwrap();
+    assert!(decoded.is_empty());
+}
+
+#[test]
+fn test_length_prefixed_commit_rejects_bad_length() {
+    let mut truncated = length_prefixed(&[1, 2, 3, 4]);
+    truncated.pop();
+    assert!(
+        matches!(decode_public_values(CoprocessorCommitFormat::LengthPrefixed, &truncated), Err(ProofServiceError::InvalidInput(msg)) if msg.contains("declare 4 bytes but carry 3"))
+    );
+    assert!(
+        matches!(decode_public_values(CoprocessorCommitFormat::LengthPrefixed, &[0; 4]), Err(ProofServiceError::InvalidInput(msg)) if msg.contains("too short"))
+    );
+}
+
+#[test]
+fn test_raw_and_abi_commits() {
+    let volume = U256::from(1234).to_be_bytes::<32>();
+    assert_eq!(
+        decode_public_values(CoprocessorCommitFormat::Raw, &volume).unwrap(),
+        volume.to_vec()
+    );
+
+    let encoded = Bytes::from(vec![0xde, 0xad]).abi_encode();
+    assert_eq!(
+        decode_public_values(CoprocessorCommitFormat::AbiEncoded, &encoded).unwrap(),
+        vec![0xde, 0xad]
+    );
+}
+
+#[test]
+fn test_public_values_policy_on_30_byte_commit() {
+    let committed = vec![0xabu8; 30];
+
+    let as_is = apply_public_values_policy(PublicValuesPolicy::AsIs, committed.clone()).unwrap();
+    assert_eq!(as_is, committed);
+
+    let padded =
+        apply_public_values_policy(PublicValuesPolicy::LeftPad32, committed.clone()).unwrap();
+    assert_eq!(padded.len(), 32);
+    assert_eq!(&padded[..2], &[0, 0]);
+    assert_eq!(&padded[2..], committed.as_slice());
+    assert_eq!(
+        U256::from_be_slice(&padded),
+        U256::from_be_slice(&committed)
+    );
+
+    let exact = apply_public_values_policy(PublicValuesPolicy::Exact32, committed);
+    assert!(
+        matches!(exact, Err(ProofServiceError::InvalidInput(msg)) if msg == "Public values are 30 bytes, Exact32 requires exactly 32")
+    );
+}
+
+#[test]
+fn test_left_pad_rejects_values_over_32_bytes() {
+    let result = apply_public_values_policy(PublicValuesPolicy::LeftPad32, vec![1u8; 33]);
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg == "Public values are 33 bytes, LeftPad32 requires at most 32")
+    );
+    let exact = apply_public_values_policy(PublicValuesPolicy::Exact32, vec![1u8; 32]).unwrap();
+    assert_eq!(exact.len(), 32);
+}
+
+fn leaf(n: u8) -> B256 {
+    B256::repeat_byte(n)
+}
+
+fn leaf_hash(leaf: B256) -> B256 {
+    keccak256([&[0x00][..], leaf.as_slice()].concat())
+}
+
+fn node_hash(left: B256, right: B256) -> B256 {
+    keccak256([&[0x01][..], left.as_slice(), right.as_slice()].concat())
+}
+
+#[test]
+fn test_merkle_root_hashes_pairs_and_carries_odd_nodes() {
+    assert_eq!(merkle_root(&[]), B256::ZERO);
+    assert_eq!(merkle_root(&[leaf(1)]), leaf_hash(leaf(1)));
+
+    let pair = node_hash(leaf_hash(leaf(1)), leaf_hash(leaf(2)));
+    assert_eq!(merkle_root(&[leaf(1), leaf(2)]), pair);
+    // The third leaf has no sibling and joins the tree one level up
+    assert_eq!(
+        merkle_root(&[leaf(1), leaf(2), leaf(3)]),
+        node_hash(pair, leaf_hash(leaf(3)))
+    );
+}
+
+#[test]
+fn test_merkle_leaves_and_nodes_are_domain_separated() {
+    let pair = merkle_root(&[leaf(1), leaf(2)]);
+    // The two leaf hashes presented as leaves don't reproduce their parent
+    let inner = [leaf_hash(leaf(1)), leaf_hash(leaf(2))];
+    assert_ne!(merkle_root(&inner), pair);
+    // Nor does a single leaf equal to the root of a larger tree
+    assert_ne!(merkle_root(&[pair]), pair);
+}
+
+#[test]
+fn test_merkle_commitment_parses_root_and_checks_leaves() {
+    let leaves = [leaf(1), leaf(2), leaf(3)];
+    let root = merkle_root(&leaves);
+
+    let root_only = parse_merkle_commitment(root.as_slice(), 3).unwrap();
+    assert_eq!(root_only.root, root.to_string());
+    assert!(root_only.leaves.is_empty());
+
+    let mut payload = root.to_vec();
+    for leaf in &leaves {
+        payload.extend_from_slice(leaf.as_slice());
+    }
+    let with_leaves = parse_merkle_commitment(&payload, 3).unwrap();
+    assert_eq!(
+        with_leaves.leaves,
+        leaves.iter().map(ToString::to_string).collect::<Vec<_>>()
+    );
+
+    assert!(
+        matches!(parse_merkle_commitment(&payload, 2), Err(ProofServiceError::InvalidInput(msg)) if msg == "Merkle commitment carries 3 leaves for 2 receipts")
+    );
+    payload[..32].copy_from_slice(leaf(9).as_slice());
+    assert!(
+        matches!(parse_merkle_commitment(&payload, 3), Err(ProofServiceError::InvalidInput(msg)) if msg.contains("does not match its leaves"))
+    );
+    assert!(matches!(
+        parse_merkle_commitment(&[0u8; 40], 0),
+        Err(ProofServiceError::InvalidInput(_))
+    ));
+}
+
+// Backend whose proofs commit `public_values`, standing in for a coprocessor program
+struct CommittingProver {
+    public_values: Vec<u8>,
+}
+
+impl ProverBackend for CommittingProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        _inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            Ok(ProofResult {
+                public_values: hex::encode(&self.public_values),
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_merkle_root_mode_returns_committed_root() {
+    let leaves = [leaf(1), leaf(2)];
+    let root = merkle_root(&leaves);
+    let mut committed = root.to_vec();
+    committed.extend(leaves.iter().flat_map(|l| l.to_vec()));
+
+    let temp_base = tempdir().unwrap();
+    let mut ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base.path().join("service"),
+    )
+    .unwrap();
+    ctx.prover_backend = Arc::new(CommittingProver {
+        public_values: committed,
+    });
+    let elf_path = temp_base.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+
+    let receipt = SerializableReceipt {
+        transaction_hash: B256::ZERO,
+        status: Some(U256::from(1)),
+        logs: Vec::new(),
+        raw_data_hex: String::new(),
+        inclusion_proof: None,
+        block_number: None,
+    };
+    let request = CoprocessorProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        blockchain_data: BlockchainData {
+            receipts: Some(vec![receipt.clone(), receipt]),
+            ..Default::default()
+        },
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        commit_mode: CommitMode::MerkleRoot,
+        ..Default::default()
+    };
+
+    let TangleResult(result) = generate_coprocessor_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(result.public_values_decoded, Some(hex::encode(root)));
+    let commitment = result.merkle_commitment.unwrap();
+    assert_eq!(commitment.root, root.to_string());
+    assert_eq!(commitment.leaves.len(), 2);
+}
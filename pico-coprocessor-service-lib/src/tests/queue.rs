@@ -0,0 +1,81 @@
+use crate::{ProofPriority, ProvingQueue};
+use std::sync::{Arc, Mutex};
+
+// Yields until `queue` has `count` waiters
+async fn wait_for_waiters(queue: &ProvingQueue, count: usize) {
+    while queue.waiting() < count {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn test_high_priority_job_acquires_freed_permit_first() {
+    let queue = ProvingQueue::new(1);
+    let running = queue.acquire(ProofPriority::Normal).await;
+    assert_eq!(queue.available(), 0);
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let spawn_job = |priority: ProofPriority| {
+        let queue = queue.clone();
+        let order = order.clone();
+        tokio::spawn(async move {
+            let _permit = queue.acquire(priority).await;
+            order.lock().unwrap().push(priority);
+        })
+    };
+
+    let low = spawn_job(ProofPriority::Low);
+    wait_for_waiters(&queue, 1).await;
+    let high = spawn_job(ProofPriority::High);
+    wait_for_waiters(&queue, 2).await;
+
+    drop(running);
+    high.await.unwrap();
+    low.await.unwrap();
+    assert_eq!(*order.lock().unwrap(), vec![
+        ProofPriority::High,
+        ProofPriority::Low
+    ]);
+    assert_eq!(queue.available(), 1);
+}
+
+#[tokio::test]
+async fn test_cancelled_waiter_does_not_leak_its_permit() {
+    let queue = ProvingQueue::new(1);
+    let running = queue.acquire(ProofPriority::Normal).await;
+
+    let waiter = {
+        let queue = queue.clone();
+        tokio::spawn(async move {
+            let _permit = queue.acquire(ProofPriority::High).await;
+        })
+    };
+    wait_for_waiters(&queue, 1).await;
+    waiter.abort();
+    let _ = waiter.await;
+
+    drop(running);
+    assert_eq!(queue.available(), 1);
+    let _next = queue.acquire(ProofPriority::Low).await;
+    assert_eq!(queue.available(), 0);
+}
+
+#[tokio::test]
+async fn test_waiter_dropped_after_its_grant_returns_the_permit() {
+    let queue = ProvingQueue::new(1);
+    let running = queue.acquire(ProofPriority::Normal).await;
+
+    let mut waiter = Box::pin(queue.acquire(ProofPriority::High));
+    assert!(futures::poll!(waiter.as_mut()).is_pending());
+    assert_eq!(queue.waiting(), 1);
+
+    // The freed slot is handed to the waiter, which is dropped before it runs again
+    drop(running);
+    assert_eq!(queue.waiting(), 0);
+    assert_eq!(queue.available(), 0);
+    drop(waiter);
+
+    assert_eq!(queue.available(), 1);
+    let _next = queue.acquire(ProofPriority::Low).await;
+    assert_eq!(queue.available(), 0);
+}
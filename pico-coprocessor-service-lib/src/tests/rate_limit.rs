@@ -0,0 +1,181 @@
+use crate::{
+    MAX_RETRY_AFTER, ProofRequest, ProofServiceError, ProvingType, RateLimiter, ServiceContext,
+    generate_proof, sign_request,
+};
+use blueprint_sdk::{alloy::primitives::Address, extract::Context, tangle::extract::TangleArg};
+use ed25519_dalek::SigningKey;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+use url::Url;
+
+fn retry_after(result: Result<(), ProofServiceError>) -> Duration {
+    match result {
+        Err(ProofServiceError::RateLimited { retry_after }) => retry_after,
+        other => panic!("Expected RateLimited, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_exhausted_bucket_is_rate_limited_until_refilled() {
+    let limiter = RateLimiter::new(2, 0.5).unwrap();
+    let start = Instant::now();
+
+    limiter.check_at("alice", start).unwrap();
+    limiter.check_at("alice", start).unwrap();
+    // One token refills every 2 seconds
+    assert_eq!(
+        retry_after(limiter.check_at("alice", start)),
+        Duration::from_secs(2)
+    );
+    assert_eq!(
+        retry_after(limiter.check_at("alice", start + Duration::from_secs(1))),
+        Duration::from_secs(1)
+    );
+
+    // Other callers have their own bucket
+    limiter.check_at("bob", start).unwrap();
+
+    limiter
+        .check_at("alice", start + Duration::from_secs(2))
+        .unwrap();
+    assert!(
+        limiter
+            .check_at("alice", start + Duration::from_secs(2))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_check_n_takes_all_tokens_or_none() {
+    let limiter = RateLimiter::new(3, 0.5).unwrap();
+    let start = Instant::now();
+    limiter.check_at("alice", start).unwrap();
+
+    // Three tokens against the two left: refused, with the wait until the third refills
+    assert_eq!(
+        retry_after(limiter.check_n_at("alice", 3, start)),
+        Duration::from_secs(2)
+    );
+    limiter.check_n_at("alice", 2, start).unwrap();
+    assert!(limiter.check_at("alice", start).is_err());
+
+    // A burst larger than the bucket can never be served
+    assert!(matches!(
+        limiter.check_n_at("bob", 4, start),
+        Err(ProofServiceError::InvalidInput(_))
+    ));
+    limiter.check_n_at("bob", 3, start).unwrap();
+}
+
+#[test]
+fn test_rate_limiter_rejects_invalid_config() {
+    assert!(matches!(
+        RateLimiter::new(0, 1.0),
+        Err(ProofServiceError::ConfigError(_))
+    ));
+    for refill_per_sec in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(
+            matches!(
+                RateLimiter::new(1, refill_per_sec),
+                Err(ProofServiceError::ConfigError(_))
+            ),
+            "{}",
+            refill_per_sec
+        );
+    }
+}
+
+#[test]
+fn test_retry_after_of_a_near_zero_refill_rate_is_capped() {
+    // The wait until the next token doesn't fit a Duration
+    let limiter = RateLimiter::new(1, f64::MIN_POSITIVE).unwrap();
+    let start = Instant::now();
+    limiter.check_at("alice", start).unwrap();
+    assert_eq!(
+        retry_after(limiter.check_at("alice", start)),
+        MAX_RETRY_AFTER
+    );
+
+    // ...and a long one that does is capped as well
+    let limiter = RateLimiter::new(1, 1e-9).unwrap();
+    limiter.check_at("alice", start).unwrap();
+    assert_eq!(
+        retry_after(limiter.check_at("alice", start)),
+        MAX_RETRY_AFTER
+    );
+}
+
+#[tokio::test]
+async fn test_proving_jobs_are_rate_limited_per_caller() {
+    let (alice, bob) = (
+        SigningKey::from_bytes(&[1; 32]),
+        SigningKey::from_bytes(&[2; 32]),
+    );
+    let mut ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        tempdir().unwrap().into_path(),
+    )
+    .unwrap();
+    ctx.authorized_keys = Some(
+        [&alice, &bob]
+            .iter()
+            .map(|key| key.verifying_key().to_bytes())
+            .collect(),
+    );
+    ctx.rate_limiter = Some(RateLimiter::new(2, 0.001).unwrap());
+
+    // Invalid hash so admitted jobs fail fast after the rate limit check
+    let request = |key: &SigningKey| {
+        let request = ProofRequest {
+            program_hash: "not-a-hash".to_string(),
+            inputs: "00".to_string(),
+            proving_type: ProvingType::Fast,
+            ..Default::default()
+        };
+        TangleArg(sign_request(request, key).unwrap())
+    };
+
+    for _ in 0..2 {
+        let result = generate_proof(Context(ctx.clone()), request(&alice)).await;
+        assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+    }
+    let result = generate_proof(Context(ctx.clone()), request(&alice)).await;
+    match result {
+        Err(ProofServiceError::RateLimited { retry_after }) => {
+            assert!(retry_after > Duration::from_secs(100), "{:?}", retry_after)
+        }
+        other => panic!("Expected RateLimited, got {:?}", other.err()),
+    }
+
+    let result = generate_proof(Context(ctx), request(&bob)).await;
+    assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+}
+
+#[tokio::test]
+async fn test_unauthenticated_jobs_share_one_bucket() {
+    let mut ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        tempdir().unwrap().into_path(),
+    )
+    .unwrap();
+    ctx.rate_limiter = Some(RateLimiter::new(2, 0.001).unwrap());
+
+    // Without authentication every job is the same caller, however it is signed
+    let request = |key: u8| {
+        let request = ProofRequest {
+            program_hash: "not-a-hash".to_string(),
+            inputs: "00".to_string(),
+            proving_type: ProvingType::Fast,
+            ..Default::default()
+        };
+        TangleArg(sign_request(request, &SigningKey::from_bytes(&[key; 32])).unwrap())
+    };
+    for key in 1..=2 {
+        let result = generate_proof(Context(ctx.clone()), request(key)).await;
+        assert!(matches!(result, Err(ProofServiceError::InvalidInput(_))));
+    }
+    let result = generate_proof(Context(ctx), request(3)).await;
+    assert!(matches!(result, Err(ProofServiceError::RateLimited { .. })));
+}
@@ -0,0 +1,472 @@
+use super::mock::{MockResponse, MockServer, revert, start_mock_rpc};
+use crate::{
+    CheckProgramRequest, CheckProgramResult, CommitMode, CoprocessorCommitFormat,
+    DescribeProgramRequest, MaxSizes, ProgramEntry, ProgramLocation, ProgramSchema,
+    ProofServiceError, RegistryRef, ServiceContext, check_program_matches_registry,
+    describe_program, evm, evm::ProgramRegistry, list_programs, namehash,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blueprint_sdk::{
+    alloy::{
+        primitives::{Address, B256},
+        sol_types::{SolCall, SolError, SolEvent, SolValue},
+    },
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tempfile::tempdir;
+
+const REGISTRY: Address = Address::repeat_byte(0x11);
+
+fn context_for(rpc_url: url::Url) -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(rpc_url, REGISTRY, temp_base).expect("Failed to create test ServiceContext")
+}
+
+fn topic_for_address(address: Address) -> String {
+    B256::left_padding_from(address.as_slice()).to_string()
+}
+
+// Builds an `eth_getLogs` entry for a registry event with the given indexed hash and string payload
+fn registry_log(signature: B256, program_hash: B256, location: &str, log_index: u64) -> Value {
+    json!({
+        "address": REGISTRY.to_string(),
+        "topics": [
+            signature.to_string(),
+            program_hash.to_string(),
+            topic_for_address(Address::repeat_byte(0xaa)),
+        ],
+        "data": format!("0x{}", hex::encode(location.to_string().abi_encode())),
+        "blockHash": B256::repeat_byte(0x01).to_string(),
+        "blockNumber": "0x10",
+        "transactionHash": B256::repeat_byte(log_index as u8 + 2).to_string(),
+        "transactionIndex": "0x0",
+        "logIndex": format!("0x{:x}", log_index),
+        "removed": false
+    })
+}
+
+#[tokio::test]
+async fn test_list_programs_returns_all_registered_programs() {
+    let first = B256::repeat_byte(0x0a);
+    let second = B256::repeat_byte(0x0b);
+    let registered = ProgramRegistry::ProgramRegistered::SIGNATURE_HASH;
+
+    let rpc = start_mock_rpc(move |method, params| match method {
+        "eth_getLogs" if params.to_string().contains(&registered.to_string()) => Ok(json!([
+            registry_log(registered, first, "https://example.com/first.elf", 0),
+            registry_log(registered, second, "ipfs://second", 1),
+        ])),
+        "eth_getLogs" => Ok(json!([])), // No ProgramUpdated events
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let ctx = context_for(rpc.url());
+
+    let programs = evm::list_programs(&ctx).await.unwrap();
+    assert_eq!(programs, vec![
+        (first, "https://example.com/first.elf".to_string()),
+        (second, "ipfs://second".to_string()),
+    ]);
+
+    let entries = list_programs(Context(ctx)).await.unwrap().0;
+    assert_eq!(entries, vec![
+        ProgramEntry {
+            program_hash: first.to_string(),
+            location: "https://example.com/first.elf".to_string(),
+        },
+        ProgramEntry {
+            program_hash: second.to_string(),
+            location: "ipfs://second".to_string(),
+        },
+    ]);
+}
+
+#[test]
+fn test_namehash_matches_eip137() {
+    assert_eq!(namehash(""), B256::ZERO);
+    assert_eq!(
+        namehash("eth").to_string(),
+        "0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+    );
+    assert_eq!(
+        "registry.myproject.eth".parse::<RegistryRef>().unwrap(),
+        RegistryRef::Ens("registry.myproject.eth".to_string())
+    );
+    assert_eq!(
+        REGISTRY.to_string().parse::<RegistryRef>().unwrap(),
+        RegistryRef::Address(REGISTRY)
+    );
+}
+
+#[test]
+fn test_registry_ref_rejects_strings_that_are_neither_address_nor_ens_name() {
+    for invalid in [
+        "0x1234",
+        "not-a-registry",
+        "registry.example.com",
+        "registry..eth",
+        ".eth",
+        "my registry.eth",
+        "https://registry.eth",
+    ] {
+        assert!(invalid.parse::<RegistryRef>().is_err(), "{}", invalid);
+        assert!(
+            serde_json::from_value::<RegistryRef>(json!(invalid)).is_err(),
+            "{}",
+            invalid
+        );
+    }
+
+    assert_eq!(
+        serde_json::from_value::<RegistryRef>(json!(REGISTRY.to_string())).unwrap(),
+        RegistryRef::Address(REGISTRY)
+    );
+    assert_eq!(
+        serde_json::from_value::<RegistryRef>(json!("registry.myproject.eth")).unwrap(),
+        RegistryRef::Ens("registry.myproject.eth".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_registry_lookup_resolves_ens_name() {
+    const ENS: Address = Address::repeat_byte(0x22);
+    const RESOLVER: Address = Address::repeat_byte(0x33);
+    let program_hash = B256::repeat_byte(0x0c);
+
+    // Dispatch eth_call by target contract: ENS registry -> resolver -> ProgramRegistry
+    let rpc = start_mock_rpc(move |method, params| {
+        if method != "eth_call" {
+            return Err(format!("unexpected method {}", method));
+        }
+        let to: Address = params[0]["to"]
+            .as_str()
+            .unwrap_or_default()
+            .parse()
+            .unwrap();
+        let output = match to {
+            ENS => RESOLVER.abi_encode(),
+            RESOLVER => REGISTRY.abi_encode(),
+            REGISTRY => {
+                (program_hash, "https://example.com/ens.elf".to_string()).abi_encode_params()
+            }
+            other => return Err(format!("unexpected call to {}", other)),
+        };
+        Ok(json!(format!("0x{}", hex::encode(output))))
+    })
+    .await;
+
+    let temp_base = tempdir().unwrap().into_path();
+    let mut ctx = ServiceContext::new(
+        rpc.url(),
+        RegistryRef::Ens("registry.myproject.eth".to_string()),
+        temp_base,
+    )
+    .unwrap();
+    ctx.ens_registry_address = ENS;
+
+    let (location, registry) =
+        evm::get_program_location_from_registry(&ctx, &ctx.eth_rpc_url, None, &program_hash)
+            .await
+            .unwrap();
+    assert!(
+        matches!(location, ProgramLocation::RemoteUrl(url) if url.as_str() == "https://example.com/ens.elf")
+    );
+    assert_eq!(registry, REGISTRY);
+    assert_eq!(rpc.hits(), 3);
+
+    // The resolution is cached: only the registry is queried the second time
+    evm::get_program_location_from_registry(&ctx, &ctx.eth_rpc_url, None, &program_hash)
+        .await
+        .unwrap();
+    assert_eq!(rpc.hits(), 4);
+}
+
+#[tokio::test]
+async fn test_registry_lookup_rejects_mismatched_stored_hash() {
+    let requested = B256::repeat_byte(0x0d);
+    let stored = B256::repeat_byte(0x0e);
+
+    let rpc = start_mock_rpc(move |method, _params| match method {
+        "eth_call" => Ok(json!(format!(
+            "0x{}",
+            hex::encode((stored, "https://example.com/other.elf".to_string()).abi_encode_params())
+        ))),
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let ctx = context_for(rpc.url());
+
+    let (entry_hash, _) = evm::get_program_entry_from_registry(
+        &ctx,
+        &ctx.eth_rpc_url,
+        &ctx.registry_contract_address,
+        &requested,
+    )
+    .await
+    .unwrap();
+    assert_eq!(entry_hash, stored);
+
+    let result =
+        evm::get_program_location_from_registry(&ctx, &ctx.eth_rpc_url, None, &requested).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProgramHashMismatch { expected, got }) if expected == requested.to_string() && got == stored.to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_registry_lookup_falls_back_in_priority_order() {
+    const FALLBACK: Address = Address::repeat_byte(0x12);
+    let program_hash = B256::repeat_byte(0x0f);
+
+    let rpc = start_mock_rpc(move |method, params| {
+        if method != "eth_call" {
+            return Err(format!("unexpected method {}", method));
+        }
+        let to: Address = params[0]["to"]
+            .as_str()
+            .unwrap_or_default()
+            .parse()
+            .unwrap();
+        match to {
+            REGISTRY => Err(revert(
+                &ProgramRegistry::ProgramRegistry__ProgramNotFound {}.abi_encode(),
+            )),
+            FALLBACK => Ok(json!(format!(
+                "0x{}",
+                hex::encode(
+                    (program_hash, "https://mirror.example.com/p.elf".to_string())
+                        .abi_encode_params()
+                )
+            ))),
+            other => Err(format!("unexpected call to {}", other)),
+        }
+    })
+    .await;
+    let mut ctx = context_for(rpc.url());
+
+    // Without fallbacks the primary's miss is reported as not found
+    let result =
+        evm::get_program_location_from_registry(&ctx, &ctx.eth_rpc_url, None, &program_hash).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProgramNotFoundInRegistry(hash)) if hash == program_hash.to_string())
+    );
+
+    ctx.fallback_registries = vec![RegistryRef::Address(FALLBACK)];
+    let (location, registry) =
+        evm::get_program_location_from_registry(&ctx, &ctx.eth_rpc_url, None, &program_hash)
+            .await
+            .unwrap();
+    assert!(
+        matches!(location, ProgramLocation::RemoteUrl(url) if url.as_str() == "https://mirror.example.com/p.elf")
+    );
+    // The registry that had it, not the primary
+    assert_eq!(registry, FALLBACK);
+    assert_eq!(rpc.hits(), 3);
+}
+
+#[tokio::test]
+async fn test_registry_without_get_program_falls_back_to_location_getter() {
+    let program_hash = B256::repeat_byte(0x13);
+    let rpc = start_mock_rpc(move |method, params| {
+        if method != "eth_call" {
+            return Err(format!("unexpected method {}", method));
+        }
+        let input = params[0]["input"]
+            .as_str()
+            .or(params[0]["data"].as_str())
+            .unwrap_or_default();
+        let selector = |selector: [u8; 4]| format!("0x{}", hex::encode(selector));
+        if input.starts_with(&selector(ProgramRegistry::getProgramCall::SELECTOR)) {
+            // Deployed before getProgram: unknown selectors revert without data
+            Err(revert(&[]))
+        } else if input.starts_with(&selector(ProgramRegistry::getProgramLocationCall::SELECTOR)) {
+            Ok(json!(format!(
+                "0x{}",
+                hex::encode(("https://example.com/old.elf".to_string(),).abi_encode_params())
+            )))
+        } else {
+            Err(format!("unexpected call {}", input))
+        }
+    })
+    .await;
+    let ctx = context_for(rpc.url());
+
+    let (location, _registry) =
+        evm::get_program_location_from_registry(&ctx, &ctx.eth_rpc_url, None, &program_hash)
+            .await
+            .unwrap();
+    assert!(
+        matches!(location, ProgramLocation::RemoteUrl(url) if url.as_str() == "https://example.com/old.elf")
+    );
+    assert_eq!(rpc.hits(), 2);
+}
+
+#[tokio::test]
+async fn test_registry_empty_location_is_not_found() {
+    let program_hash = B256::repeat_byte(0x10);
+    let rpc = start_mock_rpc(move |method, _params| match method {
+        "eth_call" => Ok(json!(format!(
+            "0x{}",
+            hex::encode((program_hash, String::new()).abi_encode_params())
+        ))),
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let ctx = context_for(rpc.url());
+
+    let result =
+        evm::get_program_location_from_registry(&ctx, &ctx.eth_rpc_url, None, &program_hash).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProgramNotFoundInRegistry(hash)) if hash == program_hash.to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_describe_program_fetches_registered_schema() {
+    let program_hash = B256::repeat_byte(0x13);
+    let schema = ProgramSchema {
+        data_categories: vec!["receipts".to_string()],
+        max_sizes: Some(MaxSizes {
+            max_receipt_size: 64,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        }),
+        commit_format: CoprocessorCommitFormat::AbiEncoded,
+        commit_mode: CommitMode::Aggregate,
+        description: Some("Trading volume over the receipts".to_string()),
+    };
+    let document = serde_json::to_vec(&schema).unwrap();
+    let schema_hash = B256::from_slice(&Sha256::digest(&document));
+
+    let server = MockServer::start(move |_request| {
+        MockResponse::ok(document.clone()).with_header("Content-Type", "application/json")
+    })
+    .await;
+    let schema_uri = server.url_for("schema.json").to_string();
+    // The registry answers getProgramSchema with the given URI and hash
+    let registry_with = |uri: String, hash: B256| {
+        start_mock_rpc(move |method, _params| match method {
+            "eth_call" => Ok(json!(format!(
+                "0x{}",
+                hex::encode((uri.clone(), hash).abi_encode_params())
+            ))),
+            other => Err(format!("unexpected method {}", other)),
+        })
+    };
+    let rpc = registry_with(schema_uri.clone(), schema_hash).await;
+    let ctx = context_for(rpc.url());
+
+    let request = DescribeProgramRequest {
+        program_hash: program_hash.to_string(),
+        ..Default::default()
+    };
+    let described = describe_program(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(described, schema);
+    assert_eq!(server.hits(), 1);
+
+    // A document that doesn't match the registered hash is refused
+    let tampered = registry_with(schema_uri, B256::ZERO).await;
+    let result = describe_program(Context(context_for(tampered.url())), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::ProgramDownloadFailed(msg)) if msg.contains("registry records"))
+    );
+}
+
+#[tokio::test]
+async fn test_describe_program_without_schema_is_rejected() {
+    let program_hash = B256::repeat_byte(0x14);
+    let rpc = start_mock_rpc(move |method, _params| match method {
+        "eth_call" => Ok(json!(format!(
+            "0x{}",
+            hex::encode((String::new(), B256::ZERO).abi_encode_params())
+        ))),
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+
+    let result = describe_program(
+        Context(context_for(rpc.url())),
+        TangleArg(DescribeProgramRequest {
+            program_hash: program_hash.to_string(),
+            ..Default::default()
+        }),
+    )
+    .await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("no published schema"))
+    );
+}
+
+#[tokio::test]
+async fn test_check_program_compares_local_elf_with_registered_one() {
+    let elf = b"\x7fELF registered build".to_vec();
+    let program_hash = B256::from_slice(&Sha256::digest(&elf));
+    let body = elf.clone();
+    let server = MockServer::start(move |_request| MockResponse::ok(body.clone())).await;
+    let location = server.url_for("program.elf").to_string();
+    let rpc = start_mock_rpc(move |method, _params| match method {
+        "eth_call" => Ok(json!(format!(
+            "0x{}",
+            hex::encode((program_hash, location.clone()).abi_encode_params())
+        ))),
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let ctx = context_for(rpc.url());
+
+    let check = |elf: &[u8]| {
+        check_program_matches_registry(
+            Context(ctx.clone()),
+            TangleArg(CheckProgramRequest {
+                program_hash: program_hash.to_string(),
+                elf: STANDARD.encode(elf),
+                ..Default::default()
+            }),
+        )
+    };
+
+    let registry_hash = hex::encode(program_hash);
+    assert_eq!(check(&elf).await.unwrap().0, CheckProgramResult {
+        matches: true,
+        local_hash: registry_hash.clone(),
+        registry_hash: registry_hash.clone(),
+    });
+    assert_eq!(
+        check(b"\x7fELF local change").await.unwrap().0,
+        CheckProgramResult {
+            matches: false,
+            local_hash: hex::encode(Sha256::digest(b"\x7fELF local change")),
+            registry_hash,
+        }
+    );
+    assert_eq!(server.hits(), 2);
+
+    let empty = check(b"").await;
+    assert!(matches!(empty, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("empty")));
+
+    // Oversized ELFs are refused before the registry is asked
+    let mut small = ctx.clone();
+    small.max_elf_bytes = 4;
+    let oversized = check_program_matches_registry(
+        Context(small),
+        TangleArg(CheckProgramRequest {
+            program_hash: program_hash.to_string(),
+            elf: STANDARD.encode(&elf),
+            ..Default::default()
+        }),
+    )
+    .await;
+    assert!(
+        matches!(oversized, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("max_elf_bytes"))
+    );
+    assert_eq!(server.hits(), 2);
+}
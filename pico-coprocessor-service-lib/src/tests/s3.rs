@@ -0,0 +1,196 @@
+use super::mock::{MockRequest, MockResponse, MockServer};
+use crate::{
+    OutputSink, ProgramLocation, ProofRequest, ProofResult, ProofServiceError, ProveOptions,
+    ProverBackend, ProvingType, S3Config, S3Store, ServiceContext, VerifyProofRequest,
+    generate_proof, s3::amz_timestamp, verify_proof,
+};
+use blueprint_sdk::{
+    alloy::primitives::Address,
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use url::Url;
+
+const PROOF: &[u8] = br#"{"commitments":[1,2,3],"opened_values":"large"}"#;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+// Answers every proof with a Full result carrying PROOF
+struct FullProver;
+
+impl ProverBackend for FullProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        inputs_hex: &'a str,
+        _proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> futures::future::BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        Box::pin(async move {
+            Ok(ProofResult {
+                public_values: hex::encode([0u8, 0, 0, 42]),
+                proof: hex::encode(PROOF),
+                proving_type: ProvingType::Full,
+                inputs: inputs_hex.to_string(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+fn full_request(elf_dir: &Path, output_sink: Option<OutputSink>) -> ProofRequest {
+    let elf_path = elf_dir.join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF large proof").unwrap();
+    ProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF large proof")),
+        inputs: "0102".to_string(),
+        proving_type: ProvingType::Full,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        output_sink,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_amz_timestamp_is_utc() {
+    // The timestamp of the Signature Version 4 examples in the AWS documentation
+    assert_eq!(
+        amz_timestamp(1_440_938_160),
+        ("20150830".to_string(), "20150830T123600Z".to_string())
+    );
+    assert_eq!(amz_timestamp(0).1, "19700101T000000Z");
+    assert_eq!(amz_timestamp(951_868_799).1, "20000229T235959Z");
+}
+
+#[tokio::test]
+async fn test_s3_output_sink_uploads_proof_and_returns_its_url() {
+    let uploads: Arc<Mutex<Vec<MockRequest>>> = Arc::new(Mutex::new(Vec::new()));
+    let seen = uploads.clone();
+    let s3 = MockServer::start(move |req| {
+        seen.lock().unwrap().push(req.clone());
+        MockResponse::ok("")
+    })
+    .await;
+    let mut ctx = setup_test_context();
+    ctx.prover_backend = Arc::new(FullProver);
+    ctx.s3_store = Some(S3Store::new(S3Config {
+        endpoint: s3.url(),
+        region: "eu-west-1".to_string(),
+        access_key_id: "AKIDEXAMPLE".to_string(),
+        secret_access_key: "secret".to_string(),
+        bucket: "proofs".to_string(),
+        key_prefix: "jobs/".to_string(),
+    }));
+
+    let elf_dir = tempdir().unwrap();
+    let request = full_request(
+        elf_dir.path(),
+        Some(OutputSink::S3 {
+            bucket: "proofs".to_string(),
+            key_prefix: "jobs/".to_string(),
+        }),
+    );
+    let TangleResult(result) = generate_proof(Context(ctx.clone()), TangleArg(request))
+        .await
+        .unwrap();
+
+    // Requests can't upload outside the configured bucket and prefix
+    for (bucket, key_prefix) in [
+        ("other", "jobs/"),
+        ("proofs", ""),
+        ("proofs", "other/jobs/"),
+    ] {
+        let sink = OutputSink::S3 {
+            bucket: bucket.to_string(),
+            key_prefix: key_prefix.to_string(),
+        };
+        let rejected = generate_proof(
+            Context(ctx.clone()),
+            TangleArg(full_request(elf_dir.path(), Some(sink))),
+        )
+        .await;
+        assert!(matches!(rejected, Err(ProofServiceError::InvalidInput(_))));
+    }
+
+    assert!(result.proof_is_reference);
+    let key = format!("jobs/{}.proof", &result.proof_commitment[2..]);
+    assert_eq!(
+        result.proof,
+        s3.url_for(&format!("proofs/{}", key)).to_string()
+    );
+    let uploads = uploads.lock().unwrap();
+    assert_eq!(uploads.len(), 1);
+    let upload = &uploads[0];
+    assert_eq!(upload.method, "PUT");
+    assert_eq!(upload.path, format!("/proofs/{}", key));
+    // The raw proof bytes are stored, signed for the configured credentials and region
+    assert_eq!(upload.body, PROOF);
+    assert_eq!(
+        upload.header("x-amz-content-sha256"),
+        Some(hex::encode(Sha256::digest(PROOF)).as_str())
+    );
+    let authorization = upload.header("authorization").unwrap();
+    assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+    assert!(authorization.contains("/eu-west-1/s3/aws4_request"));
+    assert!(authorization.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date"));
+
+    // A reference can't be verified until the proof is restored
+    let verified = verify_proof(
+        Context(ctx),
+        TangleArg(VerifyProofRequest {
+            proof: result,
+            ..Default::default()
+        }),
+    )
+    .await;
+    assert!(matches!(
+        verified,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("reference")
+    ));
+}
+
+#[tokio::test]
+async fn test_s3_output_sink_requires_a_store() {
+    let mut ctx = setup_test_context();
+    ctx.prover_backend = Arc::new(FullProver);
+    let elf_dir = tempdir().unwrap();
+    let sink = OutputSink::S3 {
+        bucket: "proofs".to_string(),
+        key_prefix: String::new(),
+    };
+
+    let result = generate_proof(
+        Context(ctx.clone()),
+        TangleArg(full_request(elf_dir.path(), Some(sink))),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("no S3 store")
+    ));
+
+    // Inline delivery is unchanged
+    let TangleResult(inline) = generate_proof(
+        Context(ctx),
+        TangleArg(full_request(elf_dir.path(), Some(OutputSink::Inline))),
+    )
+    .await
+    .unwrap();
+    assert!(!inline.proof_is_reference);
+    assert_eq!(inline.proof, hex::encode(PROOF));
+}
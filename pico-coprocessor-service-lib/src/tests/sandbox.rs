@@ -0,0 +1,209 @@
+use crate::{
+    ProgramLocation, ProofRequest, ProofResult, ProofServiceError, ProveOptions, ProverBackend,
+    ProvingType, ServiceContext, generate_proof,
+    sandbox::{RequestSandbox, SANDBOX_PREFIX},
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    let rpc_url = Url::parse("http://localhost:8545").unwrap();
+    ServiceContext::new(rpc_url, Address::ZERO, temp_base)
+        .expect("Failed to create test ServiceContext")
+}
+
+fn sandbox_dirs(base: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(base)
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy().starts_with(SANDBOX_PREFIX))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+#[test]
+fn test_sandboxes_are_disjoint_and_removed_on_drop() {
+    let base = tempdir().unwrap();
+    let first = RequestSandbox::create(base.path()).unwrap();
+    let second = RequestSandbox::create(base.path()).unwrap();
+
+    assert_ne!(first.proof_id(), second.proof_id());
+    assert!(!first.path().starts_with(second.path()));
+    assert!(!second.path().starts_with(first.path()));
+    std::fs::write(first.path().join("program.elf"), b"elf").unwrap();
+    assert_eq!(sandbox_dirs(base.path()).len(), 2);
+
+    drop(first);
+    drop(second);
+    assert!(sandbox_dirs(base.path()).is_empty());
+}
+
+#[tokio::test]
+async fn test_concurrent_jobs_clean_up_their_sandboxes() {
+    let ctx = setup_test_context();
+    let base = ctx.temp_dir_base.clone();
+    let request = ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::LocalPath(PathBuf::from(
+            "/nonexistent/program.elf",
+        ))),
+        ..Default::default()
+    };
+
+    let (first, second) = tokio::join!(
+        generate_proof(Context(ctx.clone()), TangleArg(request.clone())),
+        generate_proof(Context(ctx.clone()), TangleArg(request))
+    );
+
+    assert!(first.is_err() && second.is_err());
+    assert!(sandbox_dirs(&base).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_only_temp_dir_rejected_up_front() {
+    use std::os::unix::fs::PermissionsExt;
+    let base = tempdir().unwrap();
+    let read_only = base.path().join("read-only");
+    std::fs::create_dir(&read_only).unwrap();
+    std::fs::set_permissions(&read_only, std::fs::Permissions::from_mode(0o555)).unwrap();
+    // Permissions don't bind root; nothing to check when the dir is writable regardless
+    if std::fs::write(read_only.join("probe"), b"").is_ok() {
+        return;
+    }
+
+    let result = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        read_only.clone(),
+    );
+    assert!(
+        matches!(result, Err(ProofServiceError::ConfigError(msg)) if msg.starts_with("temp dir not writable"))
+    );
+    std::fs::set_permissions(&read_only, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+// Backend recording the output directory each proof is written under
+#[derive(Default)]
+struct OutputDirProver {
+    output_dirs: Mutex<Vec<PathBuf>>,
+}
+
+impl ProverBackend for OutputDirProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        assert!(output_base_dir.is_dir());
+        self.output_dirs
+            .lock()
+            .unwrap()
+            .push(output_base_dir.to_path_buf());
+        Box::pin(async move {
+            Ok(ProofResult {
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                inputs: inputs_hex.to_string(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_temp_dir_override_holds_the_job_and_is_cleaned_up() {
+    let mut ctx = setup_test_context();
+    let prover = Arc::new(OutputDirProver::default());
+    ctx.prover_backend = prover.clone();
+    let scratch_root = tempdir().unwrap();
+    let scratch = scratch_root.path().join("big-disk");
+    std::fs::create_dir(&scratch).unwrap();
+    let scratch = scratch.canonicalize().unwrap();
+    ctx.allowed_temp_dirs = vec![scratch_root.path().to_path_buf()];
+    let elf_dir = tempdir().unwrap();
+    let elf_path = elf_dir.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        temp_dir_override: Some(scratch.clone()),
+        ..Default::default()
+    };
+    generate_proof(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+
+    let output_dirs = prover.output_dirs.lock().unwrap().clone();
+    assert_eq!(output_dirs.len(), 1);
+    assert!(output_dirs[0].starts_with(&scratch));
+    assert!(sandbox_dirs(&scratch).is_empty());
+    assert!(sandbox_dirs(&ctx.temp_dir_base).is_empty());
+
+    // A directory that doesn't exist is refused before proving
+    let mut missing = request.clone();
+    missing.temp_dir_override = Some(scratch.join("missing"));
+    let result = generate_proof(Context(ctx.clone()), TangleArg(missing)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("temp_dir_override"))
+    );
+
+    // So are directories outside the allow-list, however they are spelled
+    let outside = tempdir().unwrap();
+    for dir in [
+        outside.path().to_path_buf(),
+        scratch
+            .join("..")
+            .join("..")
+            .join(outside.path().file_name().unwrap()),
+    ] {
+        let mut escaping = request.clone();
+        escaping.temp_dir_override = Some(dir);
+        let result = generate_proof(Context(ctx.clone()), TangleArg(escaping)).await;
+        assert!(
+            matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("outside the service's allowed_temp_dirs"))
+        );
+    }
+    #[cfg(unix)]
+    {
+        let link = scratch.join("elsewhere");
+        std::os::unix::fs::symlink(outside.path(), &link).unwrap();
+        let mut linked = request.clone();
+        linked.temp_dir_override = Some(link);
+        let result = generate_proof(Context(ctx.clone()), TangleArg(linked)).await;
+        assert!(
+            matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("outside the service's allowed_temp_dirs"))
+        );
+    }
+
+    // Without an allow-list no override is honoured
+    ctx.allowed_temp_dirs.clear();
+    let result = generate_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("requires the service to configure allowed_temp_dirs"))
+    );
+    assert_eq!(prover.output_dirs.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,269 @@
+use crate::{
+    BlockchainData, CoprocessorProofRequest, MaxSizes, ProgramLocation, ProofRequest, ProofResult,
+    ProofServiceError, ProveOptions, ProverBackend, ProvingType, SerializableStorageSlot,
+    ServiceContext,
+    fixtures::CoprocessorFixtureBuilder,
+    generate_coprocessor_proof, generate_proof,
+    jobs::coprocessor::{CoprocessorInputBundle, build_input_bundle},
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256, U256},
+    extract::Context,
+    tangle::extract::{TangleArg, TangleResult},
+};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::{TempDir, tempdir};
+use url::Url;
+
+// Shaped like the SDK's `save_inputs` output (`request_prove_inputs.json`), with one receipt
+const SDK_INPUTS: &str = r#"{"chain_id":1,"max_receipt_size":32,"max_storage_size":0,"max_tx_size":0,"receipts":[{"transaction_hash":[83,179,126,199,151,93,33,114,149,244,189,173,248,4,59,38,31,196,157,204,193,109,169,185,252,139,149,48,132,90,87,148],"block_num":18064070,"block_base_fee":"db9fc6cf07000000000000000000000000000000000000000000000000000000","block_time":1693842011,"mpt_key_path":36,"fields":[]}],"storage_slots":null,"transactions":null,"dummy_commitments":{"receipt_dummy_commitment":[0,0,0,0,0,0,0,0],"storage_dummy_commitment":[0,0,0,0,0,0,0,0],"tx_dummy_commitment":[0,0,0,0,0,0,0,0]},"input_commitments":[0,0,0,0,0,0,0,0],"commited":false}"#;
+
+// Backend recording the inputs of every proof it is asked for
+#[derive(Default)]
+struct RecordingProver {
+    inputs: Mutex<Vec<String>>,
+}
+
+impl ProverBackend for RecordingProver {
+    fn prove<'a>(
+        &'a self,
+        _elf: &'a [u8],
+        inputs_hex: &'a str,
+        proving_type: &'a ProvingType,
+        _output_base_dir: &'a Path,
+        _options: &'a ProveOptions,
+    ) -> BoxFuture<'a, Result<ProofResult, ProofServiceError>> {
+        self.inputs.lock().unwrap().push(inputs_hex.to_string());
+        Box::pin(async move {
+            Ok(ProofResult {
+                public_values: hex::encode([42u8]),
+                proof: hex::encode(b"proof"),
+                proving_type: proving_type.clone(),
+                inputs: inputs_hex.to_string(),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+fn recording_context() -> (
+    ServiceContext,
+    Arc<RecordingProver>,
+    TempDir,
+    ProgramLocation,
+) {
+    let temp_base = tempdir().unwrap();
+    let mut ctx = ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base.path().join("service"),
+    )
+    .unwrap();
+    let prover = Arc::new(RecordingProver::default());
+    ctx.prover_backend = prover.clone();
+
+    let elf_path = temp_base.path().join("program.elf");
+    std::fs::write(&elf_path, b"\x7fELF").unwrap();
+    (ctx, prover, temp_base, ProgramLocation::LocalPath(elf_path))
+}
+
+fn sdk_request(location: ProgramLocation) -> CoprocessorProofRequest {
+    CoprocessorProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        sdk_inputs: Some(SDK_INPUTS.to_string()),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_sdk_inputs_prove_like_the_saved_file() {
+    let (ctx, prover, _temp, location) = recording_context();
+
+    let TangleResult(result) = generate_coprocessor_proof(
+        Context(ctx.clone()),
+        TangleArg(sdk_request(location.clone())),
+    )
+    .await
+    .unwrap();
+    // The program reads the SDK's file byte for byte, set up as `Builder::init` was
+    assert_eq!(result.decode_inputs().unwrap(), SDK_INPUTS.as_bytes());
+    assert_eq!(result.sdk_init_params, Some((32, 0, 0)));
+
+    // ...exactly as if the file had been proven directly
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(b"\x7fELF")),
+        inputs: hex::encode(SDK_INPUTS),
+        proving_type: ProvingType::Fast,
+        program_location_override: Some(location),
+        ..Default::default()
+    };
+    let TangleResult(direct) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+    assert_eq!(direct.public_values, result.public_values);
+    let inputs = prover.inputs.lock().unwrap();
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(inputs[0], inputs[1]);
+}
+
+#[tokio::test]
+async fn test_sdk_inputs_exclude_blockchain_data_and_need_sdk_json() {
+    let (ctx, prover, _temp, location) = recording_context();
+
+    let mut request = sdk_request(location.clone());
+    request.blockchain_data = BlockchainData {
+        storage_slots: Some(vec![SerializableStorageSlot {
+            address: Address::ZERO,
+            slot: B256::ZERO,
+            value: B256::ZERO,
+            block_number: U256::ZERO,
+        }]),
+        ..Default::default()
+    };
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("replaces blockchain_data"))
+    );
+
+    let mut request = sdk_request(location);
+    request.sdk_inputs = Some(r#"{"receipts":[]}"#.to_string());
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("not coprocessor SDK save_inputs JSON"))
+    );
+    assert!(prover.inputs.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sdk_inputs_are_held_to_the_service_limits() {
+    let (ctx, prover, _temp, location) = recording_context();
+    let rejected = |ctx: &ServiceContext, request: CoprocessorProofRequest| {
+        let ctx = ctx.clone();
+        async move {
+            match generate_coprocessor_proof(Context(ctx), TangleArg(request)).await {
+                Err(err) => err,
+                Ok(_) => panic!("SDK inputs outside the service's limits were proven"),
+            }
+        }
+    };
+
+    let mut small = ctx.clone();
+    small.max_input_bytes = SDK_INPUTS.len() - 1;
+    assert!(matches!(
+        rejected(&small, sdk_request(location.clone())).await,
+        ProofServiceError::InputsTooLarge { .. }
+    ));
+
+    let mut capped = ctx.clone();
+    capped.hard_limits.max_receipts_hard = 0;
+    assert!(matches!(
+        rejected(&capped, sdk_request(location.clone())).await,
+        ProofServiceError::InvalidInput(msg) if msg.starts_with("Too many receipts")
+    ));
+
+    let mut request = sdk_request(location.clone());
+    request.sdk_inputs =
+        Some(SDK_INPUTS.replace(r#""max_receipt_size":32"#, r#""max_receipt_size":33"#));
+    assert!(matches!(
+        rejected(&ctx, request).await,
+        ProofServiceError::InvalidInput(msg) if msg.contains("max_receipt_size must be > 0 and a multiple of 32")
+    ));
+
+    // The saved receipt is from block 18064070
+    let mut request = sdk_request(location);
+    request.anchor_block_number = Some(18064069);
+    assert!(matches!(
+        rejected(&ctx, request).await,
+        ProofServiceError::InvalidInput(msg) if msg.contains("after the anchor block")
+    ));
+    assert!(prover.inputs.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_used_sizes_report_capacity_consumed() {
+    let (ctx, _prover, _temp, location) = recording_context();
+
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(3).build();
+    let mut request = fixture.request(hex::encode(Sha256::digest(b"\x7fELF")), PathBuf::new());
+    request.program_location_override = Some(location.clone());
+    let TangleResult(result) = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request))
+        .await
+        .unwrap();
+    let used = result.used_sizes.expect("used sizes reported");
+    assert_eq!(used, MaxSizes {
+        max_receipt_size: 3,
+        max_storage_size: 0,
+        max_tx_size: 0,
+    });
+    assert!(used.max_receipt_size <= fixture.max_sizes.max_receipt_size);
+    assert!(used.max_storage_size <= fixture.max_sizes.max_storage_size);
+    assert!(used.max_tx_size <= fixture.max_sizes.max_tx_size);
+
+    // SDK-format inputs report what their file carries, not the sizes it declares
+    let TangleResult(result) =
+        generate_coprocessor_proof(Context(ctx), TangleArg(sdk_request(location)))
+            .await
+            .unwrap();
+    assert_eq!(result.used_sizes.unwrap().max_receipt_size, 1);
+    assert_eq!(result.sdk_init_params, Some((32, 0, 0)));
+}
+
+#[tokio::test]
+async fn test_dumped_inputs_deserialize_to_the_proven_bundle() {
+    let (mut ctx, prover, temp, location) = recording_context();
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(3).build();
+    let mut request = fixture.request(
+        hex::encode(Sha256::digest(b"\x7fELF")),
+        PathBuf::from("unused.elf"),
+    );
+    request.program_location_override = Some(location);
+    request.dump_inputs_to = Some("inputs.json".to_string());
+
+    // Dumps are refused until the operator configures a directory for them
+    let result = generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request.clone())).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("dump_inputs_dir")
+    ));
+    ctx.dump_inputs_dir = Some(temp.path().to_path_buf());
+    let dump_path = temp.path().join("inputs.json");
+
+    generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request.clone()))
+        .await
+        .unwrap();
+
+    let dumped = std::fs::read(&dump_path).unwrap();
+    let bundle: CoprocessorInputBundle = serde_json::from_slice(&dumped).unwrap();
+    assert_eq!(bundle, build_input_bundle(&ctx, &request).unwrap());
+    // The file holds exactly the bytes the program was given
+    assert_eq!(prover.inputs.lock().unwrap()[0], hex::encode(&dumped));
+
+    // Paths out of the directory, and existing files, fail the request before anything is proven
+    for file_name in [
+        "inputs.json",
+        "../inputs.json",
+        "nested/inputs.json",
+        "..",
+        "",
+    ] {
+        request.dump_inputs_to = Some(file_name.to_string());
+        let result =
+            generate_coprocessor_proof(Context(ctx.clone()), TangleArg(request.clone())).await;
+        assert!(
+            matches!(
+                &result,
+                Err(ProofServiceError::InvalidInput(msg)) if msg.contains("dump_inputs_to")
+            ),
+            "{:?}: {:?}",
+            file_name,
+            result
+        );
+    }
+    assert_eq!(std::fs::read(&dump_path).unwrap(), dumped);
+    assert_eq!(prover.inputs.lock().unwrap().len(), 1);
+}
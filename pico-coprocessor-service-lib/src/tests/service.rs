@@ -0,0 +1,44 @@
+use crate::{
+    CHECK_PROGRAM_JOB_ID, DESCRIBE_PROGRAM_JOB_ID, GENERATE_PROOF_JOB_ID, LIST_PROGRAMS_JOB_ID,
+    ProofServiceBuilder, ProofServiceError, ServiceContext, ServiceMode, VERIFY_PROOF_JOB_ID,
+};
+use blueprint_sdk::Router;
+
+#[test]
+fn test_each_job_is_registered_once_per_mode() {
+    let mut verify_only = ProofServiceBuilder::new(ServiceMode::VerifyOnly);
+    verify_only
+        .register_jobs(Router::<ServiceContext>::new())
+        .unwrap();
+    assert_eq!(verify_only.job_ids(), [
+        VERIFY_PROOF_JOB_ID,
+        LIST_PROGRAMS_JOB_ID,
+        DESCRIBE_PROGRAM_JOB_ID,
+        CHECK_PROGRAM_JOB_ID
+    ]);
+
+    let mut proving = ProofServiceBuilder::new(ServiceMode::ProveAndVerify);
+    proving
+        .register_jobs(Router::<ServiceContext>::new())
+        .unwrap();
+    let mut ids = proving.job_ids().to_vec();
+    ids.sort_unstable();
+    assert_eq!(ids, (1..=11).collect::<Vec<u32>>());
+}
+
+#[test]
+fn test_double_registration_is_detected() {
+    let mut builder = ProofServiceBuilder::new(ServiceMode::ProveAndVerify);
+    builder.claim(GENERATE_PROOF_JOB_ID).unwrap();
+    assert!(matches!(
+        builder.claim(GENERATE_PROOF_JOB_ID),
+        Err(ProofServiceError::ConfigError(msg)) if msg == "Job ID 1 registered twice"
+    ));
+
+    // A job claimed elsewhere can't be routed again by register_jobs
+    let result = builder.register_jobs(Router::<ServiceContext>::new());
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::ConfigError(msg)) if msg.contains("Job ID 1")
+    ));
+}
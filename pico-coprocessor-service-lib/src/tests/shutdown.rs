@@ -0,0 +1,61 @@
+use crate::{ProofServiceError, ServiceContext, graceful_shutdown};
+use blueprint_sdk::alloy::primitives::Address;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    ServiceContext::new(
+        Url::parse("http://localhost:8545").unwrap(),
+        Address::ZERO,
+        temp_base,
+    )
+    .expect("Failed to create test ServiceContext")
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_in_flight_proof() {
+    let ctx = setup_test_context();
+    let orphan = ctx.temp_dir_base.join("req_orphaned");
+    std::fs::create_dir(&orphan).unwrap();
+
+    let guard = ctx.in_flight.begin().unwrap();
+    let finished = Arc::new(AtomicBool::new(false));
+    let job_finished = finished.clone();
+    let job = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        job_finished.store(true, Ordering::SeqCst);
+        drop(guard);
+    });
+
+    let report = graceful_shutdown(&ctx, Duration::from_secs(5)).await;
+    assert!(
+        finished.load(Ordering::SeqCst),
+        "shutdown returned before the proof finished"
+    );
+    assert_eq!(report.abandoned, 0);
+    assert_eq!(report.removed_sandboxes, 1);
+    assert!(!orphan.exists());
+    job.await.unwrap();
+
+    // No new work is admitted once shutdown has started
+    assert!(matches!(
+        ctx.in_flight.begin(),
+        Err(ProofServiceError::ShuttingDown)
+    ));
+}
+
+#[tokio::test]
+async fn test_shutdown_gives_up_after_grace_period() {
+    let ctx = setup_test_context();
+    let _stuck = ctx.in_flight.begin().unwrap();
+
+    let report = graceful_shutdown(&ctx, Duration::from_millis(20)).await;
+    assert_eq!(report.abandoned, 1);
+    assert!(ctx.in_flight.is_closed());
+}
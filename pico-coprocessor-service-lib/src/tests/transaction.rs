@@ -0,0 +1,87 @@
+use crate::{ProofServiceError, SerializableTransaction};
+use blueprint_sdk::alloy::{
+    consensus::{Transaction as ConsensusTransaction, TxEnvelope},
+    eips::eip2718::Decodable2718,
+    primitives::{Address, B256, U256, address, b256},
+    rpc::types::Transaction,
+};
+use serde_json::json;
+
+const USDC: Address = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+const SENDER: Address = address!("6a000f20005980200259b80c5102003040001068");
+const TX_HASH: B256 = b256!("d97c7863076f6b8a2430f3cc363220a1d67ee990d2673c927c93822fa541d39c");
+// transfer(0x88e6...5640, 1000 USDC)
+const TRANSFER_INPUT: &str = "a9059cbb00000000000000000000000088e6a0c2ddd26feeb64f039a2c41296fcb3f5640000000000000000000000000000000000000000000000000000000003b9aca00";
+
+// An EIP-1559 USDC transfer as returned by eth_getTransactionByHash
+fn rpc_transaction() -> Transaction {
+    serde_json::from_value(json!({
+        "blockHash": "0x5c5d2a3e0b0d0e6c1a1b2d3f4e5a6b7c8d9e0f1a2b3c4d5e6f708192a3b4c5d6",
+        "blockNumber": "0x1406f40",
+        "transactionIndex": "0x0",
+        "hash": TX_HASH,
+        "type": "0x2",
+        "chainId": "0x1",
+        "nonce": "0x2a",
+        "from": SENDER,
+        "to": USDC,
+        "value": "0x0",
+        "gas": "0xfde8",
+        "gasPrice": "0x4a817c800",
+        "maxFeePerGas": "0x4a817c800",
+        "maxPriorityFeePerGas": "0x3b9aca00",
+        "input": format!("0x{}", TRANSFER_INPUT),
+        "accessList": [],
+        "v": "0x1",
+        "yParity": "0x1",
+        "r": "0x1b5e176d927f8e9ab405058b2d2457392da3e20f328b16ddabcebc33eaac5fea",
+        "s": "0x4ba69724e8f69de52f0125ad8b3c5c2cef33019bac3249e2c0a2192766d1721c"
+    }))
+    .expect("valid RPC transaction")
+}
+
+#[test]
+fn test_transaction_round_trips_through_alloy() {
+    let tx = rpc_transaction();
+    let serialized = SerializableTransaction::from_alloy(&tx);
+    assert_eq!(serialized.transaction_hash, TX_HASH);
+    assert_eq!(serialized.from, SENDER);
+    assert_eq!(serialized.to, Some(USDC));
+    assert_eq!(serialized.value, U256::ZERO);
+    assert_eq!(serialized.input_data_hex, TRANSFER_INPUT);
+    assert!(serialized.raw_data_hex.starts_with("02")); // EIP-1559 type byte
+
+    // Survives the JSON the job receives
+    let json = serde_json::to_string(&serialized).unwrap();
+    let serialized: SerializableTransaction = serde_json::from_str(&json).unwrap();
+
+    let fields = serialized.to_alloy_fields().unwrap();
+    assert_eq!(fields.transaction_hash, TX_HASH);
+    assert_eq!(fields.from, SENDER);
+    assert_eq!(fields.to, Some(USDC));
+    assert_eq!(fields.value, U256::ZERO);
+    assert_eq!(&fields.input, ConsensusTransaction::input(&tx));
+
+    // The raw encoding decodes back to the same signed transaction
+    let envelope = TxEnvelope::decode_2718(&mut fields.raw.as_ref()).unwrap();
+    assert_eq!(envelope.nonce(), 42);
+    assert_eq!(envelope.gas_limit(), 65_000);
+    assert_eq!(envelope.to(), Some(USDC));
+    assert_eq!(envelope.input(), &fields.input);
+}
+
+#[test]
+fn test_transaction_fields_accept_0x_and_reject_bad_hex() {
+    let mut serialized = SerializableTransaction::from_alloy(&rpc_transaction());
+    serialized.input_data_hex = format!("0x{}", TRANSFER_INPUT);
+    serialized.raw_data_hex = String::new();
+    let fields = serialized.to_alloy_fields().unwrap();
+    assert_eq!(hex::encode(&fields.input), TRANSFER_INPUT);
+    assert!(fields.raw.is_empty());
+
+    serialized.raw_data_hex = "f8zz".to_string();
+    assert!(matches!(
+        serialized.to_alloy_fields(),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("raw_data_hex")
+    ));
+}
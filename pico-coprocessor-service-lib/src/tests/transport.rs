@@ -0,0 +1,117 @@
+use crate::{
+    BlockchainData, CoprocessorProofRequest, MaxSizes, OutputEncoding, PUBLIC_VALUES_LENGTH_HEADER,
+    ProofResult, ProofServiceError, ProvingType, SerializableLog, SerializableReceipt,
+    decode_request_body, encode_proof_response, reencode_result,
+};
+use blueprint_sdk::alloy::primitives::{Address, B256, U256};
+use flate2::{Compression, write::GzEncoder};
+use std::io::Write;
+
+const LIMIT: usize = 1 << 20;
+
+fn sample_request() -> CoprocessorProofRequest {
+    let receipt = SerializableReceipt {
+        transaction_hash: B256::repeat_byte(0xab),
+        status: Some(U256::from(1)),
+        logs: vec![SerializableLog {
+            address: Address::repeat_byte(0x11),
+            topics: vec![B256::repeat_byte(0x22)],
+            data_hex: "00".repeat(32),
+            log_pos: None,
+        }],
+        raw_data_hex: String::new(),
+        inclusion_proof: None,
+        block_number: None,
+    };
+    CoprocessorProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        blockchain_data: BlockchainData {
+            receipts: Some(vec![receipt; 64]),
+            storage_slots: None,
+            transactions: None,
+        },
+        max_sizes: MaxSizes {
+            max_receipt_size: 64,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    }
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn test_gzip_request_body_decodes_like_uncompressed() {
+    let body = serde_json::to_vec(&sample_request()).unwrap();
+    let compressed = gzip(&body);
+    assert!(compressed.len() < body.len());
+
+    let plain: CoprocessorProofRequest = decode_request_body(None, &body, LIMIT).unwrap();
+    let unzipped: CoprocessorProofRequest =
+        decode_request_body(Some("gzip"), &compressed, LIMIT).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&plain).unwrap(),
+        serde_json::to_value(&unzipped).unwrap()
+    );
+    assert_eq!(plain.blockchain_data, unzipped.blockchain_data);
+}
+
+#[test]
+fn test_request_body_rejects_unknown_encoding_and_oversized_bodies() {
+    let body = serde_json::to_vec(&sample_request()).unwrap();
+
+    let result = decode_request_body::<CoprocessorProofRequest>(Some("br"), &body, LIMIT);
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("Unsupported Content-Encoding"))
+    );
+
+    let result = decode_request_body::<CoprocessorProofRequest>(Some("gzip"), &gzip(&body), 16);
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("exceeds 16 bytes"))
+    );
+}
+
+fn sample_result() -> ProofResult {
+    ProofResult {
+        public_values: hex::encode([0x11u8; 32]),
+        proof: hex::encode([0x22u8, 0x33, 0x44]),
+        proving_type: ProvingType::Fast,
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_octet_stream_response_carries_raw_bytes() {
+    let result = sample_result();
+    let json = encode_proof_response(None, &result).unwrap();
+    assert_eq!(json.content_type, "application/json");
+    let decoded: ProofResult = serde_json::from_slice(&json.body).unwrap();
+
+    let raw = encode_proof_response(
+        Some("application/octet-stream, application/json;q=0.5"),
+        &result,
+    )
+    .unwrap();
+    assert_eq!(raw.content_type, "application/octet-stream");
+    assert_eq!(raw.headers, vec![(
+        PUBLIC_VALUES_LENGTH_HEADER,
+        "32".to_string()
+    )]);
+    let (public_values, proof) = raw.body.split_at(32);
+    assert_eq!(public_values, hex::decode(&decoded.public_values).unwrap());
+    assert_eq!(proof, hex::decode(&decoded.proof).unwrap());
+
+    // Raw bytes don't depend on the result's output encoding
+    let base64 = reencode_result(result, OutputEncoding::Base64).unwrap();
+    let raw_from_base64 = encode_proof_response(Some("application/octet-stream"), &base64).unwrap();
+    assert_eq!(raw_from_base64.body, raw.body);
+}
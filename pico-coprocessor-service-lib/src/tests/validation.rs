@@ -0,0 +1,472 @@
+use crate::{
+    BlockchainData, CoprocessorProofRequest, HardLimits, MaxSizes, ProofRequest, ProofServiceError,
+    ProvingType, SerializableLog, SerializableReceipt, SerializableStorageSlot,
+    SerializableTransaction, ServiceContext, generate_coprocessor_proof, generate_proof, unix_now,
+    validate_expiry, validate_hard_limits, validate_max_sizes, validate_receipt_blocks,
+    validate_receipt_log_structure, validate_topic_counts,
+};
+use blueprint_sdk::{
+    alloy::primitives::{Address, B256, U256},
+    extract::Context,
+    tangle::extract::TangleArg,
+};
+use std::collections::{HashMap, HashSet};
+use tempfile::tempdir;
+use url::Url;
+
+fn setup_test_context() -> ServiceContext {
+    let temp_base = tempdir()
+        .expect("Failed to create base temp dir for tests")
+        .into_path();
+    let rpc_url = Url::parse("http://localhost:8545").unwrap();
+    ServiceContext::new(rpc_url, Address::ZERO, temp_base)
+        .expect("Failed to create test ServiceContext")
+}
+
+fn log_at(log_pos: Option<u64>) -> SerializableLog {
+    SerializableLog {
+        address: Address::repeat_byte(0x88),
+        topics: vec![B256::repeat_byte(0xc4)],
+        data_hex: String::new(),
+        log_pos,
+    }
+}
+
+fn receipt_with_logs(logs: Vec<SerializableLog>) -> SerializableReceipt {
+    SerializableReceipt {
+        transaction_hash: B256::repeat_byte(0xd9),
+        status: Some(U256::from(1)),
+        logs,
+        raw_data_hex: String::new(),
+        inclusion_proof: None,
+        block_number: None,
+    }
+}
+
+fn data_with(receipts: Vec<SerializableReceipt>) -> BlockchainData {
+    BlockchainData {
+        receipts: Some(receipts),
+        storage_slots: None,
+        transactions: None,
+    }
+}
+
+fn request_with(data: BlockchainData) -> CoprocessorProofRequest {
+    CoprocessorProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        blockchain_data: data,
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_receipt_log_structure_accepts_paired_logs() {
+    let data = data_with(vec![receipt_with_logs(vec![
+        log_at(Some(17)),
+        log_at(Some(17)),
+    ])]);
+    assert!(validate_receipt_log_structure(&data).is_ok());
+}
+
+#[test]
+fn test_receipt_log_structure_rejects_missing_second_log() {
+    let data = data_with(vec![receipt_with_logs(vec![log_at(Some(17))])]);
+    assert!(
+        matches!(validate_receipt_log_structure(&data), Err(ProofServiceError::InvalidInput(msg)) if msg.contains("expected at least 2"))
+    );
+}
+
+#[tokio::test]
+async fn test_coprocessor_job_rejects_mismatched_log_pos_when_enabled() {
+    let data = data_with(vec![receipt_with_logs(vec![
+        log_at(Some(17)),
+        log_at(Some(18)),
+    ])]);
+    let mut request = request_with(data);
+    request.validate_log_structure = true;
+
+    let result =
+        generate_coprocessor_proof(Context(setup_test_context()), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("must share log_pos"))
+    );
+}
+
+#[test]
+fn test_expiry_rejects_passed_deadline() {
+    assert!(validate_expiry(None, 100).is_ok());
+    assert!(validate_expiry(Some(101), 100).is_ok());
+    assert!(
+        matches!(validate_expiry(Some(100), 100), Err(ProofServiceError::InvalidInput(msg)) if msg.contains("expired"))
+    );
+}
+
+#[tokio::test]
+async fn test_jobs_reject_expired_requests() {
+    let expired = Some(unix_now() - 60);
+
+    let mut request = request_with(data_with(vec![]));
+    request.expires_at = expired;
+    let result =
+        generate_coprocessor_proof(Context(setup_test_context()), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("expired"))
+    );
+
+    let request = ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: String::new(),
+        proving_type: ProvingType::Fast,
+        expires_at: expired,
+        ..Default::default()
+    };
+    let result = generate_proof(Context(setup_test_context()), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("expired"))
+    );
+}
+
+fn sizes(max_receipt_size: usize, max_storage_size: usize, max_tx_size: usize) -> MaxSizes {
+    MaxSizes {
+        max_receipt_size,
+        max_storage_size,
+        max_tx_size,
+    }
+}
+
+fn assert_max_sizes_error(result: Result<(), ProofServiceError>, expected: &[&str]) {
+    match result {
+        Err(ProofServiceError::InvalidInput(msg)) => {
+            assert!(msg.contains("Invalid max_sizes"), "{}", msg);
+            for part in expected {
+                assert!(msg.contains(part), "expected {:?} in {:?}", part, msg);
+            }
+        }
+        other => panic!("Expected InvalidInput, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_default_max_sizes_pass_validation() {
+    assert!(validate_max_sizes(&MaxSizes::default(), &BlockchainData::default()).is_ok());
+    assert!(
+        validate_max_sizes(
+            &CoprocessorProofRequest::default().max_sizes,
+            &data_with(vec![receipt_with_logs(Vec::new()); 32])
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_max_sizes_names_each_invalid_category() {
+    let data = BlockchainData::default();
+    assert!(validate_max_sizes(&sizes(32, 64, 96), &data).is_ok());
+
+    assert_max_sizes_error(validate_max_sizes(&sizes(33, 32, 32), &data), &[
+        "max_receipt_size",
+        "33",
+    ]);
+    assert_max_sizes_error(validate_max_sizes(&sizes(32, 0, 32), &data), &[
+        "max_storage_size",
+        "got 0",
+    ]);
+    assert_max_sizes_error(validate_max_sizes(&sizes(32, 32, 48), &data), &[
+        "max_tx_size",
+        "48",
+    ]);
+}
+
+#[test]
+fn test_max_sizes_names_each_overflowing_category() {
+    let receipts = data_with(vec![receipt_with_logs(Vec::new()); 33]);
+    assert_max_sizes_error(validate_max_sizes(&sizes(32, 32, 32), &receipts), &[
+        "33 receipts",
+        "max_receipt_size of 32",
+    ]);
+
+    let storage = BlockchainData {
+        storage_slots: Some(vec![
+            SerializableStorageSlot {
+                address: Address::ZERO,
+                slot: B256::ZERO,
+                value: B256::ZERO,
+                block_number: U256::ZERO,
+            };
+            33
+        ]),
+        ..Default::default()
+    };
+    assert_max_sizes_error(validate_max_sizes(&sizes(32, 32, 32), &storage), &[
+        "33 storage slots",
+        "max_storage_size of 32",
+    ]);
+
+    let transactions = BlockchainData {
+        transactions: Some(vec![
+            SerializableTransaction {
+                transaction_hash: B256::ZERO,
+                from: Address::ZERO,
+                to: None,
+                value: U256::ZERO,
+                input_data_hex: String::new(),
+                raw_data_hex: String::new(),
+            };
+            65
+        ]),
+        ..Default::default()
+    };
+    assert_max_sizes_error(validate_max_sizes(&sizes(32, 32, 64), &transactions), &[
+        "65 transactions",
+        "max_tx_size of 64",
+    ]);
+}
+
+fn small_limits() -> HardLimits {
+    HardLimits {
+        max_receipts_hard: 2,
+        max_logs_per_receipt_hard: 2,
+        max_storage_slots_hard: 1,
+        max_transactions_hard: 1,
+    }
+}
+
+fn assert_hard_limit_error(result: Result<(), ProofServiceError>, expected: &str) {
+    match result {
+        Err(ProofServiceError::InvalidInput(msg)) => {
+            assert!(
+                msg.contains(expected),
+                "expected {:?} in {:?}",
+                expected,
+                msg
+            )
+        }
+        other => panic!("Expected InvalidInput, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_hard_limits_reject_each_category() {
+    let limits = small_limits();
+    let paired = || receipt_with_logs(vec![log_at(Some(1)), log_at(Some(1))]);
+    assert!(validate_hard_limits(&limits, &data_with(vec![paired(), paired()])).is_ok());
+
+    assert_hard_limit_error(
+        validate_hard_limits(&limits, &data_with(vec![paired(), paired(), paired()])),
+        "Too many receipts: 3 exceeds the service limit of 2",
+    );
+    assert_hard_limit_error(
+        validate_hard_limits(
+            &limits,
+            &data_with(vec![receipt_with_logs(vec![log_at(Some(1)); 3])]),
+        ),
+        "Too many logs in receipt 0: 3",
+    );
+
+    let slot = SerializableStorageSlot {
+        address: Address::ZERO,
+        slot: B256::ZERO,
+        value: B256::ZERO,
+        block_number: U256::ZERO,
+    };
+    let storage = BlockchainData {
+        storage_slots: Some(vec![slot.clone(), slot]),
+        ..Default::default()
+    };
+    assert_hard_limit_error(
+        validate_hard_limits(&limits, &storage),
+        "Too many storage slots",
+    );
+
+    let tx = SerializableTransaction {
+        transaction_hash: B256::ZERO,
+        from: Address::ZERO,
+        to: None,
+        value: U256::ZERO,
+        input_data_hex: String::new(),
+        raw_data_hex: String::new(),
+    };
+    let transactions = BlockchainData {
+        transactions: Some(vec![tx.clone(), tx]),
+        ..Default::default()
+    };
+    assert_hard_limit_error(
+        validate_hard_limits(&limits, &transactions),
+        "Too many transactions",
+    );
+}
+
+#[tokio::test]
+async fn test_coprocessor_job_enforces_hard_limits_despite_max_sizes() {
+    let mut ctx = setup_test_context();
+    ctx.hard_limits = small_limits();
+
+    // Declared sizes are generous and valid, but the service cap is lower
+    let mut request = request_with(data_with(vec![receipt_with_logs(Vec::new()); 3]));
+    request.max_sizes.max_receipt_size = 1024;
+
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("Too many receipts"))
+    );
+}
+
+#[tokio::test]
+async fn test_coprocessor_job_rejects_bundle_over_prover_input_limit() {
+    let mut ctx = setup_test_context();
+    ctx.max_prover_input_bytes = 512;
+
+    // Well within max_sizes and the hard limits, but the serialized bundle is far over 512 bytes
+    let receipts = vec![receipt_with_logs(vec![log_at(Some(1)), log_at(Some(1))]); 8];
+    let result =
+        generate_coprocessor_proof(Context(ctx), TangleArg(request_with(data_with(receipts))))
+            .await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::InputsTooLarge { size, limit: 512 } if size > 512
+    ));
+}
+
+#[test]
+fn test_topic_counts_accept_matching_logs() {
+    // log_at emits a single topic0 of 0xc4..
+    let expected = HashMap::from([(B256::repeat_byte(0xc4), 1)]);
+    let data = data_with(vec![receipt_with_logs(vec![
+        log_at(Some(1)),
+        log_at(Some(1)),
+    ])]);
+    assert!(validate_topic_counts(&data, &expected).is_ok());
+
+    // Unlisted events are not checked
+    let other = HashMap::from([(B256::repeat_byte(0x01), 4)]);
+    assert!(validate_topic_counts(&data, &other).is_ok());
+}
+
+#[tokio::test]
+async fn test_coprocessor_job_rejects_log_with_too_few_topics() {
+    let signature = B256::repeat_byte(0xc4);
+    let mut swap = log_at(Some(1));
+    swap.topics.push(B256::repeat_byte(0x6a)); // topic1: user
+    let truncated = log_at(Some(1)); // topic0 only
+
+    let mut request = request_with(data_with(vec![receipt_with_logs(vec![swap, truncated])]));
+    request.expected_topic_counts = Some(HashMap::from([(signature, 2)]));
+
+    let result =
+        generate_coprocessor_proof(Context(setup_test_context()), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("Receipt 0 log 1") && msg.contains("has 1 topics, expected 2"))
+    );
+}
+
+#[test]
+fn test_rpc_override_host_must_be_allow_listed() {
+    let mut ctx = setup_test_context();
+    // Without an allow-list any well-formed override is accepted
+    assert_eq!(
+        ctx.rpc_url_for(Some("http://169.254.169.254/"))
+            .unwrap()
+            .as_str(),
+        "http://169.254.169.254/"
+    );
+    // Without an override the service's own RPC applies
+    assert_eq!(ctx.rpc_url_for(None).unwrap(), ctx.eth_rpc_url);
+    assert!(matches!(
+        ctx.rpc_url_for(Some("not a url")),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("eth_rpc_url_override")
+    ));
+
+    ctx.allowed_rpc_hosts = Some(HashSet::from(["rpc.example.com".to_string()]));
+    ctx.rpc_url_for(Some("https://rpc.example.com/v1/key"))
+        .unwrap();
+    ctx.rpc_url_for(Some("https://RPC.Example.com:8545"))
+        .unwrap();
+    assert_eq!(ctx.rpc_url_for(None).unwrap(), ctx.eth_rpc_url);
+    for disallowed in [
+        "http://169.254.169.254/latest/meta-data",
+        "http://localhost:8545",
+        "https://rpc.example.com.attacker.net",
+    ] {
+        assert!(matches!(
+            ctx.rpc_url_for(Some(disallowed)),
+            Err(ProofServiceError::InvalidInput(msg)) if msg.starts_with("rpc host not allowed")
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_jobs_reject_disallowed_rpc_override() {
+    let mut ctx = setup_test_context();
+    ctx.allowed_rpc_hosts = Some(HashSet::from(["rpc.example.com".to_string()]));
+    let rpc_override = Some("http://10.0.0.1:8545".to_string());
+
+    let request = ProofRequest {
+        program_hash: B256::repeat_byte(0x01).to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        eth_rpc_url_override: rpc_override.clone(),
+        ..Default::default()
+    };
+    let result = generate_proof(Context(ctx.clone()), TangleArg(request)).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.starts_with("rpc host not allowed")
+    ));
+
+    let request = CoprocessorProofRequest {
+        program_hash: B256::repeat_byte(0x01).to_string(),
+        max_sizes: MaxSizes {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        },
+        proving_type: ProvingType::Fast,
+        eth_rpc_url_override: rpc_override,
+        ..Default::default()
+    };
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(
+        result,
+        Err(ProofServiceError::InvalidInput(msg)) if msg.starts_with("rpc host not allowed")
+    ));
+}
+
+#[test]
+fn test_receipt_blocks_must_be_ascending_and_set_on_every_receipt() {
+    let in_block = |block_number: Option<u64>| SerializableReceipt {
+        block_number,
+        ..receipt_with_logs(vec![])
+    };
+
+    // No block numbers at all is the single-block default
+    validate_receipt_blocks(&data_with(vec![in_block(None), in_block(None)]), None).unwrap();
+    // Repeated blocks are fine; the anchor block is the last one allowed
+    let ascending = data_with(vec![
+        in_block(Some(7)),
+        in_block(Some(7)),
+        in_block(Some(9)),
+    ]);
+    validate_receipt_blocks(&ascending, None).unwrap();
+    validate_receipt_blocks(&ascending, Some(9)).unwrap();
+    assert!(matches!(
+        validate_receipt_blocks(&ascending, Some(8)),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.starts_with("Receipt 2 is from block 9")
+    ));
+
+    let descending = data_with(vec![in_block(Some(9)), in_block(Some(7))]);
+    assert!(matches!(
+        validate_receipt_blocks(&descending, None),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("ascending block order")
+    ));
+    let mixed = data_with(vec![in_block(Some(7)), in_block(None)]);
+    assert!(matches!(
+        validate_receipt_blocks(&mixed, None),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.starts_with("Receipt 1 has no block_number")
+    ));
+}
@@ -1,15 +1,29 @@
 // tests/integration_tests.rs
+use super::mock::{MockResponse, MockServer, start_mock_rpc};
 use crate::{
-    GENERATE_PROOF_JOB_ID, ProgramLocation, ProofRequest, ProofResult, ProofServiceError,
-    ProvingType, ServiceContext, generate_proof,
+    CoprocessorProofRequest, CoprocessorVerification, FailurePolicy, FieldConfig,
+    GENERATE_PROOF_JOB_ID, PROVER_VERSION, ProgramLocation, ProofRequest, ProofResult,
+    ProofServiceError, ProvingType, ServiceContext, ServiceMode, VerifyProofRequest,
+    fixtures::CoprocessorFixtureBuilder, generate_coprocessor_proof, generate_proof,
+    generate_proof_or_default, pico, program, verify_coprocessor_proof, verify_proof,
+    verify_proofs_stream,
 };
+use crate::{ProverBackend, ProvingQueue, RemoteProver, SubprocessProver};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use blueprint_sdk::alloy::sol_types::SolValue;
 use blueprint_sdk::{
-    alloy::primitives::Address,
+    alloy::primitives::{Address, B256, U256},
     extract::Context,
     tangle::extract::{Optional, TangleArg, TangleResult}, // Make sure extractors are public or re-exported if needed
 };
-use std::path::PathBuf;
+use futures::StreamExt;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use tempfile::tempdir;
 use url::Url;
 
@@ -37,6 +51,7 @@ async fn test_generate_proof_job_invalid_hash() {
         program_location_override: None,
         eth_rpc_url_override: None,
         registry_address_override: None,
+        ..Default::default()
     };
 
     let tangle_arg = TangleArg(request);
@@ -54,6 +69,388 @@ async fn test_generate_proof_job_invalid_hash() {
     }
 }
 
+#[tokio::test]
+async fn test_generate_proof_job_invalid_shard_size() {
+    let ctx = setup_test_context();
+    let request = ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        shard_size: Some((1 << 20) + 1), // Not a power of two
+        ..Default::default()
+    };
+
+    let result = generate_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("Invalid shard_size"))
+    );
+}
+
+#[tokio::test]
+async fn test_options_the_backend_cant_apply_are_rejected_up_front() {
+    let ctx = setup_test_context();
+    let request = ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        shard_size: Some(1 << 20),
+        ..Default::default()
+    };
+
+    // The in-process prover can't apply a valid shard size, so nothing is fetched or queued
+    let result = generate_proof(Context(ctx.clone()), TangleArg(request.clone())).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("prover_subprocess"))
+    );
+    #[cfg(feature = "evm-proving")]
+    {
+        let evm_request = ProofRequest {
+            proving_type: ProvingType::FullWithEvm,
+            shard_size: None,
+            memory_limit_bytes: Some(1 << 30),
+            ..request.clone()
+        };
+        let result = generate_proof(Context(ctx.clone()), TangleArg(evm_request)).await;
+        assert!(
+            matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("memory_limit_bytes"))
+        );
+    }
+
+    // Prover subprocesses are started with it, so the job goes on to fetch the program
+    let mut isolated = ctx;
+    isolated.prover_backend = Arc::new(SubprocessProver::current_exe().unwrap());
+    let result = generate_proof(Context(isolated), TangleArg(request)).await;
+    assert!(!matches!(
+        result.err().unwrap(),
+        ProofServiceError::InvalidInput(_)
+    ));
+}
+
+#[test]
+fn test_shard_size_validation_and_application() {
+    assert!(pico::validate_shard_size(pico::MIN_SHARD_SIZE / 2).is_err());
+    assert!(pico::validate_shard_size(pico::MAX_SHARD_SIZE * 2).is_err());
+    assert!(matches!(
+        pico::ensure_shard_size_applied(Some(3 << 18)),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("Invalid shard_size")
+    ));
+
+    pico::ensure_shard_size_applied(None).unwrap();
+    // The test process wasn't started with the override, so it can't be honored in process
+    assert!(std::env::var_os(pico::SHARD_SIZE_ENV).is_none());
+    assert!(matches!(
+        pico::ensure_shard_size_applied(Some(1 << 20)),
+        Err(ProofServiceError::InvalidInput(msg)) if msg.contains("prover_subprocess")
+    ));
+}
+
+#[tokio::test]
+async fn test_proving_rejected_in_verify_only_mode() {
+    let mut ctx = setup_test_context();
+    ctx.mode = ServiceMode::VerifyOnly;
+
+    let request = ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+    let result = generate_proof(Context(ctx.clone()), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::UnsupportedProvingType(msg) if msg == "service is verify-only")
+    );
+
+    let request = CoprocessorProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        ..Default::default()
+    };
+    let result = generate_coprocessor_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::UnsupportedProvingType(msg) if msg == "service is verify-only")
+    );
+}
+
+#[cfg(not(feature = "evm-proving"))]
+#[tokio::test]
+async fn test_full_with_evm_rejected_without_evm_proving_feature() {
+    let output_dir = tempdir().unwrap();
+    let result = pico::execute_pico_prove(
+        &PathBuf::from("/nonexistent/program.elf"),
+        "00",
+        &ProvingType::FullWithEvm,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await;
+    assert!(
+        matches!(result, Err(ProofServiceError::UnsupportedProvingType(msg)) if msg == "FullWithEvm not enabled in this build")
+    );
+}
+
+#[cfg(feature = "evm-proving")]
+#[test]
+fn test_full_with_evm_supported_with_evm_proving_feature() {
+    assert!(pico::ensure_proving_type_supported(&ProvingType::FullWithEvm).is_ok());
+}
+
+#[test]
+fn test_evm_output_dir_outlives_job_output_dir() {
+    let persistent = tempdir().unwrap();
+    let job_output = tempdir().unwrap();
+    let options = pico::ProveOptions {
+        evm_output_dir: Some(persistent.path().to_path_buf()),
+        ..Default::default()
+    };
+    let dir = pico::evm_proof_output_dir(job_output.path(), &options).unwrap();
+    drop(job_output);
+    assert!(dir.is_dir());
+    assert!(dir.starts_with(persistent.path()));
+
+    // Without one, artifacts stay in the job's output dir
+    let job_output = tempdir().unwrap();
+    let dir =
+        pico::evm_proof_output_dir(job_output.path(), &pico::ProveOptions::default()).unwrap();
+    assert!(dir.starts_with(job_output.path()));
+}
+
+#[test]
+fn test_expired_evm_output_dirs_are_pruned() {
+    let persistent = tempdir().unwrap();
+    let aged = |name: &str, age: Duration| {
+        let dir = persistent.path().join(name);
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::File::open(&dir)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+        dir
+    };
+    let expired = aged("proof_evm_1_old", Duration::from_secs(7200));
+    let recent = aged("proof_evm_2_new", Duration::from_secs(60));
+    let unrelated = aged("keys", Duration::from_secs(7200));
+
+    // Pruned as the next proof's dir is created
+    let options = pico::ProveOptions {
+        evm_output_dir: Some(persistent.path().to_path_buf()),
+        evm_output_retention: Some(Duration::from_secs(3600)),
+        ..Default::default()
+    };
+    let created = pico::evm_proof_output_dir(Path::new("/nonexistent"), &options).unwrap();
+    assert!(!expired.exists());
+    assert!(recent.is_dir());
+    assert!(unrelated.is_dir());
+    assert!(created.is_dir());
+
+    // Without a retention nothing is removed
+    let expired = aged("proof_evm_3_old", Duration::from_secs(7200));
+    let options = pico::ProveOptions {
+        evm_output_retention: None,
+        ..options
+    };
+    pico::evm_proof_output_dir(Path::new("/nonexistent"), &options).unwrap();
+    assert!(expired.is_dir());
+}
+
+// Needs the gnark container, so it is skipped where Docker isn't available
+#[cfg(feature = "evm-proving")]
+#[tokio::test]
+async fn test_real_evm_artifacts_outlive_the_job() {
+    let docker_available = std::process::Command::new("docker")
+        .arg("info")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !docker_available {
+        eprintln!("Skipping: Docker is not available for the gnark container");
+        return;
+    }
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let elf = std::fs::read(&elf_path).expect("fixture ELF tests/fixtures/trading_volume.elf");
+    let evm_output = tempdir().unwrap();
+    let mut ctx = setup_test_context();
+    ctx.evm_output_dir = Some(evm_output.path().to_path_buf());
+
+    let request = ProofRequest {
+        program_hash: hex::encode(Sha256::digest(&elf)),
+        inputs: CoprocessorFixtureBuilder::new().build().inputs_hex(),
+        proving_type: ProvingType::FullWithEvm,
+        program_location_override: Some(ProgramLocation::LocalPath(elf_path)),
+        ..Default::default()
+    };
+    let TangleResult(result) = generate_proof(Context(ctx), TangleArg(request))
+        .await
+        .unwrap();
+
+    // The job's sandbox is gone by now; the artifacts the prover wrote are not
+    let output_dir = PathBuf::from(
+        result
+            .output_dir
+            .expect("FullWithEvm reports its output dir"),
+    );
+    assert!(output_dir.starts_with(evm_output.path()));
+    assert!(output_dir.join("proof.data").is_file());
+    assert!(!result.proof.is_empty());
+}
+
+#[test]
+fn test_missing_evm_artifacts_reported_as_artifact_missing() {
+    let output_dir = tempdir().unwrap();
+    let result = pico::read_evm_artifacts(output_dir.path());
+    assert!(
+        matches!(result, Err(ProofServiceError::ArtifactMissing { artifact, .. }) if artifact == "proof.data")
+    );
+
+    // The proof alone isn't enough; the public values must be there too
+    std::fs::write(output_dir.path().join("proof.data"), b"proof").unwrap();
+    let result = pico::read_evm_artifacts(output_dir.path());
+    assert!(
+        matches!(result, Err(ProofServiceError::ArtifactMissing { artifact, .. }) if artifact == "pv_file or inputs.json")
+    );
+
+    std::fs::write(output_dir.path().join("pv_file"), "0102").unwrap();
+    let (proof, pv) = pico::read_evm_artifacts(output_dir.path()).unwrap();
+    assert_eq!(proof, b"proof");
+    assert_eq!(pv, vec![1, 2]);
+}
+
+#[test]
+fn test_evm_inputs_json_parsed_into_typed_fields() {
+    let output_dir = tempdir().unwrap();
+    assert_eq!(pico::read_evm_inputs(output_dir.path()).unwrap(), None);
+
+    std::fs::write(output_dir.path().join("proof.data"), b"proof").unwrap();
+    std::fs::write(
+        output_dir.path().join("inputs.json"),
+        include_str!("fixtures/inputs.json"),
+    )
+    .unwrap();
+    let inputs = pico::read_evm_inputs(output_dir.path()).unwrap().unwrap();
+    assert_eq!(
+        inputs.riscv_vkey,
+        "0x00a3c5fa5e1cb7b7cb9c3d6a2b0fa5f0bd9dbc55a4c49fbbb0b9cb7dc1a5dd3e"
+    );
+    // The proof's eight words are joined into one calldata string, short words left-padded
+    assert_eq!(inputs.proof.len(), 2 + 8 * 64);
+    assert!(
+        inputs
+            .proof
+            .starts_with("0x1f4c0ad7b2d4f87a0c33a5434ba8b3d31bd2e3c168e7b4a3dcd0c0169b71b9b1")
+    );
+    assert!(
+        inputs
+            .proof
+            .contains("009a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8")
+    );
+    let mut expected_pv = vec![0u8; 32];
+    expected_pv[31] = 42;
+    assert_eq!(inputs.public_values_bytes().unwrap(), expected_pv);
+
+    // Without a pv_file the public values come from inputs.json
+    let (_, pv) = pico::read_evm_artifacts(output_dir.path()).unwrap();
+    assert_eq!(pv, expected_pv);
+
+    let invalid = r#"{"riscvVKey":"0x01","proof":["0xzz"],"publicValues":"0x"}"#;
+    assert!(matches!(
+        pico::parse_evm_inputs_json(invalid, Path::new("inputs.json")),
+        Err(ProofServiceError::ProvingError(msg)) if msg.contains("Failed to parse EVM inputs")
+    ));
+}
+
+#[tokio::test]
+async fn test_generate_proof_job_inputs_too_large() {
+    let mut ctx = setup_test_context();
+    ctx.max_input_bytes = 4;
+    let request = ProofRequest {
+        program_hash: B256::ZERO.to_string(),
+        inputs: "00".repeat(8),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    };
+
+    let result = generate_proof(Context(ctx), TangleArg(request)).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        ProofServiceError::InputsTooLarge { size: 8, limit: 4 }
+    ));
+}
+
+#[test]
+fn test_full_recursion_failure_falls_back_to_fast() {
+    let recursion_failure = || -> Result<ProofResult, ProofServiceError> {
+        Err(ProofServiceError::ProvingError(
+            "Full proving failed: recursion".to_string(),
+        ))
+    };
+    let fast_proof = || {
+        Ok(ProofResult {
+            proving_type: ProvingType::Fast,
+            public_values: "01".to_string(),
+            ..Default::default()
+        })
+    };
+
+    let (result, downgraded) =
+        pico::with_failure_policy(FailurePolicy::FallbackFast, recursion_failure, fast_proof)
+            .unwrap();
+    assert!(downgraded);
+    assert_eq!(result.proving_type, ProvingType::Fast);
+    assert_eq!(result.public_values, "01");
+
+    // The default policy surfaces the recursion failure untouched
+    let result = pico::with_failure_policy(FailurePolicy::Error, recursion_failure, fast_proof);
+    assert!(
+        matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("recursion"))
+    );
+}
+
+#[test]
+fn test_missing_and_empty_public_value_streams_are_told_apart() {
+    // No stream at all means the prover lost the committed values, whatever the request allows
+    for require in [false, true] {
+        let result = pico::committed_public_values(None, "Fast", require);
+        assert!(
+            matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("missing public values stream"))
+        );
+    }
+
+    // An empty stream is a program that committed nothing, accepted unless the request requires values
+    assert_eq!(
+        pico::committed_public_values(Some(Vec::new()), "Fast", false).unwrap(),
+        Vec::<u8>::new()
+    );
+    let result = pico::committed_public_values(Some(Vec::new()), "Full (RISCV part)", true);
+    assert!(
+        matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("committed no public values"))
+    );
+
+    assert_eq!(
+        pico::committed_public_values(Some(vec![1, 2]), "Fast", true).unwrap(),
+        vec![1, 2]
+    );
+}
+
+// Records the field string instead of running the gnark container
+#[derive(Default)]
+struct RecordingEvmProver {
+    field_types: RefCell<Vec<String>>,
+}
+
+impl pico::EvmProver for RecordingEvmProver {
+    fn prove_evm(&self, _need_setup: bool, _dir: PathBuf, field_type: &str) -> Result<(), String> {
+        self.field_types.borrow_mut().push(field_type.to_string());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_prove_evm_field_type_follows_field_config() {
+    let prover = RecordingEvmProver::default();
+    let dir = PathBuf::from("/tmp/evm");
+
+    pico::run_prove_evm(&prover, FieldConfig::selected(), false, dir).unwrap();
+    assert_eq!(*prover.field_types.borrow(), vec!["kb"]);
+    assert_eq!(FieldConfig::selected(), FieldConfig::KoalaBear);
+}
+
 // --- TODO: More Tests ---
 // - test_generate_proof_job_program_not_found (requires mocking EVM call)
 // - test_generate_proof_job_download_fails (requires mocking HTTP call)
@@ -62,3 +459,533 @@ async fn test_generate_proof_job_invalid_hash() {
 // - test_generate_proof_job_success_local_file (requires a dummy ELF and local path override)
 // - test_generate_proof_job_success_remote_file (requires HTTP mock server)
 // - test_generate_proof_job_success_evm (most complex, needs Pico mock/dummy and EVM mock)
+
+#[tokio::test]
+async fn test_absent_request_proves_service_default() {
+    let mut ctx = setup_test_context();
+    // Without a default there is nothing to prove
+    let result = generate_proof_or_default(Context(ctx.clone()), TangleArg(Optional(None))).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("no default_proof_request"))
+    );
+
+    // The default is proven when the argument is absent; its invalid hash shows it was used
+    ctx.default_proof_request = Some(ProofRequest {
+        program_hash: "default-program".to_string(),
+        inputs: "00".to_string(),
+        proving_type: ProvingType::Fast,
+        ..Default::default()
+    });
+    let result = generate_proof_or_default(Context(ctx.clone()), TangleArg(Optional(None))).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("default-program"))
+    );
+
+    // A given request takes precedence over the default
+    let request = ProofRequest {
+        program_hash: "given-program".to_string(),
+        ..Default::default()
+    };
+    let result = generate_proof_or_default(Context(ctx), TangleArg(Optional(Some(request)))).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("given-program"))
+    );
+}
+
+#[tokio::test]
+async fn test_mmap_loading_matches_read_to_vec() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let elf_bytes =
+        pico::read_elf_file(&elf_path).expect("fixture ELF tests/fixtures/trading_volume.elf");
+    let program_hash = program::calculate_file_hash(&elf_path).await.unwrap();
+
+    // The job's mapped copy holds the same bytes the prover would read into memory
+    let work_dir = tempdir().unwrap();
+    let ctx = setup_test_context();
+    let (_elf_temp_dir, fetched) = program::fetch_program(
+        &ctx,
+        &ProgramLocation::LocalPath(elf_path),
+        &program_hash,
+        work_dir.path(),
+        false,
+    )
+    .await
+    .unwrap();
+    let mmap = program::map_fetched_program(&fetched).await.unwrap();
+    assert_eq!(&mmap[..], elf_bytes.as_slice());
+}
+
+#[test]
+fn test_capture_logs_collects_events_emitted_inside() {
+    let (value, log) = pico::capture_logs(|| {
+        blueprint_sdk::info!("riscv phase started");
+        7
+    });
+    assert_eq!(value, 7);
+    assert!(log.contains("riscv phase started"));
+}
+
+#[tokio::test]
+async fn test_fast_proof_captures_logs_only_when_requested() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let output_dir = tempdir().unwrap();
+
+    let captured = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Fast,
+        output_dir.path(),
+        &pico::ProveOptions {
+            capture_logs: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let debug_log = captured.debug_log.expect("logs captured");
+    assert!(debug_log.contains("Executing fast proof"));
+
+    let silent = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Fast,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    assert!(silent.debug_log.is_none());
+}
+
+#[tokio::test]
+async fn test_proof_records_prover_version() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let output_dir = tempdir().unwrap();
+
+    let result = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Fast,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.prover_version, PROVER_VERSION);
+    assert!(!result.prover_version.is_empty());
+}
+
+#[test]
+fn test_prover_version_policy() {
+    assert!(pico::ensure_prover_version("1.1.0", "1.1.0").is_ok());
+    assert!(pico::ensure_prover_version("1.10.0", "1.2").is_ok());
+    assert!(pico::ensure_prover_version("v2.0.0", "1.1.0").is_ok());
+    assert!(pico::ensure_prover_version("1.2", "1.2.0").is_ok());
+    assert!(pico::ensure_prover_version("1.2.0", "1.2").is_ok());
+    assert!(pico::ensure_prover_version("1.2.0.1", "1.2").is_ok());
+    for old in ["1.0.9", "1.0.10.0", "", "unknown"] {
+        assert!(
+            matches!(
+                pico::ensure_prover_version(old, "1.1.0"),
+                Err(ProofServiceError::InvalidInput(_))
+            ),
+            "{:?}",
+            old
+        );
+    }
+    assert!(matches!(
+        pico::ensure_prover_version("1.1.0", "latest"),
+        Err(ProofServiceError::ConfigError(_))
+    ));
+}
+
+#[test]
+fn test_prover_version_follows_the_pinned_pico_revision() {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let manifest = std::fs::read_to_string(workspace.join("Cargo.toml")).unwrap();
+    let pin = format!("rev = \"{}\"", pico::PICO_VM_REV);
+    for dependency in ["pico-sdk = ", "pico-vm = "] {
+        let line = manifest
+            .lines()
+            .find(|line| line.starts_with(dependency))
+            .unwrap();
+        assert!(line.contains(&pin), "{} is not pinned to {}", line, pin);
+    }
+
+    // The lock file isn't committed; where one has been generated it must agree
+    let Ok(lock) = std::fs::read_to_string(workspace.join("Cargo.lock")) else {
+        return;
+    };
+    let mut lines = lock.lines();
+    lines.find(|line| *line == r#"name = "pico-vm""#).unwrap();
+    assert_eq!(
+        lines.next().unwrap(),
+        format!("version = \"{}\"", PROVER_VERSION)
+    );
+    assert!(lines.next().unwrap().ends_with(pico::PICO_VM_REV));
+}
+
+#[tokio::test]
+async fn test_verify_rejects_proofs_from_older_prover() {
+    let mut ctx = setup_test_context();
+    ctx.min_prover_version = Some(PROVER_VERSION.to_string());
+    let request = VerifyProofRequest {
+        proof: ProofResult {
+            program_hash: B256::ZERO.to_string(),
+            proving_type: ProvingType::Fast,
+            prover_version: "0.9.0".to_string(),
+            ..Default::default()
+        },
+        program_location_override: None,
+    };
+
+    let result = verify_proof(Context(ctx), TangleArg(request)).await;
+    assert!(
+        matches!(result.err().unwrap(), ProofServiceError::InvalidInput(msg) if msg.contains("0.9.0"))
+    );
+}
+
+#[tokio::test]
+async fn test_coprocessor_verification_checks_committed_value() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let fixture = CoprocessorFixtureBuilder::new().build();
+    let output_dir = tempdir().unwrap();
+    let proof = pico::execute_pico_prove(
+        &elf_path,
+        &fixture.inputs_hex(),
+        &ProvingType::Fast,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+
+    let expected = hex::encode(fixture.expected_volume.to_be_bytes::<32>());
+    assert_eq!(
+        verify_coprocessor_proof(&elf_path, &proof, &expected).unwrap(),
+        CoprocessorVerification {
+            crypto_valid: true,
+            value_matches: true,
+        }
+    );
+
+    let other = hex::encode((fixture.expected_volume + U256::from(1)).to_be_bytes::<32>());
+    assert_eq!(
+        verify_coprocessor_proof(&elf_path, &proof, &other).unwrap(),
+        CoprocessorVerification {
+            crypto_valid: true,
+            value_matches: false,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_full_proof_returns_riscv_proof_when_requested() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let output_dir = tempdir().unwrap();
+
+    let result = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Full,
+        output_dir.path(),
+        &pico::ProveOptions {
+            include_riscv_proof: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.proving_type, ProvingType::Full);
+    assert!(!result.proof.is_empty());
+    let riscv_proof = result.riscv_proof.expect("RISCV proof included");
+    assert!(!hex::decode(&riscv_proof).unwrap().is_empty());
+    assert_ne!(riscv_proof, result.proof);
+
+    let without = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Full,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    assert!(without.riscv_proof.is_none());
+}
+
+#[tokio::test]
+async fn test_proof_artifacts_written_to_caller_output_dir() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let orchestrator_dir = tempdir().unwrap();
+
+    let result = pico::execute_pico_prove_in_dir(
+        &elf_path,
+        &inputs,
+        &ProvingType::Full,
+        orchestrator_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    // The artifacts land in the given dir itself, not a per-run subdirectory
+    assert_eq!(
+        result.output_dir.as_deref(),
+        Some(orchestrator_dir.path().to_string_lossy().as_ref())
+    );
+    assert!(
+        std::fs::read_dir(orchestrator_dir.path())
+            .unwrap()
+            .flatten()
+            .any(|entry| entry.path().is_file())
+    );
+
+    let missing = orchestrator_dir.path().join("missing");
+    let result = pico::execute_pico_prove_in_dir(
+        &elf_path,
+        &inputs,
+        &ProvingType::Full,
+        &missing,
+        &pico::ProveOptions::default(),
+    )
+    .await;
+    assert!(
+        matches!(result, Err(ProofServiceError::InvalidInput(msg)) if msg.contains("not a directory"))
+    );
+    assert!(!missing.exists());
+}
+
+#[tokio::test]
+async fn test_proofs_record_phase_timings() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let output_dir = tempdir().unwrap();
+
+    let fast = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Fast,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    let timings = fast.phase_timings.expect("Fast proof records timings");
+    assert!(timings[pico::PHASE_RISCV] > 0);
+    assert!(!timings.contains_key(pico::PHASE_RECURSION));
+
+    let full = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Full,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    let timings = full.phase_timings.expect("Full proof records timings");
+    assert!(timings[pico::PHASE_RISCV] > 0);
+    assert!(timings[pico::PHASE_RECURSION] > 0);
+}
+
+#[tokio::test]
+async fn test_fast_with_verify_self_verifies_and_catches_corruption() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let output_dir = tempdir().unwrap();
+
+    let result = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::FastWithVerify,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.proving_type, ProvingType::FastWithVerify);
+    assert!(result.self_verified);
+    assert!(pico::verify_pico_proof(&elf_path, &result, &pico::ProverCache::default()).unwrap());
+
+    let elf = std::fs::read(&elf_path).unwrap();
+    let client = pico_sdk::client::DefaultProverClient::new(&elf);
+    let proof = hex::decode(&result.proof).unwrap();
+    let mut public_values = hex::decode(&result.public_values).unwrap();
+    pico::self_verify_fast_proof(&client, &proof, &public_values).unwrap();
+
+    *public_values.last_mut().unwrap() ^= 0xff;
+    assert!(matches!(
+        pico::self_verify_fast_proof(&client, &proof, &public_values),
+        Err(ProofServiceError::ProvingError(msg)) if msg.starts_with("Fast proof self-verification failed")
+    ));
+    assert!(matches!(
+        pico::self_verify_fast_proof(&client, b"not a proof", &public_values),
+        Err(ProofServiceError::ProvingError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_verify_proofs_stream_flags_tampered_proofs() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let elf = std::fs::read(&elf_path).unwrap();
+    let program_hash = B256::from_slice(&Sha256::digest(&elf));
+    // The registry serves the program inline
+    let location = format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(&elf)
+    );
+    let rpc = start_mock_rpc(move |method, _params| match method {
+        "eth_call" => Ok(json!(format!(
+            "0x{}",
+            hex::encode((program_hash, location.clone()).abi_encode_params())
+        ))),
+        other => Err(format!("unexpected method {}", other)),
+    })
+    .await;
+    let temp_base = tempdir().unwrap().into_path();
+    let mut ctx = ServiceContext::new(rpc.url(), Address::repeat_byte(0x11), temp_base).unwrap();
+    ctx.proving_queue = Some(ProvingQueue::new(2));
+
+    let output_dir = tempdir().unwrap();
+    let mut valid = pico::execute_pico_prove(
+        &elf_path,
+        &CoprocessorFixtureBuilder::new().build().inputs_hex(),
+        &ProvingType::Fast,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    valid.program_hash = program_hash.to_string();
+    let commitment = valid.compute_proof_commitment().unwrap().to_string();
+
+    let mut tampered_values = valid.clone();
+    let mut public_values = hex::decode(&valid.public_values).unwrap();
+    *public_values.last_mut().unwrap() ^= 0xff;
+    tampered_values.public_values = hex::encode(public_values);
+    let mut tampered_proof = valid.clone();
+    tampered_proof.proof = hex::encode(b"not a proof");
+    let tampered_commitment = tampered_proof
+        .compute_proof_commitment()
+        .unwrap()
+        .to_string();
+
+    let proofs = vec![valid.clone(), tampered_values, tampered_proof, valid];
+    let mut results: Vec<(String, bool)> =
+        verify_proofs_stream(&ctx, futures::stream::iter(proofs), 3)
+            .collect()
+            .await;
+    results.sort();
+    let mut expected = vec![
+        (commitment.clone(), true),
+        (commitment.clone(), false),
+        (tampered_commitment, false),
+        (commitment, true),
+    ];
+    expected.sort();
+    assert_eq!(results, expected);
+    // Every slot is back once the stream is drained
+    assert_eq!(ctx.proving_queue.as_ref().unwrap().available(), 2);
+}
+
+#[tokio::test]
+async fn test_remote_fast_proofs_are_verified_against_the_elf() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let elf = std::fs::read(&elf_path).unwrap();
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let output_dir = tempdir().unwrap();
+    let valid = pico::execute_pico_prove(
+        &elf_path,
+        &inputs,
+        &ProvingType::Fast,
+        output_dir.path(),
+        &pico::ProveOptions::default(),
+    )
+    .await
+    .unwrap();
+    let mut tampered = valid.clone();
+    let mut public_values = hex::decode(&valid.public_values).unwrap();
+    *public_values.last_mut().unwrap() ^= 0xff;
+    tampered.public_values = hex::encode(public_values);
+
+    for (answer, verifies) in [(valid, true), (tampered, false)] {
+        let body = serde_json::to_value(&answer).unwrap();
+        let server = MockServer::start(move |_| MockResponse::json(&body)).await;
+        let result = RemoteProver::new(server.url())
+            .prove(
+                &elf,
+                &inputs,
+                &ProvingType::Fast,
+                output_dir.path(),
+                &pico::ProveOptions::default(),
+            )
+            .await;
+        if verifies {
+            assert_eq!(result.unwrap().proof, answer.proof);
+        } else {
+            assert!(
+                matches!(result, Err(ProofServiceError::ProvingError(msg)) if msg.contains("does not verify"))
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_result_fields_are_the_same_for_every_proving_type() {
+    let elf_path = PathBuf::from("./tests/fixtures/trading_volume.elf");
+    let inputs = CoprocessorFixtureBuilder::new().build().inputs_hex();
+    let output_dir = tempdir().unwrap();
+    let mut proving_types = vec![
+        ProvingType::Fast,
+        ProvingType::FastWithVerify,
+        ProvingType::Full,
+    ];
+    if cfg!(feature = "evm-proving") {
+        proving_types.push(ProvingType::FullWithEvm);
+    }
+
+    let mut schemas = Vec::new();
+    for proving_type in &proving_types {
+        let result = pico::execute_pico_prove(
+            &elf_path,
+            &inputs,
+            proving_type,
+            output_dir.path(),
+            &pico::ProveOptions::default(),
+        )
+        .await
+        .unwrap();
+        let value = serde_json::to_value(&result).unwrap();
+        let mut fields: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        fields.sort();
+        schemas.push((proving_type, fields));
+    }
+    let (_, fast_fields) = &schemas[0];
+    assert!(fast_fields.contains(&"output_dir".to_string()));
+    assert!(fast_fields.contains(&"riscv_proof".to_string()));
+    for (proving_type, fields) in &schemas {
+        assert_eq!(fields, fast_fields, "fields of a {:?} result", proving_type);
+    }
+}
+
+#[test]
+fn test_warming_sets_up_the_prover_of_the_requested_proving_type() {
+    let elf = std::fs::read("./tests/fixtures/trading_volume.elf").unwrap();
+    let cache = pico::ProverCache::new(1);
+
+    assert!(cache.warm(&elf, &ProvingType::Fast));
+    assert!(cache.contains(&elf, &ProvingType::FastWithVerify));
+    // Full proofs run on a chain of their own, which the client doesn't stand in for
+    assert!(!cache.contains(&elf, &ProvingType::Full));
+    assert!(cache.warm(&elf, &ProvingType::Full));
+    assert!(cache.contains(&elf, &ProvingType::Full));
+
+    // A cache that keeps nothing has nothing ready afterwards
+    let uncached = pico::ProverCache::new(0);
+    assert!(!uncached.warm(&elf, &ProvingType::Full));
+    assert!(!uncached.contains(&elf, &ProvingType::Full));
+}
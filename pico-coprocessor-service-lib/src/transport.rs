@@ -0,0 +1,102 @@
+// pico-coprocessor-service-lib/src/transport.rs
+// Body handling for HTTP front-ends that forward requests to the proving jobs and return their results.
+use crate::errors::ProofServiceError;
+use crate::types::ProofResult;
+use flate2::read::GzDecoder;
+use serde::de::DeserializeOwned;
+use std::io::Read;
+
+/// Decodes a request body according to its `Content-Encoding` and deserializes it.
+///
+/// Supports `gzip` (and `x-gzip`) as well as uncompressed bodies (`identity` or no header).
+/// Decompression stops with `InvalidInput` once `max_decoded_bytes` is exceeded.
+/// Request signatures are checked by the jobs (see `auth::authenticate`) against the re-serialized
+/// request, so a front-end can forward the decoded value as-is.
+pub fn decode_request_body<T: DeserializeOwned>(
+    content_encoding: Option<&str>,
+    body: &[u8],
+    max_decoded_bytes: usize,
+) -> Result<T, ProofServiceError> {
+    let encoding = content_encoding
+        .map(|e| e.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match encoding.as_str() {
+        "" | "identity" => {
+            if body.len() > max_decoded_bytes {
+                return Err(body_too_large(max_decoded_bytes));
+            }
+            Ok(serde_json::from_slice(body)?)
+        }
+        "gzip" | "x-gzip" => {
+            let mut decoded = Vec::new();
+            GzDecoder::new(body)
+                .take(max_decoded_bytes as u64 + 1)
+                .read_to_end(&mut decoded)
+                .map_err(|e| {
+                    ProofServiceError::InvalidInput(format!("Invalid gzip request body: {}", e))
+                })?;
+            if decoded.len() > max_decoded_bytes {
+                return Err(body_too_large(max_decoded_bytes));
+            }
+            Ok(serde_json::from_slice(&decoded)?)
+        }
+        other => Err(ProofServiceError::InvalidInput(format!(
+            "Unsupported Content-Encoding: {}",
+            other
+        ))),
+    }
+}
+
+fn body_too_large(limit: usize) -> ProofServiceError {
+    ProofServiceError::InvalidInput(format!(
+        "Request body exceeds {} bytes after decoding",
+        limit
+    ))
+}
+
+/// Header carrying the length of the `public_values` prefix of an `application/octet-stream` proof body.
+pub const PUBLIC_VALUES_LENGTH_HEADER: &str = "x-public-values-length";
+
+/// A proof endpoint response body, ready to be written by the front-end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofResponseBody {
+    pub content_type: &'static str,
+    /// Extra headers (name, value) to send with the body.
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Encodes a proof job's result according to the client's `Accept` header.
+///
+/// `application/octet-stream` returns the raw `public_values` bytes followed by the raw `proof`
+/// bytes, with the split given by [`PUBLIC_VALUES_LENGTH_HEADER`]. Anything else (including no
+/// header) gets the JSON `ProofResult`.
+pub fn encode_proof_response(
+    accept: Option<&str>,
+    result: &ProofResult,
+) -> Result<ProofResponseBody, ProofServiceError> {
+    let wants_raw = accept.is_some_and(|accept| {
+        accept.split(',').any(|media| {
+            let media_type = media.split(';').next().unwrap_or_default().trim();
+            media_type.eq_ignore_ascii_case("application/octet-stream")
+        })
+    });
+
+    if !wants_raw {
+        return Ok(ProofResponseBody {
+            content_type: "application/json",
+            headers: Vec::new(),
+            body: serde_json::to_vec(result)?,
+        });
+    }
+
+    let mut body = result.public_values_bytes()?;
+    let public_values_len = body.len();
+    body.extend(result.output_encoding.decode(&result.proof)?);
+    Ok(ProofResponseBody {
+        content_type: "application/octet-stream",
+        headers: vec![(PUBLIC_VALUES_LENGTH_HEADER, public_values_len.to_string())],
+        body,
+    })
+}
@@ -1,7 +1,16 @@
 // pico-coprocessor-service-lib/src/types.rs
+use crate::encoding::{decode_hex, strip_hex_prefix};
+use crate::errors::ProofServiceError;
+use blueprint_sdk::alloy::consensus::Transaction as ConsensusTransaction;
+use blueprint_sdk::alloy::eips::eip2718::Encodable2718;
+use blueprint_sdk::alloy::network::TransactionResponse;
 use blueprint_sdk::alloy::primitives::{Address, B256, Bytes, U256};
+use blueprint_sdk::alloy::rpc::types::Transaction;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 use url::Url; // Use Alloy types
 
 // --- Shared Types (ProgramLocation, ProvingType, ProofResult) ---
@@ -11,25 +20,449 @@ use url::Url; // Use Alloy types
 pub enum ProgramLocation {
     RemoteUrl(Url),
     LocalPath(PathBuf),
+    /// ELF embedded inline as a base64 `data:` URI, e.g. `data:application/octet-stream;base64,...`
+    DataUri(String),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+/// A ProgramRegistry contract, given either by address or by an ENS name resolved on first use.
+///
+/// Deserialized from a string as [`FromStr`] parses it, so a malformed address is rejected
+/// rather than taken for an ENS name.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum RegistryRef {
+    Address(Address),
+    Ens(String),
+}
+
+impl From<Address> for RegistryRef {
+    fn from(address: Address) -> Self {
+        RegistryRef::Address(address)
+    }
+}
+
+impl FromStr for RegistryRef {
+    type Err = String;
+
+    /// Hex strings are parsed as addresses; anything else must be an `.eth` name such as
+    /// `registry.myproject.eth`, with non-empty labels of letters, digits, `-` and `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(address) = Address::from_str(s) {
+            return Ok(RegistryRef::Address(address));
+        }
+        let labels: Vec<&str> = s.split('.').collect();
+        let valid_name = labels.len() >= 2
+            && labels.last() == Some(&"eth")
+            && labels.iter().all(|label| {
+                !label.is_empty()
+                    && label
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            });
+        if s.starts_with("0x") || !valid_name {
+            return Err(format!("not an address or ENS name: {}", s));
+        }
+        Ok(RegistryRef::Ens(s.to_string()))
+    }
+}
+
+impl<'de> Deserialize<'de> for RegistryRef {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for RegistryRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryRef::Address(address) => write!(f, "{}", address),
+            RegistryRef::Ens(name) => f.write_str(name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum ProvingType {
     Fast,
     #[default]
     Full,
     FullWithEvm,
+    /// A `Fast` proof the service verifies itself before returning it, to catch prover bugs or
+    /// corruption.
+    FastWithVerify,
+}
+
+impl ProvingType {
+    /// Whether proofs of this type are RISCV (`Fast`) proofs.
+    pub fn is_fast(&self) -> bool {
+        matches!(self, ProvingType::Fast | ProvingType::FastWithVerify)
+    }
+}
+
+/// Hash function a program's ELF is registered under, selected by the `hash=` annotation of its
+/// location (see `program::location_hash_algorithm`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    /// BLAKE3 with its default 32-byte output, for tooling that commits to ELFs with it.
+    Blake3,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = ProofServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "blake3" => Ok(Self::Blake3),
+            other => Err(ProofServiceError::InvalidInput(format!(
+                "Unknown hash algorithm: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Layout of the public values a coprocessor program commits.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoprocessorCommitFormat {
+    /// The committed bytes as-is (e.g. a raw 32-byte `U256`).
+    #[default]
+    Raw,
+    /// A u64 little-endian length followed by the payload.
+    LengthPrefixed,
+    /// A single ABI-encoded `bytes` value.
+    AbiEncoded,
+}
+
+/// Canonical receipt order a coprocessor request can ask for (`CoprocessorProofRequest::sort_by`).
+///
+/// Sorting is stable: receipts with equal keys keep their submission order. Receipts from several
+/// blocks are sorted within their block, so they stay grouped by block.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiptOrder {
+    /// Ascending transaction hash.
+    TransactionHash,
+    /// Ascending `log_pos` of each receipt's first log; receipts without one go last.
+    LogPos,
+}
+
+impl ReceiptOrder {
+    pub fn sort(self, receipts: &mut [SerializableReceipt]) {
+        match self {
+            ReceiptOrder::TransactionHash => {
+                receipts.sort_by_key(|r| (r.block_number, r.transaction_hash))
+            }
+            ReceiptOrder::LogPos => receipts.sort_by_key(|r| {
+                let log_pos = r.logs.first().and_then(|log| log.log_pos);
+                (r.block_number, log_pos.is_none(), log_pos)
+            }),
+        }
+    }
+}
+
+/// What the decoded payload of a coprocessor proof commits to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommitMode {
+    /// A single value aggregated over all the blockchain data.
+    #[default]
+    Aggregate,
+    /// A Merkle root over one 32-byte result per receipt, so verifiers can check individual
+    /// receipts against the root.
+    ///
+    /// The program commits (in its `commit_format`) the 32-byte root, optionally followed by the
+    /// leaves: one 32-byte result per receipt, in receipt order. Leaves are hashed as
+    /// `keccak256(0x00 || leaf)`, parents are `keccak256(0x01 || left || right)` and an odd node is
+    /// carried up unhashed (see [`merkle_root`](crate::merkle_root)). When leaves are committed
+    /// they must reproduce the root.
+    MerkleRoot,
+}
+
+/// Root and per-receipt leaves committed under [`CommitMode::MerkleRoot`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MerkleCommitment {
+    pub root: String, // hex encoded B256
+    // hex encoded B256 per receipt; empty when the program committed only the root
+    #[serde(default)]
+    pub leaves: Vec<String>,
+}
+
+/// Verifier calldata Pico writes next to a `FullWithEvm` proof (`inputs.json`), in the form the
+/// on-chain Pico verifier takes it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EvmInputsJson {
+    /// Verification key of the program's RISCV proof (0x-prefixed hex).
+    #[serde(rename = "riscvVKey")]
+    pub riscv_vkey: String,
+    /// The Groth16 proof (0x-prefixed hex). Pico writes it as an array of field elements, which
+    /// are read as 32-byte words and concatenated.
+    #[serde(deserialize_with = "deserialize_evm_proof")]
+    pub proof: String,
+    /// The committed public values (0x-prefixed hex).
+    #[serde(rename = "publicValues")]
+    pub public_values: String,
+}
+
+impl EvmInputsJson {
+    /// Decodes `public_values`.
+    pub fn public_values_bytes(&self) -> Result<Vec<u8>, ProofServiceError> {
+        Ok(decode_hex(self.public_values.trim())?)
+    }
+}
+
+// Accepts the proof as one hex string or as Pico's array of hex field elements
+fn deserialize_evm_proof<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Proof {
+        Hex(String),
+        Words(Vec<String>),
+    }
+
+    match Proof::deserialize(deserializer)? {
+        Proof::Hex(hex_str) => Ok(hex_str),
+        Proof::Words(words) => {
+            let mut proof = String::from("0x");
+            for word in words {
+                let digits = word.trim().trim_start_matches("0x");
+                if digits.len() > 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(serde::de::Error::custom(format!(
+                        "invalid proof element {:?}: expected at most 32 bytes of hex",
+                        word
+                    )));
+                }
+                proof.push_str(&format!("{:0>64}", digits));
+            }
+            Ok(proof)
+        }
+    }
+}
+
+/// Order in which queued proofs acquire a proving slot (see `ServiceContext::proving_queue`).
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+pub enum ProofPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// How a coprocessor's decoded public values are sized before being returned.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PublicValuesPolicy {
+    /// Return the committed bytes at whatever length the program produced.
+    #[default]
+    AsIs,
+    /// Require exactly 32 bytes (e.g. a `U256`).
+    Exact32,
+    /// Left-pad shorter values with zeros to 32 bytes; longer values are rejected.
+    LeftPad32,
+}
+
+/// Text encoding of the byte fields in a `ProofResult`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+/// Serialization of a `Full` proof's embed (recursion) proof in `ProofResult::proof`.
+///
+/// Only the KoalaBear elements of the proof are converted; its BN254 elements and integer fields
+/// keep the prover's serialization. See [`crate::encode_recursion_proof`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecursionProofFormat {
+    /// JSON as the prover serializes it, with KoalaBear elements as their Montgomery form.
+    #[default]
+    Json,
+    /// JSON with KoalaBear elements in canonical form (`0..p`), for verifiers that don't work in
+    /// Montgomery form.
+    JsonCanonical,
+}
+
+/// Where a proving job delivers the proof.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub enum OutputSink {
+    /// In `ProofResult::proof`, encoded per `output_encoding`.
+    #[default]
+    Inline,
+    /// Uploaded as `{key_prefix}{proof_commitment}.proof` to the service's S3 store, with
+    /// `ProofResult::proof` set to the object's URL and `proof_is_reference` set. For large
+    /// `Full`/`FullWithEvm` proofs that are impractical to return inline.
+    S3 {
+        bucket: String,
+        #[serde(default)]
+        key_prefix: String,
+    },
+}
+
+/// What the `inputs` of a `ProofResult` hold, so verifiers can recover the exact bytes the program read.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputsEncoding {
+    /// The program's raw input bytes, encoded per `output_encoding` (hex by default).
+    /// Produced by `generate_proof`.
+    #[default]
+    Hex,
+    /// The JSON text of the input document itself, unaffected by `output_encoding`.
+    JsonUtf8,
+    /// The JSON input document's bytes, encoded per `output_encoding`.
+    /// Produced by `generate_coprocessor_proof` for its input bundle.
+    HexOfJson,
+}
+
+/// What to do when a `Full` proof fails during recursion.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailurePolicy {
+    /// Fail the job.
+    #[default]
+    Error,
+    /// Return a `Fast` proof instead, marked `downgraded`.
+    FallbackFast,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ProofResult {
-    pub public_values: String, // hex encoded
-    pub proof: String,         // hex encoded (SCALE encoded proof data)
+    pub public_values: String, // encoded per output_encoding
+    pub proof: String,         // encoded per output_encoding (serialized proof data)
     pub proving_type: ProvingType,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    // FullWithEvm artifact directory. Like every optional field it is always serialized (null
+    // when unset), so every result has the same fields
+    #[serde(default)]
     pub output_dir: Option<String>,
     pub program_hash: String, // hex encoded
-    pub inputs: String,       // hex encoded (original inputs provided to the job)
+    pub inputs: String,       // encoded per output_encoding (original inputs provided to the job)
+    // Id of the generate_proof job that produced it: its sandbox and checkpoint (see resume_proof)
+    #[serde(default)]
+    pub proof_id: Option<String>,
+    // How inputs is encoded; results without it predate coprocessor bundles and are Hex
+    #[serde(default)]
+    pub inputs_encoding: InputsEncoding,
+    // Encoding of public_values, proof, inputs, public_values_decoded and riscv_proof
+    #[serde(default)]
+    pub output_encoding: OutputEncoding,
+    // (receipt_count, storage_count, tx_count) for coprocessor SDK's `Builder::init`
+    #[serde(default)]
+    pub sdk_init_params: Option<(u32, u32, u32)>,
+    // Capacity the data actually used per category, to right-size later requests' max_sizes
+    #[serde(default)]
+    pub used_sizes: Option<MaxSizes>,
+    // Set when public_values holds the zstd compression of the committed bytes; see public_values_bytes
+    #[serde(default)]
+    pub public_values_compressed: bool,
+    // Payload extracted from public_values per the request's commit format (the root under CommitMode::MerkleRoot)
+    #[serde(default)]
+    pub public_values_decoded: Option<String>,
+    // Root and leaves parsed from the payload under CommitMode::MerkleRoot (always hex)
+    #[serde(default)]
+    pub merkle_commitment: Option<MerkleCommitment>,
+    // Set when a Full request fell back to Fast under FailurePolicy::FallbackFast
+    #[serde(default)]
+    pub downgraded: bool,
+    // Intermediate RISCV proof of a Full proof (encoded per output_encoding), when requested
+    #[serde(default)]
+    pub riscv_proof: Option<String>,
+    // Freshness indicators echoed from the request, for replay protection downstream
+    #[serde(default)]
+    pub nonce: Option<B256>,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    // Hex of ProofResult::to_scale (program hash, public values, proof commitment), when requested
+    #[serde(default)]
+    pub scale_metadata_hex: Option<String>,
+    // keccak256 of the raw proof (0x-prefixed hex), to index proofs without storing them; empty for
+    // results predating the field
+    #[serde(default)]
+    pub proof_commitment: String,
+    // Pico VM/SDK version that produced the proof; empty for results predating the field
+    #[serde(default)]
+    pub prover_version: String,
+    // The service verified the proof itself before returning it (FastWithVerify)
+    #[serde(default)]
+    pub self_verified: bool,
+    // Prover logs, present when the request set capture_logs
+    #[serde(default)]
+    pub debug_log: Option<String>,
+    // Wall time in milliseconds of each proving phase the prover ran, keyed by pico::PHASE_*
+    #[serde(default)]
+    pub phase_timings: Option<HashMap<String, u64>>,
+    // Verifier calldata read from a FullWithEvm proof's inputs.json, for submitting it on chain
+    #[serde(default)]
+    pub evm_inputs: Option<EvmInputsJson>,
+    // Labels echoed from the request's tags, to attribute the proof to a customer or job
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    // Set when proof holds the URL of the uploaded raw proof (OutputSink::S3) instead of the proof
+    #[serde(default)]
+    pub proof_is_reference: bool,
+    // Chain (eth_chainId of the RPC used) and registry that resolved the program, for audits; unset
+    // when the chain couldn't be determined or no registry was consulted
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    #[serde(default)]
+    pub registry_address: Option<Address>,
+    // Serialization of proof when it is a Full proof; Json for results predating the field
+    #[serde(default)]
+    pub recursion_proof_format: RecursionProofFormat,
+}
+
+/// Outcome of verifying a coprocessor proof against an expected result.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoprocessorVerification {
+    /// The proof verifies for the program.
+    pub crypto_valid: bool,
+    /// The proof's public values equal the expected ones.
+    pub value_matches: bool,
+}
+
+/// A program registered in the `ProgramRegistry` contract.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProgramEntry {
+    pub program_hash: String, // hex encoded B256
+    pub location: String,
+}
+
+/// Interface a program publishes through the registry (`setProgramSchema`), returned by the
+/// `describe_program` job so clients can build requests for a program they don't know.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProgramSchema {
+    /// Categories of blockchain data the program reads: any of `receipts`, `storage_slots` and
+    /// `transactions`.
+    #[serde(default)]
+    pub data_categories: Vec<String>,
+    /// Sizes the program expects coprocessor requests to declare.
+    #[serde(default)]
+    pub max_sizes: Option<MaxSizes>,
+    /// Layout of the public values the program commits.
+    #[serde(default)]
+    pub commit_format: CoprocessorCommitFormat,
+    /// What the committed payload is.
+    #[serde(default)]
+    pub commit_mode: CommitMode,
+    /// Free-form description of the program and its result.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Caller signature over a request, checked against `ServiceContext::authorized_keys`.
+///
+/// The signed message is the request with `signature` unset, as compact JSON with every object's
+/// keys sorted (see `to_canonical_json`); unset optional fields are omitted or `null` exactly as
+/// the request serializes them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RequestSignature {
+    pub public_key: String, // hex encoded ed25519 public key (32 bytes)
+    pub signature: String,  // hex encoded ed25519 signature (64 bytes)
 }
 
 // --- Generic Proof Job Input ---
@@ -43,7 +476,60 @@ pub struct ProofRequest {
     #[serde(default)]
     pub eth_rpc_url_override: Option<String>,
     #[serde(default)]
-    pub registry_address_override: Option<Address>,
+    pub registry_address_override: Option<RegistryRef>,
+    #[serde(default)]
+    pub shard_size: Option<usize>, // power of two, see pico::validate_shard_size
+    #[serde(default)]
+    pub on_full_failure: FailurePolicy,
+    #[serde(default)]
+    pub output_encoding: OutputEncoding, // encoding of the result's byte fields
+    #[serde(default)]
+    pub capture_logs: bool, // return the prover's logs in ProofResult::debug_log
+    #[serde(default)]
+    pub priority: ProofPriority, // order among queued proofs when proving slots are limited
+    #[serde(default)]
+    pub include_riscv_proof: bool, // also return a Full proof's RISCV proof
+    #[serde(default)]
+    pub require_public_values: bool, // fail when the program commits nothing instead of returning empty public values
+    #[serde(default)]
+    pub recursion_proof_format: RecursionProofFormat, // serialization of a Full proof
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_bytes: Option<usize>, // memory ceiling; also caps the FullWithEvm Docker container
+    #[serde(default)]
+    pub private_inputs: bool, // omit inputs from result, logs and checkpoints; in-process only
+    #[serde(default)]
+    pub include_scale_metadata: bool, // attach ProofResult::scale_metadata_hex for on-chain verifiers
+    #[serde(default)]
+    pub compress_public_values: bool, // return public_values zstd-compressed (ProofResult::public_values_compressed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<B256>, // echoed into the result so downstream systems can reject replays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>, // unix seconds; rejected at submission once passed, echoed into the result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_dir_override: Option<PathBuf>, // scratch directory for this job instead of the service's temp_dir_base, within its allowed_temp_dirs
+    #[serde(default)]
+    pub insecure_skip_hash_verify: bool, // LocalPath programs only, and only when the service sets allow_insecure
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>, // caller labels for billing, echoed into the result and metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_sink: Option<OutputSink>, // upload the proof and return its URL instead; None keeps it inline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<RequestSignature>,
+}
+
+// --- Multi-input Proof Job Input ---
+// One program proven against several input sets (generate_proof_multi), fetching it once
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MultiProofRequest {
+    pub program_hash: String, // hex encoded B256
+    pub inputs: Vec<String>,  // hex encoded bytes, one entry per proof
+    pub proving_type: ProvingType,
+    #[serde(default)]
+    pub program_location_override: Option<ProgramLocation>,
+    #[serde(default)]
+    pub registry_address_override: Option<RegistryRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<RequestSignature>,
 }
 
 // --- zkCoprocessor Specific Types ---
@@ -58,6 +544,35 @@ pub struct SerializableReceipt {
     // Add other relevant fields like gas_used, contract_address, etc.
     // Use hex encoding for byte fields if not using Bytes directly
     pub raw_data_hex: String, // Allow passing raw RLP or similar if needed
+    // Proves `raw_data_hex` is in the anchor block's receipts trie; checked before proving
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inclusion_proof: Option<ReceiptInclusionProof>,
+    // Block the receipt is from, for data spanning several blocks; set on every receipt or none,
+    // in ascending order (see ReceiptBlock)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+}
+
+/// The receipts of one block in a coprocessor input bundle whose receipts carry `block_number`s.
+///
+/// Receipts reach the program grouped by block, blocks in ascending order, so each block's
+/// receipts are `data.receipts[first_receipt..first_receipt + receipt_count]`. Within a block they
+/// keep their submission order, or the request's `sort_by`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceiptBlock {
+    pub block_number: u64,
+    pub first_receipt: usize,
+    pub receipt_count: usize,
+}
+
+/// Merkle-Patricia proof that a receipt's encoding (`raw_data_hex`, the consensus encoding with
+/// the type byte of typed receipts) is stored in its block's receipts trie.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptInclusionProof {
+    /// Position of the receipt's transaction in the block; the trie key is its RLP encoding.
+    pub transaction_index: u64,
+    /// Hex encoded RLP trie nodes from the root down to the receipt's leaf.
+    pub proof: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -66,6 +581,9 @@ pub struct SerializableLog {
     pub address: Address,
     pub topics: Vec<B256>,
     pub data_hex: String,
+    // Position of the log within its block, as used by coprocessor LogFieldData
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_pos: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -89,6 +607,50 @@ pub struct SerializableTransaction {
     pub raw_data_hex: String, // Allow passing raw RLP or similar if needed
 }
 
+/// The fields of a [`SerializableTransaction`] as alloy types, with the hex strings decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionFields {
+    pub transaction_hash: B256,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    /// EIP-2718 encoding of the signed transaction; empty if none was given.
+    pub raw: Bytes,
+}
+
+impl SerializableTransaction {
+    /// Builds the transaction from an RPC transaction, hex encoding its input and its EIP-2718
+    /// (RLP) encoding.
+    pub fn from_alloy(tx: &Transaction) -> Self {
+        Self {
+            transaction_hash: TransactionResponse::tx_hash(tx),
+            from: TransactionResponse::from(tx),
+            to: ConsensusTransaction::to(tx),
+            value: ConsensusTransaction::value(tx),
+            input_data_hex: hex::encode(ConsensusTransaction::input(tx)),
+            raw_data_hex: hex::encode(tx.inner.encoded_2718()),
+        }
+    }
+
+    /// Decodes the hex fields back into alloy types. Hex may be given with or without `0x`.
+    pub fn to_alloy_fields(&self) -> Result<TransactionFields, ProofServiceError> {
+        fn decode(field: &str, value: &str) -> Result<Bytes, ProofServiceError> {
+            decode_hex(value)
+                .map(Bytes::from)
+                .map_err(|e| ProofServiceError::InvalidInput(format!("Invalid {}: {}", field, e)))
+        }
+        Ok(TransactionFields {
+            transaction_hash: self.transaction_hash,
+            from: self.from,
+            to: self.to,
+            value: self.value,
+            input: decode("input_data_hex", &self.input_data_hex)?,
+            raw: decode("raw_data_hex", &self.raw_data_hex)?,
+        })
+    }
+}
+
 /// Container for blockchain data inputs to the coprocessor job.
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct BlockchainData {
@@ -100,14 +662,129 @@ pub struct BlockchainData {
     pub transactions: Option<Vec<SerializableTransaction>>,
 }
 
+impl BlockchainData {
+    /// Counts passed to the coprocessor SDK's `Builder::init(receipts, storage, txs)` for this data.
+    pub fn sdk_init_params(&self) -> (u32, u32, u32) {
+        fn count<T>(items: &Option<Vec<T>>) -> u32 {
+            items
+                .as_ref()
+                .map_or(0, |v| u32::try_from(v.len()).unwrap_or(u32::MAX))
+        }
+        (
+            count(&self.receipts),
+            count(&self.storage_slots),
+            count(&self.transactions),
+        )
+    }
+
+    /// Removes the `0x` prefixes clients may put on the hex fields (log `data_hex`, receipt and
+    /// transaction `raw_data_hex`, transaction `input_data_hex`), so prefixed and unprefixed data
+    /// reach the program, and the proof cache, as the same bytes.
+    pub fn strip_hex_prefixes(&mut self) {
+        fn strip(value: &mut String) {
+            let stripped = strip_hex_prefix(value);
+            if stripped.len() != value.len() {
+                *value = stripped.to_string();
+            }
+        }
+        for receipt in self.receipts.iter_mut().flatten() {
+            strip(&mut receipt.raw_data_hex);
+            for log in &mut receipt.logs {
+                strip(&mut log.data_hex);
+            }
+        }
+        for tx in self.transactions.iter_mut().flatten() {
+            strip(&mut tx.input_data_hex);
+            strip(&mut tx.raw_data_hex);
+        }
+    }
+
+    /// How much of each `MaxSizes` category this data occupies, in the units `max_sizes` is
+    /// declared in (items per category).
+    pub fn used_sizes(&self) -> MaxSizes {
+        fn len<T>(items: &Option<Vec<T>>) -> usize {
+            items.as_ref().map_or(0, Vec::len)
+        }
+        MaxSizes {
+            max_receipt_size: len(&self.receipts),
+            max_storage_size: len(&self.storage_slots),
+            max_tx_size: len(&self.transactions),
+        }
+    }
+}
+
+/// Where a coprocessor request's blockchain data is fetched from instead of being sent inline.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockchainDataSource {
+    /// A tar archive (optionally gzip-compressed) holding the `BlockchainData` JSON as
+    /// `blockchain_data.json`, so every input a proof used can be published as one file and
+    /// fetched again by its hash.
+    Archive(Url),
+}
+
 /// Required max sizes for coprocessor SDK initialization.
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+///
+/// Each size must be a positive multiple of 32; the default is that minimum for every category,
+/// so a defaulted request passes `validate_max_sizes` when its data fits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct MaxSizes {
     pub max_receipt_size: usize,
     pub max_storage_size: usize,
     pub max_tx_size: usize,
 }
 
+impl Default for MaxSizes {
+    fn default() -> Self {
+        Self {
+            max_receipt_size: 32,
+            max_storage_size: 32,
+            max_tx_size: 32,
+        }
+    }
+}
+
+/// Part of a log a program field is read from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldSource {
+    /// `topics[index]`; index 0 is the event signature itself.
+    Topic(usize),
+    /// The 32-byte word starting at this byte offset of the log data.
+    Data(usize),
+}
+
+/// A named program field and where it comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub name: String,
+    pub source: FieldSource,
+}
+
+/// Declares, per event signature (topic0), which log topics or data words feed which program fields.
+/// Logs whose event isn't listed are passed through unmapped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct FieldMapping {
+    pub events: HashMap<B256, Vec<FieldSpec>>,
+}
+
+/// A field value extracted from a log according to a [`FieldMapping`], mirroring the
+/// coprocessor SDK's `LogFieldData`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MappedField {
+    pub name: String,
+    pub receipt_index: usize,
+    pub log_index: usize,
+    pub contract: Address,
+    pub event: B256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_pos: Option<u64>,
+    pub is_topic: bool,
+    // Topic index, or data word index (offset / 32)
+    pub field_index: usize,
+    pub value: B256,
+}
+
 /// Input structure for the zkCoprocessor proof generation job.
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct CoprocessorProofRequest {
@@ -115,6 +792,26 @@ pub struct CoprocessorProofRequest {
     pub program_hash: String, // hex encoded B256
     /// Blockchain data to be processed by the zkVM program.
     pub blockchain_data: BlockchainData,
+    /// Fetch the blockchain data from here instead; `blockchain_data` must then be left empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockchain_data_source: Option<BlockchainDataSource>,
+    /// SHA256 of the file at `blockchain_data_source`, required with it. Data whose hash differs
+    /// is refused before anything is proven.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockchain_data_hash: Option<B256>,
+    /// Inputs already built with the coprocessor SDK: the JSON its `save_inputs` writes
+    /// (`request_prove_inputs.json`), passed to the program as-is instead of the input bundle.
+    ///
+    /// The SDK file carries its own data and sizes, so `blockchain_data` and `field_mapping` must
+    /// be left empty; `max_sizes` and the context's input preprocessors don't apply. The file is
+    /// still held to `max_input_bytes`, the hard limits and the anchor block, and its own sizes
+    /// must hold its data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sdk_inputs: Option<String>,
+    /// Canonical order to put the receipts in before bundling; by default they keep the order they
+    /// were submitted in. Field extraction and per-receipt Merkle leaves follow the resulting order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<ReceiptOrder>,
     /// Max size configuration for the coprocessor SDK.
     pub max_sizes: MaxSizes,
     /// Type of proof to generate.
@@ -127,5 +824,210 @@ pub struct CoprocessorProofRequest {
     pub eth_rpc_url_override: Option<String>,
     /// Optional override for Registry contract address.
     #[serde(default)]
-    pub registry_address_override: Option<Address>,
+    pub registry_address_override: Option<RegistryRef>,
+    /// Optional override for the prover's shard (chunk) size. Must be a power of two, and needs a
+    /// service that proves in subprocesses (`prover_subprocess`).
+    #[serde(default)]
+    pub shard_size: Option<usize>,
+    /// Block the data was read at, passed to the program so verifiers know the state root context.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_block_hash: Option<B256>,
+    /// Number of `anchor_block_hash`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_block_number: Option<u64>,
+    /// Check over RPC that `anchor_block_hash` is the canonical hash of `anchor_block_number`.
+    #[serde(default)]
+    pub verify_anchor: bool,
+    /// Expected number of topics per event signature (topic0), checked on every log before proving.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_topic_counts: Option<HashMap<B256, usize>>,
+    /// Explicit receipt-to-field contract; mapped values are validated and passed to the program.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_mapping: Option<FieldMapping>,
+    /// Reject receipts whose logs don't follow the paired `log_pos` layout before proving.
+    #[serde(default)]
+    pub validate_log_structure: bool,
+    /// Behaviour when a `Full` proof fails during recursion.
+    #[serde(default)]
+    pub on_full_failure: FailurePolicy,
+    /// How the program's committed public values are laid out.
+    #[serde(default)]
+    pub commit_format: CoprocessorCommitFormat,
+    /// Whether the payload is one aggregate value or a Merkle root over per-receipt results.
+    #[serde(default)]
+    pub commit_mode: CommitMode,
+    /// Sizing applied to the decoded public values (`ProofResult::public_values_decoded`).
+    #[serde(default)]
+    pub public_values_policy: PublicValuesPolicy,
+    /// Encoding of the byte fields in the returned `ProofResult`.
+    #[serde(default)]
+    pub output_encoding: OutputEncoding,
+    /// Return the prover's logs in `ProofResult::debug_log`, for debugging a failing program.
+    #[serde(default)]
+    pub capture_logs: bool,
+    /// Order among queued proofs when proving slots are limited.
+    #[serde(default)]
+    pub priority: ProofPriority,
+    /// Also return a `Full` proof's intermediate RISCV proof in `ProofResult::riscv_proof`.
+    #[serde(default)]
+    pub include_riscv_proof: bool,
+    /// Fail the proof when the program commits no public values, instead of returning them empty.
+    /// A missing public values stream is always an error.
+    #[serde(default)]
+    pub require_public_values: bool,
+    /// Serialization of the proof when `proving_type` is `Full`; other proving types ignore it.
+    #[serde(default)]
+    pub recursion_proof_format: RecursionProofFormat,
+    /// Memory ceiling for the proof. Rejected up front when the host has less available; for
+    /// `FullWithEvm` it is also passed to the gnark container as `--memory`, which needs a service
+    /// that proves in subprocesses (`prover_subprocess`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_bytes: Option<usize>,
+    /// Leave the input bundle out of `ProofResult::inputs` (returned empty) and out of the logs,
+    /// so the result reveals only the public values.
+    ///
+    /// This only hides the inputs from the service's outputs: whether the proof keeps them private
+    /// depends on the program committing nothing but its result. Such requests are never
+    /// checkpointed, and are refused with `dump_inputs_to` or a remote or subprocess prover.
+    #[serde(default)]
+    pub private_inputs: bool,
+    /// Attach the SCALE-encoded proof metadata (`ProofResult::scale_metadata_hex`) for on-chain
+    /// verifiers alongside the JSON result.
+    #[serde(default)]
+    pub include_scale_metadata: bool,
+    /// Return `public_values` zstd-compressed, for results committing many fields; marked by
+    /// `ProofResult::public_values_compressed`. `public_values_decoded` is left uncompressed.
+    #[serde(default)]
+    pub compress_public_values: bool,
+    /// Caller-chosen value echoed into `ProofResult::nonce` and passed to the program in the input
+    /// bundle, so a program that commits it binds the proof to this request against replay.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<B256>,
+    /// Unix time (seconds) after which the request is stale: rejected at submission once passed,
+    /// and echoed into the result and input bundle like `nonce`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Scratch directory for this job's ELF and prover outputs instead of the service's
+    /// `temp_dir_base`, e.g. a larger disk for a big proof. Must exist, be writable, and lie within
+    /// one of the service's `allowed_temp_dirs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_dir_override: Option<PathBuf>,
+    /// Also write the serialized inputs the program reads (the canonical JSON input bundle, or
+    /// `sdk_inputs` as given) to a file of this name in the service's `dump_inputs_dir` before
+    /// proving, to debug a failing program. Must be a bare file name that doesn't exist yet;
+    /// rejected unless the service configures the directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dump_inputs_to: Option<String>,
+    /// Use a `LocalPath` program without checking its hash, for local development. Ignored unless
+    /// the service sets `allow_insecure`; proofs made this way are not cached.
+    #[serde(default)]
+    pub insecure_skip_hash_verify: bool,
+    /// Caller-chosen labels (e.g. customer or job ids) for billing and analytics, echoed into
+    /// `ProofResult::tags` and recorded as labels on the service's proof metrics. Keys are
+    /// limited to `[A-Za-z0-9_]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    /// Deliver the proof to object storage and return its URL (see [`OutputSink`]); `None` returns
+    /// it inline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_sink: Option<OutputSink>,
+    /// Caller signature, required when the service has authorized keys configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<RequestSignature>,
+}
+
+/// Input structure for the prove job: either kind of proof request, proven by the matching job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum AnyProofRequest {
+    Generic(ProofRequest),
+    Coprocessor(CoprocessorProofRequest),
+}
+
+/// Input structure for the resume_proof job.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ResumeProofRequest {
+    /// Id of the interrupted proof, as logged by `generate_proof` (and returned in
+    /// `ProofResult::proof_id`).
+    pub proof_id: String,
+    /// Caller signature, required when the service authenticates requests. Must be by the caller
+    /// that requested the interrupted proof.
+    #[serde(default)]
+    pub signature: Option<RequestSignature>,
+}
+
+/// Input structure for the describe_program job.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DescribeProgramRequest {
+    /// Hash of the program to describe.
+    pub program_hash: String, // hex encoded B256
+    /// Optional override for the registry the schema is looked up in.
+    #[serde(default)]
+    pub registry_address_override: Option<RegistryRef>,
+}
+
+/// Input structure for the warm_program job. The program is always looked up in the service's
+/// registries, so callers can't make it fetch from anywhere else.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct WarmProgramRequest {
+    /// Hash of the program to fetch into the ELF cache.
+    pub program_hash: String, // hex encoded B256
+    /// Proving type to set the prover up for: `Full` proofs run on a prover chain of their own,
+    /// the other types on the program's prover client.
+    #[serde(default)]
+    pub proving_type: ProvingType,
+    /// Signature over the request, required when the service enables authentication.
+    #[serde(default)]
+    pub signature: Option<RequestSignature>,
+}
+
+/// Outcome of the warm_program job.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmProgramResult {
+    pub program_hash: String,
+    /// The program was already cached, so nothing was fetched.
+    pub already_cached: bool,
+    /// Size of the cached ELF.
+    pub elf_bytes: u64,
+    /// Time spent looking the program up, downloading and verifying it.
+    pub fetch_ms: u64,
+    /// The prover for the requested proving type is ready in the service's prover cache, so the
+    /// first proof of that type skips setting it up as well. False for backends proving
+    /// elsewhere, or when the prover cache keeps nothing.
+    #[serde(default)]
+    pub prover_client_cached: bool,
+}
+
+/// Input structure for the check_program_matches_registry job.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CheckProgramRequest {
+    /// Hash of the registered program to compare against.
+    pub program_hash: String, // hex encoded B256
+    /// Base64 encoded ELF to check, e.g. a local build. The ELF is sent rather than named by a
+    /// path, so the job never reads files on the node.
+    pub elf: String,
+    /// Optional override for the registry the program is looked up in.
+    #[serde(default)]
+    pub registry_address_override: Option<RegistryRef>,
+}
+
+/// Outcome of the check_program_matches_registry job.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckProgramResult {
+    /// The local ELF is the registered program.
+    pub matches: bool,
+    /// Hash of the local ELF, under the algorithm the program is registered with.
+    pub local_hash: String,
+    /// Hash of the registered ELF.
+    pub registry_hash: String,
+}
+
+/// Input structure for the proof verification job.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct VerifyProofRequest {
+    /// Proof to verify, as returned by one of the proving jobs.
+    pub proof: ProofResult,
+    /// Optional override for program location.
+    #[serde(default)]
+    pub program_location_override: Option<ProgramLocation>,
 }
@@ -0,0 +1,272 @@
+// pico-coprocessor-service-lib/src/validation.rs
+use crate::context::HardLimits;
+use crate::errors::ProofServiceError;
+use crate::types::{BlockchainData, MaxSizes};
+use blueprint_sdk::alloy::primitives::B256;
+use std::collections::{BTreeMap, HashMap};
+
+/// Checks the receipt layout programs following the trading-volume pattern rely on: every receipt
+/// carries at least two logs, and the first two share the same `log_pos`.
+///
+/// Such programs panic on violations, so rejecting them here saves a wasted proving run.
+pub fn validate_receipt_log_structure(data: &BlockchainData) -> Result<(), ProofServiceError> {
+    for (index, receipt) in data.receipts.iter().flatten().enumerate() {
+        if receipt.logs.len() < 2 {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Receipt {} has {} logs, expected at least 2",
+                index,
+                receipt.logs.len()
+            )));
+        }
+        let (first, second) = (&receipt.logs[0], &receipt.logs[1]);
+        if first.log_pos.is_none() || first.log_pos != second.log_pos {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Receipt {}: logs 0 and 1 must share log_pos (got {:?} and {:?})",
+                index, first.log_pos, second.log_pos
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the block context of receipts spanning several blocks: either no receipt has a
+/// `block_number` or every one does, in ascending (non-decreasing) order and none after the
+/// `anchor_block_number`, when there is one.
+pub fn validate_receipt_blocks(
+    data: &BlockchainData,
+    anchor_block_number: Option<u64>,
+) -> Result<(), ProofServiceError> {
+    let block_numbers: Vec<Option<u64>> = data
+        .receipts
+        .iter()
+        .flatten()
+        .map(|receipt| receipt.block_number)
+        .collect();
+    check_receipt_blocks(&block_numbers, anchor_block_number)
+}
+
+// The check of `validate_receipt_blocks`, over the block number of each receipt in order
+pub(crate) fn check_receipt_blocks(
+    block_numbers: &[Option<u64>],
+    anchor_block_number: Option<u64>,
+) -> Result<(), ProofServiceError> {
+    if block_numbers.iter().all(Option::is_none) {
+        return Ok(());
+    }
+    let mut previous = None;
+    for (index, &block_number) in block_numbers.iter().enumerate() {
+        let Some(block_number) = block_number else {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Receipt {} has no block_number, but other receipts do; set it on every receipt",
+                index
+            )));
+        };
+        if let Some(previous) = previous.filter(|&previous| block_number < previous) {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Receipt {} is from block {}, before block {} of the receipt preceding it; \
+                 receipts must be in ascending block order",
+                index, block_number, previous
+            )));
+        }
+        if let Some(anchor) = anchor_block_number.filter(|&anchor| block_number > anchor) {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Receipt {} is from block {}, after the anchor block {}",
+                index, block_number, anchor
+            )));
+        }
+        previous = Some(block_number);
+    }
+    Ok(())
+}
+
+/// Checks each `max_sizes` entry is a non-zero multiple of 32 and large enough for its category of
+/// `data`. Errors name the offending size field.
+pub fn validate_max_sizes(
+    sizes: &MaxSizes,
+    data: &BlockchainData,
+) -> Result<(), ProofServiceError> {
+    fn len<T>(items: &Option<Vec<T>>) -> usize {
+        items.as_ref().map_or(0, Vec::len)
+    }
+    let categories = [
+        (
+            "max_receipt_size",
+            sizes.max_receipt_size,
+            "receipts",
+            len(&data.receipts),
+        ),
+        (
+            "max_storage_size",
+            sizes.max_storage_size,
+            "storage slots",
+            len(&data.storage_slots),
+        ),
+        (
+            "max_tx_size",
+            sizes.max_tx_size,
+            "transactions",
+            len(&data.transactions),
+        ),
+    ];
+
+    for (field, limit, items, count) in categories {
+        if limit == 0 {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Invalid max_sizes: {} must be > 0 and a multiple of 32, got {}",
+                field, limit
+            )));
+        }
+        check_max_size(field, limit, items, count)?;
+    }
+    Ok(())
+}
+
+// Checks one declared size is a multiple of 32 holding the `count` items of its category
+pub(crate) fn check_max_size(
+    field: &str,
+    limit: usize,
+    items: &str,
+    count: usize,
+) -> Result<(), ProofServiceError> {
+    if limit % 32 != 0 {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Invalid max_sizes: {} must be > 0 and a multiple of 32, got {}",
+            field, limit
+        )));
+    }
+    if count > limit {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Invalid max_sizes: {} {} exceed {} of {}",
+            count, items, field, limit
+        )));
+    }
+    Ok(())
+}
+
+/// Checks `data` against the service's hard caps, which apply whatever `max_sizes` the request declares.
+pub fn validate_hard_limits(
+    limits: &HardLimits,
+    data: &BlockchainData,
+) -> Result<(), ProofServiceError> {
+    let receipts = data.receipts.as_deref().unwrap_or_default();
+    check_hard_limit("receipts", receipts.len(), limits.max_receipts_hard)?;
+    for (index, receipt) in receipts.iter().enumerate() {
+        check_hard_limit(
+            &format!("logs in receipt {}", index),
+            receipt.logs.len(),
+            limits.max_logs_per_receipt_hard,
+        )?;
+    }
+    check_hard_limit(
+        "storage slots",
+        data.storage_slots.as_ref().map_or(0, Vec::len),
+        limits.max_storage_slots_hard,
+    )?;
+    check_hard_limit(
+        "transactions",
+        data.transactions.as_ref().map_or(0, Vec::len),
+        limits.max_transactions_hard,
+    )
+}
+
+// Checks `count` items of a kind against the service's hard cap on them
+pub(crate) fn check_hard_limit(
+    what: &str,
+    count: usize,
+    cap: usize,
+) -> Result<(), ProofServiceError> {
+    if count > cap {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Too many {}: {} exceeds the service limit of {}",
+            what, count, cap
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that every log whose topic0 appears in `expected` carries exactly that many topics.
+pub fn validate_topic_counts(
+    data: &BlockchainData,
+    expected: &HashMap<B256, usize>,
+) -> Result<(), ProofServiceError> {
+    for (receipt_index, receipt) in data.receipts.iter().flatten().enumerate() {
+        for (log_index, log) in receipt.logs.iter().enumerate() {
+            let Some(signature) = log.topics.first() else {
+                continue;
+            };
+            if let Some(&count) = expected.get(signature) {
+                if log.topics.len() != count {
+                    return Err(ProofServiceError::InvalidInput(format!(
+                        "Receipt {} log {}: event {} has {} topics, expected {}",
+                        receipt_index,
+                        log_index,
+                        signature,
+                        log.topics.len(),
+                        count
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a request whose `expires_at` (unix seconds) is not after `now`.
+pub fn validate_expiry(expires_at: Option<u64>, now: u64) -> Result<(), ProofServiceError> {
+    match expires_at {
+        Some(expires_at) if expires_at <= now => Err(ProofServiceError::InvalidInput(format!(
+            "Request expired at {} (now {})",
+            expires_at, now
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Most tags a request may carry.
+pub const MAX_TAGS: usize = 16;
+/// Longest tag key, in bytes.
+pub const MAX_TAG_KEY_BYTES: usize = 64;
+/// Longest tag value, in bytes.
+pub const MAX_TAG_VALUE_BYTES: usize = 256;
+
+/// Rejects request tags that are too many or too long, or whose key is empty or has characters
+/// other than ASCII letters, digits and `_`. Keys become metric label names as they are, so two
+/// distinct keys never share a label.
+pub fn validate_tags(tags: &BTreeMap<String, String>) -> Result<(), ProofServiceError> {
+    if tags.len() > MAX_TAGS {
+        return Err(ProofServiceError::InvalidInput(format!(
+            "Request has {} tags, at most {} are allowed",
+            tags.len(),
+            MAX_TAGS
+        )));
+    }
+    for (key, value) in tags {
+        if key.is_empty() || key.len() > MAX_TAG_KEY_BYTES {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Tag key {:?} must be 1 to {} bytes",
+                key, MAX_TAG_KEY_BYTES
+            )));
+        }
+        if !key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Tag key {:?} may only contain ASCII letters, digits and '_'",
+                key
+            )));
+        }
+        if value.len() > MAX_TAG_VALUE_BYTES {
+            return Err(ProofServiceError::InvalidInput(format!(
+                "Value of tag {:?} exceeds {} bytes",
+                key, MAX_TAG_VALUE_BYTES
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Current unix time in seconds, as compared against `expires_at`.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -16,7 +16,9 @@ pub struct TradingVolumnReceipts {
     pub max_receipts: usize,
 }
 // update this to change the workload of trading volumn receipts
-const MAX_RECEIPT: usize = 64;
+// the values below mirror the service's `CoprocessorFixtureBuilder`, which the requests example
+// uses; the guest can't link the service crate, so keep them in step with its fixture module
+pub const MAX_RECEIPT: usize = 64;
 
 pub fn prepare_test_receipts() -> TradingVolumnReceipts {
     let transaction_hash: &str =
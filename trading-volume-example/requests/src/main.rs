@@ -4,7 +4,12 @@ use std::{
 
 use coprocessor_sdk::sdk::Builder;
 use log::{error, info};
-use trading_volumn_lib::prepare_test_receipts;
+use pico_coprocessor_service_blueprint_lib::fixtures::CoprocessorFixtureBuilder;
+use sha2::{Digest, Sha256};
+use trading_volumn_lib::{prepare_test_receipts, MAX_RECEIPT};
+
+// built by `cargo pico build` in ../app
+const APP_ELF: &str = "../app/elf/riscv32im-pico-zkvm-elf";
 
 // test batchQueryAsync,test submit proof,
 fn main() {
@@ -13,9 +18,33 @@ fn main() {
         .filter_level(log::LevelFilter::Info) // Set default log level to Info
         .init();
 
+    // the same workload as a request to the proving service, built by the service's own fixture
+    // so the receipt layout and sizes match what its tests prove
+    let fixture = CoprocessorFixtureBuilder::new().num_receipts(MAX_RECEIPT).build();
+    let elf_path = PathBuf::from(APP_ELF);
+    match std::fs::read(&elf_path) {
+        Ok(elf) => {
+            let program_hash = hex::encode(Sha256::digest(&elf));
+            let request = fixture.request(program_hash, elf_path);
+            std::fs::create_dir_all("./example/trading_volumn_prover/inputs/").unwrap();
+            std::fs::write(
+                "./example/trading_volumn_prover/inputs/coprocessor_request.json",
+                serde_json::to_vec_pretty(&request).unwrap(),
+            )
+            .unwrap();
+            info!(
+                "Wrote the service request, expecting volume {}",
+                fixture.expected_volume
+            );
+        }
+        Err(e) => error!("Failed to read {} (run `cargo pico build` in app): {}", APP_ELF, e),
+    }
+
     // add data
     let test_receipts = prepare_test_receipts();
-    let mut sdk = Builder::new().with_receipts(test_receipts.receipts).init(64, 0, 0);
+    let mut sdk = Builder::new()
+        .with_receipts(test_receipts.receipts)
+        .init(fixture.max_sizes.max_receipt_size as u32, 0, 0);
     sdk.chain_id = 1;
     sdk.save_inputs(PathBuf::from("./example/trading_volumn_prover/inputs/"))
         .unwrap();